@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use nalgebra::{point, vector, Point2, Scale2, Vector2};
 use serde::Deserialize;
 use yapgeir_geometry::Box2D;
@@ -23,6 +23,8 @@ pub struct Sprite {
     pub trimmed: bool,
     pub sprite_source_size: Rect,
     pub source_size: Size,
+    /// Frame duration in milliseconds. Absent in TexturePacker exports.
+    #[serde(default)]
     pub duration: u32,
 }
 
@@ -38,6 +40,7 @@ pub struct FrameTag {
 #[serde(rename_all = "camelCase")]
 pub struct Meta {
     pub size: Size,
+    #[serde(default)]
     pub frame_tags: Vec<FrameTag>,
 }
 
@@ -78,7 +81,7 @@ impl Rect {
 }
 
 impl Sprite {
-    fn to_sprite(&self, texel_scale: Scale2<f32>) -> Drawable {
+    pub(super) fn to_sprite(&self, texel_scale: Scale2<f32>) -> Drawable {
         let half_size = self.source_size.vector() * 0.5;
 
         // Clip is logically inverted, because it was calculated based on Y down,
@@ -111,12 +114,19 @@ impl AsepriteAtlas {
         Ok(serde_json::from_str(json)?)
     }
 
-    pub fn to_atlas(&self) -> Atlas {
+    /// Converts the decoded atlas to sprites in texture space.
+    ///
+    /// Trimmed frames are supported, but rotated frames are not, and result in an error.
+    pub fn to_atlas(&self) -> Result<Atlas> {
         let texel_space = Scale2::new(self.meta.size.w, self.meta.size.h)
             .cast::<f32>()
             .pseudo_inverse();
 
-        Atlas {
+        for (name, sprite) in &self.frames {
+            ensure!(!sprite.rotated, "Rotated frame {name} is not supported");
+        }
+
+        Ok(Atlas {
             drawables: self
                 .frames
                 .iter()
@@ -128,6 +138,41 @@ impl AsepriteAtlas {
                 .iter()
                 .map(|f| (f.name.clone(), f.from..=f.to))
                 .collect(),
-        }
+        })
+    }
+}
+
+/// Parses an atlas exported from Aseprite as a JSON hash.
+pub fn from_aseprite_json(json: &str) -> Result<Atlas> {
+    AsepriteAtlas::decode(json)?.to_atlas()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../fixtures/aseprite_atlas.json");
+
+    #[test]
+    fn test_parse_aseprite_json() {
+        let atlas = from_aseprite_json(FIXTURE).unwrap();
+
+        assert_eq!(atlas.drawables.len(), 3);
+        assert_eq!(atlas.frame_tags["idle"], 0..=0);
+        assert_eq!(atlas.frame_tags["walk"], 1..=2);
+
+        let trimmed = &atlas.drawables["hero 1.aseprite"];
+        assert_eq!(trimmed.size, [16, 16]);
+        assert_eq!(
+            trimmed.sprite.sub_texture,
+            Box2D::new([0.25, 0.], [0.4375, 0.4375])
+        );
+        assert_eq!(trimmed.sprite.boundaries, Box2D::new([-6., -8.], [6., 6.]));
+    }
+
+    #[test]
+    fn test_rotated_frame_is_rejected() {
+        let json = FIXTURE.replacen("\"rotated\": false", "\"rotated\": true", 1);
+        assert!(from_aseprite_json(&json).is_err());
     }
 }
@@ -3,6 +3,8 @@ use std::{collections::HashMap, ops::RangeInclusive};
 use yapgeir_world_2d::Drawable;
 
 pub mod ase;
+pub mod padding;
+pub mod texturepacker;
 
 #[derive(Debug, Constructor)]
 pub struct Atlas {
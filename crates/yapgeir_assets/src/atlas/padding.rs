@@ -0,0 +1,169 @@
+use anyhow::{ensure, Result};
+use yapgeir_geometry::{Box2D, Rect, Size};
+use yapgeir_graphics_hal::texture::PixelFormat;
+
+/// Returns the number of bytes per pixel of the given format.
+fn stride(format: PixelFormat) -> usize {
+    match format {
+        PixelFormat::Alpha | PixelFormat::Lumi => 1,
+        PixelFormat::Lumia | PixelFormat::Depth16 => 2,
+        PixelFormat::Rgb => 3,
+        PixelFormat::Rgba | PixelFormat::Srgba | PixelFormat::Depth24 => 4,
+    }
+}
+
+/// An atlas image with a gutter around every sprite, see `pad_sprites`.
+#[derive(Debug, Clone)]
+pub struct PaddedAtlas {
+    pub pixels: Vec<u8>,
+    pub size: Size<u32>,
+    /// Rectangles of the sprites in the padded atlas in pixels, excluding the gutters,
+    /// in the same order as the rectangles passed to `pad_sprites`.
+    pub rects: Vec<Rect<u32>>,
+}
+
+impl PaddedAtlas {
+    /// Returns the rectangles of the sprites in texture space, e.g. for `Sprite::sub_texture`.
+    pub fn sub_textures(&self) -> impl Iterator<Item = Box2D<f32>> + '_ {
+        let [w, h] = [self.size.w as f32, self.size.h as f32];
+        self.rects.iter().map(move |rect| {
+            Box2D::new(
+                [rect.x as f32 / w, rect.y as f32 / h],
+                [(rect.x + rect.w) as f32 / w, (rect.y + rect.h) as f32 / h],
+            )
+        })
+    }
+}
+
+/// Moves sprites apart along one axis: every sprite starts after the gutters
+/// of all sprites which end before it, and its own gutter.
+fn padded_starts(spans: impl Iterator<Item = (u32, u32)> + Clone, padding: u32) -> Vec<u32> {
+    let mut ends: Vec<u32> = spans.clone().map(|(start, len)| start + len).collect();
+    ends.sort_unstable();
+    ends.dedup();
+
+    spans
+        .map(|(start, _)| {
+            let before = ends.partition_point(|end| *end <= start) as u32;
+            start + padding + 2 * padding * before
+        })
+        .collect()
+}
+
+/// Adds a gutter of `padding` pixels around every sprite of an atlas, filled with the nearest
+/// edge pixels of the sprite, so that filtering at the edges doesn't bleed neighbouring sprites.
+///
+/// `rects` are the sprites in pixels with (0; 0) in the top-left corner, which must not overlap.
+/// Sprites keep their relative placement, moved apart to make room for the gutters,
+/// and the space not covered by sprites or gutters is transparent.
+pub fn pad_sprites(
+    pixels: &[u8],
+    size: Size<u32>,
+    format: PixelFormat,
+    rects: &[Rect<u32>],
+    padding: u32,
+) -> Result<PaddedAtlas> {
+    let stride = stride(format);
+    ensure!(
+        pixels.len() == size.w as usize * size.h as usize * stride,
+        "Expected {} bytes for a {}x{} {format:?} atlas, got {}",
+        size.w as usize * size.h as usize * stride,
+        size.w,
+        size.h,
+        pixels.len()
+    );
+    for rect in rects {
+        ensure!(
+            rect.x + rect.w <= size.w && rect.y + rect.h <= size.h,
+            "Sprite {rect:?} is out of bounds of a {}x{} atlas",
+            size.w,
+            size.h
+        );
+    }
+
+    let xs = padded_starts(rects.iter().map(|r| (r.x, r.w)), padding);
+    let ys = padded_starts(rects.iter().map(|r| (r.y, r.h)), padding);
+    let padded: Vec<Rect<u32>> = rects
+        .iter()
+        .zip(xs.into_iter().zip(ys))
+        .map(|(rect, (x, y))| Rect::new(x, y, rect.w, rect.h))
+        .collect();
+
+    let padded_size = Size::new(
+        padded
+            .iter()
+            .map(|r| r.x + r.w + padding)
+            .max()
+            .unwrap_or(0),
+        padded
+            .iter()
+            .map(|r| r.y + r.h + padding)
+            .max()
+            .unwrap_or(0),
+    );
+
+    let mut output = vec![0; padded_size.w as usize * padded_size.h as usize * stride];
+    for (source, target) in rects.iter().zip(&padded) {
+        if source.w == 0 || source.h == 0 {
+            continue;
+        }
+
+        for y in target.y - padding..target.y + target.h + padding {
+            // Gutter pixels are clamped to the nearest edge of the sprite.
+            let source_y = source.y + (y.max(target.y) - target.y).min(source.h - 1);
+            for x in target.x - padding..target.x + target.w + padding {
+                let source_x = source.x + (x.max(target.x) - target.x).min(source.w - 1);
+
+                let from = (source_y as usize * size.w as usize + source_x as usize) * stride;
+                let to = (y as usize * padded_size.w as usize + x as usize) * stride;
+                output[to..to + stride].copy_from_slice(&pixels[from..from + stride]);
+            }
+        }
+    }
+
+    Ok(PaddedAtlas {
+        pixels: output,
+        size: padded_size,
+        rects: padded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkerboard_gutter() {
+        // A 2x2 checkerboard sprite, and a solid sprite next to it.
+        #[rustfmt::skip]
+        let pixels = [
+            255, 0,   9, 9,
+            0, 255,   9, 9,
+        ];
+        let rects = [Rect::new(0, 0, 2, 2), Rect::new(2, 0, 2, 2)];
+
+        let atlas = pad_sprites(&pixels, Size::new(4, 2), PixelFormat::Lumi, &rects, 1).unwrap();
+        assert_eq!(atlas.size, Size::new(8, 4));
+        assert_eq!(atlas.rects, [Rect::new(1, 1, 2, 2), Rect::new(5, 1, 2, 2)]);
+
+        // Every gutter pixel repeats the nearest edge pixel of its sprite.
+        #[rustfmt::skip]
+        assert_eq!(atlas.pixels, [
+            255, 255, 0, 0,       9, 9, 9, 9,
+            255, 255, 0, 0,       9, 9, 9, 9,
+            0, 0, 255, 255,       9, 9, 9, 9,
+            0, 0, 255, 255,       9, 9, 9, 9,
+        ]);
+
+        let sub_textures: Vec<_> = atlas.sub_textures().collect();
+        assert_eq!(sub_textures[0], Box2D::new([0.125, 0.25], [0.375, 0.75]));
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let pixels = [0; 4];
+        let rect = [Rect::new(1, 1, 2, 2)];
+        assert!(pad_sprites(&pixels, Size::new(2, 2), PixelFormat::Lumi, &rect, 1).is_err());
+        assert!(pad_sprites(&pixels, Size::new(2, 2), PixelFormat::Rgb, &[], 1).is_err());
+    }
+}
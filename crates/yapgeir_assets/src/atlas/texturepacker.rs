@@ -0,0 +1,147 @@
+use anyhow::{anyhow, ensure, Result};
+use nalgebra::Scale2;
+use roxmltree::{Document, Node};
+
+use super::{
+    ase::{self, Rect, Size},
+    Atlas,
+};
+
+fn attribute(node: Node, name: &str) -> Result<Option<i64>> {
+    node.attribute(name)
+        .map(|value| {
+            value
+                .parse()
+                .map_err(|e| anyhow!("Invalid attribute {name}=\"{value}\": {e}"))
+        })
+        .transpose()
+}
+
+fn required(node: Node, name: &str) -> Result<u32> {
+    let value = attribute(node, name)?
+        .ok_or_else(|| anyhow!("Missing attribute {name} on <{}>", node.tag_name().name()))?;
+    Ok(u32::try_from(value)?)
+}
+
+fn sprite(node: Node) -> Result<ase::Sprite> {
+    let frame = Rect {
+        x: required(node, "x")?,
+        y: required(node, "y")?,
+        w: required(node, "width")?,
+        h: required(node, "height")?,
+    };
+
+    ensure!(
+        node.attribute("rotated") != Some("true"),
+        "Rotated sub texture {} is not supported",
+        node.attribute("name").unwrap_or_default()
+    );
+
+    // Trimmed sprites have a negative offset of the trimmed rectangle
+    // and the original size in the frame attributes.
+    let (sprite_source_size, source_size) = match attribute(node, "frameWidth")? {
+        Some(_) => (
+            Rect {
+                x: u32::try_from(-attribute(node, "frameX")?.unwrap_or(0))?,
+                y: u32::try_from(-attribute(node, "frameY")?.unwrap_or(0))?,
+                w: frame.w,
+                h: frame.h,
+            },
+            Size {
+                w: required(node, "frameWidth")?,
+                h: required(node, "frameHeight")?,
+            },
+        ),
+        None => (
+            Rect {
+                w: frame.w,
+                h: frame.h,
+                ..Default::default()
+            },
+            Size {
+                w: frame.w,
+                h: frame.h,
+            },
+        ),
+    };
+
+    Ok(ase::Sprite {
+        trimmed: sprite_source_size.w != source_size.w || sprite_source_size.h != source_size.h,
+        frame,
+        rotated: false,
+        sprite_source_size,
+        source_size,
+        duration: 0,
+    })
+}
+
+/// Parses an atlas exported from TexturePacker in the Sparrow/Starling XML format.
+///
+/// The root `<TextureAtlas>` element must declare the sheet size with `width` and `height`
+/// attributes. Sprite names are used as keys as is, including any directory prefixes.
+pub fn from_texturepacker_xml(xml: &str) -> Result<Atlas> {
+    let document = Document::parse(xml)?;
+    let root = document.root_element();
+    ensure!(
+        root.has_tag_name("TextureAtlas"),
+        "Expected <TextureAtlas> root element, found <{}>",
+        root.tag_name().name()
+    );
+
+    let texel_space = Scale2::new(required(root, "width")?, required(root, "height")?)
+        .cast::<f32>()
+        .pseudo_inverse();
+
+    let drawables = root
+        .children()
+        .filter(|node| node.has_tag_name("SubTexture"))
+        .map(|node| {
+            let name = node
+                .attribute("name")
+                .ok_or_else(|| anyhow!("Missing attribute name on <SubTexture>"))?;
+            Ok((name.to_owned(), sprite(node)?.to_sprite(texel_space)))
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(Atlas::new(drawables, Default::default()))
+}
+
+/// Parses an atlas exported from TexturePacker as a JSON hash.
+///
+/// The format is shared with Aseprite, but has no frame tags.
+pub fn from_texturepacker_json(json: &str) -> Result<Atlas> {
+    ase::from_aseprite_json(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_geometry::Box2D;
+
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../../fixtures/texturepacker_atlas.xml");
+
+    #[test]
+    fn test_parse_texturepacker_xml() {
+        let atlas = from_texturepacker_xml(FIXTURE).unwrap();
+
+        assert_eq!(atlas.drawables.len(), 2);
+        assert!(atlas.frame_tags.is_empty());
+
+        let button = &atlas.drawables["ui/button.png"];
+        assert_eq!(button.size, [32, 16]);
+        assert_eq!(
+            button.sprite.sub_texture,
+            Box2D::new([0., 0.], [0.25, 0.25])
+        );
+        assert_eq!(button.sprite.boundaries, Box2D::new([-16., -8.], [16., 8.]));
+
+        let star = &atlas.drawables["ui/icons/star.png"];
+        assert_eq!(star.size, [20, 18]);
+        assert_eq!(
+            star.sprite.sub_texture,
+            Box2D::new([0.25, 0.], [0.375, 0.25])
+        );
+        assert_eq!(star.sprite.boundaries, Box2D::new([-8., -8.], [8., 8.]));
+    }
+}
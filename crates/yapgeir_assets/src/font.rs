@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use ab_glyph::{Font, FontRef, GlyphId, ScaleFont};
+use anyhow::{anyhow, Result};
+use yapgeir_geometry::{Box2D, Size};
+use yapgeir_graphics_hal::texture::PixelFormat;
+use yapgeir_renderer_2d::text_renderer::{BitmapFont, Glyph};
+
+/// Empty pixels between glyphs, so that linear filtering doesn't bleed neighbours.
+const PADDING: u32 = 1;
+
+/// A font rasterized into an atlas texture.
+pub struct FontAtlas {
+    /// Coverage of every atlas pixel, in `format`.
+    pub bytes: Vec<u8>,
+    pub size: Size<u32>,
+    /// Always `PixelFormat::Alpha`, since glyphs are tinted when drawn.
+    pub format: PixelFormat,
+    /// Glyph regions and metrics, ready to be used with a `TextRenderer`.
+    pub font: BitmapFont,
+}
+
+struct Bitmap {
+    id: GlyphId,
+    size: Size<u32>,
+    bearing: [f32; 2],
+    coverage: Vec<u8>,
+}
+
+fn rasterize(font: &FontRef, id: GlyphId, px: f32) -> Bitmap {
+    let Some(outline) = font.outline_glyph(id.with_scale(px)) else {
+        // Glyphs without an outline, e.g. a space, only advance the pen.
+        return Bitmap {
+            id,
+            size: Size::new(0, 0),
+            bearing: [0., 0.],
+            coverage: Vec::new(),
+        };
+    };
+
+    let bounds = outline.px_bounds();
+    let size = Size::new(bounds.width() as u32, bounds.height() as u32);
+    let mut coverage = vec![0; (size.w * size.h) as usize];
+    outline.draw(|x, y, c| {
+        coverage[(y * size.w + x) as usize] = (c.clamp(0., 1.) * 255.).round() as u8;
+    });
+
+    Bitmap {
+        id,
+        size,
+        // ab_glyph bounds are Y-down relative to the pen on the baseline.
+        bearing: [bounds.min.x, -bounds.min.y],
+        coverage,
+    }
+}
+
+/// Places bitmaps in rows sorted by height, returning their positions,
+/// or `None` if they don't fit into `width` x `width` pixels.
+fn pack(bitmaps: &[Bitmap], width: u32) -> Option<Vec<[u32; 2]>> {
+    let mut order: Vec<_> = (0..bitmaps.len()).collect();
+    order.sort_by_key(|i| std::cmp::Reverse(bitmaps[*i].size.h));
+
+    let mut positions = vec![[0, 0]; bitmaps.len()];
+    let (mut x, mut y, mut row_height) = (PADDING, PADDING, 0);
+    for i in order {
+        let size = bitmaps[i].size;
+        if x + size.w + PADDING > width {
+            (x, y, row_height) = (PADDING, y + row_height + PADDING, 0);
+        }
+
+        if x + size.w + PADDING > width || y + size.h + PADDING > width {
+            return None;
+        }
+
+        positions[i] = [x, y];
+        x += size.w + PADDING;
+        row_height = row_height.max(size.h);
+    }
+
+    Some(positions)
+}
+
+/// Rasterizes `chars` of a TrueType or OpenType font at `px` pixels per em
+/// into a square power of two atlas.
+///
+/// Characters missing from the font are mapped to the `.notdef` glyph,
+/// which is usually drawn as an empty box.
+pub fn rasterize_font(ttf: &[u8], px: f32, chars: &str) -> Result<FontAtlas> {
+    let font = FontRef::try_from_slice(ttf).map_err(|e| anyhow!("Unable to load font: {e}"))?;
+    let scaled = font.as_scaled(px);
+
+    let mut ids: HashMap<char, GlyphId> = HashMap::new();
+    let mut bitmaps: Vec<Bitmap> = Vec::new();
+    for c in chars.chars() {
+        // Missing characters resolve to glyph 0, which is `.notdef`.
+        let id = font.glyph_id(c);
+        if !bitmaps.iter().any(|b| b.id == id) {
+            bitmaps.push(rasterize(&font, id, px));
+        }
+        ids.insert(c, id);
+    }
+
+    let area: u32 = bitmaps
+        .iter()
+        .map(|b| (b.size.w + PADDING) * (b.size.h + PADDING))
+        .sum();
+    let mut width = ((area as f32).sqrt() as u32).next_power_of_two().max(1);
+    let positions = loop {
+        match pack(&bitmaps, width) {
+            Some(positions) => break positions,
+            None => width *= 2,
+        }
+    };
+
+    let size = Size::new(width, width);
+    let mut bytes = vec![0; (width * width) as usize];
+    let mut glyphs = HashMap::new();
+    for (bitmap, [x, y]) in bitmaps.iter().zip(positions) {
+        for row in 0..bitmap.size.h {
+            let source = (row * bitmap.size.w) as usize;
+            let target = ((y + row) * width + x) as usize;
+            let len = bitmap.size.w as usize;
+            bytes[target..target + len].copy_from_slice(&bitmap.coverage[source..source + len]);
+        }
+
+        let texel = |x: u32, y: u32| [x as f32 / width as f32, y as f32 / width as f32];
+        let glyph = Glyph {
+            region: Box2D::new(texel(x, y), texel(x + bitmap.size.w, y + bitmap.size.h)),
+            size: Size::new(bitmap.size.w as f32, bitmap.size.h as f32),
+            bearing: bitmap.bearing,
+            advance: scaled.h_advance(bitmap.id),
+        };
+        glyphs.insert(bitmap.id, glyph);
+    }
+
+    Ok(FontAtlas {
+        bytes,
+        size,
+        format: PixelFormat::Alpha,
+        font: BitmapFont {
+            glyphs: ids.into_iter().map(|(c, id)| (c, glyphs[&id])).collect(),
+            line_height: scaled.height() + scaled.line_gap(),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A font with only `.notdef` and `A` glyphs.
+    const FONT: &[u8] = include_bytes!("../fixtures/demo.ttf");
+
+    #[test]
+    fn test_rasterize_glyph() {
+        let atlas = rasterize_font(FONT, 32., "A").unwrap();
+        assert_eq!(atlas.bytes.len(), (atlas.size.w * atlas.size.h) as usize);
+
+        let a = atlas.font.glyphs[&'A'];
+        assert!(a.size.w > 0. && a.size.h > 0.);
+        assert!(a.advance > a.size.w / 2. && a.advance < 32.);
+
+        // The glyph region is covered, while the padding is not.
+        let region = |x: f32, y: f32| [x * atlas.size.w as f32, y * atlas.size.h as f32];
+        let [x0, y0] = region(a.region.a[0], a.region.a[1]).map(|v| v as u32);
+        let [x1, y1] = region(a.region.b[0], a.region.b[1]).map(|v| v as u32);
+        let pixel = |x: u32, y: u32| atlas.bytes[(y * atlas.size.w + x) as usize];
+        assert!((y0..y1).any(|y| (x0..x1).any(|x| pixel(x, y) > 0)));
+        assert!((0..atlas.size.w).all(|x| pixel(x, 0) == 0));
+    }
+
+    #[test]
+    fn test_missing_glyph_is_notdef() {
+        let atlas = rasterize_font(FONT, 16., "AB?").unwrap();
+
+        let glyphs = &atlas.font.glyphs;
+        assert_eq!(glyphs[&'B'], glyphs[&'?']);
+        assert_ne!(glyphs[&'A'], glyphs[&'B']);
+    }
+}
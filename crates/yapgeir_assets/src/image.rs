@@ -0,0 +1,83 @@
+use anyhow::{bail, Result};
+use yapgeir_geometry::Size;
+use yapgeir_graphics_hal::texture::PixelFormat;
+
+/// Image formats recognized by `decode_image`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    WebP,
+}
+
+impl ImageFormat {
+    /// Detects the format of an encoded image by its magic bytes.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n', ..] => Some(Self::Png),
+            [0xFF, 0xD8, 0xFF, ..] => Some(Self::Jpeg),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some(Self::WebP),
+            _ => None,
+        }
+    }
+}
+
+/// Decodes a PNG, JPEG or WebP image, detecting the format by its magic bytes.
+///
+/// JPEG and WebP decoding require the `jpeg` and `webp` features respectively.
+pub fn decode_image(bytes: &[u8]) -> Result<(Vec<u8>, Size<u32>, PixelFormat)> {
+    match ImageFormat::sniff(bytes) {
+        Some(ImageFormat::Png) => crate::png::decode_png(bytes),
+        #[cfg(feature = "jpeg")]
+        Some(ImageFormat::Jpeg) => crate::jpeg::decode_jpeg(bytes),
+        #[cfg(feature = "webp")]
+        Some(ImageFormat::WebP) => crate::webp::decode_webp(bytes),
+        #[allow(unreachable_patterns)]
+        Some(format) => bail!("Decoding {format:?} images requires a disabled cargo feature"),
+        None => bail!("Unknown image format"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff() {
+        let sniff = ImageFormat::sniff;
+        assert_eq!(
+            sniff(include_bytes!("../fixtures/rgb.png")),
+            Some(ImageFormat::Png)
+        );
+        assert_eq!(
+            sniff(include_bytes!("../fixtures/grey.jpg")),
+            Some(ImageFormat::Jpeg)
+        );
+        assert_eq!(
+            sniff(include_bytes!("../fixtures/rgba.webp")),
+            Some(ImageFormat::WebP)
+        );
+        assert_eq!(sniff(b"RIFF\0\0\0\0WAVE"), None);
+        assert_eq!(sniff(b""), None);
+    }
+
+    #[test]
+    fn test_decode_image() {
+        let (_, size, format) = decode_image(include_bytes!("../fixtures/rgb.png")).unwrap();
+        assert_eq!((size, format), (Size::new(4, 2), PixelFormat::Rgb));
+
+        #[cfg(feature = "jpeg")]
+        {
+            let (_, size, format) = decode_image(include_bytes!("../fixtures/grey.jpg")).unwrap();
+            assert_eq!((size, format), (Size::new(4, 2), PixelFormat::Lumi));
+        }
+
+        #[cfg(feature = "webp")]
+        {
+            let (_, size, format) = decode_image(include_bytes!("../fixtures/rgba.webp")).unwrap();
+            assert_eq!((size, format), (Size::new(4, 2), PixelFormat::Rgba));
+        }
+
+        assert!(decode_image(b"not an image").is_err());
+    }
+}
@@ -0,0 +1,41 @@
+use anyhow::{anyhow, bail, Result};
+use jpeg_decoder::{Decoder, PixelFormat as JpegPixelFormat};
+use yapgeir_geometry::Size;
+use yapgeir_graphics_hal::texture::PixelFormat;
+
+/// Decodes a JPEG image, keeping grayscale images as `PixelFormat::Lumi`.
+///
+/// 16 bit and CMYK images are not supported.
+pub fn decode_jpeg(jpeg: &[u8]) -> Result<(Vec<u8>, Size<u32>, PixelFormat)> {
+    let mut decoder = Decoder::new(jpeg);
+    let pixels = decoder.decode()?;
+    let info = decoder
+        .info()
+        .ok_or_else(|| anyhow!("JPEG image has no header"))?;
+
+    let format = match info.pixel_format {
+        JpegPixelFormat::L8 => PixelFormat::Lumi,
+        JpegPixelFormat::RGB24 => PixelFormat::Rgb,
+        format => bail!("Unsupported JPEG pixel format {format:?}"),
+    };
+
+    Ok((
+        pixels,
+        Size::new(info.width as u32, info.height as u32),
+        format,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_grey() {
+        let (image, size, format) = decode_jpeg(include_bytes!("../fixtures/grey.jpg")).unwrap();
+
+        assert_eq!(format, PixelFormat::Lumi);
+        assert_eq!(size, Size::new(4, 2));
+        assert_eq!(image, [200; 4 * 2]);
+    }
+}
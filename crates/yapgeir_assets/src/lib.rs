@@ -1,3 +1,10 @@
 pub mod animations;
 pub mod atlas;
+pub mod font;
+pub mod image;
+#[cfg(feature = "jpeg")]
+pub mod jpeg;
 pub mod png;
+pub mod texture_loader;
+#[cfg(feature = "webp")]
+pub mod webp;
@@ -1,10 +1,77 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
+use lodepng::{ColorType, Image};
 use rgb::ComponentBytes;
+use yapgeir_geometry::Size;
+use yapgeir_graphics_hal::texture::PixelFormat;
 
-pub fn decode_png(png: &[u8]) -> Result<(Vec<u8>, (u32, u32))> {
-    let image = lodepng::decode32(png)?;
-    let size = (image.width as u32, image.height as u32);
-    let image = image.buffer.as_bytes().to_owned();
+/// Decodes a PNG image, keeping the color type of the source image,
+/// so that grayscale and RGB images are not expanded to RGBA.
+///
+/// Channels are always decoded as 8 bit, and palette images are expanded to RGBA.
+pub fn decode_png(png: &[u8]) -> Result<(Vec<u8>, Size<u32>, PixelFormat)> {
+    let mut decoder = lodepng::Decoder::new();
+    decoder.inspect(png)?;
 
-    Ok((image, size))
+    let (color_type, format) = match decoder.info_png().color.colortype() {
+        ColorType::GREY => (ColorType::GREY, PixelFormat::Lumi),
+        ColorType::GREY_ALPHA => (ColorType::GREY_ALPHA, PixelFormat::Lumia),
+        ColorType::RGB => (ColorType::RGB, PixelFormat::Rgb),
+        _ => (ColorType::RGBA, PixelFormat::Rgba),
+    };
+
+    let (image, width, height) = match lodepng::decode_memory(png, color_type, 8)? {
+        Image::Grey(image) => (
+            image.buffer.as_bytes().to_owned(),
+            image.width,
+            image.height,
+        ),
+        Image::GreyAlpha(image) => (
+            image.buffer.as_bytes().to_owned(),
+            image.width,
+            image.height,
+        ),
+        Image::RGB(image) => (
+            image.buffer.as_bytes().to_owned(),
+            image.width,
+            image.height,
+        ),
+        Image::RGBA(image) => (
+            image.buffer.as_bytes().to_owned(),
+            image.width,
+            image.height,
+        ),
+        _ => bail!("Unexpected decoded PNG color type"),
+    };
+
+    Ok((image, Size::new(width as u32, height as u32), format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_decoded(png: &[u8], format: PixelFormat, bytes_per_pixel: usize) {
+        let (image, size, decoded_format) = decode_png(png).unwrap();
+
+        assert_eq!(decoded_format, format);
+        assert_eq!(size, Size::new(4, 2));
+        assert_eq!(image.len(), 4 * 2 * bytes_per_pixel);
+    }
+
+    #[test]
+    fn test_decode_color_types() {
+        assert_decoded(include_bytes!("../fixtures/grey.png"), PixelFormat::Lumi, 1);
+        assert_decoded(
+            include_bytes!("../fixtures/grey_alpha.png"),
+            PixelFormat::Lumia,
+            2,
+        );
+        assert_decoded(include_bytes!("../fixtures/rgb.png"), PixelFormat::Rgb, 3);
+        assert_decoded(include_bytes!("../fixtures/rgba.png"), PixelFormat::Rgba, 4);
+        assert_decoded(
+            include_bytes!("../fixtures/palette.png"),
+            PixelFormat::Rgba,
+            4,
+        );
+    }
 }
@@ -0,0 +1,243 @@
+use std::{borrow::Cow, collections::VecDeque, rc::Rc};
+
+use anyhow::Result;
+use yapgeir_geometry::Size;
+use yapgeir_graphics_hal::{texture::PixelFormat, Graphics};
+use yapgeir_realm::{Realm, Res, ResMut};
+
+use crate::png::decode_png;
+
+/// Image data of a texture to be loaded.
+pub enum TextureSource {
+    /// An encoded PNG image, which is decoded on the main thread right before the upload.
+    Png(Cow<'static, [u8]>),
+    /// Already decoded pixel data, e.g. decoded on a background thread.
+    Pixels {
+        bytes: Vec<u8>,
+        size: Size<u32>,
+        format: PixelFormat,
+    },
+}
+
+impl TextureSource {
+    fn decode(self) -> Result<(Vec<u8>, Size<u32>, PixelFormat)> {
+        match self {
+            TextureSource::Png(png) => decode_png(&png),
+            TextureSource::Pixels {
+                bytes,
+                size,
+                format,
+            } => Ok((bytes, size, format)),
+        }
+    }
+}
+
+/// A handle of a queued texture, which can be resolved to a texture once it is uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextureHandle(usize);
+
+enum Slot<T> {
+    Pending,
+    Ready(Rc<T>),
+    Failed,
+    Removed,
+}
+
+struct LoadQueue<T> {
+    queue: VecDeque<(TextureHandle, TextureSource)>,
+    slots: Vec<Slot<T>>,
+}
+
+impl<T> LoadQueue<T> {
+    fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            slots: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, source: TextureSource) -> TextureHandle {
+        let handle = TextureHandle(self.slots.len());
+        self.slots.push(Slot::Pending);
+        self.queue.push_back((handle, source));
+        handle
+    }
+
+    /// Decodes and uploads at most `budget` queued textures.
+    fn process(
+        &mut self,
+        budget: usize,
+        mut upload: impl FnMut(Vec<u8>, Size<u32>, PixelFormat) -> T,
+    ) {
+        for _ in 0..budget {
+            let Some((handle, source)) = self.queue.pop_front() else {
+                return;
+            };
+
+            self.slots[handle.0] = match source.decode() {
+                Ok((bytes, size, format)) => Slot::Ready(Rc::new(upload(bytes, size, format))),
+                Err(e) => {
+                    eprintln!("Unable to load texture {handle:?}: {e}");
+                    Slot::Failed
+                }
+            };
+        }
+    }
+
+    fn get(&self, handle: TextureHandle) -> Option<&Rc<T>> {
+        match &self.slots[handle.0] {
+            Slot::Ready(texture) => Some(texture),
+            Slot::Pending | Slot::Failed | Slot::Removed => None,
+        }
+    }
+
+    /// Releases the slot of the handle, cancelling the load if it is still queued.
+    fn take(&mut self, handle: TextureHandle) -> Option<Rc<T>> {
+        match std::mem::replace(&mut self.slots[handle.0], Slot::Removed) {
+            Slot::Ready(texture) => Some(texture),
+            Slot::Pending => {
+                self.queue.retain(|(queued, _)| *queued != handle);
+                None
+            }
+            Slot::Failed | Slot::Removed => None,
+        }
+    }
+
+    fn is_failed(&self, handle: TextureHandle) -> bool {
+        matches!(self.slots[handle.0], Slot::Failed)
+    }
+}
+
+/// A resource that loads textures over multiple frames, instead of stalling a single one.
+///
+/// Queued textures are uploaded by a system added by `plugin`, at most
+/// `uploads_per_frame` textures on every frame.
+pub struct TextureLoader<G: Graphics> {
+    queue: LoadQueue<G::Texture>,
+    pub uploads_per_frame: usize,
+}
+
+impl<G: Graphics> TextureLoader<G> {
+    pub fn new(uploads_per_frame: usize) -> Self {
+        Self {
+            queue: LoadQueue::new(),
+            uploads_per_frame,
+        }
+    }
+
+    /// Queue a texture for loading, returning a handle to it.
+    pub fn load(&mut self, source: TextureSource) -> TextureHandle {
+        self.queue.push(source)
+    }
+
+    /// Returns the texture if it has already been uploaded.
+    pub fn get(&self, handle: TextureHandle) -> Option<&Rc<G::Texture>> {
+        self.queue.get(handle)
+    }
+
+    /// Removes the texture from the loader, returning it if it has already been uploaded.
+    ///
+    /// The loader no longer keeps the texture alive, so it's dropped together with the
+    /// last clone of the returned `Rc`. A texture that is still queued is never uploaded.
+    pub fn take(&mut self, handle: TextureHandle) -> Option<Rc<G::Texture>> {
+        self.queue.take(handle)
+    }
+
+    /// Returns true if the texture could not be decoded, and will never be resolved.
+    pub fn is_failed(&self, handle: TextureHandle) -> bool {
+        self.queue.is_failed(handle)
+    }
+
+    /// Number of textures waiting to be uploaded.
+    pub fn pending(&self) -> usize {
+        self.queue.queue.len()
+    }
+}
+
+fn upload_textures<G: Graphics>(graphics: Res<G>, mut loader: ResMut<TextureLoader<G>>) {
+    let budget = loader.uploads_per_frame;
+    loader.queue.process(budget, |bytes, size, format| {
+        graphics.new_texture(format, size, Some(&bytes))
+    });
+}
+
+/// Adds a `TextureLoader` uploading a single texture per frame.
+pub fn plugin<G: Graphics>(realm: &mut Realm) {
+    realm
+        .add_resource(TextureLoader::<G>::new(1))
+        .add_system(upload_textures::<G>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixels() -> TextureSource {
+        TextureSource::Pixels {
+            bytes: vec![0; 4],
+            size: Size::new(1, 1),
+            format: PixelFormat::Rgba,
+        }
+    }
+
+    #[test]
+    fn test_one_upload_per_frame() {
+        let mut queue = LoadQueue::<usize>::new();
+        let handles: Vec<_> = (0..5).map(|_| queue.push(pixels())).collect();
+
+        let mut uploaded = 0;
+        for (frame, handle) in handles.iter().enumerate() {
+            queue.process(1, |_, _, _| {
+                uploaded += 1;
+                uploaded
+            });
+
+            assert!(queue.get(*handle).is_some());
+            assert_eq!(
+                handles.iter().filter(|h| queue.get(**h).is_some()).count(),
+                frame + 1
+            );
+        }
+
+        assert_eq!(uploaded, handles.len());
+        assert!(queue.queue.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_png_fails() {
+        let mut queue = LoadQueue::<()>::new();
+        let handle = queue.push(TextureSource::Png(Cow::Borrowed(&b"not a png"[..])));
+
+        queue.process(1, |_, _, _| ());
+        assert!(queue.is_failed(handle));
+        assert!(queue.get(handle).is_none());
+    }
+
+    #[test]
+    fn test_taken_texture_is_dropped() {
+        let mut queue = LoadQueue::<()>::new();
+        let handle = queue.push(pixels());
+        queue.process(1, |_, _, _| ());
+
+        let texture = Rc::downgrade(queue.get(handle).unwrap());
+        let taken = queue.take(handle);
+        assert!(taken.is_some());
+        assert!(queue.get(handle).is_none());
+        assert!(queue.take(handle).is_none());
+
+        drop(taken);
+        assert!(texture.upgrade().is_none());
+    }
+
+    #[test]
+    fn test_taking_pending_texture_cancels_upload() {
+        let mut queue = LoadQueue::<()>::new();
+        let pending = queue.push(pixels());
+        let next = queue.push(pixels());
+
+        assert!(queue.take(pending).is_none());
+        queue.process(1, |_, _, _| ());
+        assert!(queue.get(pending).is_none());
+        assert!(queue.get(next).is_some());
+    }
+}
@@ -0,0 +1,40 @@
+use std::io::Cursor;
+
+use anyhow::{anyhow, Result};
+use image_webp::WebPDecoder;
+use yapgeir_geometry::Size;
+use yapgeir_graphics_hal::texture::PixelFormat;
+
+/// Decodes a lossy or lossless WebP image as RGB, or RGBA if it has an alpha channel.
+///
+/// Animated images are decoded as their first frame.
+pub fn decode_webp(webp: &[u8]) -> Result<(Vec<u8>, Size<u32>, PixelFormat)> {
+    let mut decoder = WebPDecoder::new(Cursor::new(webp))?;
+    let (width, height) = decoder.dimensions();
+    let format = match decoder.has_alpha() {
+        true => PixelFormat::Rgba,
+        false => PixelFormat::Rgb,
+    };
+
+    let len = decoder
+        .output_buffer_size()
+        .ok_or_else(|| anyhow!("WebP image is too large"))?;
+    let mut pixels = vec![0; len];
+    decoder.read_image(&mut pixels)?;
+
+    Ok((pixels, Size::new(width, height), format))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_lossless() {
+        let (image, size, format) = decode_webp(include_bytes!("../fixtures/rgba.webp")).unwrap();
+
+        assert_eq!(format, PixelFormat::Rgba);
+        assert_eq!(size, Size::new(4, 2));
+        assert_eq!(image, [10, 20, 30, 128].repeat(4 * 2));
+    }
+}
@@ -44,6 +44,8 @@ impl ToTokens for PersistentSlotMapKeys {
             .to_owned()
             .unwrap_or_else(|| parse_quote!(std::string::String));
 
+        let field_idents = fields.iter().map(|field| field.ident.as_ref().unwrap());
+
         let fields = fields.iter().filter_map(|field| {
             let field_ident = field.ident.as_ref().unwrap();
 
@@ -81,6 +83,10 @@ impl ToTokens for PersistentSlotMapKeys {
                         #(#fields,)*
                     }
                 }
+
+                fn is_valid<V>(&self, slot_map: &yapgeir_collections::PersistentSlotMap<#key_ty, V>) -> bool {
+                    true #(&& slot_map.contains(self.#field_idents.clone().into()))*
+                }
             }
         });
     }
@@ -1,3 +1,7 @@
 pub use persistent_slot_map::*;
 
+// Allows using the derive macros, which refer to `yapgeir_collections`, in tests of this crate.
+#[cfg(test)]
+extern crate self as yapgeir_collections;
+
 mod persistent_slot_map;
@@ -3,8 +3,15 @@ use std::{borrow::Borrow, collections::HashMap, ops::Index};
 
 pub use yapgeir_collections_macro::PersistentSlotMapKeys;
 
+/// A reference to a value in a `PersistentSlotMap`.
+///
+/// The generation distinguishes values which were stored at the same index:
+/// a slot of a removed value never refers to a value inserted later.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Slot(pub usize);
+pub struct Slot {
+    pub index: usize,
+    pub generation: u32,
+}
 
 /// A collection that allows storing a heap of same-type structures
 /// with a true O(1) access time.
@@ -19,9 +26,10 @@ pub struct Slot(pub usize);
 /// for all of the keys (for example hardcoded in the code), and then
 /// using the slot to reference the data.
 ///
-/// This collection is not generational and persistent,
-/// meaning that the data inserted is only cleaned when the
-/// PersistentSlotMap is dropped itself.
+/// This collection is persistent, meaning that the data inserted is only
+/// cleaned when it's explicitly removed, or when the PersistentSlotMap is dropped itself.
+/// Slots of removed values are reused, but slots are generational, so a stale slot
+/// doesn't alias a value inserted later.
 ///
 /// An insert with the existing key will overwrite the data and
 /// return the existing slot.
@@ -34,18 +42,31 @@ pub struct Slot(pub usize);
 /// will still remain valid in that case.
 #[derive(Debug)]
 pub struct PersistentSlotMap<K, V> {
-    slots: Vec<V>,
+    slots: Vec<Entry<V>>,
+    free: Vec<usize>,
     key_to_slot: HashMap<K, Slot>,
 }
 
+#[derive(Debug)]
+struct Entry<V> {
+    generation: u32,
+    value: Option<V>,
+}
+
+/// A set of slots resolved by their keys, see the `PersistentSlotMapKeys` derive macro.
 pub trait PersistentSlotMapKeys<K> {
     fn new<V>(slot_map: &PersistentSlotMap<K, V>) -> Self;
+
+    /// Returns false if any of the slots is stale, e.g. because its value was removed,
+    /// in which case the slots should be resolved again with `new`.
+    fn is_valid<V>(&self, slot_map: &PersistentSlotMap<K, V>) -> bool;
 }
 
 impl<K, V> Default for PersistentSlotMap<K, V> {
     fn default() -> Self {
         Self {
             slots: Default::default(),
+            free: Default::default(),
             key_to_slot: Default::default(),
         }
     }
@@ -53,22 +74,65 @@ impl<K, V> Default for PersistentSlotMap<K, V> {
 
 impl<K: Eq + Hash, V> PersistentSlotMap<K, V> {
     pub fn insert(&mut self, key: K, value: V) -> Slot {
-        match self.key_to_slot.get(&key) {
-            Some(&Slot(index)) => {
-                self.slots[index] = value;
-                Slot(index)
+        if let Some(&slot) = self.key_to_slot.get(&key) {
+            self.slots[slot.index].value = Some(value);
+            return slot;
+        }
+
+        let slot = match self.free.pop() {
+            Some(index) => {
+                let entry = &mut self.slots[index];
+                entry.value = Some(value);
+                Slot {
+                    index,
+                    generation: entry.generation,
+                }
             }
             None => {
-                let index = self.slots.len();
-                self.slots.push(value);
-                self.key_to_slot.insert(key, Slot(index));
-                Slot(index)
+                self.slots.push(Entry {
+                    generation: 0,
+                    value: Some(value),
+                });
+                Slot {
+                    index: self.slots.len() - 1,
+                    generation: 0,
+                }
             }
-        }
+        };
+
+        self.key_to_slot.insert(key, slot);
+        slot
+    }
+
+    /// Removes a value along with its key, and frees the slot for reuse.
+    /// Returns `None` if the slot is stale.
+    ///
+    /// Takes linear time in the number of keys, since keys are only mapped to slots.
+    pub fn remove(&mut self, slot: Slot) -> Option<V> {
+        let entry = self
+            .slots
+            .get_mut(slot.index)
+            .filter(|entry| entry.generation == slot.generation)?;
+        let value = entry.value.take()?;
+
+        entry.generation = entry.generation.wrapping_add(1);
+        self.free.push(slot.index);
+        self.key_to_slot.retain(|_, s| *s != slot);
+
+        Some(value)
+    }
+
+    /// Returns true if the slot refers to a value, and is not stale.
+    pub fn contains(&self, slot: Slot) -> bool {
+        self.get(slot).is_some()
     }
 
     pub fn get(&self, slot: Slot) -> Option<&V> {
-        self.slots.get(slot.0)
+        self.slots
+            .get(slot.index)
+            .filter(|entry| entry.generation == slot.generation)?
+            .value
+            .as_ref()
     }
 
     pub fn find_slot_by_key<Q: ?Sized>(&self, key: &Q) -> Option<Slot>
@@ -84,7 +148,14 @@ impl<K, V> Index<Slot> for PersistentSlotMap<K, V> {
     type Output = V;
 
     fn index(&self, slot: Slot) -> &Self::Output {
-        &self.slots[slot.0]
+        match self.slots.get(slot.index) {
+            Some(Entry {
+                generation,
+                value: Some(value),
+            }) if *generation == slot.generation => value,
+            Some(_) => panic!("Slot {slot:?} is stale, its value was removed"),
+            None => panic!("Slot {slot:?} is out of bounds"),
+        }
     }
 }
 
@@ -92,7 +163,78 @@ impl<K: Eq + Hash, V> Index<&K> for PersistentSlotMap<K, V> {
     type Output = V;
 
     fn index(&self, key: &K) -> &Self::Output {
-        let index = self.key_to_slot[key];
-        &self.slots[index.0]
+        &self[self.key_to_slot[key]]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remove_and_reuse_slot() {
+        let mut map = PersistentSlotMap::default();
+        let walk = map.insert("walk", 1);
+        let run = map.insert("run", 2);
+
+        assert_eq!(map.remove(walk), Some(1));
+        assert_eq!(map.remove(walk), None);
+        assert_eq!(map.get(walk), None);
+        assert_eq!(map.find_slot_by_key("walk"), None);
+
+        // The freed slot is reused, but the stale slot doesn't alias the new value.
+        let jump = map.insert("jump", 3);
+        assert_eq!(jump.index, walk.index);
+        assert_ne!(jump, walk);
+        assert_eq!(map.get(walk), None);
+        assert_eq!(map.remove(walk), None);
+        assert_eq!(map[jump], 3);
+
+        // Other slots and keys stay valid.
+        assert_eq!(map[run], 2);
+        assert_eq!(map[&"jump"], 3);
+        assert_eq!(map.find_slot_by_key("run"), Some(run));
+
+        // Reinserting a removed key gives it a new slot.
+        let walk_again = map.insert("walk", 4);
+        assert_ne!(walk_again, walk);
+        assert_eq!(map[walk_again], 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "stale")]
+    fn test_index_stale_slot() {
+        let mut map = PersistentSlotMap::default();
+        let slot = map.insert("walk", 1);
+        map.remove(slot);
+        map.insert("run", 2);
+        let _ = map[slot];
+    }
+
+    #[derive(PersistentSlotMapKeys)]
+    struct Keys {
+        walk: Slot,
+        run: Slot,
+    }
+
+    #[test]
+    fn test_stale_keys() {
+        let mut map = PersistentSlotMap::default();
+        map.insert("walk".to_owned(), 1);
+        map.insert("run".to_owned(), 2);
+        let keys = Keys::new(&map);
+        assert!(keys.is_valid(&map));
+
+        // A removed and reinserted value gets a new slot, so the old one doesn't alias it.
+        assert_eq!(map.remove(keys.walk), Some(1));
+        map.insert("walk".to_owned(), 3);
+        assert_eq!(map.get(keys.walk), None);
+        assert!(!map.contains(keys.walk));
+        assert!(!keys.is_valid(&map));
+
+        let keys = Keys::new(&map);
+        assert!(keys.is_valid(&map));
+        assert_eq!(map[keys.walk], 3);
+        assert_eq!(map[keys.run], 2);
     }
 }
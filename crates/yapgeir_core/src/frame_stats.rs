@@ -1,3 +1,6 @@
+use std::collections::VecDeque;
+
+use smart_default::SmartDefault;
 use yapgeir_realm::{Realm, Res, ResMut};
 
 #[cfg(feature = "reflection")]
@@ -8,24 +11,71 @@ use yapgeir_reflection::{
 
 use crate::Delta;
 
-#[derive(Default)]
+#[derive(SmartDefault)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
 pub struct FrameStats {
     pub frames: u64,
     pub average_fps: f32,
+    /// Number of recent frames used for the smoothed FPS and frame time percentiles.
+    /// Can be changed at any time, and the oldest frames will be dropped on the next frame.
+    #[default(120)]
+    pub window: usize,
     fps_cache: u64,
     fps_time: f64,
+    /// Durations of the recent frames in seconds, oldest first.
+    #[cfg_attr(feature = "reflection", reflect(ignore))]
+    frame_times: VecDeque<f32>,
+}
+
+impl FrameStats {
+    fn push(&mut self, delta: f32) {
+        self.frame_times.push_back(delta);
+        while self.frame_times.len() > self.window.max(1) {
+            self.frame_times.pop_front();
+        }
+    }
+
+    /// Average FPS over the recent frames in the window, 0 if no frames were recorded.
+    pub fn smoothed_fps(&self) -> f32 {
+        let time: f32 = self.frame_times.iter().sum();
+        match time > 0. {
+            true => self.frame_times.len() as f32 / time,
+            false => 0.,
+        }
+    }
+
+    /// Returns the frame time in seconds, that `percentile`% of the recent frames
+    /// don't exceed, e.g. `frame_time_percentile(99.)` for the p99 frame time.
+    pub fn frame_time_percentile(&self, percentile: f32) -> f32 {
+        if self.frame_times.is_empty() {
+            return 0.;
+        }
+
+        let mut sorted: Vec<f32> = self.frame_times.iter().copied().collect();
+        sorted.sort_by(f32::total_cmp);
+
+        // Nearest-rank method
+        let rank = (percentile.clamp(0., 100.) / 100. * sorted.len() as f32).ceil() as usize;
+        sorted[rank.clamp(1, sorted.len()) - 1]
+    }
 }
 
 fn update(mut frame: ResMut<FrameStats>, delta: Res<Delta>) {
+    frame.push(**delta);
+
     frame.fps_cache += 1;
     frame.fps_time += **delta as f64;
     if frame.fps_time >= 1f64 {
         frame.average_fps = (frame.fps_cache as f64 / frame.fps_time) as f32;
 
         println!(
-            "FPS: {}, frames: {}, time: {}, lastDelta: {}",
-            frame.average_fps, frame.fps_cache, frame.fps_time, **delta
+            "FPS: {}, smoothed: {:.1}, frame time p50/p95/p99: {:.2}/{:.2}/{:.2} ms, frames: {}",
+            frame.average_fps,
+            frame.smoothed_fps(),
+            frame.frame_time_percentile(50.) * 1000.,
+            frame.frame_time_percentile(95.) * 1000.,
+            frame.frame_time_percentile(99.) * 1000.,
+            frame.fps_cache,
         );
 
         frame.fps_cache = 0;
@@ -41,3 +91,28 @@ pub fn plugin(realm: &mut Realm) {
 
     realm.initialize_resource::<FrameStats>().add_system(update);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percentiles() {
+        let mut stats = FrameStats {
+            window: 100,
+            ..Default::default()
+        };
+
+        // Frames outside of the window are dropped.
+        stats.push(1.);
+        for ms in 1..=100 {
+            stats.push(ms as f32 / 1000.);
+        }
+
+        assert_eq!(stats.frame_time_percentile(50.), 0.05);
+        assert_eq!(stats.frame_time_percentile(95.), 0.095);
+        assert_eq!(stats.frame_time_percentile(99.), 0.099);
+        assert_eq!(stats.frame_time_percentile(100.), 0.1);
+        assert!((stats.smoothed_fps() - 100. / 5.05).abs() < 1e-3);
+    }
+}
@@ -2,6 +2,7 @@ use std::borrow::Cow;
 
 use derive_more::{Constructor, Deref};
 use smart_default::SmartDefault;
+use yapgeir_realm::{IntoSystem, Res, System};
 
 #[cfg(feature = "reflection")]
 use yapgeir_reflection::bevy_reflect::{self, Reflect};
@@ -13,6 +14,8 @@ use yapgeir_reflection::bevy_reflect::{self, Reflect};
 pub mod __reflection_stubs;
 
 pub mod frame_stats;
+#[cfg(feature = "hecs")]
+pub mod named_index;
 
 /// A resource that holds time that passed since the previous frame in seconds.
 #[derive(Default, Clone, Copy, Deref, Debug, PartialEq)]
@@ -37,6 +40,51 @@ pub struct WindowSize {
     pub h: u32,
 }
 
+/// An event sent by the window manager plugin when the window is resized.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WindowResized {
+    /// New window size in pixels.
+    pub size: WindowSize,
+    pub ppt: ScreenPpt,
+}
+
+/// State of the graphics context. Refreshed automatically by the graphics plugin on each frame.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "reflection", derive(Reflect))]
+pub struct GraphicsState {
+    /// True while the graphics context is lost, and nothing can be rendered.
+    pub context_lost: bool,
+}
+
+impl GraphicsState {
+    /// Updates the state with the availability of the context reported by the graphics backend,
+    /// and returns the event to send if it has changed.
+    pub fn update(&mut self, context_lost: bool) -> Option<GraphicsContextEvent> {
+        if context_lost == self.context_lost {
+            return None;
+        }
+
+        self.context_lost = context_lost;
+        match context_lost {
+            true => Some(GraphicsContextEvent::Lost),
+            false => Some(GraphicsContextEvent::Restored),
+        }
+    }
+}
+
+/// An event sent by the graphics plugin when the graphics context is lost or restored.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphicsContextEvent {
+    Lost,
+    Restored,
+}
+
+/// A run condition which is false while the graphics context is lost,
+/// e.g. `render.run_if(graphics_available())`.
+pub fn graphics_available() -> impl System<bool> {
+    (|state: Option<Res<GraphicsState>>| !state.is_some_and(|state| state.context_lost)).system()
+}
+
 /// A component that can be used for debugging your entities. A name is either an owned string,
 /// or a reference with a 'static lifetime.
 #[derive(Default, Debug, Clone, PartialEq, Eq, Deref)]
@@ -49,3 +97,44 @@ impl Named {
         Self(name.into())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use yapgeir_realm::{IntoFilteredSystem, Realm};
+
+    use super::*;
+
+    #[test]
+    fn test_rendering_paused_while_context_lost() {
+        let rendered = Rc::new(Cell::new(0u32));
+        let render = || {
+            (|rendered: Res<Rc<Cell<u32>>>| rendered.set(rendered.get() + 1))
+                .run_if(graphics_available())
+        };
+
+        let mut realm = Realm::default();
+        realm
+            .add_resource(rendered.clone())
+            .add_resource(GraphicsState { context_lost: true })
+            .run_system(render());
+        assert_eq!(rendered.get(), 0);
+
+        realm
+            .add_resource(GraphicsState::default())
+            .run_system(render());
+        assert_eq!(rendered.get(), 1);
+    }
+
+    #[test]
+    fn test_graphics_state_events() {
+        let mut state = GraphicsState::default();
+        assert_eq!(state.update(false), None);
+        assert_eq!(state.update(true), Some(GraphicsContextEvent::Lost));
+        assert_eq!(state.update(true), None);
+        assert!(state.context_lost);
+        assert_eq!(state.update(false), Some(GraphicsContextEvent::Restored));
+        assert!(!state.context_lost);
+    }
+}
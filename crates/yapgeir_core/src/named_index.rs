@@ -0,0 +1,174 @@
+use std::{borrow::Cow, collections::HashMap};
+
+use hecs::{ChangeTracker, Entity, World};
+use yapgeir_events::Events;
+use yapgeir_realm::{Realm, ResMut};
+
+use crate::Named;
+
+/// A resource mapping names of entities with a `Named` component to the entities,
+/// so that they can be found without scanning the whole world.
+///
+/// Only entities whose `Named` component was added, changed or removed since the previous
+/// update are reindexed. If multiple entities have the same name, the most recently named
+/// one wins, and a `DuplicateName` event is sent.
+pub struct NamedIndex {
+    tracker: ChangeTracker<Named>,
+    /// Entities with each name, the most recently named one being the last.
+    entities: HashMap<Cow<'static, str>, Vec<Entity>>,
+    /// Names of all indexed entities, used to remove renamed and despawned entities.
+    names: HashMap<Entity, Cow<'static, str>>,
+}
+
+/// An event sent by the `NamedIndex` plugin when an entity is given a name
+/// which another entity already has.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateName {
+    pub name: Named,
+    /// The entity which was found by this name until now.
+    pub previous: Entity,
+    pub entity: Entity,
+}
+
+impl Default for NamedIndex {
+    fn default() -> Self {
+        Self {
+            tracker: ChangeTracker::new(),
+            entities: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+}
+
+impl NamedIndex {
+    /// Returns the entity with the given name.
+    pub fn get(&self, name: &str) -> Option<Entity> {
+        self.entities.get(name)?.last().copied()
+    }
+
+    fn insert(&mut self, entity: Entity, name: Cow<'static, str>) -> Option<DuplicateName> {
+        self.names.insert(entity, name.clone());
+        let entities = self.entities.entry(name.clone()).or_default();
+        let previous = entities.last().copied();
+        entities.push(entity);
+
+        previous.map(|previous| DuplicateName {
+            name: Named(name),
+            previous,
+            entity,
+        })
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(name) = self.names.remove(&entity) else {
+            return;
+        };
+
+        if let Some(entities) = self.entities.get_mut(&name) {
+            // A duplicate that lost to this entity takes over once it's gone.
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.entities.remove(&name);
+            }
+        }
+    }
+
+    /// Reindexes entities with changed names, returning the duplicate names found.
+    fn update(&mut self, world: &mut World) -> Vec<DuplicateName> {
+        // The change tracker doesn't report despawned entities.
+        let despawned: Vec<Entity> = self
+            .names
+            .keys()
+            .filter(|entity| !world.contains(**entity))
+            .copied()
+            .collect();
+
+        let mut changes = self.tracker.track(world);
+        let added: Vec<_> = (changes.added())
+            .map(|(entity, name)| (entity, name.0.clone()))
+            .collect();
+        let changed: Vec<_> = (changes.changed())
+            .map(|(entity, _, name)| (entity, name.0.clone()))
+            .collect();
+        let removed: Vec<_> = changes.removed().map(|(entity, _)| entity).collect();
+        drop(changes);
+
+        let renamed = changed.iter().map(|(entity, _)| *entity);
+        for entity in despawned.into_iter().chain(removed).chain(renamed) {
+            self.remove(entity);
+        }
+
+        (added.into_iter().chain(changed))
+            .filter_map(|(entity, name)| self.insert(entity, name))
+            .collect()
+    }
+}
+
+fn update_named_index(
+    mut world: ResMut<World>,
+    mut index: ResMut<NamedIndex>,
+    mut duplicates: ResMut<Events<DuplicateName>>,
+) {
+    duplicates.extend(index.update(&mut world));
+}
+
+/// Adds a `NamedIndex` updated on every frame, and `DuplicateName` events.
+pub fn plugin(realm: &mut Realm) {
+    realm
+        .add_plugin(yapgeir_events::plugin::<DuplicateName>)
+        .initialize_resource::<NamedIndex>()
+        .add_system(update_named_index);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let mut world = World::new();
+        let player = world.spawn((Named::new("Player"),));
+        let anchor = world.spawn((Named::new("Anchor"),));
+
+        let mut index = NamedIndex::default();
+        assert_eq!(index.update(&mut world), vec![]);
+        assert_eq!(index.get("Player"), Some(player));
+        assert_eq!(index.get("Anchor"), Some(anchor));
+        assert_eq!(index.get("Enemy"), None);
+
+        world.despawn(anchor).unwrap();
+        world.insert_one(player, Named::new("Hero")).unwrap();
+        index.update(&mut world);
+        assert_eq!(index.get("Anchor"), None);
+        assert_eq!(index.get("Player"), None);
+        assert_eq!(index.get("Hero"), Some(player));
+
+        world.remove_one::<Named>(player).unwrap();
+        index.update(&mut world);
+        assert_eq!(index.get("Hero"), None);
+    }
+
+    #[test]
+    fn test_duplicate_names() {
+        let mut world = World::new();
+        let first = world.spawn((Named::new("Enemy"),));
+
+        let mut index = NamedIndex::default();
+        index.update(&mut world);
+
+        let second = world.spawn((Named::new("Enemy"),));
+        assert_eq!(
+            index.update(&mut world),
+            vec![DuplicateName {
+                name: Named::new("Enemy"),
+                previous: first,
+                entity: second,
+            }]
+        );
+        assert_eq!(index.get("Enemy"), Some(second));
+
+        world.despawn(second).unwrap();
+        index.update(&mut world);
+        assert_eq!(index.get("Enemy"), Some(first));
+    }
+}
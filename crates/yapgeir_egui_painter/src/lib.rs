@@ -1,15 +1,19 @@
-use std::{collections::HashMap, mem::size_of};
+use std::{
+    any::{Any, TypeId},
+    collections::{HashMap, HashSet},
+    mem::size_of,
+};
 
 use bytemuck::{Pod, Zeroable};
 use egui::{
     epaint::{textures::TextureFilter, Primitive, Vertex},
-    ClippedPrimitive, Color32, TexturesDelta,
+    ClippedPrimitive, Color32, PaintCallback, PaintCallbackInfo, TextureOptions, TexturesDelta,
 };
 use yapgeir_graphics_hal::{
     buffer::{Buffer, BufferKind, BufferUsage},
     draw_descriptor::VertexBindings,
     draw_params::{Blend, DrawParameters},
-    frame_buffer::{FrameBuffer, Indices},
+    frame_buffer::{DepthStencilAttachment, FlipSource, FrameBuffer, Indices},
     index_buffer::PrimitiveMode,
     sampler::{Filter, MinFilter, Sampler, SamplerState, WrapFunction},
     samplers::SamplerAttribute,
@@ -71,6 +75,7 @@ const SHADER: TextShaderSource = TextShaderSource {
             gl_FragColor = v_rgba_gamma * texture2D(u_sampler, v_tc);
         }
     "#,
+    defines: &[],
 };
 
 #[cfg(target_os = "vita")]
@@ -106,6 +111,7 @@ const SHADER: TextShaderSource = TextShaderSource {
             return v_rgba_gamma * tex2D(u_sampler, v_tc);
         }
     "#,
+    defines: &[],
 };
 
 const VERTEX_FORMAT: &'static [VertexAttribute] = &[
@@ -114,18 +120,21 @@ const VERTEX_FORMAT: &'static [VertexAttribute] = &[
         offset: 0,
         kind: AttributeKind::F32,
         size: VectorSize::N2,
+        divisor: 0,
     },
     VertexAttribute {
         name: "a_tc",
         offset: 8,
         kind: AttributeKind::F32,
         size: VectorSize::N2,
+        divisor: 0,
     },
     VertexAttribute {
         name: "a_srgba",
         offset: 16,
         kind: AttributeKind::U8,
         size: VectorSize::N4,
+        divisor: 0,
     },
 ];
 
@@ -193,10 +202,83 @@ impl<G: Graphics> DrawResources<G> {
     }
 }
 
+type CallbackHandler<FB> = Box<dyn Fn(&dyn Any, &FB, &PaintCallbackInfo)>;
+
+/// Handlers of `egui::PaintCallback`s, keyed by the type of the callback value.
+struct CallbackHandlers<FB> {
+    handlers: HashMap<TypeId, CallbackHandler<FB>>,
+    /// Callback types without a handler, which were already reported.
+    unhandled: HashSet<TypeId>,
+}
+
+impl<FB> Default for CallbackHandlers<FB> {
+    fn default() -> Self {
+        Self {
+            handlers: HashMap::new(),
+            unhandled: HashSet::new(),
+        }
+    }
+}
+
+impl<FB> CallbackHandlers<FB> {
+    fn insert<T: Any>(&mut self, handler: impl Fn(&T, &FB, &PaintCallbackInfo) + 'static) {
+        self.handlers.insert(
+            TypeId::of::<T>(),
+            Box::new(move |callback, fb, info| {
+                if let Some(callback) = callback.downcast_ref::<T>() {
+                    handler(callback, fb, info);
+                }
+            }),
+        );
+    }
+
+    fn call(&mut self, callback: &PaintCallback, fb: &FB, info: &PaintCallbackInfo) {
+        let value: &dyn Any = callback.callback.as_ref();
+        match self.handlers.get(&value.type_id()) {
+            Some(handler) => handler(value, fb, info),
+            None => {
+                if self.unhandled.insert(value.type_id()) {
+                    eprintln!(
+                        "No handler registered for egui paint callback {:?}, skipping",
+                        value.type_id()
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Distinct sampler states used by egui textures.
+///
+/// Egui uses only a few filtering options, so textures refer to a shared state by its index,
+/// and the graphics implementation can reuse a single sampler object for each of them.
+#[derive(Default)]
+struct SamplerStates(Vec<SamplerState>);
+
+impl SamplerStates {
+    fn key(&mut self, state: SamplerState) -> usize {
+        match self.0.iter().position(|s| *s == state) {
+            Some(key) => key,
+            None => {
+                self.0.push(state);
+                self.0.len() - 1
+            }
+        }
+    }
+}
+
+struct EguiTexture<G: Graphics> {
+    texture: Rc<G::Texture>,
+    /// Index of the sampler state in `SamplerStates`.
+    state: usize,
+}
+
 pub struct EguiPainter<G: Graphics> {
     resources: DrawResources<G>,
     uniform_buffer: G::UniformBuffer<EguiUniforms>,
-    samplers: HashMap<egui::TextureId, Sampler<G, G::Texture>>,
+    textures: HashMap<egui::TextureId, EguiTexture<G>>,
+    sampler_states: SamplerStates,
+    callbacks: CallbackHandlers<G::FrameBuffer>,
 }
 
 impl<G: Graphics> EguiPainter<G> {
@@ -206,10 +288,25 @@ impl<G: Graphics> EguiPainter<G> {
                 screen_size: [0., 0.],
             }),
             resources: DrawResources::new(ctx),
-            samplers: Default::default(),
+            textures: Default::default(),
+            sampler_states: Default::default(),
+            callbacks: Default::default(),
         }
     }
 
+    /// Register a handler for `egui::PaintCallback`s with a callback value of type `T`.
+    ///
+    /// The handler is called in the middle of painting, and can draw custom geometry
+    /// to the frame buffer. It should set its own scissor from the clip rectangle of
+    /// `PaintCallbackInfo` to stay within the egui region.
+    /// Callbacks without a registered handler are skipped.
+    pub fn add_callback_handler<T: Any>(
+        &mut self,
+        handler: impl Fn(&T, &G::FrameBuffer, &PaintCallbackInfo) + 'static,
+    ) {
+        self.callbacks.insert(handler);
+    }
+
     pub fn paint(
         &mut self,
         fb: &G::FrameBuffer,
@@ -238,14 +335,22 @@ impl<G: Graphics> EguiPainter<G> {
                 Primitive::Mesh(mesh) => {
                     self.paint_mesh(fb, pixels_per_point, &m.clip_rect, &mesh);
                 }
-                Primitive::Callback(_) => {
-                    panic!("Custom rendering callbacks are not implemented");
+                Primitive::Callback(callback) => {
+                    let size = fb.size();
+                    let info = PaintCallbackInfo {
+                        viewport: callback.rect,
+                        clip_rect: m.clip_rect,
+                        pixels_per_point,
+                        screen_size_px: [size.w, size.h],
+                    };
+
+                    self.callbacks.call(callback, fb, &info);
                 }
             }
         }
 
         for &id in &delta.free {
-            self.samplers.remove(&id);
+            self.textures.remove(&id);
         }
     }
 
@@ -272,7 +377,10 @@ impl<G: Graphics> EguiPainter<G> {
 
         self.uniform_buffer.write(&EguiUniforms { screen_size });
 
-        if let Some(sampler) = self.samplers.get(&mesh.texture_id) {
+        if let Some(texture) = self.textures.get(&mesh.texture_id) {
+            let sampler =
+                Sampler::<G, _>::new(&*texture.texture, self.sampler_states.0[texture.state]);
+
             // Transform clip rect to physical pixels:
             let clip_min_x = pixels_per_point * clip_rect.min.x;
             let clip_min_y = pixels_per_point * clip_rect.min.y;
@@ -305,7 +413,7 @@ impl<G: Graphics> EguiPainter<G> {
                 self.resources.draw_descriptor(),
                 &draw_parameters,
                 &SamplerAttribute::named([("u_sampler", &sampler)]),
-                Some(&self.uniform_buffer),
+                &[&self.uniform_buffer],
                 &Indices {
                     mode: PrimitiveMode::Triangles,
                     offset: 0,
@@ -323,45 +431,223 @@ impl<G: Graphics> EguiPainter<G> {
     ) {
         let pixels = bytemuck::cast_slice(&pixels);
 
-        let sampler_state = SamplerState {
-            wrap: WrapFunction::Clamp,
-            min_filter: MinFilter::Origin(filter(delta.options.minification)),
-            mag_filter: filter(delta.options.magnification),
-        };
+        let state = self.sampler_states.key(sampler_state(delta.options));
 
         if let Some(pos) = delta.pos {
             // update a sub-region
-            if let Some(sampler) = self.samplers.get_mut(&tex_id) {
-                sampler.texture.write_rect(
-                    1,
-                    PixelFormat::Rgba.into(),
-                    Rect::new(
-                        pos[0] as u32,
-                        pos[1] as u32,
-                        delta.image.width() as u32,
-                        delta.image.height() as u32,
-                    ),
-                    &pixels,
+            if let Some(texture) = self.textures.get_mut(&tex_id) {
+                let rect = Rect::new(
+                    pos[0] as u32,
+                    pos[1] as u32,
+                    delta.image.width() as u32,
+                    delta.image.height() as u32,
                 );
-                sampler.state = sampler_state;
+
+                if let Some(size) = grown_size(texture.texture.size(), rect) {
+                    texture.texture = grow_texture(&self.resources.ctx, &texture.texture, size);
+                }
+
+                texture
+                    .texture
+                    .write_rect(0, PixelFormat::Rgba.into(), rect, pixels);
+                texture.state = state;
             }
         } else {
-            let texture = self.resources.ctx.new_texture(
+            let texture = Rc::new(self.resources.ctx.new_texture(
                 PixelFormat::Rgba,
                 Size::new(delta.image.width() as u32, delta.image.height() as u32),
                 Some(pixels),
-            );
+            ));
 
-            let sampler = Sampler::new(texture, sampler_state);
-
-            self.samplers.insert(tex_id, sampler);
+            self.textures.insert(tex_id, EguiTexture { texture, state });
         }
     }
 }
 
+/// Returns a size of the texture large enough to fit the rectangle,
+/// or None if the rectangle is already within the texture.
+fn grown_size(size: Size<u32>, rect: Rect<u32>) -> Option<Size<u32>> {
+    let required = Size::new(rect.x + rect.w, rect.y + rect.h);
+    if required.w <= size.w && required.h <= size.h {
+        return None;
+    }
+
+    Some(Size::new(size.w.max(required.w), size.h.max(required.h)))
+}
+
+/// Creates a texture of a larger size with the contents of `texture` in its top-left corner.
+fn grow_texture<G: Graphics>(ctx: &G, texture: &Rc<G::Texture>, size: Size<u32>) -> Rc<G::Texture> {
+    let grown = Rc::new(ctx.new_texture_empty(PixelFormat::Rgba, size));
+
+    let rect = Rect::new(0, 0, texture.size().w, texture.size().h);
+    let source = ctx.new_frame_buffer(texture.clone(), DepthStencilAttachment::None);
+    let destination = ctx.new_frame_buffer(grown.clone(), DepthStencilAttachment::None);
+    destination.blit(&source, rect, rect, FlipSource::None, Filter::Nearest);
+
+    grown
+}
+
+fn sampler_state(options: TextureOptions) -> SamplerState {
+    SamplerState {
+        wrap: WrapFunction::Clamp,
+        min_filter: MinFilter::Origin(filter(options.minification)),
+        mag_filter: filter(options.magnification),
+        ..Default::default()
+    }
+}
+
 fn filter(filter: TextureFilter) -> Filter {
     match filter {
         TextureFilter::Nearest => Filter::Nearest,
         TextureFilter::Linear => Filter::Linear,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc, sync::Arc};
+
+    use egui::{epaint::ImageDelta, ColorImage, Pos2};
+    use yapgeir_graphics_hal::frame_buffer::ReadFormat;
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+
+    struct Custom(u32);
+
+    fn callback(value: impl Any + Send + Sync) -> PaintCallback {
+        PaintCallback {
+            rect: egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(10., 10.)),
+            callback: Arc::new(value),
+        }
+    }
+
+    #[test]
+    fn test_textures_share_sampler_states() {
+        let mut states = SamplerStates::default();
+
+        let font = states.key(sampler_state(TextureOptions::LINEAR));
+        let image = states.key(sampler_state(TextureOptions::LINEAR));
+        let pixel_art = states.key(sampler_state(TextureOptions::NEAREST));
+
+        assert_eq!(font, image);
+        assert_ne!(font, pixel_art);
+        assert_eq!(states.0.len(), 2);
+    }
+
+    #[test]
+    fn test_oversized_partial_update() {
+        let size = Size::new(256, 256);
+        assert_eq!(grown_size(size, Rect::new(0, 0, 256, 256)), None);
+        assert_eq!(grown_size(size, Rect::new(200, 10, 16, 16)), None);
+        assert_eq!(
+            grown_size(size, Rect::new(250, 300, 16, 16)),
+            Some(Size::new(266, 316))
+        );
+        assert_eq!(
+            grown_size(size, Rect::new(0, 0, 512, 8)),
+            Some(Size::new(512, 256))
+        );
+    }
+
+    #[test]
+    fn test_grown_texture_keeps_contents() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let fb = graphics.default_frame_buffer();
+        let mut painter = EguiPainter::new(&graphics);
+
+        let id = egui::TextureId::Managed(0);
+        let mut set_texture = |delta| {
+            let delta = TexturesDelta {
+                set: vec![(id, delta)],
+                free: vec![],
+            };
+            painter.paint(
+                &fb,
+                1.,
+                &EguiDrawData {
+                    meshes: vec![],
+                    delta,
+                },
+            );
+        };
+        set_texture(ImageDelta::full(
+            ColorImage::new([2, 2], Color32::RED),
+            TextureOptions::NEAREST,
+        ));
+        set_texture(ImageDelta::partial(
+            [2, 1],
+            ColorImage::new([1, 1], Color32::BLUE),
+            TextureOptions::NEAREST,
+        ));
+
+        let texture = painter.textures[&id].texture.clone();
+        assert_eq!(texture.size(), Size::new(3, 2));
+
+        let fb = graphics.new_frame_buffer(texture, DepthStencilAttachment::None);
+        let mut pixels = [0; 3 * 2 * 4];
+        fb.read(Rect::new(0, 0, 3, 2), ReadFormat::Rgba, &mut pixels);
+
+        let [red, blue] = [Color32::RED, Color32::BLUE].map(|c| c.to_array());
+        assert_eq!(pixels[..12], [red, red, [0; 4]].concat());
+        assert_eq!(pixels[12..], [red, red, blue].concat());
+    }
+
+    #[test]
+    fn test_callback_screen_size() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let texture = Rc::new(graphics.new_texture_empty(PixelFormat::Rgba, (8, 4)));
+        let fb = graphics.new_frame_buffer(texture, DepthStencilAttachment::None);
+
+        let screen_size = Rc::new(Cell::new([0, 0]));
+        let mut painter = EguiPainter::new(&graphics);
+        painter.add_callback_handler({
+            let screen_size = screen_size.clone();
+            move |_: &Custom, _, info| screen_size.set(info.screen_size_px)
+        });
+
+        let meshes = vec![ClippedPrimitive {
+            clip_rect: egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(4., 4.)),
+            primitive: Primitive::Callback(callback(Custom(0))),
+        }];
+        painter.paint(
+            &fb,
+            1.,
+            &EguiDrawData {
+                meshes,
+                delta: Default::default(),
+            },
+        );
+
+        // The size is of the frame buffer being painted to, not of the window.
+        assert_eq!(screen_size.get(), [8, 4]);
+    }
+
+    #[test]
+    fn test_callbacks() {
+        let info = PaintCallbackInfo {
+            viewport: egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(10., 10.)),
+            clip_rect: egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(5., 5.)),
+            pixels_per_point: 2.,
+            screen_size_px: [100, 100],
+        };
+
+        let called = Rc::new(Cell::new(0));
+        let mut handlers = CallbackHandlers::<()>::default();
+        handlers.insert({
+            let called = called.clone();
+            move |custom: &Custom, _, info| {
+                assert_eq!(info.pixels_per_point, 2.);
+                called.set(called.get() + custom.0);
+            }
+        });
+
+        // Unhandled callbacks are skipped instead of aborting.
+        handlers.call(&callback("unhandled"), &(), &info);
+        handlers.call(&callback(Custom(3)), &(), &info);
+        handlers.call(&callback("unhandled"), &(), &info);
+
+        assert_eq!(called.get(), 3);
+        assert_eq!(handlers.unhandled.len(), 1);
+    }
+}
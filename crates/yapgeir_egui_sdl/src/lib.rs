@@ -5,7 +5,8 @@ use yapgeir_core::ScreenPpt;
 use yapgeir_egui_painter::{EguiDrawData, EguiPainter};
 use yapgeir_events::Events;
 use yapgeir_graphics_hal::{frame_buffer::FrameBuffer, Graphics, Size};
-use yapgeir_realm::{IntoSystem, Plugin, Realm, Res, ResMut, System};
+use yapgeir_input::keyboard::TextInputEvent;
+use yapgeir_realm::{stage, IntoSystem, Plugin, Realm, Res, ResMut, System};
 
 pub struct EguiRenderer<G: Graphics> {
     painter: EguiPainter<G>,
@@ -42,12 +43,21 @@ fn process_input(
     sdl: Res<sdl2::Sdl>,
     video: Res<sdl2::VideoSubsystem>,
     events: Res<Events<sdl2::event::Event>>,
+    text_input_events: Option<ResMut<Events<TextInputEvent>>>,
     ppt: Res<ScreenPpt>,
 ) {
     for event in events.iter() {
         gui.platform.handle_event(&event, &sdl, &video);
     }
 
+    // Text typed into a focused egui widget should not reach the game.
+    // Clearing keeps event readers in sync, so they still get the text typed after egui loses focus.
+    if let Some(mut text_input_events) = text_input_events {
+        if gui.context().wants_keyboard_input() {
+            text_input_events.clear();
+        }
+    }
+
     let elapsed = gui.start_time.elapsed().as_secs_f64();
     gui.platform.update_time(elapsed);
     gui.platform.set_pixels_per_point(Some(**ppt));
@@ -89,9 +99,9 @@ pub fn plugin<'a, G: Graphics, I, S: System<()> + 'static>(
             .initialize_resource_with(|ctx: Res<G>| EguiRenderer {
                 painter: EguiPainter::new(ctx.deref()),
                 data: Default::default(),
-            })
-            .add_system(process_input)
-            .add_system(gui_system)
-            .add_system(tesselate::<G>);
+            });
+        realm.add_system_to_stage(stage::INPUT, process_input);
+        realm.add_system(gui_system);
+        realm.add_system_to_stage(stage::RENDER, tesselate::<G>);
     }
 }
@@ -1,14 +1,115 @@
-use derive_more::{Deref, DerefMut};
-use yapgeir_realm::Realm;
-use yapgeir_realm::ResMut;
+use std::marker::PhantomData;
+
+use derive_more::Deref;
+use yapgeir_realm::{stage, Realm, ResMut};
 
 /// A generic resource for any events used for cross-system communication.
-/// Events are cleared at the beginning of every frame.
-#[derive(Deref, DerefMut)]
-pub struct Events<E: 'static>(Vec<E>);
+///
+/// Dereferences to the events sent during the current frame, which are cleared
+/// at the beginning of every frame. Events are double buffered: after the clear
+/// they are still kept for one more frame, so that an `EventReader` sees every event
+/// regardless of the order in which systems run.
+///
+/// Events are numbered in the order they are sent, which is how readers track what they've seen,
+/// so they can only be added with `send` or `extend`, and removed with `clear`.
+#[derive(Deref)]
+pub struct Events<E: 'static> {
+    #[deref]
+    current: Vec<E>,
+    previous: Vec<E>,
+    /// Sequence number of the first event in the `current` buffer.
+    current_start: usize,
+}
+
+impl<E: 'static> Default for Events<E> {
+    fn default() -> Self {
+        Self {
+            current: Vec::new(),
+            previous: Vec::new(),
+            current_start: 0,
+        }
+    }
+}
+
+impl<E: 'static> Events<E> {
+    pub fn send(&mut self, event: E) {
+        self.current.push(event);
+    }
+
+    /// Swap the buffers, dropping events from the previous frame.
+    pub fn update(&mut self) {
+        self.current_start += self.current.len();
+        std::mem::swap(&mut self.current, &mut self.previous);
+        self.current.clear();
+    }
+
+    /// Drop all events from both frames, e.g. to consume them before other systems read them.
+    ///
+    /// Readers will still see the events sent after the clear.
+    pub fn clear(&mut self) {
+        self.current_start += self.current.len();
+        self.current.clear();
+        self.previous.clear();
+    }
+
+    fn previous_start(&self) -> usize {
+        self.current_start - self.previous.len()
+    }
 
-fn clear_events<E: 'static>(mut e: ResMut<Events<E>>) {
-    e.0.clear();
+    fn end(&self) -> usize {
+        self.current_start + self.current.len()
+    }
+}
+
+impl<E: 'static> Extend<E> for Events<E> {
+    fn extend<T: IntoIterator<Item = E>>(&mut self, iter: T) {
+        self.current.extend(iter);
+    }
+}
+
+/// Reads events which it hasn't seen yet, from both the previous and the current frame.
+///
+/// Readers are not resources, instead each system owns its reader, e.g. by capturing it in a closure:
+///
+/// ```ignore
+/// let mut reader = EventReader::default();
+/// realm.add_system(move |events: Res<Events<Collision>>| {
+///     for collision in reader.read(&events) { /* ... */ }
+/// });
+/// ```
+pub struct EventReader<E: 'static> {
+    /// Sequence number of the next unseen event.
+    cursor: usize,
+    event: PhantomData<fn() -> E>,
+}
+
+impl<E: 'static> Default for EventReader<E> {
+    fn default() -> Self {
+        Self {
+            cursor: 0,
+            event: PhantomData,
+        }
+    }
+}
+
+impl<E: 'static> EventReader<E> {
+    /// Returns the events sent since the last read, oldest first.
+    pub fn read<'a>(&mut self, events: &'a Events<E>) -> impl Iterator<Item = &'a E> {
+        let start = self.cursor.max(events.previous_start()).min(events.end());
+        self.cursor = events.end();
+
+        let previous = events
+            .previous
+            .get(start - events.previous_start()..)
+            .unwrap_or_default();
+        let current = &events.current[start.saturating_sub(events.current_start)..];
+
+        previous.iter().chain(current)
+    }
+}
+
+fn update_events<E: 'static>(mut e: ResMut<Events<E>>) {
+    e.update();
 }
 
 /// Registers a plugin for events of a specific type.
@@ -16,6 +117,60 @@ fn clear_events<E: 'static>(mut e: ResMut<Events<E>>) {
 /// at the beginning of each frame events are cleared.
 pub fn plugin<E: 'static>(realm: &mut Realm) {
     realm
-        .add_resource(Events::<E>(Default::default()))
-        .add_system(clear_events::<E>);
+        .add_resource(Events::<E>::default())
+        .add_system_to_stage(stage::FIRST, update_events::<E>);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reader_sees_events_for_two_frames() {
+        let mut events = Events::default();
+        events.send(1);
+        events.send(2);
+
+        // A reader created after the events were sent, and only reading on the next frame.
+        let mut reader = EventReader::default();
+        events.update();
+        events.send(3);
+        assert!(events.iter().eq(&[3]));
+        assert!(reader.read(&events).eq(&[1, 2, 3]));
+        assert_eq!(reader.read(&events).count(), 0);
+
+        events.send(4);
+        assert!(reader.read(&events).eq(&[4]));
+
+        // Events are dropped after two updates.
+        let mut late = EventReader::default();
+        events.update();
+        events.update();
+        assert_eq!(late.read(&events).count(), 0);
+        assert_eq!(reader.read(&events).count(), 0);
+    }
+
+    #[test]
+    fn test_reader_sees_events_sent_after_clear() {
+        let mut events = Events::default();
+        let mut reader = EventReader::default();
+        events.send(1);
+        events.update();
+        events.send(2);
+        assert!(reader.read(&events).eq(&[1, 2]));
+
+        let mut unread = EventReader::default();
+        events.send(3);
+        assert!(reader.read(&events).eq(&[3]));
+        events.clear();
+        assert!(events.is_empty());
+        assert_eq!(unread.read(&events).count(), 0);
+
+        // Events sent after the clear are new to the reader, even though the buffers were emptied.
+        events.send(4);
+        assert!(reader.read(&events).eq(&[4]));
+        events.update();
+        events.send(5);
+        assert!(reader.read(&events).eq(&[5]));
+    }
 }
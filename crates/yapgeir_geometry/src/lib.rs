@@ -48,6 +48,7 @@ impl From<Size<u32>> for (u32, u32) {
 }
 
 #[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "reflection", derive(Reflect))]
 pub struct Rgba<T> {
     pub r: T,
     pub g: T,
@@ -1,5 +1,5 @@
 use darling::{ast, util, FromDeriveInput, FromField};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 
 #[derive(Debug, FromField)]
 #[darling(attributes(uniforms))]
@@ -11,6 +11,10 @@ struct UniformsField {
     name: Option<String>,
     #[darling(default)]
     ignore: bool,
+    /// Overrides the `UniformKind` inferred from the field type, e.g. #[uniforms(kind = "FloatVec3")]
+    /// for a `[[f32; 3]; 3]` field bound to a `vec3[3]` array instead of a `mat3`.
+    #[darling(default)]
+    kind: Option<String>,
 }
 
 #[derive(Debug, FromDeriveInput)]
@@ -66,11 +70,22 @@ impl ToTokens for Uniforms {
                     }
                 };
 
+                let kind = match &field.kind {
+                    Some(kind) => {
+                        let kind = format_ident!("{}", kind);
+                        quote!(yapgeir_graphics_hal::uniforms::UniformKind::#kind)
+                    }
+                    None => {
+                        quote!(<#field_ty as yapgeir_graphics_hal::uniforms::AsUniformKind>::KIND)
+                    }
+                };
+
                 Some(quote! {
                     yapgeir_graphics_hal::uniforms::UniformAttribute {
                         name: #name,
                         offset: #offset,
                         size: std::mem::size_of::<#field_ty>(),
+                        kind: #kind,
                     }
                 })
             });
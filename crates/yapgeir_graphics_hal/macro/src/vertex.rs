@@ -11,14 +11,20 @@ struct VertexField {
     name: Option<String>,
     #[darling(default)]
     ignore: bool,
+    #[darling(default)]
+    divisor: Option<u32>,
 }
 
 #[derive(Debug, FromDeriveInput)]
-#[darling(supports(struct_named))]
+#[darling(attributes(vertex), supports(struct_named))]
 pub struct Vertex {
     ident: syn::Ident,
     generics: syn::Generics,
     data: ast::Data<util::Ignored, VertexField>,
+
+    /// Default divisor for all fields, #[vertex(divisor = 1)] makes a per-instance vertex.
+    #[darling(default)]
+    divisor: u32,
 }
 
 impl ToTokens for Vertex {
@@ -27,6 +33,7 @@ impl ToTokens for Vertex {
             ref ident,
             ref generics,
             ref data,
+            divisor,
         } = *self;
 
         let (imp, ty, wher) = generics.split_for_impl();
@@ -39,6 +46,7 @@ impl ToTokens for Vertex {
             .filter_map(|field| {
                 let field_ident = field.ident.as_ref().unwrap();
                 let field_ty = &field.ty;
+                let divisor = field.divisor.unwrap_or(divisor);
 
                 // Attribute name is taken from the macro #[vertex(name)] attribute if it's defined,
                 // and defaulted to a field name.
@@ -71,7 +79,8 @@ impl ToTokens for Vertex {
                         name: #name,
                         offset: #offset,
                         kind: <#field_ty as yapgeir_graphics_hal::vertex_buffer::AsAttributeKind>::KIND,
-                        size: <#field_ty as yapgeir_graphics_hal::vertex_buffer::AsAttributeKind>::SIZE
+                        size: <#field_ty as yapgeir_graphics_hal::vertex_buffer::AsAttributeKind>::SIZE,
+                        divisor: #divisor,
                     }
                 })
             });
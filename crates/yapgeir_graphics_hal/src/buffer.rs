@@ -101,6 +101,12 @@ pub trait ByteBuffer<G: Graphics> {
     /// Writes the data to a buffer at a given offset.
     /// Panics if the data stretches beyond the buffer boundaries.
     fn write(&self, offset: usize, data: &[u8]);
+
+    /// Discards the contents of the buffer, keeping its size.
+    ///
+    /// This hints the GPU that it can allocate fresh storage for the following writes,
+    /// instead of waiting for the pending draw calls that still read the old data.
+    fn orphan(&self);
 }
 
 /// Buffer is a type retaining proxy for a ByteBuffer.
@@ -139,4 +145,10 @@ impl<G: Graphics, T: Pod> Buffer<G, T> {
         let data = bytemuck::cast_slice(data);
         self.bytes.write(offset * size_of::<T>(), data);
     }
+
+    /// Discards the contents of the buffer, keeping its size.
+    /// See `ByteBuffer::orphan`.
+    pub fn orphan(&self) {
+        self.bytes.orphan();
+    }
 }
@@ -86,6 +86,124 @@ impl Blend {
             ..Default::default()
         }
     }
+
+    /// Alpha blending for colors with alpha not multiplied into them, e.g. semi-transparent
+    /// sprites. Destination alpha is blended the same way as with premultiplied alpha.
+    pub fn straight_alpha() -> Self {
+        Self {
+            function: SeparateBlending {
+                rgb: BlendingFunction {
+                    source: BlendingFactor::SourceAlpha,
+                    destination: BlendingFactor::OneMinusSourceAlpha,
+                },
+                alpha: BlendingFunction {
+                    source: BlendingFactor::One,
+                    destination: BlendingFactor::OneMinusSourceAlpha,
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Alpha blending for colors with alpha already multiplied into them.
+    pub fn premultiplied_alpha() -> Self {
+        Self {
+            function: SeparateBlending::all(BlendingFunction {
+                source: BlendingFactor::One,
+                destination: BlendingFactor::OneMinusSourceAlpha,
+            }),
+            ..Default::default()
+        }
+    }
+
+    /// Adds the source color weighted by its alpha to the destination, e.g. for lights
+    /// and particles. Destination alpha is kept.
+    pub fn additive() -> Self {
+        Self {
+            function: SeparateBlending {
+                rgb: BlendingFunction {
+                    source: BlendingFactor::SourceAlpha,
+                    destination: BlendingFactor::One,
+                },
+                alpha: BlendingFunction {
+                    source: BlendingFactor::Zero,
+                    destination: BlendingFactor::One,
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Multiplies the destination color by the source color, e.g. for shadows.
+    /// Destination alpha is kept.
+    pub fn multiply() -> Self {
+        Self {
+            function: SeparateBlending {
+                rgb: BlendingFunction {
+                    source: BlendingFactor::DestinationColor,
+                    destination: BlendingFactor::Zero,
+                },
+                alpha: BlendingFunction {
+                    source: BlendingFactor::Zero,
+                    destination: BlendingFactor::One,
+                },
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Blends a source color into a destination color on the CPU, the same way as
+    /// it is done by the GPU for a frame buffer with normalized colors.
+    pub fn apply(&self, source: Rgba<f32>, destination: Rgba<f32>) -> Rgba<f32> {
+        let src: [f32; 4] = source.into();
+        let dst: [f32; 4] = destination.into();
+        let constant: [f32; 4] = self.color.into();
+
+        let blended: [f32; 4] = std::array::from_fn(|channel| {
+            let (equation, function) = match channel {
+                3 => (self.equation.alpha, self.function.alpha),
+                _ => (self.equation.rgb, self.function.rgb),
+            };
+
+            let s = src[channel] * function.source.value(channel, src, dst, constant);
+            let d = dst[channel] * function.destination.value(channel, src, dst, constant);
+            let value = match equation {
+                BlendingEquation::Add => s + d,
+                BlendingEquation::Subtract => s - d,
+                BlendingEquation::ReverseSubtract => d - s,
+            };
+
+            value.clamp(0., 1.)
+        });
+
+        blended.into()
+    }
+}
+
+impl BlendingFactor {
+    /// Value of the factor for a color channel, with the alpha channel being the last one.
+    fn value(self, channel: usize, src: [f32; 4], dst: [f32; 4], constant: [f32; 4]) -> f32 {
+        match self {
+            BlendingFactor::Zero => 0.,
+            BlendingFactor::One => 1.,
+            BlendingFactor::SourceColor => src[channel],
+            BlendingFactor::OneMinusSourceColor => 1. - src[channel],
+            BlendingFactor::DestinationColor => dst[channel],
+            BlendingFactor::OneMinusDestinationColor => 1. - dst[channel],
+            BlendingFactor::SourceAlpha => src[3],
+            BlendingFactor::OneMinusSourceAlpha => 1. - src[3],
+            BlendingFactor::DestinationAlpha => dst[3],
+            BlendingFactor::OneMinusDestinationAlpha => 1. - dst[3],
+            BlendingFactor::ConstantColor => constant[channel],
+            BlendingFactor::OneMinusConstantColor => 1. - constant[channel],
+            BlendingFactor::ConstantAlpha => constant[3],
+            BlendingFactor::OneMinusConstantAlpha => 1. - constant[3],
+            BlendingFactor::SourceAlphaSaturate => match channel {
+                3 => 1.,
+                _ => src[3].min(1. - dst[3]),
+            },
+        }
+    }
 }
 
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
@@ -97,6 +215,56 @@ pub enum CullFaceMode {
     All,
 }
 
+impl CullFaceMode {
+    /// Returns true if a polygon, which is facing front or not, is culled.
+    pub fn culls(self, front: bool) -> bool {
+        match self {
+            CullFaceMode::Front => front,
+            CullFaceMode::Back => !front,
+            CullFaceMode::All => true,
+        }
+    }
+}
+
+/// Winding order of the vertices of front-facing polygons, which is used by `CullFaceMode`.
+///
+/// Winding is evaluated in normalized device coordinates, which have Y going up.
+/// Projections flipping the Y axis, as well as negative scales, reverse the winding.
+/// Flipping the default frame buffer (see `GlesSettings::flip_default_frame_buffer`)
+/// happens after rasterization and doesn't affect culling, even though polygons
+/// appear with the opposite winding in the presented image.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontFace {
+    /// Clockwise, which matches the quads drawn by the 2D renderers.
+    #[default]
+    Cw,
+    /// Counter-clockwise, which is the OpenGL default.
+    Ccw,
+}
+
+impl FrontFace {
+    /// Returns true if a triangle in normalized device coordinates is facing front.
+    pub fn is_front(self, [a, b, c]: [[f32; 2]; 3]) -> bool {
+        let area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+        match self {
+            FrontFace::Cw => area < 0.,
+            FrontFace::Ccw => area > 0.,
+        }
+    }
+}
+
+/// Rasterization mode of polygons, mostly useful for debugging geometry.
+/// Only supported by desktop OpenGL contexts, and ignored on OpenGL ES.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PolygonMode {
+    #[default]
+    Fill,
+    /// Draw polygon edges as lines, i.e. wireframe.
+    Line,
+    /// Draw polygon vertices as points.
+    Point,
+}
+
 #[derive(Default, Clone, Copy, Debug, PartialEq)]
 pub struct PolygonOffset {
     pub factor: f32,
@@ -172,6 +340,7 @@ pub struct DrawParameters {
     #[default(Rgba::all(true))]
     pub color_mask: Rgba<bool>,
     pub cull_face: Option<CullFaceMode>,
+    pub front_face: FrontFace,
     pub depth: Option<Depth>,
     pub stencil: Option<Stencil>,
     pub scissor: Option<Rect<u32>>,
@@ -179,5 +348,89 @@ pub struct DrawParameters {
     #[default(1.)]
     pub line_width: f32,
     pub polygon_offset: Option<PolygonOffset>,
+    /// Polygon rasterization mode, `None` is the same as `PolygonMode::Fill`.
+    pub polygon_mode: Option<PolygonMode>,
     pub dithering: bool,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Box2D;
+
+    #[test]
+    fn test_polygon_mode() {
+        let default = DrawParameters::default();
+        assert_eq!(default.polygon_mode, None);
+        assert_eq!(PolygonMode::default(), PolygonMode::Fill);
+
+        let wireframe = DrawParameters {
+            polygon_mode: Some(PolygonMode::Line),
+            ..default
+        };
+        assert_eq!(wireframe.clone().polygon_mode, Some(PolygonMode::Line));
+    }
+
+    #[test]
+    fn test_front_face_culling() {
+        // Triangles of a quad in the order drawn by the renderers, which is clockwise.
+        let quad = Box2D::new([-1., -1.], [1., 1.]).points();
+        let triangles = [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]];
+        let visible = |front_face: FrontFace| {
+            triangles
+                .iter()
+                .filter(|t| !CullFaceMode::Back.culls(front_face.is_front(**t)))
+                .count()
+        };
+
+        assert_eq!(visible(FrontFace::Cw), 2);
+        assert_eq!(visible(FrontFace::Ccw), 0);
+        assert_eq!(DrawParameters::default().front_face, FrontFace::Cw);
+    }
+
+    #[test]
+    fn test_blend_presets() {
+        let function = |source, destination| BlendingFunction {
+            source,
+            destination,
+        };
+        use BlendingFactor::*;
+
+        let premultiplied = Blend::premultiplied_alpha();
+        assert_eq!(
+            premultiplied.function,
+            SeparateBlending::all(function(One, OneMinusSourceAlpha))
+        );
+
+        let additive = Blend::additive();
+        assert_eq!(additive.function.rgb, function(SourceAlpha, One));
+        assert_eq!(additive.function.alpha, function(Zero, One));
+
+        let multiply = Blend::multiply();
+        assert_eq!(multiply.function.rgb, function(DestinationColor, Zero));
+        assert_eq!(multiply.function.alpha, function(Zero, One));
+
+        for blend in [premultiplied, additive, multiply] {
+            assert_eq!(blend.equation, SeparateBlending::all(BlendingEquation::Add));
+        }
+    }
+
+    #[test]
+    fn test_additive_blend_brightens_overlaps() {
+        let additive = Blend::additive();
+        let light = Rgba::new(0.4, 0.2, 0., 0.5);
+        let background = Rgba::new(0.1, 0.1, 0.1, 1.);
+
+        let once = additive.apply(light, background);
+        let twice = additive.apply(light, once);
+        assert_eq!(once, Rgba::new(0.3, 0.2, 0.1, 1.));
+        assert_eq!(twice, Rgba::new(0.5, 0.3, 0.1, 1.));
+
+        // Colors saturate instead of overflowing.
+        let saturated = additive.apply(Rgba::all(1.), twice);
+        assert_eq!(saturated, Rgba::all(1.));
+
+        let shadow = Blend::multiply().apply(Rgba::all(0.5), twice);
+        assert_eq!(shadow, Rgba::new(0.25, 0.15, 0.05, 1.));
+    }
+}
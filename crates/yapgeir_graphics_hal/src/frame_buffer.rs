@@ -1,11 +1,10 @@
 use std::{borrow::Borrow, rc::Rc};
 
-use bytemuck::Pod;
 use derive_more::Constructor;
 
 use crate::{
     draw_params::DrawParameters, index_buffer::PrimitiveMode, sampler::Filter,
-    samplers::SamplerAttribute, uniforms::Uniforms, Graphics, Rect, Rgba, Size,
+    samplers::SamplerAttribute, uniforms::AnyUniformBuffer, Graphics, Rect, Rgba, Size,
 };
 
 pub enum Attachment<G: Graphics> {
@@ -13,6 +12,46 @@ pub enum Attachment<G: Graphics> {
     RenderBuffer(Rc<G::RenderBuffer>),
 }
 
+impl<G: Graphics> From<Rc<G::Texture>> for Attachment<G> {
+    fn from(value: Rc<G::Texture>) -> Self {
+        Self::Texture(value)
+    }
+}
+
+/// Color attachments of a frame buffer.
+///
+/// A frame buffer with multiple attachments is drawn to in a single pass, with fragment
+/// shaders writing to `gl_FragData[i]` instead of `gl_FragColor`. All attachments must have
+/// the same size, and their count is limited by `Graphics::max_draw_buffers`.
+///
+/// OpenGL ES 2 only supports it with `GL_EXT_draw_buffers`, which fragment shaders
+/// have to enable with `#extension GL_EXT_draw_buffers : require`.
+pub struct DrawAttachments<G: Graphics>(pub Vec<Attachment<G>>);
+
+impl<G: Graphics> From<Attachment<G>> for DrawAttachments<G> {
+    fn from(value: Attachment<G>) -> Self {
+        Self(vec![value])
+    }
+}
+
+impl<G: Graphics> From<Rc<G::Texture>> for DrawAttachments<G> {
+    fn from(value: Rc<G::Texture>) -> Self {
+        Self(vec![Attachment::Texture(value)])
+    }
+}
+
+impl<G: Graphics> From<Vec<Attachment<G>>> for DrawAttachments<G> {
+    fn from(value: Vec<Attachment<G>>) -> Self {
+        Self(value)
+    }
+}
+
+impl<G: Graphics, const N: usize> From<[Rc<G::Texture>; N]> for DrawAttachments<G> {
+    fn from(value: [Rc<G::Texture>; N]) -> Self {
+        Self(value.into_iter().map(Attachment::Texture).collect())
+    }
+}
+
 pub enum DepthStencilAttachment<R: Graphics> {
     None,
     Depth(Attachment<R>),
@@ -63,11 +102,18 @@ pub trait FrameBuffer<G: Graphics> {
 
     /// Create a new frame buffer.
     ///
-    /// A frame buffer uses a Texture for a depth component,
+    /// A frame buffer uses textures or render buffers for draw components,
     /// and can optionally have depth and/or stencil components.
     ///
     /// Depth and stencil components can be a texture or a renderbuffer.
-    fn new(renderer: G, draw: Rc<G::Texture>, depth_stencil: DepthStencilAttachment<G>) -> Self;
+    ///
+    /// A multisampled render buffer can be used as a draw component for anti-aliasing.
+    /// Frame buffers drawing into a render buffer can only be read by blitting them.
+    ///
+    /// Panics if there are no draw attachments, more than `Graphics::max_draw_buffers`,
+    /// or if they have different sizes.
+    fn new(renderer: G, draw: DrawAttachments<G>, depth_stencil: DepthStencilAttachment<G>)
+        -> Self;
 
     /// Returns the size of the frame buffer in pixels.
     fn size(&self) -> Size<u32>;
@@ -102,21 +148,45 @@ pub trait FrameBuffer<G: Graphics> {
     /// top corner.
     /// * `textures` - a set of samplers that will be used in a shader. Describes
     /// which textures are used, how they are sampled, and where are they bound to.
-    /// * `uniforms` - a set of uniforms that will be used in a shader. Only a
-    /// single uniform buffer binding is supported.
+    /// * `uniforms` - uniform buffers that will be used in a shader. Each uniform is bound
+    ///   by its name, uniforms which are not used by the shader are skipped. A uniform name
+    ///   should be provided by at most one of the buffers.
     /// * `indices` - describes how to interpret the indices. Uses an index buffer
     /// that was bound to a `draw_descriptor`. If no index buffer was bound to
     /// a `draw_descriptor`, then indices are sequential.
-    fn draw<U: Uniforms + Pod>(
+    fn draw(
         &self,
         draw_descriptor: &G::DrawDescriptor,
         draw_parameters: &DrawParameters,
 
         samplers: &[SamplerAttribute<G, impl Borrow<G::Texture>>],
-        uniforms: Option<&G::UniformBuffer<U>>,
+        uniforms: &[&dyn AnyUniformBuffer<G>],
         indices: &Indices,
     );
 
+    /// Draws multiple instances of the vertices on the frame buffer with a single draw call.
+    ///
+    /// Vertex attributes with a non-zero `divisor` advance once per `divisor` instances,
+    /// the remaining ones advance per vertex as usual.
+    ///
+    /// Panics if the implementation doesn't support instancing,
+    /// which can be checked with `Graphics::supports_instancing`.
+    ///
+    /// # Arguments
+    ///
+    /// Same as in `draw`, plus
+    /// * `instances` - the number of instances to draw.
+    fn draw_instanced(
+        &self,
+        draw_descriptor: &G::DrawDescriptor,
+        draw_parameters: &DrawParameters,
+
+        samplers: &[SamplerAttribute<G, impl Borrow<G::Texture>>],
+        uniforms: &[&dyn AnyUniformBuffer<G>],
+        indices: &Indices,
+        instances: usize,
+    );
+
     /// Draws a rectangle of another frame buffers draw attachment in a rectangle
     /// of this frame buffers draw attachment.
     ///
@@ -127,6 +197,9 @@ pub trait FrameBuffer<G: Graphics> {
     /// * `destination` - specifies the bounds of the destination rectangle within thr target frame buffer.
     /// * `flip` - allows doing a horizontal and/or a vertical flip of the source image.
     /// * `filter` - specifies the filter that will be used when sampling the source texture.
+    ///
+    /// Blitting a multisampled frame buffer resolves it. In this case `source` and `destination`
+    /// must have the same size.
     fn blit(
         &self,
         read_frame_buffer: &G::FrameBuffer,
@@ -137,6 +210,15 @@ pub trait FrameBuffer<G: Graphics> {
     );
 
     /// Reads the data from the frame buffers draw texture to the provided
-    /// byte slice.
+    /// byte slice. Frame buffers with multiple draw attachments read the first one.
     fn read(&self, rect: Rect<u32>, read_format: Self::ReadFormat, target: &mut [u8]);
+
+    /// Reads the data from a draw attachment with the given index, see `read`.
+    fn read_attachment(
+        &self,
+        attachment: usize,
+        rect: Rect<u32>,
+        read_format: Self::ReadFormat,
+        target: &mut [u8],
+    );
 }
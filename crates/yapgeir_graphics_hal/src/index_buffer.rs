@@ -35,6 +35,8 @@ impl Index for u32 {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum PrimitiveMode {
+    /// Every vertex is a square point. The vertex shader sets the point size in pixels
+    /// by writing to `gl_PointSize`, otherwise the size is undefined.
     Points,
     Lines,
     LineStrip,
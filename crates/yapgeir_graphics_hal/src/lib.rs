@@ -3,11 +3,12 @@ use std::{ffi::c_void, rc::Rc};
 use buffer::{Buffer, BufferData, BufferKind, BufferUsage, ByteBuffer};
 use bytemuck::Pod;
 use draw_descriptor::{DrawDescriptor, IndexBinding, VertexBindings};
-use frame_buffer::{DepthStencilAttachment, FrameBuffer, ReadFormat};
+use frame_buffer::{DepthStencilAttachment, DrawAttachments, FrameBuffer, ReadFormat};
+use query::TimerQuery;
 use render_buffer::{RenderBuffer, RenderBufferFormat};
-use shader::{Shader, TextShaderSource};
+use shader::{Shader, ShaderError, TextShaderSource};
 use texture::{PixelFormat, Texture};
-use uniforms::{UniformBuffer, Uniforms};
+use uniforms::{AnyUniformBuffer, UniformBuffer, Uniforms};
 
 pub use yapgeir_geometry::*;
 
@@ -16,6 +17,7 @@ pub mod draw_descriptor;
 pub mod draw_params;
 pub mod frame_buffer;
 pub mod index_buffer;
+pub mod query;
 pub mod render_buffer;
 pub mod sampler;
 pub mod samplers;
@@ -23,6 +25,7 @@ pub mod shader;
 pub mod texture;
 pub mod uniforms;
 pub mod vertex_buffer;
+pub mod viewport;
 
 pub trait WindowBackend
 where
@@ -38,6 +41,13 @@ where
     /// This method is called by Graphics every frame to ensure
     /// correct work when window size is changed.
     fn default_frame_buffer_size(&self) -> Size<u32>;
+
+    /// Returns true if the graphics context is lost, e.g. when a WebGL canvas
+    /// loses its context. Backends that can detect it should report it here,
+    /// because restoring a lost context can only be detected by the backend.
+    fn is_context_lost(&self) -> bool {
+        false
+    }
 }
 
 pub trait Graphics
@@ -55,7 +65,8 @@ where
     type FrameBuffer: FrameBuffer<Self, ReadFormat = Self::ReadFormat>;
     type BufferUsage: From<BufferUsage>;
     type ByteBuffer: ByteBuffer<Self, Usage = Self::BufferUsage>;
-    type UniformBuffer<T: Pod>: UniformBuffer<Self, T>;
+    type UniformBuffer<T: Uniforms + Pod>: UniformBuffer<Self, T> + AnyUniformBuffer<Self>;
+    type TimerQuery: TimerQuery<Self>;
 
     fn new(backend: Self::Backend) -> Self;
 
@@ -75,6 +86,13 @@ where
         Self::Shader::new(self.clone(), source)
     }
 
+    fn try_new_shader<'a>(&self, source: &TextShaderSource) -> Result<Self::Shader, ShaderError>
+    where
+        Self: 'a,
+    {
+        Self::Shader::try_new(self.clone(), source)
+    }
+
     fn new_buffer<'a, T: Pod>(
         &self,
         kind: BufferKind,
@@ -96,6 +114,9 @@ where
         Self::DrawDescriptor::new(self.clone(), shader, indices.into(), vertices.as_ref())
     }
 
+    /// Creates a texture, uploading the whole image from `bytes`.
+    ///
+    /// Passing `None` is the same as `new_texture_empty`.
     fn new_texture<'a>(
         &self,
         format: impl Into<Self::PixelFormat>,
@@ -105,6 +126,28 @@ where
         Self::Texture::new(self.clone(), format.into(), size.into(), bytes.into())
     }
 
+    /// Creates a texture without uploading any data, e.g. a render target,
+    /// or an atlas which is filled with `Texture::write_rect`.
+    ///
+    /// The contents of the texture are unspecified until they are written or rendered to,
+    /// so parts which are never written must not be sampled.
+    fn new_texture_empty(
+        &self,
+        format: impl Into<Self::PixelFormat>,
+        size: impl Into<Size<u32>>,
+    ) -> Self::Texture {
+        self.new_texture(format, size, None)
+    }
+
+    fn new_cubemap(
+        &self,
+        format: impl Into<Self::PixelFormat>,
+        size: impl Into<Size<u32>>,
+        faces: [Option<&[u8]>; 6],
+    ) -> Self::Texture {
+        Self::Texture::new_cubemap(self.clone(), format.into(), size.into(), faces)
+    }
+
     fn new_render_buffer(
         &self,
         size: impl Into<Size<u32>>,
@@ -115,15 +158,50 @@ where
 
     fn new_frame_buffer(
         &self,
-        draw: Rc<Self::Texture>,
+        draw: impl Into<DrawAttachments<Self>>,
         depth_stencil: impl Into<DepthStencilAttachment<Self>>,
     ) -> Self::FrameBuffer {
-        Self::FrameBuffer::new(self.clone(), draw, depth_stencil.into())
+        Self::FrameBuffer::new(self.clone(), draw.into(), depth_stencil.into())
     }
 
     fn new_uniform_buffer<'a, T: Uniforms + Pod>(&self, initial: &T) -> Self::UniformBuffer<T> {
         Self::UniformBuffer::new(self.clone(), initial)
     }
 
+    fn new_timer_query(&self) -> Self::TimerQuery {
+        Self::TimerQuery::new(self.clone())
+    }
+
+    /// Returns true if the implementation supports vertex attribute divisors
+    /// and `FrameBuffer::draw_instanced`.
+    fn supports_instancing(&self) -> bool;
+
+    /// Returns true if the implementation supports textures with depth pixel formats,
+    /// which can be attached to a frame buffer and sampled afterwards.
+    fn supports_depth_textures(&self) -> bool;
+
+    /// Returns the maximum number of draw attachments of a frame buffer,
+    /// which is 1 if multiple render targets are unsupported.
+    fn max_draw_buffers(&self) -> usize;
+
+    /// Returns true if the implementation supports sRGB textures, which convert
+    /// linear color to sRGB when rendered to, and back when sampled.
+    fn supports_srgb(&self) -> bool;
+
+    /// Returns true if the implementation supports `WrapFunction::Border`.
+    fn supports_border_clamp(&self) -> bool;
+
     fn swap_buffers(&self);
+
+    /// Returns true if the graphics context was lost, e.g. a WebGL tab was backgrounded
+    /// or the GPU was reset. Draw calls have no effect while the context is lost.
+    ///
+    /// Context loss is checked during `swap_buffers`.
+    fn is_context_lost(&self) -> bool;
+
+    /// Register a callback to be called when a lost context is restored.
+    ///
+    /// All textures, buffers and shaders created before the loss are invalid by then,
+    /// and should be recreated by their owners.
+    fn on_context_restored(&self, callback: impl FnMut() + 'static);
 }
@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::Graphics;
+
+/// A query measuring the time it took a GPU to execute the commands
+/// issued between `begin` and `end`.
+///
+/// Only one timer query can be active at a time, so timer queries can't be nested.
+pub trait TimerQuery<G: Graphics> {
+    fn new(renderer: G) -> Self;
+
+    /// Start measuring the GPU time of the commands issued after this call.
+    ///
+    /// Panics if the query has already begun and hasn't ended yet.
+    fn begin(&self);
+
+    /// Stop measuring. The result will become available after the GPU executes the
+    /// commands, which is usually a few frames later.
+    fn end(&self);
+
+    /// Returns the measured time once it is available without blocking. The result is
+    /// returned only once per `begin`/`end` pair.
+    ///
+    /// If timer queries are not supported by the implementation, or the measurement was
+    /// invalidated by the GPU, returns `None`.
+    fn poll(&self) -> Option<Duration>;
+}
@@ -7,10 +7,27 @@ pub enum RenderBufferFormat {
     Depth,
     Stencil,
     DepthStencil,
+    /// A multisampled color buffer, which can be used as a draw attachment of a frame buffer.
+    /// To sample the result it must be resolved by blitting it to a frame buffer with a texture.
+    ColorMsaa {
+        samples: u8,
+    },
+    /// A multisampled depth and stencil buffer. Must be used together with a multisampled
+    /// color buffer with the same number of samples.
+    DepthStencilMsaa {
+        samples: u8,
+    },
 }
 
 pub trait RenderBuffer<G: Graphics> {
     type Format;
 
     fn new(renderer: G, size: Size<u32>, format: Self::Format) -> Self;
+
+    /// Returns the number of samples per pixel the render buffer was actually created with.
+    ///
+    /// This is 1 for single-sampled buffers, and can be lower than requested for
+    /// multisampled ones if the implementation doesn't support as many samples,
+    /// or doesn't support multisampling at all.
+    fn samples(&self) -> u8;
 }
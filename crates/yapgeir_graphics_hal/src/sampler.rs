@@ -1,6 +1,11 @@
-use std::{borrow::Borrow, marker::PhantomData};
+use std::{
+    borrow::Borrow,
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    mem::{discriminant, Discriminant},
+};
 
-use crate::Graphics;
+use crate::{Graphics, Rgba};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum Filter {
@@ -11,8 +16,21 @@ pub enum Filter {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum MinFilter {
+    /// Sample the base level of the texture.
     Origin(Filter),
-    Mipmap { mipmap: Filter, texel: Filter },
+    /// Sample the mipmaps, which must be generated with `Texture::generate_mipmaps`.
+    Mipmap {
+        /// Filtering between two closest mipmap levels, `Nearest` only uses the closest level.
+        mipmap: Filter,
+        /// Filtering of texels within a mipmap level.
+        texel: Filter,
+    },
+}
+
+impl MinFilter {
+    pub fn is_mipmapped(self) -> bool {
+        matches!(self, MinFilter::Mipmap { .. })
+    }
 }
 
 impl Default for MinFilter {
@@ -24,28 +42,109 @@ impl Default for MinFilter {
     }
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Default, Debug, Clone, Copy)]
 pub enum WrapFunction {
     Clamp,
     #[default]
     Repeat,
     MirrorClamp,
     MirrorRepeat,
+    /// Samples outside of the texture return a constant color, e.g. to pad sprites in an atlas
+    /// without bleeding their edges. Falls back to `Clamp` with a warning
+    /// if not supported, which can be checked with `Graphics::supports_border_clamp`.
+    Border(Rgba<f32>),
 }
 
-#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+// The border color is compared by its bits, so that sampler states can be used as cache keys.
+impl PartialEq for WrapFunction {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for WrapFunction {}
+
+impl Hash for WrapFunction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl WrapFunction {
+    fn key(&self) -> (Discriminant<Self>, Option<[u32; 4]>) {
+        let border = match self {
+            WrapFunction::Border(color) => Some(<[f32; 4]>::from(*color).map(f32::to_bits)),
+            _ => None,
+        };
+
+        (discriminant(self), border)
+    }
+
+    /// Maps a normalized texture coordinate into the [0; 1] range the way a sampler does,
+    /// or returns `None` if the border color is sampled instead.
+    pub fn wrap(self, coordinate: f32) -> Option<f32> {
+        match self {
+            WrapFunction::Clamp => Some(coordinate.clamp(0., 1.)),
+            WrapFunction::Repeat => Some(coordinate.rem_euclid(1.)),
+            WrapFunction::MirrorClamp => Some(coordinate.abs().min(1.)),
+            WrapFunction::MirrorRepeat => {
+                let coordinate = coordinate.rem_euclid(2.);
+                Some(if coordinate > 1. {
+                    2. - coordinate
+                } else {
+                    coordinate
+                })
+            }
+            WrapFunction::Border(_) => (0. ..=1.).contains(&coordinate).then_some(coordinate),
+        }
+    }
+}
+
+#[derive(Default, Debug, Clone, Copy)]
 pub struct SamplerState {
     pub wrap: WrapFunction,
     pub min_filter: MinFilter,
     pub mag_filter: Filter,
+    /// Maximum degree of anisotropic filtering, improving the quality of textures
+    /// viewed at an angle. Clamped to the implementation limit, and ignored
+    /// if anisotropic filtering is not supported.
+    pub anisotropy: Option<f32>,
+    /// A bias added to the mipmap level of detail. Ignored if not supported.
+    pub lod_bias: f32,
+}
+
+// Floats are compared by their bits, so that sampler states can be used as cache keys.
+impl PartialEq for SamplerState {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SamplerState {}
+
+impl Hash for SamplerState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
 }
 
 impl SamplerState {
+    fn key(&self) -> (WrapFunction, MinFilter, Filter, Option<u32>, u32) {
+        (
+            self.wrap,
+            self.min_filter,
+            self.mag_filter,
+            self.anisotropy.map(f32::to_bits),
+            self.lod_bias.to_bits(),
+        )
+    }
+
     pub fn linear() -> Self {
         SamplerState {
             wrap: WrapFunction::Clamp,
             min_filter: MinFilter::Origin(Filter::Linear),
             mag_filter: Filter::Linear,
+            ..Default::default()
         }
     }
 
@@ -54,6 +153,39 @@ impl SamplerState {
             wrap: WrapFunction::Clamp,
             min_filter: MinFilter::Origin(Filter::Nearest),
             mag_filter: Filter::Nearest,
+            ..Default::default()
+        }
+    }
+
+    /// Linear filtering within and between mipmap levels.
+    ///
+    /// The texture must have mipmaps generated with `Texture::generate_mipmaps`,
+    /// otherwise it is incomplete and can't be sampled.
+    pub fn linear_mipmapped() -> Self {
+        SamplerState {
+            wrap: WrapFunction::Clamp,
+            min_filter: MinFilter::Mipmap {
+                mipmap: Filter::Linear,
+                texel: Filter::Linear,
+            },
+            mag_filter: Filter::Linear,
+            ..Default::default()
+        }
+    }
+
+    /// Nearest texel of the closest mipmap level.
+    ///
+    /// The texture must have mipmaps generated with `Texture::generate_mipmaps`,
+    /// otherwise it is incomplete and can't be sampled.
+    pub fn nearest_mipmapped() -> Self {
+        SamplerState {
+            wrap: WrapFunction::Clamp,
+            min_filter: MinFilter::Mipmap {
+                mipmap: Filter::Nearest,
+                texel: Filter::Nearest,
+            },
+            mag_filter: Filter::Nearest,
+            ..Default::default()
         }
     }
 }
@@ -83,6 +215,16 @@ impl<G: Graphics, T: Borrow<G::Texture>> Sampler<G, T> {
         Self::new(texture, SamplerState::nearest())
     }
 
+    /// See `SamplerState::linear_mipmapped`.
+    pub fn linear_mipmapped(texture: T) -> Self {
+        Self::new(texture, SamplerState::linear_mipmapped())
+    }
+
+    /// See `SamplerState::nearest_mipmapped`.
+    pub fn nearest_mipmapped(texture: T) -> Self {
+        Self::new(texture, SamplerState::nearest_mipmapped())
+    }
+
     pub fn as_borrowed<'a>(&'a self) -> Sampler<G, &'a G::Texture> {
         Sampler::new(self.texture.borrow(), self.state)
     }
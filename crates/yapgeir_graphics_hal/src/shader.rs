@@ -1,13 +1,67 @@
+use std::{error::Error, fmt::Display};
+
 use crate::Graphics;
 
 #[derive(Debug, Clone)]
 pub struct TextShaderSource<'a> {
     pub vertex: &'a str,
     pub fragment: &'a str,
+    /// A list of `(KEY, VALUE)` pairs that will be added as `#define KEY VALUE`
+    /// to both vertex and fragment sources before compilation.
+    pub defines: &'a [(&'a str, &'a str)],
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,
+    Fragment,
+}
+
+/// An error returned when a shader program can't be built from the source.
+#[derive(Debug, Clone)]
+pub enum ShaderError {
+    /// One of the shader stages failed to compile.
+    Compile {
+        stage: ShaderStage,
+        log: String,
+        /// The source of the stage as it was passed to the compiler.
+        source: String,
+    },
+    /// Shader stages were compiled, but the program failed to link.
+    Link { log: String },
+}
+
+impl Display for ShaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShaderError::Compile { stage, log, source } => {
+                write!(
+                    f,
+                    "Error compiling {stage:?} shader: {log}. Shader: \n {source}"
+                )
+            }
+            ShaderError::Link { log } => write!(f, "Error linking shader program: {log}"),
+        }
+    }
 }
 
+impl Error for ShaderError {}
+
 pub trait Shader<G: Graphics> {
     type Source;
 
-    fn new(renderer: G, source: &TextShaderSource) -> Self;
+    /// Creates a new shader, panicking if it can't be compiled or linked.
+    fn new(renderer: G, source: &TextShaderSource) -> Self
+    where
+        Self: Sized,
+    {
+        match Self::try_new(renderer, source) {
+            Ok(shader) => shader,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    fn try_new(renderer: G, source: &TextShaderSource) -> Result<Self, ShaderError>
+    where
+        Self: Sized;
 }
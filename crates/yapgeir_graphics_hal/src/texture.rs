@@ -9,6 +9,36 @@ pub enum PixelFormat {
     Lumia,
     Rgb,
     Rgba,
+    /// 8 bit RGBA in the sRGB color space, decoded to linear when sampled.
+    /// Requires `Graphics::supports_srgb`.
+    Srgba,
+    /// 16 bit depth component. Requires `Graphics::supports_depth_textures`.
+    Depth16,
+    /// 24 bit depth component. Requires `Graphics::supports_depth_textures`.
+    Depth24,
+}
+
+/// A face of a cubemap texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CubemapFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubemapFace {
+    /// All faces in the order expected by `Texture::new_cubemap`.
+    pub const ALL: [CubemapFace; 6] = [
+        CubemapFace::PositiveX,
+        CubemapFace::NegativeX,
+        CubemapFace::PositiveY,
+        CubemapFace::NegativeY,
+        CubemapFace::PositiveZ,
+        CubemapFace::NegativeZ,
+    ];
 }
 
 pub trait Texture<G: Graphics> {
@@ -16,6 +46,30 @@ pub trait Texture<G: Graphics> {
 
     fn new(renderer: G, format: G::PixelFormat, size: Size<u32>, bytes: Option<&[u8]>) -> Self;
 
+    /// Creates a cubemap texture with six square faces, ordered as `CubemapFace::ALL`.
+    ///
+    /// Cubemaps are bound through `samplerCube` uniforms, and can't be written
+    /// with `write` and `write_rect` or attached to a frame buffer.
+    fn new_cubemap(
+        renderer: G,
+        format: G::PixelFormat,
+        size: Size<u32>,
+        faces: [Option<&[u8]>; 6],
+    ) -> Self;
+
+    /// Returns true if the texture is a cubemap.
+    fn is_cubemap(&self) -> bool;
+
+    /// Writes the data to a single face of a cubemap texture.
+    fn write_face(
+        &self,
+        face: CubemapFace,
+        mipmap_level: u32,
+        format: G::PixelFormat,
+        size: Size<u32>,
+        bytes: &[u8],
+    );
+
     fn size(&self) -> Size<u32>;
 
     fn write(&self, mipmap_level: u32, format: G::PixelFormat, size: Size<u32>, bytes: &[u8]);
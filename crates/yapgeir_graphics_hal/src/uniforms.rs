@@ -1,3 +1,5 @@
+use std::cell::Ref;
+
 use crate::Graphics;
 use bytemuck::Pod;
 use derive_more::Constructor;
@@ -9,6 +11,126 @@ pub struct UniformAttribute {
     pub name: &'static str,
     pub offset: usize,
     pub size: usize,
+    /// Type of the uniform in the shader. An array uniform has the kind of its elements.
+    pub kind: UniformKind,
+}
+
+/// Type of a shader uniform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UniformKind {
+    Int,
+    IntVec2,
+    IntVec3,
+    IntVec4,
+    Float,
+    FloatVec2,
+    FloatVec3,
+    FloatVec4,
+    Mat2,
+    Mat3,
+    Mat4,
+}
+
+impl UniformKind {
+    /// Size of a single value of this kind in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            UniformKind::Int => 4,
+            UniformKind::IntVec2 => 4 * 2,
+            UniformKind::IntVec3 => 4 * 3,
+            UniformKind::IntVec4 => 4 * 4,
+            UniformKind::Float => 4,
+            UniformKind::FloatVec2 => 4 * 2,
+            UniformKind::FloatVec3 => 4 * 3,
+            UniformKind::FloatVec4 => 4 * 4,
+            UniformKind::Mat2 => 4 * 4,
+            UniformKind::Mat3 => 4 * 9,
+            UniformKind::Mat4 => 4 * 16,
+        }
+    }
+
+    /// Returns true if a uniform of this kind and size in bytes can be bound to a uniform
+    /// declared in the shader with `shader_kind` and `shader_size`.
+    ///
+    /// A uniform of the same kind must provide at least as much data as the shader expects.
+    /// Different kinds are compatible if they are made of the same numbers and have
+    /// the same size, e.g. `mat2` and `vec2[2]`, while a `mat3` can't be bound to a `mat4`.
+    pub fn is_compatible(self, size: usize, shader_kind: UniformKind, shader_size: usize) -> bool {
+        match self == shader_kind {
+            true => shader_size <= size,
+            false => self.is_int() == shader_kind.is_int() && shader_size == size,
+        }
+    }
+
+    fn is_int(self) -> bool {
+        matches!(
+            self,
+            UniformKind::Int | UniformKind::IntVec2 | UniformKind::IntVec3 | UniformKind::IntVec4
+        )
+    }
+
+    /// A vector of `n` components of a scalar kind, or the scalar kind itself for arrays.
+    const fn vector(self, n: usize) -> Self {
+        match (self, n) {
+            (UniformKind::Int, 2) => UniformKind::IntVec2,
+            (UniformKind::Int, 3) => UniformKind::IntVec3,
+            (UniformKind::Int, 4) => UniformKind::IntVec4,
+            (UniformKind::Float, 2) => UniformKind::FloatVec2,
+            (UniformKind::Float, 3) => UniformKind::FloatVec3,
+            (UniformKind::Float, 4) => UniformKind::FloatVec4,
+            _ => self,
+        }
+    }
+
+    /// A square matrix of a scalar kind, or an array of vectors of `rows` components.
+    const fn matrix(self, columns: usize, rows: usize) -> Self {
+        match (self, columns, rows) {
+            (UniformKind::Float, 2, 2) => UniformKind::Mat2,
+            (UniformKind::Float, 3, 3) => UniformKind::Mat3,
+            (UniformKind::Float, 4, 4) => UniformKind::Mat4,
+            _ => self.vector(rows),
+        }
+    }
+}
+
+/// Maps a field type to the kind of the uniform it's bound to by the `Uniforms` derive.
+///
+/// Arrays of 2 to 4 numbers are vectors, and square arrays of arrays of floats are matrices.
+/// Other arrays are arrays of their elements, e.g. `[f32; 16]` is `float[16]`. The kind can
+/// be overridden with `#[uniforms(kind = "FloatVec3")]`, and the shader may declare another
+/// kind of the same size, e.g. `mat4` for `[f32; 16]`, see `UniformKind::is_compatible`.
+///
+/// Fields of other types, e.g. math library matrices, have no kind to infer, so they don't
+/// compile without an explicit kind since kinds are checked against the shader.
+pub trait AsUniformKind {
+    const KIND: UniformKind;
+}
+
+/// Numbers uniforms consist of.
+pub trait UniformScalar {
+    const KIND: UniformKind;
+}
+
+macro_rules! impl_uniform_scalar {
+    ($(($t:ty, $cons:ident)),*) => {
+        $(impl UniformScalar for $t {
+            const KIND: UniformKind = UniformKind::$cons;
+        }
+
+        impl AsUniformKind for $t {
+            const KIND: UniformKind = UniformKind::$cons;
+        })*
+    };
+}
+
+impl_uniform_scalar!((i32, Int), (u32, Int), (f32, Float));
+
+impl<T: UniformScalar, const N: usize> AsUniformKind for [T; N] {
+    const KIND: UniformKind = T::KIND.vector(N);
+}
+
+impl<T: UniformScalar, const C: usize, const R: usize> AsUniformKind for [[T; R]; C] {
+    const KIND: UniformKind = T::KIND.matrix(C, R);
 }
 
 pub trait Uniforms {
@@ -20,6 +142,16 @@ pub trait UniformBuffer<G: Graphics, T: Pod> {
     fn write(&self, value: &T);
 }
 
+/// A uniform buffer with an erased data type. Type erasure is necessary to allow binding
+/// multiple uniform buffers of different types to a single draw call in a slice,
+/// e.g. per-frame camera uniforms along with per-object uniforms.
+pub trait AnyUniformBuffer<G: Graphics> {
+    /// Describes the uniforms provided by the buffer.
+    fn format(&self) -> &'static [UniformAttribute];
+    /// Returns the current value of the buffer, laid out as described by `format`.
+    fn bytes(&self) -> Ref<'_, [u8]>;
+}
+
 impl Uniforms for () {
     const FORMAT: &'static [UniformAttribute] = &[];
 }
@@ -15,6 +15,10 @@ pub struct VertexAttribute {
     pub offset: usize,
     pub kind: AttributeKind,
     pub size: VectorSize,
+    /// The number of instances that will pass between updates of the attribute
+    /// during instanced draw calls. Zero means that the attribute advances per vertex.
+    /// Ignored by implementations that don't support instancing.
+    pub divisor: u32,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq)]
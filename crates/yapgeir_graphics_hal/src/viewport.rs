@@ -0,0 +1,110 @@
+use crate::{draw_params::DrawParameters, frame_buffer::FrameBuffer, Graphics, Rect, Rgba, Size};
+
+/// A region of a frame buffer to draw into, e.g. a view of a split screen or a letterboxed image.
+///
+/// The region is in pixels with (0; 0) in the top-left corner, like all rectangles
+/// in `DrawParameters`, and the implementation takes care of flipping it if needed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Viewport {
+    pub rect: Rect<u32>,
+}
+
+impl Viewport {
+    pub fn new(rect: Rect<u32>) -> Self {
+        Self { rect }
+    }
+
+    /// The largest region of a frame buffer of a given `size` with the aspect ratio of `content`,
+    /// centered and letterboxed on the sides which don't fit.
+    pub fn letterbox(size: Size<u32>, content: Size<u32>) -> Self {
+        let (cw, ch) = (content.w.max(1) as u64, content.h.max(1) as u64);
+        let (w, h) = match size.w as u64 * ch <= size.h as u64 * cw {
+            true => (size.w, (size.w as u64 * ch / cw) as u32),
+            false => ((size.h as u64 * cw / ch) as u32, size.h),
+        };
+
+        Self::new(Rect::new((size.w - w) / 2, (size.h - h) / 2, w, h))
+    }
+
+    /// Splits a frame buffer of a given `size` into a grid of equal regions,
+    /// e.g. for local multiplayer, returned row by row from the top-left one.
+    ///
+    /// Pixels which don't divide evenly are left at the right and bottom edges.
+    pub fn split(size: Size<u32>, columns: u32, rows: u32) -> Vec<Self> {
+        let (w, h) = (size.w / columns.max(1), size.h / rows.max(1));
+
+        (0..rows)
+            .flat_map(|row| (0..columns).map(move |column| (column, row)))
+            .map(|(column, row)| Self::new(Rect::new(column * w, row * h, w, h)))
+            .collect()
+    }
+
+    /// Set both the viewport and the scissor of `draw_parameters` to the region,
+    /// so that normalized device coordinates are mapped to it, and nothing is drawn outside of it.
+    ///
+    /// The viewport alone doesn't clip everything, e.g. wide lines and points can cross it.
+    pub fn apply(&self, draw_parameters: &mut DrawParameters) {
+        draw_parameters.viewport = Some(self.rect);
+        draw_parameters.scissor = Some(self.rect);
+    }
+
+    /// Returns a copy of `draw_parameters` with the region applied, see `apply`.
+    pub fn draw_parameters(&self, draw_parameters: &DrawParameters) -> DrawParameters {
+        let mut draw_parameters = draw_parameters.clone();
+        self.apply(&mut draw_parameters);
+        draw_parameters
+    }
+
+    /// Clear only the region of the frame buffer, see `FrameBuffer::clear`.
+    pub fn clear<G: Graphics>(
+        &self,
+        frame_buffer: &G::FrameBuffer,
+        color: Option<Rgba<f32>>,
+        depth: Option<f32>,
+        stencil: Option<u8>,
+    ) {
+        frame_buffer.clear(Some(self.rect), color, depth, stencil);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_letterbox() {
+        let size = Size::new(1000, 600);
+        assert_eq!(
+            Viewport::letterbox(size, Size::new(4, 3)).rect,
+            Rect::new(100, 0, 800, 600)
+        );
+        assert_eq!(
+            Viewport::letterbox(size, Size::new(2, 1)).rect,
+            Rect::new(0, 50, 1000, 500)
+        );
+        assert_eq!(
+            Viewport::letterbox(size, Size::new(5, 3)).rect,
+            Rect::new(0, 0, 1000, 600)
+        );
+    }
+
+    #[test]
+    fn test_split() {
+        let views = Viewport::split(Size::new(801, 600), 2, 2);
+        let rects: Vec<_> = views.iter().map(|view| view.rect).collect();
+        assert_eq!(
+            rects,
+            [
+                Rect::new(0, 0, 400, 300),
+                Rect::new(400, 0, 400, 300),
+                Rect::new(0, 300, 400, 300),
+                Rect::new(400, 300, 400, 300),
+            ]
+        );
+
+        let mut draw_parameters = DrawParameters::default();
+        views[2].apply(&mut draw_parameters);
+        assert_eq!(draw_parameters.viewport, Some(Rect::new(0, 300, 400, 300)));
+        assert_eq!(draw_parameters.scissor, draw_parameters.viewport);
+    }
+}
@@ -10,6 +10,7 @@ pub struct GlesBuffer<B: WindowBackend> {
     pub ctx: Gles<B>,
     pub len: usize,
     pub kind: BufferKind,
+    pub usage: BufferUsage,
     pub buffer: glow::Buffer,
 }
 
@@ -52,6 +53,7 @@ impl<B: WindowBackend> ByteBuffer<Gles<B>> for GlesBuffer<B> {
             len,
             buffer,
             kind,
+            usage,
         }
     }
 
@@ -74,6 +76,17 @@ impl<B: WindowBackend> ByteBuffer<Gles<B>> for GlesBuffer<B> {
                 .buffer_sub_data_u8_slice(self.kind.gl_const(), offset as i32, data)
         };
     }
+
+    fn orphan(&self) {
+        let mut ctx = self.ctx.get_ref();
+        ctx.bind_vertex_array(None);
+        ctx.bind_buffer(self.kind, Some(self.buffer));
+
+        unsafe {
+            ctx.gl
+                .buffer_data_size(self.kind.gl_const(), self.len as i32, self.usage.gl_const())
+        };
+    }
 }
 
 impl<B: WindowBackend> Drop for GlesBuffer<B> {
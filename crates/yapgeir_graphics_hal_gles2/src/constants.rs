@@ -1,11 +1,13 @@
 use yapgeir_graphics_hal::{
     buffer::{BufferKind, BufferUsage},
     draw_params::{
-        BlendingEquation, BlendingFactor, CullFaceMode, DepthStencilTest, StencilActionMode,
+        BlendingEquation, BlendingFactor, CullFaceMode, DepthStencilTest, FrontFace, PolygonMode,
+        StencilActionMode,
     },
     index_buffer::{IndexKind, PrimitiveMode},
     render_buffer::RenderBufferFormat,
     sampler::{Filter, MinFilter, WrapFunction},
+    texture::CubemapFace,
     vertex_buffer::AttributeKind,
 };
 
@@ -118,12 +120,46 @@ impl GlConstant for CullFaceMode {
     }
 }
 
+impl GlConstant for FrontFace {
+    fn gl_const(self) -> u32 {
+        match self {
+            FrontFace::Cw => glow::CW,
+            FrontFace::Ccw => glow::CCW,
+        }
+    }
+}
+
+impl GlConstant for PolygonMode {
+    fn gl_const(self) -> u32 {
+        match self {
+            PolygonMode::Fill => glow::FILL,
+            PolygonMode::Line => glow::LINE,
+            PolygonMode::Point => glow::POINT,
+        }
+    }
+}
+
+impl GlConstant for CubemapFace {
+    fn gl_const(self) -> u32 {
+        match self {
+            CubemapFace::PositiveX => glow::TEXTURE_CUBE_MAP_POSITIVE_X,
+            CubemapFace::NegativeX => glow::TEXTURE_CUBE_MAP_NEGATIVE_X,
+            CubemapFace::PositiveY => glow::TEXTURE_CUBE_MAP_POSITIVE_Y,
+            CubemapFace::NegativeY => glow::TEXTURE_CUBE_MAP_NEGATIVE_Y,
+            CubemapFace::PositiveZ => glow::TEXTURE_CUBE_MAP_POSITIVE_Z,
+            CubemapFace::NegativeZ => glow::TEXTURE_CUBE_MAP_NEGATIVE_Z,
+        }
+    }
+}
+
 impl GlConstant for RenderBufferFormat {
     fn gl_const(self) -> u32 {
         match self {
             RenderBufferFormat::Depth => glow::DEPTH_COMPONENT24,
             RenderBufferFormat::Stencil => glow::STENCIL_INDEX8,
             RenderBufferFormat::DepthStencil => glow::DEPTH24_STENCIL8,
+            RenderBufferFormat::ColorMsaa { .. } => glow::RGBA8,
+            RenderBufferFormat::DepthStencilMsaa { .. } => glow::DEPTH24_STENCIL8,
         }
     }
 }
@@ -159,6 +195,7 @@ impl GlConstant for WrapFunction {
             WrapFunction::Repeat => glow::REPEAT,
             WrapFunction::MirrorClamp => glow::MIRROR_CLAMP_TO_EDGE,
             WrapFunction::MirrorRepeat => glow::MIRRORED_REPEAT,
+            WrapFunction::Border(_) => glow::CLAMP_TO_BORDER,
         }
     }
 }
@@ -176,14 +213,15 @@ impl GlConstant for MinFilter {
     fn gl_const(self) -> u32 {
         match self {
             MinFilter::Origin(filter) => filter.gl_const(),
+            // GL constants are named after the texel filter, and then the mipmap filter.
             MinFilter::Mipmap {
                 mipmap: Filter::Linear,
                 texel: Filter::Nearest,
-            } => glow::LINEAR_MIPMAP_NEAREST,
+            } => glow::NEAREST_MIPMAP_LINEAR,
             MinFilter::Mipmap {
                 mipmap: Filter::Nearest,
                 texel: Filter::Linear,
-            } => glow::NEAREST_MIPMAP_LINEAR,
+            } => glow::LINEAR_MIPMAP_NEAREST,
             MinFilter::Mipmap {
                 mipmap: Filter::Linear,
                 texel: Filter::Linear,
@@ -1,4 +1,7 @@
-use std::cell::{Cell, RefCell, RefMut};
+use std::{
+    cell::{Cell, RefCell, RefMut},
+    ffi::c_void,
+};
 
 use derive_more::Constructor;
 use enum_map::EnumMap;
@@ -6,7 +9,9 @@ use glow::HasContext;
 use smart_default::SmartDefault;
 use yapgeir_graphics_hal::{
     buffer::BufferKind,
-    draw_params::{Blend, CullFaceMode, Depth, PolygonOffset, Stencil, StencilCheck},
+    draw_params::{
+        Blend, CullFaceMode, Depth, FrontFace, PolygonMode, PolygonOffset, Stencil, StencilCheck,
+    },
     sampler::SamplerState,
     Rect, Rgba, Size, WindowBackend,
 };
@@ -54,9 +59,12 @@ impl<T: PartialEq> Feature<T> {
     }
 }
 
-#[derive(Default, Clone)]
+#[derive(SmartDefault, Clone)]
 pub struct TextureUnit {
     pub texture: Option<glow::Texture>,
+    /// Target of the bound texture, `GL_TEXTURE_2D` or `GL_TEXTURE_CUBE_MAP`.
+    #[default(glow::TEXTURE_2D)]
+    pub target: u32,
     pub sampler: SamplerState,
 }
 
@@ -70,17 +78,23 @@ pub struct GlesState {
 
     pub blend: Feature<Blend>,
     pub cull_face: Feature<CullFaceMode>,
+    /// OpenGL initially treats counter-clockwise polygons as front-facing.
+    #[default(FrontFace::Ccw)]
+    pub front_face: FrontFace,
     pub depth: Feature<Depth>,
     pub stencil: Feature<Stencil>,
     pub scissor: Feature<Rect<u32>>,
     pub polygon_offset: Feature<PolygonOffset>,
+    pub polygon_mode: PolygonMode,
+    /// Set once unsupported polygon mode has been reported, to warn only once.
+    pub polygon_mode_warned: bool,
 
     #[default(Rgba::all(true))]
     pub color_mask: Rgba<bool>,
     #[default(1.)]
     pub line_width: f32,
     pub dithering: bool,
-    pub viewport: Rect<u32>,
+    pub viewport: Rect<i32>,
 
     pub active_texture_unit: u32,
     pub texture_unit_limit: usize,
@@ -99,10 +113,113 @@ pub struct GlesState {
     pub draw_descriptor_cache: super::draw_descriptor::DrawDescriptorCache,
 }
 
+#[derive(Default)]
 pub struct Extensions {
     pub vertex_array_objects: bool,
     pub sampler_objects: bool,
     pub blit_framebuffer: bool,
+    pub instanced_arrays: bool,
+    pub timer_query: bool,
+    pub disjoint_timer_query: bool,
+    /// Maximum number of samples for multisampled render buffers, 1 if multisampling is unsupported.
+    pub max_samples: u8,
+    pub depth_texture: bool,
+    /// Whether the depth component can be read with `glReadPixels`.
+    pub read_depth: bool,
+    /// Maximum degree of anisotropic filtering, `None` if anisotropic filtering is unsupported.
+    pub max_anisotropy: Option<f32>,
+    /// Whether textures support a mipmap level of detail bias, which is not available in GLES.
+    pub lod_bias: bool,
+    /// Pixel transfer format of sRGB textures, `None` if sRGB is unsupported.
+    /// OpenGL ES requires it to match the internal format, while desktop OpenGL expects RGBA.
+    pub srgb_format: Option<u32>,
+    /// Whether sRGB conversion on writes can be toggled with `GL_FRAMEBUFFER_SRGB`.
+    /// Without it, writes to sRGB attachments are always converted.
+    pub srgb_write_control: bool,
+    /// Whether `glPolygonMode` is available, which is only the case on desktop OpenGL.
+    pub polygon_mode: bool,
+    /// Whether `f32` textures are supported. Linear filtering of them may still be unsupported.
+    pub float_texture: bool,
+    /// Component type of half float textures, `None` if they are unsupported.
+    /// `GL_OES_texture_half_float` uses a different constant than `GL_HALF_FLOAT`.
+    pub half_float_type: Option<u32>,
+    /// Whether float textures use sized internal formats, which is the case
+    /// for desktop OpenGL and OpenGL ES 3.
+    pub sized_float_formats: bool,
+    /// Whether float color attachments can be rendered to and read.
+    pub color_buffer_float: bool,
+    /// Maximum number of color attachments of a framebuffer, 1 if multiple render targets are unsupported.
+    pub max_draw_buffers: usize,
+    /// `glDrawBuffersEXT` from `GL_EXT_draw_buffers`, which replaces `glDrawBuffers` on OpenGL ES 2.
+    pub draw_buffers_ext: Option<DrawBuffersExt>,
+    /// Whether textures with sizes which are not a power of two can be mipmapped.
+    pub npot_mipmaps: bool,
+    /// Whether `GL_CLAMP_TO_BORDER` and `GL_TEXTURE_BORDER_COLOR` are supported.
+    /// `GL_EXT_texture_border_clamp` uses the same constants.
+    pub border_clamp: bool,
+}
+
+/// Signature of `glDrawBuffersEXT`.
+pub type DrawBuffersExt = unsafe extern "system" fn(n: i32, bufs: *const u32);
+
+/// Loads `glDrawBuffersEXT`, since glow only loads the core `glDrawBuffers`,
+/// which OpenGL ES 2 doesn't have.
+unsafe fn load_draw_buffers_ext(backend: &impl WindowBackend) -> Option<DrawBuffersExt> {
+    let function = backend.get_proc_address("glDrawBuffersEXT");
+    (!function.is_null()).then(|| std::mem::transmute::<*const c_void, DrawBuffersExt>(function))
+}
+
+/// `GL_HALF_FLOAT_OES` from `GL_OES_texture_half_float`.
+pub const HALF_FLOAT_OES: u32 = 0x8D61;
+
+/// A change of the context availability, detected at the end of a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextEvent {
+    Lost,
+    Restored,
+}
+
+/// Tracks whether the context is lost.
+///
+/// Loss and restoration are reported by the backend, e.g. by `isContextLost()` of a WebGL canvas.
+/// `glGetError` is not polled for `GL_CONTEXT_LOST`, since doing so every frame stalls the pipeline.
+#[derive(Default)]
+pub struct ContextLoss {
+    pub lost: bool,
+}
+
+impl ContextLoss {
+    pub fn update(&mut self, backend_lost: bool) -> Option<ContextEvent> {
+        if backend_lost == self.lost {
+            return None;
+        }
+
+        self.lost = backend_lost;
+        match backend_lost {
+            true => Some(ContextEvent::Lost),
+            false => Some(ContextEvent::Restored),
+        }
+    }
+}
+
+/// Sets up the global state which is not tracked by `GlesState`.
+unsafe fn configure(gl: &glow::Context, settings: &GlesSettings, extensions: &Extensions) {
+    gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
+    gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
+
+    // Desktop OpenGL ignores gl_PointSize written by shaders unless enabled.
+    if !gl.version().is_embedded {
+        gl.enable(glow::PROGRAM_POINT_SIZE);
+    }
+
+    if srgb_default_frame_buffer(settings, extensions) && extensions.srgb_write_control {
+        gl.enable(glow::FRAMEBUFFER_SRGB);
+    }
+}
+
+/// Returns true if the default framebuffer should be gamma-correct, which requires sRGB support.
+fn srgb_default_frame_buffer(settings: &GlesSettings, extensions: &Extensions) -> bool {
+    settings.srgb_default_frame_buffer && extensions.srgb_format.is_some()
 }
 
 pub struct GlesContext<B: WindowBackend> {
@@ -114,7 +231,10 @@ pub struct GlesContext<B: WindowBackend> {
     pub settings: GlesSettings,
 
     pub fake_default_frame_buffer: Option<RefCell<FakeDefaultFrameBuffer>>,
-    pub frame_buffer_blitter: FrameBufferBlitter,
+    pub frame_buffer_blitter: RefCell<FrameBufferBlitter>,
+
+    pub context_loss: RefCell<ContextLoss>,
+    pub restore_callbacks: RefCell<Vec<Box<dyn FnMut()>>>,
 }
 
 impl<B: WindowBackend> Drop for GlesContext<B> {
@@ -132,7 +252,7 @@ impl<B: WindowBackend> Drop for GlesContext<B> {
             unsafe { fake_default_frame_buffer.borrow().destroy(&self.gl) };
         }
 
-        unsafe { self.frame_buffer_blitter.destroy(&self.gl) };
+        unsafe { self.frame_buffer_blitter.borrow().destroy(&self.gl) };
     }
 }
 
@@ -140,16 +260,95 @@ impl<B: WindowBackend> GlesContext<B> {
     pub unsafe fn new(backend: B, settings: GlesSettings) -> Self {
         let gl = glow::Context::from_loader_function(|s| backend.get_proc_address(s));
 
-        gl.pixel_store_i32(glow::PACK_ALIGNMENT, 1);
-        gl.pixel_store_i32(glow::UNPACK_ALIGNMENT, 1);
-
+        let version = gl.version();
         let extensions = gl.supported_extensions();
+        let blit_framebuffer = extensions.contains("GL_EXT_framebuffer_blit");
+
+        // Multisampled buffers can only be resolved by blitting them
+        let multisample = blit_framebuffer
+            && (version.major >= 3
+                || extensions.contains("GL_EXT_framebuffer_multisample")
+                || extensions.contains("GL_ARB_framebuffer_object"));
+
         let extensions = Extensions {
             vertex_array_objects: extensions.contains("GL_OES_vertex_array_object"),
             sampler_objects: extensions.contains("GL_ARB_sampler_objects"),
-            blit_framebuffer: extensions.contains("GL_EXT_framebuffer_blit"),
+            blit_framebuffer,
+            instanced_arrays: extensions.contains("GL_ANGLE_instanced_arrays")
+                || extensions.contains("GL_EXT_instanced_arrays")
+                || extensions.contains("GL_ARB_instanced_arrays"),
+            timer_query: extensions.contains("GL_EXT_disjoint_timer_query")
+                || extensions.contains("GL_ARB_timer_query"),
+            disjoint_timer_query: extensions.contains("GL_EXT_disjoint_timer_query"),
+            max_samples: match multisample {
+                true => gl
+                    .get_parameter_i32(glow::MAX_SAMPLES)
+                    .clamp(1, u8::MAX as i32) as u8,
+                false => 1,
+            },
+            depth_texture: !version.is_embedded
+                || version.major >= 3
+                || extensions.contains("GL_OES_depth_texture")
+                || extensions.contains("GL_ANGLE_depth_texture"),
+            read_depth: !version.is_embedded || extensions.contains("GL_NV_read_depth"),
+            max_anisotropy: match extensions.contains("GL_EXT_texture_filter_anisotropic")
+                || extensions.contains("GL_ARB_texture_filter_anisotropic")
+            {
+                true => Some(gl.get_parameter_f32(glow::MAX_TEXTURE_MAX_ANISOTROPY_EXT)),
+                false => None,
+            },
+            lod_bias: !version.is_embedded,
+            srgb_format: match version.is_embedded {
+                true => extensions
+                    .contains("GL_EXT_sRGB")
+                    .then_some(glow::SRGB_ALPHA),
+                false => (version.major >= 3 || extensions.contains("GL_ARB_framebuffer_sRGB"))
+                    .then_some(glow::RGBA),
+            },
+            srgb_write_control: !version.is_embedded
+                || extensions.contains("GL_EXT_sRGB_write_control"),
+            polygon_mode: !version.is_embedded,
+            float_texture: !version.is_embedded
+                || version.major >= 3
+                || extensions.contains("GL_OES_texture_float"),
+            half_float_type: match !version.is_embedded || version.major >= 3 {
+                true => Some(glow::HALF_FLOAT),
+                false => extensions
+                    .contains("GL_OES_texture_half_float")
+                    .then_some(HALF_FLOAT_OES),
+            },
+            sized_float_formats: !version.is_embedded || version.major >= 3,
+            color_buffer_float: !version.is_embedded
+                || extensions.contains("GL_EXT_color_buffer_float")
+                || extensions.contains("GL_EXT_color_buffer_half_float"),
+            max_draw_buffers: match !version.is_embedded
+                || version.major >= 3
+                || extensions.contains("GL_EXT_draw_buffers")
+                || extensions.contains("WEBGL_draw_buffers")
+            {
+                // GL_MAX_DRAW_BUFFERS_EXT has the same value as GL_MAX_DRAW_BUFFERS.
+                true => gl.get_parameter_i32(glow::MAX_DRAW_BUFFERS).max(1) as usize,
+                false => 1,
+            },
+            draw_buffers_ext: match version.is_embedded
+                && version.major < 3
+                && extensions.contains("GL_EXT_draw_buffers")
+            {
+                true => load_draw_buffers_ext(&backend),
+                false => None,
+            },
+            npot_mipmaps: !version.is_embedded
+                || version.major >= 3
+                || extensions.contains("GL_OES_texture_npot"),
+            border_clamp: !version.is_embedded
+                || (version.major, version.minor) >= (3, 2)
+                || extensions.contains("GL_EXT_texture_border_clamp")
+                || extensions.contains("GL_OES_texture_border_clamp"),
         };
 
+        configure(&gl, &settings, &extensions);
+        let srgb = srgb_default_frame_buffer(&settings, &extensions);
+
         let default_framebuffer_size = backend.default_frame_buffer_size();
 
         let mut texture_unit_limit =
@@ -176,7 +375,7 @@ impl<B: WindowBackend> GlesContext<B> {
 
             let fake_default_frame_buffer = match settings.flip_default_frame_buffer {
                 true => Some(RefCell::new(unsafe {
-                    FakeDefaultFrameBuffer::new(&mut ctx, default_framebuffer_size)
+                    FakeDefaultFrameBuffer::new(&mut ctx, default_framebuffer_size, srgb)
                 })),
                 false => None,
             };
@@ -192,8 +391,49 @@ impl<B: WindowBackend> GlesContext<B> {
             settings,
             default_framebuffer_size: Cell::new(Some(default_framebuffer_size)),
             fake_default_frame_buffer,
-            frame_buffer_blitter,
+            frame_buffer_blitter: RefCell::new(frame_buffer_blitter),
+            context_loss: Default::default(),
+            restore_callbacks: Default::default(),
+        }
+    }
+
+    /// Recreates internal objects and resets the cached state after the context is restored,
+    /// and lets the owners of other resources recreate them.
+    pub unsafe fn restore(&self) {
+        configure(&self.gl, &self.settings, &self.extensions);
+
+        // Old objects are gone with the lost context, so they are forgotten instead of deleted.
+        {
+            let mut state = self.state.borrow_mut();
+            *state = GlesState {
+                texture_unit_limit: state.texture_unit_limit,
+                ..Default::default()
+            };
+        }
+
+        {
+            let mut ctx = self.get_ref();
+            *self.frame_buffer_blitter.borrow_mut() = FrameBufferBlitter::new(&mut ctx);
+
+            if let Some(fake_default_frame_buffer) = &self.fake_default_frame_buffer {
+                let mut fake_default_frame_buffer = fake_default_frame_buffer.borrow_mut();
+                let (size, srgb) = (
+                    fake_default_frame_buffer.size,
+                    fake_default_frame_buffer.srgb,
+                );
+                *fake_default_frame_buffer = FakeDefaultFrameBuffer::new(&mut ctx, size, srgb);
+            }
+        }
+
+        // Callbacks may register new callbacks while recreating resources.
+        let mut callbacks = std::mem::take(&mut *self.restore_callbacks.borrow_mut());
+        for callback in &mut callbacks {
+            callback();
         }
+
+        let mut restore_callbacks = self.restore_callbacks.borrow_mut();
+        callbacks.append(&mut restore_callbacks);
+        *restore_callbacks = callbacks;
     }
 
     pub fn default_framebuffer_size(&self) -> Size<u32> {
@@ -224,6 +464,14 @@ pub struct GlesContextRef<'a> {
 }
 
 impl<'a> GlesContextRef<'a> {
+    /// Sets the color attachments of the bound framebuffer drawn to by `gl_FragData`.
+    pub unsafe fn draw_buffers(&self, buffers: &[u32]) {
+        match self.extensions.draw_buffers_ext {
+            Some(draw_buffers_ext) => draw_buffers_ext(buffers.len() as i32, buffers.as_ptr()),
+            None => self.gl.draw_buffers(buffers),
+        }
+    }
+
     pub fn set_polygon_offset(&mut self, polygon_offset: Option<PolygonOffset>) {
         self.state.polygon_offset.update(
             &self.gl,
@@ -235,6 +483,27 @@ impl<'a> GlesContextRef<'a> {
         );
     }
 
+    pub fn set_polygon_mode(&mut self, polygon_mode: Option<PolygonMode>) {
+        let polygon_mode = polygon_mode.unwrap_or_default();
+        if polygon_mode == self.state.polygon_mode {
+            return;
+        }
+
+        if !self.extensions.polygon_mode {
+            if !self.state.polygon_mode_warned {
+                eprintln!("Polygon mode {polygon_mode:?} is not supported on OpenGL ES, ignoring");
+                self.state.polygon_mode_warned = true;
+            }
+            return;
+        }
+
+        unsafe {
+            self.gl
+                .polygon_mode(glow::FRONT_AND_BACK, polygon_mode.gl_const())
+        };
+        self.state.polygon_mode = polygon_mode;
+    }
+
     pub fn set_cull_face(&mut self, cull_face: Option<CullFaceMode>) {
         self.state
             .cull_face
@@ -243,6 +512,13 @@ impl<'a> GlesContextRef<'a> {
             });
     }
 
+    pub fn set_front_face(&mut self, front_face: FrontFace) {
+        if self.state.front_face != front_face {
+            unsafe { self.gl.front_face(front_face.gl_const()) };
+            self.state.front_face = front_face;
+        }
+    }
+
     pub fn set_scissor(&mut self, scissor: Option<Rect<u32>>) {
         self.state
             .scissor
@@ -358,11 +634,11 @@ impl<'a> GlesContextRef<'a> {
         }
     }
 
-    pub fn set_viewport(&mut self, viewport: Rect<u32>) {
+    pub fn set_viewport(&mut self, viewport: Rect<i32>) {
         if viewport != self.state.viewport {
             unsafe {
-                let rect: Rect<i32> = (&viewport).into();
-                self.gl.viewport(rect.x, rect.y, rect.w, rect.h)
+                self.gl
+                    .viewport(viewport.x, viewport.y, viewport.w, viewport.h)
             };
             self.state.viewport = viewport;
         }
@@ -436,15 +712,29 @@ impl<'a> GlesContextRef<'a> {
         }
     }
 
-    pub fn bind_texture(&mut self, unit: u32, texture: Option<glow::Texture>) {
-        if self.state.texture_units[unit as usize].texture != texture {
-            self.activate_texture_unit(unit);
-            unsafe { self.gl.bind_texture(glow::TEXTURE_2D, texture) };
-            self.state.texture_units[unit as usize].texture = texture;
+    pub fn bind_texture(&mut self, unit: u32, target: u32, texture: Option<glow::Texture>) {
+        let current = &self.state.texture_units[unit as usize];
+        if current.texture == texture {
+            return;
+        }
+
+        let (current_texture, current_target) = (current.texture, current.target);
+        self.activate_texture_unit(unit);
+        unsafe {
+            // Keep a single texture per unit, even if the targets differ.
+            if current_texture.is_some() && current_target != target {
+                self.gl.bind_texture(current_target, None);
+            }
+
+            self.gl.bind_texture(target, texture);
         }
+
+        let unit = &mut self.state.texture_units[unit as usize];
+        unit.texture = texture;
+        unit.target = target;
     }
 
-    pub fn activate_texture(&mut self, texture: glow::Texture) {
+    pub fn activate_texture(&mut self, texture: glow::Texture, target: u32) {
         let mut empty_unit = None;
         let mut bound_unit = None;
 
@@ -465,11 +755,7 @@ impl<'a> GlesContextRef<'a> {
             self.activate_texture_unit(bound_unit as u32);
         } else {
             let empty_unit = empty_unit.unwrap_or(0) as u32;
-            self.activate_texture_unit(empty_unit);
-            unsafe { self.gl.bind_texture(glow::TEXTURE_2D, Some(texture)) };
-
-            let unit = self.state.active_texture_unit as usize;
-            self.state.texture_units[unit].texture = Some(texture);
+            self.bind_texture(empty_unit, target, Some(texture));
         }
     }
 
@@ -504,3 +790,45 @@ impl<'a> GlesContextRef<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_srgb_setting_requires_extension() {
+        let settings = GlesSettings {
+            srgb_default_frame_buffer: true,
+            ..Default::default()
+        };
+
+        assert!(!srgb_default_frame_buffer(
+            &settings,
+            &Extensions::default()
+        ));
+
+        let extensions = Extensions {
+            srgb_format: Some(glow::SRGB_ALPHA),
+            ..Default::default()
+        };
+        assert!(srgb_default_frame_buffer(&settings, &extensions));
+        assert!(!srgb_default_frame_buffer(
+            &GlesSettings::default(),
+            &extensions
+        ));
+    }
+
+    #[test]
+    fn test_context_loss() {
+        let mut loss = ContextLoss::default();
+        assert_eq!(loss.update(false), None);
+
+        assert_eq!(loss.update(true), Some(ContextEvent::Lost));
+        assert_eq!(loss.update(true), None);
+        assert!(loss.lost);
+
+        assert_eq!(loss.update(false), Some(ContextEvent::Restored));
+        assert_eq!(loss.update(false), None);
+        assert!(!loss.lost);
+    }
+}
@@ -105,6 +105,13 @@ unsafe fn bind_buffers<'a, B: WindowBackend>(
                     stride,
                     attribute.offset as i32,
                 );
+
+                // Divisors are a part of the global state when VAO are not supported,
+                // so they are always set to reset the values left by other descriptors.
+                // Without instancing support divisors are ignored.
+                if ctx.extensions.instanced_arrays {
+                    ctx.gl.vertex_attrib_divisor(location, attribute.divisor);
+                }
             } else {
                 continue;
             }
@@ -12,10 +12,22 @@ pub struct FakeDefaultFrameBuffer {
     pub framebuffer: glow::Framebuffer,
     pub draw_texture: glow::Texture,
     pub depth_stencil: glow::Renderbuffer,
+    /// Whether the draw texture is in the sRGB color space.
+    pub srgb: bool,
 }
 
 impl FakeDefaultFrameBuffer {
-    pub unsafe fn new(ctx: &mut GlesContextRef, size: Size<u32>) -> Self {
+    pub unsafe fn new(ctx: &mut GlesContextRef, size: Size<u32>, srgb: bool) -> Self {
+        let (internal_format, format) = match srgb {
+            true => (
+                glow::SRGB_ALPHA,
+                ctx.extensions
+                    .srgb_format
+                    .expect("sRGB framebuffer requires GL_EXT_sRGB!"),
+            ),
+            false => (glow::RGBA, glow::RGBA),
+        };
+
         // Create a new draw texture
         let draw_texture = ctx.gl.create_texture().expect("unable to create a texture");
         ctx.activate_texture_unit(ctx.state.texture_unit_limit as u32);
@@ -23,11 +35,11 @@ impl FakeDefaultFrameBuffer {
         ctx.gl.tex_image_2d(
             glow::TEXTURE_2D,
             0,
-            glow::RGBA as i32,
+            internal_format as i32,
             size.w as i32,
             size.h as i32,
             0,
-            glow::RGBA,
+            format,
             glow::UNSIGNED_BYTE,
             None,
         );
@@ -92,6 +104,7 @@ impl FakeDefaultFrameBuffer {
             framebuffer,
             draw_texture,
             depth_stencil,
+            srgb,
         }
     }
 
@@ -103,7 +116,7 @@ impl FakeDefaultFrameBuffer {
         if size != self.size {
             self.size = size;
             self.destroy(&ctx.gl);
-            *self = FakeDefaultFrameBuffer::new(ctx, size);
+            *self = FakeDefaultFrameBuffer::new(ctx, size, self.srgb);
         }
 
         self.framebuffer
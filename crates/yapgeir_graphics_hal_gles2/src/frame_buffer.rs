@@ -1,30 +1,29 @@
 use core::panic;
-use std::{borrow::Borrow, ops::Deref, rc::Rc};
+use std::borrow::Borrow;
 
 use bitvec::prelude::BitArray;
-use bm::Pod;
 use bytemuck as bm;
 use glow::HasContext;
 use yapgeir_graphics_hal::{
     draw_params::DrawParameters,
     frame_buffer::{
-        Attachment, DepthStencilAttachment, FlipSource, FrameBuffer, Indices, ReadFormat,
+        Attachment, DepthStencilAttachment, DrawAttachments, FlipSource, FrameBuffer, Indices,
+        ReadFormat,
     },
     sampler::{Filter, SamplerState},
     samplers::SamplerAttribute,
-    uniforms::{UniformAttribute, Uniforms},
+    uniforms::{AnyUniformBuffer, UniformAttribute, UniformKind, Uniforms},
     Rect, Rgba, Size, WindowBackend,
 };
 
 use crate::{
     constants::GlConstant,
-    context::{GlesContext, GlesContextRef},
+    context::{Extensions, GlesContext, GlesContextRef, TextureUnit},
     draw_descriptor::GlesDrawDescriptor,
     frame_buffer_blitter::{BlitSourceRect, ReadSource},
     render_buffer::GlesRenderBuffer,
-    shader::{GlesShader, ShaderState, UniformKind},
+    shader::{GlesShader, ShaderState},
     texture::{GlesTexture, RgbLayout, RgbaLayout},
-    uniforms::GlesUniformBuffer,
     Gles,
 };
 
@@ -33,6 +32,14 @@ pub enum GlesReadFormat {
     Alpha,
     Rgb(RgbLayout),
     Rgba(RgbaLayout),
+    /// Reads the depth component as `f32` values in the range `[0, 1]`.
+    ///
+    /// The target slice must be 4 bytes per pixel, for example an `f32` buffer cast
+    /// with `bytemuck::cast_slice_mut`. Not supported by most OpenGL ES implementations.
+    Depth,
+    /// Reads float RGBA color, 16 bytes per pixel.
+    /// Requires a float color attachment, and `GL_EXT_color_buffer_float` on OpenGL ES.
+    RgbaF32,
 }
 
 impl From<ReadFormat> for GlesReadFormat {
@@ -46,11 +53,28 @@ impl From<ReadFormat> for GlesReadFormat {
 }
 
 impl GlesReadFormat {
+    /// Panics if the format can't be read by the implementation.
+    fn check(self, extensions: &Extensions) {
+        match self {
+            GlesReadFormat::Depth => assert!(
+                extensions.read_depth,
+                "Reading depth requires GL_NV_read_depth on OpenGL ES!"
+            ),
+            GlesReadFormat::RgbaF32 => assert!(
+                extensions.color_buffer_float,
+                "Reading float color requires GL_EXT_color_buffer_float on OpenGL ES!"
+            ),
+            _ => {}
+        }
+    }
+
     fn gl(self) -> (u32, u32) {
         match self {
             GlesReadFormat::Alpha => (glow::ALPHA, glow::UNSIGNED_BYTE),
             GlesReadFormat::Rgb(f) => (glow::RGB, f.gl_const()),
             GlesReadFormat::Rgba(f) => (glow::RGBA, f.gl_const()),
+            GlesReadFormat::Depth => (glow::DEPTH_COMPONENT, glow::FLOAT),
+            GlesReadFormat::RgbaF32 => (glow::RGBA, glow::FLOAT),
         }
     }
 }
@@ -60,6 +84,10 @@ unsafe fn attach_texture<B: WindowBackend>(
     texture: &GlesTexture<B>,
     attachment: u32,
 ) {
+    assert!(
+        texture.target == glow::TEXTURE_2D,
+        "cubemaps can't be attached to a frame buffer"
+    );
     gl.framebuffer_texture_2d(
         glow::FRAMEBUFFER,
         attachment,
@@ -93,11 +121,71 @@ unsafe fn attach<B: WindowBackend>(
     }
 }
 
+/// Returns color attachment points for `count` draw attachments.
+fn draw_buffers(count: usize, max_draw_buffers: usize) -> Vec<u32> {
+    assert!(
+        count > 0,
+        "A frame buffer requires at least one draw attachment"
+    );
+    assert!(
+        count <= max_draw_buffers,
+        "A frame buffer with {count} draw attachments was requested, \
+        but the implementation supports at most {max_draw_buffers}"
+    );
+
+    (0..count)
+        .map(|i| glow::COLOR_ATTACHMENT0 + i as u32)
+        .collect()
+}
+
+/// Returns the size of draw attachments, which must be the same for all of them.
+fn attachments_size<B: WindowBackend>(draw: &[Attachment<Gles<B>>]) -> Size<u32> {
+    let sizes = draw.iter().map(|attachment| match attachment {
+        Attachment::Texture(texture) => texture.size,
+        Attachment::RenderBuffer(render_buffer) => render_buffer.size,
+    });
+    same_size(sizes)
+}
+
+fn same_size(mut sizes: impl Iterator<Item = Size<u32>>) -> Size<u32> {
+    let size = sizes
+        .next()
+        .expect("A frame buffer requires at least one draw attachment");
+    for other in sizes {
+        assert_eq!(
+            size, other,
+            "All draw attachments of a frame buffer must have the same size"
+        );
+    }
+    size
+}
+
 // OpenGL uses Y-up coordinate system for everything.
-// This function is used to convert scissor and viewport rectangles from
-// y-down coordinates.
-fn to_y_up(rect: &Rect<u32>, size: &Size<u32>) -> Rect<u32> {
-    Rect::new(rect.x, size.h - rect.y - rect.h, rect.w, rect.h)
+// These functions are used to convert viewport and scissor rectangles from
+// y-down coordinates, unless the default frame buffer is flipped, in which case
+// everything is drawn upside down and the rectangles are used as is.
+
+// Viewports may cross the edges of the frame buffer, so the converted
+// rectangle can start below the bottom of it.
+fn gl_viewport(rect: Rect<u32>, size: Size<u32>, flip_default_framebuffer: bool) -> Rect<i32> {
+    let rect: Rect<i32> = (&rect).into();
+    match flip_default_framebuffer {
+        true => rect,
+        false => Rect::new(rect.x, size.h as i32 - rect.y - rect.h, rect.w, rect.h),
+    }
+}
+
+// Scissors are clipped to the frame buffer first, since nothing outside of it is drawn anyway.
+fn gl_scissor(rect: Rect<u32>, size: Size<u32>, flip_default_framebuffer: bool) -> Rect<u32> {
+    let x = rect.x.min(size.w);
+    let y = rect.y.min(size.h);
+    let w = rect.w.min(size.w - x);
+    let h = rect.h.min(size.h - y);
+
+    match flip_default_framebuffer {
+        true => Rect::new(x, y, w, h),
+        false => Rect::new(x, size.h - y - h, w, h),
+    }
 }
 
 enum Resources<B: WindowBackend> {
@@ -105,7 +193,7 @@ enum Resources<B: WindowBackend> {
     Managed {
         size: Size<u32>,
         framebuffer: glow::Framebuffer,
-        _draw_texture: Rc<GlesTexture<B>>,
+        draw: Vec<Attachment<Gles<B>>>,
         _depth_stencil: DepthStencilAttachment<Gles<B>>,
     },
 }
@@ -150,9 +238,12 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
 
     fn new(
         ctx: Gles<B>,
-        draw_texture: Rc<GlesTexture<B>>,
+        DrawAttachments(draw): DrawAttachments<Gles<B>>,
         depth_stencil: DepthStencilAttachment<Gles<B>>,
     ) -> Self {
+        let buffers = draw_buffers(draw.len(), ctx.extensions.max_draw_buffers);
+        let size = attachments_size(&draw);
+
         let framebuffer = unsafe {
             let mut ctx = ctx.get_ref();
             let fb = ctx
@@ -161,7 +252,14 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
                 .expect("unable to create a framebuffer");
             ctx.bind_frame_buffer(Some(fb));
 
-            attach_texture(ctx.gl, &draw_texture, glow::COLOR_ATTACHMENT0);
+            for (attachment, buffer) in draw.iter().zip(&buffers) {
+                attach(ctx.gl, attachment, *buffer);
+            }
+
+            // A single attachment is drawn to by default, which is the only option without extensions.
+            if buffers.len() > 1 {
+                ctx.draw_buffers(&buffers);
+            }
 
             match &depth_stencil {
                 DepthStencilAttachment::None => {}
@@ -186,9 +284,9 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
         Self {
             ctx,
             res: Resources::Managed {
-                size: draw_texture.size,
+                size,
                 framebuffer,
-                _draw_texture: draw_texture,
+                draw,
                 _depth_stencil: depth_stencil,
             },
         }
@@ -205,15 +303,8 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
         depth: Option<f32>,
         stencil: Option<u8>,
     ) {
-        // Flip scissor coordinates, unless we're conforming to a coordinate space
-        let scissor = if self.ctx.settings.flip_default_frame_buffer {
-            scissor
-        } else {
-            scissor.map(|scissor| {
-                let size = self.size();
-                to_y_up(&scissor, &size)
-            })
-        };
+        let flip = self.ctx.settings.flip_default_frame_buffer;
+        let scissor = scissor.map(|scissor| gl_scissor(scissor, self.size(), flip));
 
         let fb = self.res.framebuffer(&self.ctx);
         let mut ctx = self.ctx.get_ref();
@@ -221,36 +312,45 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
         ctx.clear(scissor, color, depth, stencil);
     }
 
-    fn draw<U: Uniforms + Pod>(
+    fn draw(
         &self,
         draw_descriptor: &GlesDrawDescriptor<B>,
         draw_parameters: &DrawParameters,
         textures: &[SamplerAttribute<Gles<B>, impl Borrow<GlesTexture<B>>>],
-        uniforms: Option<&GlesUniformBuffer<U>>,
+        uniforms: &[&dyn AnyUniformBuffer<Gles<B>>],
         indices: &Indices,
     ) {
-        let size = self.size();
-        let fb = self.res.framebuffer(&self.ctx);
-        let mut ctx = self.ctx.get_ref();
-        ctx.use_program(Some(draw_descriptor.shader.program));
-        bind_textures(&mut ctx, &draw_descriptor.shader, textures);
+        self.draw_with_instances(
+            draw_descriptor,
+            draw_parameters,
+            textures,
+            uniforms,
+            indices,
+            None,
+        );
+    }
 
-        if let Some(uniforms) = uniforms {
-            let uniforms = uniforms.value.borrow();
-            let uniforms = bm::bytes_of(uniforms.deref());
-            bind_uniforms(&mut ctx, &draw_descriptor.shader, uniforms, U::FORMAT);
-        }
+    fn draw_instanced(
+        &self,
+        draw_descriptor: &GlesDrawDescriptor<B>,
+        draw_parameters: &DrawParameters,
+        textures: &[SamplerAttribute<Gles<B>, impl Borrow<GlesTexture<B>>>],
+        uniforms: &[&dyn AnyUniformBuffer<Gles<B>>],
+        indices: &Indices,
+        instances: usize,
+    ) {
+        assert!(
+            self.ctx.extensions.instanced_arrays,
+            "Instanced draw calls are not supported by the implementation"
+        );
 
-        // To reduce code duplication, the remaining code without generics is
-        // extracted as a function
-        draw_impl(
-            &mut ctx,
-            fb,
+        self.draw_with_instances(
             draw_descriptor,
             draw_parameters,
-            size,
+            textures,
+            uniforms,
             indices,
-            self.ctx.settings.flip_default_frame_buffer,
+            Some(instances),
         );
     }
 
@@ -269,19 +369,22 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
             Resources::Managed {
                 size,
                 framebuffer,
-                _draw_texture: tex,
+                draw,
                 _depth_stencil,
             } => (
                 size.clone(),
                 framebuffer.clone(),
-                ReadSource::Texture(tex.texture),
+                match &draw[0] {
+                    Attachment::Texture(texture) => ReadSource::Texture(texture.texture),
+                    Attachment::RenderBuffer(_) => ReadSource::RenderBuffer,
+                },
             ),
         };
 
         let fb_write = self.res.framebuffer(&self.ctx);
 
         unsafe {
-            self.ctx.frame_buffer_blitter.blit(
+            self.ctx.frame_buffer_blitter.borrow().blit(
                 &mut self.ctx.get_ref(),
                 fb_write,
                 read,
@@ -293,6 +396,18 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
     }
 
     fn read(&self, rect: Rect<u32>, format: GlesReadFormat, target: &mut [u8]) {
+        self.read_attachment(0, rect, format, target);
+    }
+
+    fn read_attachment(
+        &self,
+        attachment: usize,
+        rect: Rect<u32>,
+        format: GlesReadFormat,
+        target: &mut [u8],
+    ) {
+        format.check(&self.ctx.extensions);
+
         let fb = self.res.framebuffer(&self.ctx);
 
         let mut ctx = self.ctx.get_ref();
@@ -300,7 +415,27 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
 
         let (format, ty) = format.gl();
 
+        let attachments = match &self.res {
+            Resources::Default => 1,
+            Resources::Managed { draw, .. } => draw.len(),
+        };
+        assert!(
+            attachment < attachments,
+            "Frame buffer has no draw attachment {attachment}"
+        );
+
+        // OpenGL ES 2 has no glReadBuffer, so other attachments are read
+        // by temporarily attaching them to the first attachment point.
+        let draw = match &self.res {
+            Resources::Managed { draw, .. } if attachment > 0 => Some(draw),
+            _ => None,
+        };
+
         unsafe {
+            if let Some(draw) = draw {
+                attach(ctx.gl, &draw[attachment], glow::COLOR_ATTACHMENT0);
+            }
+
             ctx.gl.read_pixels(
                 rect.x as i32,
                 rect.y as i32,
@@ -310,33 +445,72 @@ impl<B: WindowBackend + 'static> FrameBuffer<Gles<B>> for GlesFrameBuffer<B> {
                 ty,
                 glow::PixelPackData::Slice(target),
             );
+
+            if let Some(draw) = draw {
+                attach(ctx.gl, &draw[0], glow::COLOR_ATTACHMENT0);
+            }
         }
     }
 }
 
+impl<B: WindowBackend + 'static> GlesFrameBuffer<B> {
+    fn draw_with_instances(
+        &self,
+        draw_descriptor: &GlesDrawDescriptor<B>,
+        draw_parameters: &DrawParameters,
+        textures: &[SamplerAttribute<Gles<B>, impl Borrow<GlesTexture<B>>>],
+        uniforms: &[&dyn AnyUniformBuffer<Gles<B>>],
+        indices: &Indices,
+        instances: Option<usize>,
+    ) {
+        let size = self.size();
+        let fb = self.res.framebuffer(&self.ctx);
+        let mut ctx = self.ctx.get_ref();
+        ctx.use_program(Some(draw_descriptor.shader.program));
+        bind_textures(&mut ctx, &draw_descriptor.shader, textures);
+
+        bind_uniforms(&mut ctx, &draw_descriptor.shader, uniforms);
+        set_draw_parameters(
+            &mut ctx,
+            draw_parameters,
+            size,
+            self.ctx.settings.flip_default_frame_buffer,
+        );
+
+        // To reduce code duplication, the remaining code without generics is
+        // extracted as a function
+        draw_impl(&mut ctx, fb, draw_descriptor, indices, instances);
+    }
+}
+
 fn draw_impl<'a, B: WindowBackend>(
     ctx: &mut GlesContextRef<'_>,
     frame_buffer: Option<glow::Framebuffer>,
     draw_descriptor: &GlesDrawDescriptor<B>,
-    draw_parameters: &DrawParameters,
-    size: Size<u32>,
     indices: &Indices,
-    flip_default_framebuffer: bool,
+    instances: Option<usize>,
 ) {
     ctx.bind_frame_buffer(frame_buffer);
     draw_descriptor.bind(ctx);
-    set_draw_parameters(ctx, draw_parameters, size, flip_default_framebuffer);
 
     unsafe {
-        match &draw_descriptor.index_kind {
-            None => {
+        match (&draw_descriptor.index_kind, instances) {
+            (None, None) => {
                 ctx.gl.draw_arrays(
                     indices.mode.gl_const(),
                     indices.offset as i32,
                     indices.len as i32,
                 );
             }
-            Some(kind) => {
+            (None, Some(instances)) => {
+                ctx.gl.draw_arrays_instanced(
+                    indices.mode.gl_const(),
+                    indices.offset as i32,
+                    indices.len as i32,
+                    instances as i32,
+                );
+            }
+            (Some(kind), None) => {
                 ctx.gl.draw_elements(
                     indices.mode.gl_const(),
                     indices.len as i32,
@@ -344,6 +518,15 @@ fn draw_impl<'a, B: WindowBackend>(
                     (indices.offset * kind.size()) as i32,
                 );
             }
+            (Some(kind), Some(instances)) => {
+                ctx.gl.draw_elements_instanced(
+                    indices.mode.gl_const(),
+                    indices.len as i32,
+                    kind.gl_const(),
+                    (indices.offset * kind.size()) as i32,
+                    instances as i32,
+                );
+            }
         }
     }
 }
@@ -369,35 +552,25 @@ fn set_draw_parameters<'a>(
     framebuffer_size: Size<u32>,
     flip_default_framebuffer: bool,
 ) {
-    let (scissor, viewport) = if flip_default_framebuffer {
-        (
-            draw_parameters.scissor.clone(),
-            draw_parameters.viewport.clone(),
-        )
-    } else {
-        let scissor = draw_parameters
-            .scissor
-            .as_ref()
-            .map(|scissor| to_y_up(&scissor, &framebuffer_size));
-
-        let viewport = draw_parameters
-            .viewport
-            .clone()
-            .map(|viewport| to_y_up(&viewport, &framebuffer_size));
-        (scissor, viewport)
-    };
-
-    let viewport = viewport.unwrap_or_else(|| framebuffer_size.into());
+    let scissor = draw_parameters
+        .scissor
+        .map(|rect| gl_scissor(rect, framebuffer_size, flip_default_framebuffer));
+    let viewport = draw_parameters.viewport.map_or_else(
+        || (&Rect::from(framebuffer_size)).into(),
+        |rect| gl_viewport(rect, framebuffer_size, flip_default_framebuffer),
+    );
 
     ctx.set_blend(draw_parameters.blend.clone());
     ctx.set_color_mask(draw_parameters.color_mask);
     ctx.set_cull_face(draw_parameters.cull_face);
+    ctx.set_front_face(draw_parameters.front_face);
     ctx.set_depth(draw_parameters.depth.clone());
     ctx.set_stencil(draw_parameters.stencil.clone());
     ctx.set_scissor(scissor);
     ctx.set_viewport(viewport);
     ctx.set_line_width(draw_parameters.line_width);
     ctx.set_polygon_offset(draw_parameters.polygon_offset);
+    ctx.set_polygon_mode(draw_parameters.polygon_mode);
     ctx.set_dithering(draw_parameters.dithering);
 }
 
@@ -453,6 +626,17 @@ fn reuse_texture_unit(
     false
 }
 
+/// Pick a texture unit for a new binding. An empty unit is preferred,
+/// otherwise a unit that is not used by another binding of the same draw call is overridden.
+fn free_texture_unit(units: &[TextureUnit], used_units: &BitArray<u32>) -> Option<usize> {
+    let used = |unit: usize| used_units.get(unit).as_deref().cloned().unwrap_or(false);
+
+    units
+        .iter()
+        .position(|u| u.texture.is_none())
+        .or_else(|| (0..units.len()).find(|unit| !used(*unit)))
+}
+
 fn bind_texture<B: WindowBackend>(
     ctx: &mut GlesContextRef,
     used_units: &mut BitArray<u32>,
@@ -468,24 +652,14 @@ fn bind_texture<B: WindowBackend>(
         None => return,
     };
 
+    ctx.warn_npot_mipmaps(texture.texture, texture.size, sampler);
+
     // Check if no re-binding is necessary
     if reuse_texture_unit(ctx, *cached_unit, texture.texture, sampler, used_units) {
         return;
     }
 
-    let mut empty_unit = None;
-    let mut overridable_unit = None;
-
     for unit in 0..ctx.state.texture_unit_limit {
-        if empty_unit == None && ctx.state.texture_units[unit].texture.is_none() {
-            empty_unit = Some(unit);
-            continue;
-        }
-
-        if overridable_unit == None && used_units.get(unit).as_deref().cloned().unwrap_or(false) {
-            overridable_unit = Some(unit);
-        }
-
         if reuse_texture_unit(ctx, unit, texture.texture, sampler, used_units) {
             unsafe { ctx.gl.uniform_1_i32(Some(&location), unit as i32) };
             *cached_unit = unit;
@@ -493,10 +667,10 @@ fn bind_texture<B: WindowBackend>(
         }
     }
 
-    // Prefer empty unit over overridable unit
-    match empty_unit.or(overridable_unit) {
+    let units = &ctx.state.texture_units[..ctx.state.texture_unit_limit];
+    match free_texture_unit(units, used_units) {
         Some(unit) => unsafe {
-            ctx.bind_texture(unit as u32, Some(texture.texture));
+            ctx.bind_texture(unit as u32, texture.target, Some(texture.texture));
             ctx.bind_sampler(unit as u32, sampler);
             used_units.set(unit, true);
 
@@ -509,67 +683,240 @@ fn bind_texture<B: WindowBackend>(
     }
 }
 
+/// Panics if a uniform attribute doesn't match the type of the uniform declared in the shader,
+/// e.g. a mat3 is bound to a mat4 uniform. See `UniformKind::is_compatible`.
+fn check_uniform(attribute: &UniformAttribute, kind: UniformKind, size: usize) {
+    assert!(
+        attribute.kind.is_compatible(attribute.size, kind, size),
+        "Shader expects {:?} of {} bytes, but uniform {} is {:?} of {} bytes",
+        kind,
+        size,
+        attribute.name,
+        attribute.kind,
+        attribute.size
+    );
+}
+
 fn bind_uniforms<'a, B: WindowBackend>(
     ctx: &mut GlesContextRef<'a>,
     shader: &GlesShader<B>,
-    uniforms: &[u8],
-    format: &'static [UniformAttribute],
+    uniforms: &[&dyn AnyUniformBuffer<Gles<B>>],
 ) {
     let mut shader_state = shader.state.borrow_mut();
 
-    let same_type = std::ptr::eq(shader_state.uniforms_cache.0, format);
+    // Values of the buffers bound in the previous draw call are cached per slot.
+    let cache = &mut shader_state.uniforms_cache;
+    cache.resize_with(uniforms.len(), || (<()>::FORMAT, Vec::new()));
+
+    for (buffer, (cached_format, cached)) in uniforms.iter().zip(cache.iter_mut()) {
+        let format = buffer.format();
+        let bytes = buffer.bytes();
+        let same_type = std::ptr::eq(*cached_format, format);
+
+        for attribute in format.iter() {
+            let (location, kind, size) = match shader.uniform_attributes.get(attribute.name) {
+                Some(location) => location,
+                None => {
+                    // Uniform not defined in our shader, skipping binding
+                    continue;
+                }
+            };
 
-    for attribute in format.iter() {
-        let (location, kind, size) = match shader.uniform_attributes.get(attribute.name) {
-            Some(location) => location,
-            None => {
-                // Uniform not defined in our shader, skipping binding
+            check_uniform(attribute, *kind, *size);
+
+            let range = attribute.offset..(attribute.offset + attribute.size);
+            let new = &bytes[range.clone()];
+            if same_type && &cached[range] == new {
                 continue;
             }
-        };
 
-        let range = attribute.offset..(attribute.offset + attribute.size);
-        let new = &uniforms[range.clone()];
-        if same_type {
-            if &shader_state.uniforms_cache.1[range] == new {
-                continue;
+            let l = Some(location);
+            unsafe {
+                match kind {
+                    UniformKind::Int => ctx.gl.uniform_1_i32_slice(l, bm::cast_slice(new)),
+                    UniformKind::IntVec2 => ctx.gl.uniform_2_i32_slice(l, bm::cast_slice(new)),
+                    UniformKind::IntVec3 => ctx.gl.uniform_3_i32_slice(l, bm::cast_slice(new)),
+                    UniformKind::IntVec4 => ctx.gl.uniform_4_i32_slice(l, bm::cast_slice(new)),
+                    UniformKind::Float => ctx.gl.uniform_1_f32_slice(l, bm::cast_slice(new)),
+                    UniformKind::FloatVec2 => ctx.gl.uniform_2_f32_slice(l, bm::cast_slice(new)),
+                    UniformKind::FloatVec3 => ctx.gl.uniform_3_f32_slice(l, bm::cast_slice(new)),
+                    UniformKind::FloatVec4 => ctx.gl.uniform_4_f32_slice(l, bm::cast_slice(new)),
+                    UniformKind::Mat2 => {
+                        ctx.gl
+                            .uniform_matrix_2_f32_slice(l, false, bm::cast_slice(new))
+                    }
+                    UniformKind::Mat3 => {
+                        ctx.gl
+                            .uniform_matrix_3_f32_slice(l, false, bm::cast_slice(new))
+                    }
+                    UniformKind::Mat4 => {
+                        ctx.gl
+                            .uniform_matrix_4_f32_slice(l, false, bm::cast_slice(new))
+                    }
+                }
             }
         }
 
-        assert!(
-            *size <= attribute.size,
-            "Shader expects larger value than provided by uniform for uniform {}",
-            attribute.name
+        *cached_format = format;
+        cached.clear();
+        cached.extend_from_slice(&bytes);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use yapgeir_graphics_hal::viewport::Viewport;
+
+    use super::*;
+
+    #[test]
+    fn test_draw_buffers() {
+        assert_eq!(
+            draw_buffers(2, 4),
+            [glow::COLOR_ATTACHMENT0, glow::COLOR_ATTACHMENT1]
         );
+    }
 
-        let l = Some(location);
-        unsafe {
-            match kind {
-                UniformKind::Int => ctx.gl.uniform_1_i32_slice(l, bm::cast_slice(new)),
-                UniformKind::IntVec2 => ctx.gl.uniform_2_i32_slice(l, bm::cast_slice(new)),
-                UniformKind::IntVec3 => ctx.gl.uniform_3_i32_slice(l, bm::cast_slice(new)),
-                UniformKind::IntVec4 => ctx.gl.uniform_4_i32_slice(l, bm::cast_slice(new)),
-                UniformKind::Float => ctx.gl.uniform_1_f32_slice(l, bm::cast_slice(new)),
-                UniformKind::FloatVec2 => ctx.gl.uniform_2_f32_slice(l, bm::cast_slice(new)),
-                UniformKind::FloatVec3 => ctx.gl.uniform_3_f32_slice(l, bm::cast_slice(new)),
-                UniformKind::FloatVec4 => ctx.gl.uniform_4_f32_slice(l, bm::cast_slice(new)),
-                UniformKind::Mat2 => {
-                    ctx.gl
-                        .uniform_matrix_2_f32_slice(l, false, bm::cast_slice(new))
-                }
-                UniformKind::Mat3 => {
-                    ctx.gl
-                        .uniform_matrix_3_f32_slice(l, false, bm::cast_slice(new))
-                }
-                UniformKind::Mat4 => {
-                    ctx.gl
-                        .uniform_matrix_4_f32_slice(l, false, bm::cast_slice(new))
-                }
-            }
+    #[test]
+    #[should_panic(expected = "at most 1")]
+    fn test_too_many_draw_buffers() {
+        draw_buffers(2, 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have the same size")]
+    fn test_draw_attachments_of_different_sizes() {
+        same_size([Size::new(4, 4), Size::new(4, 4), Size::new(2, 2)].into_iter());
+    }
+
+    #[test]
+    fn test_read_depth() {
+        assert_eq!(
+            GlesReadFormat::Depth.gl(),
+            (glow::DEPTH_COMPONENT, glow::FLOAT)
+        );
+        GlesReadFormat::Depth.check(&Extensions {
+            read_depth: true,
+            ..Default::default()
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "Reading depth requires GL_NV_read_depth")]
+    fn test_read_depth_requires_extension() {
+        GlesReadFormat::Depth.check(&Extensions::default());
+    }
+
+    #[test]
+    fn test_gl_rect() {
+        let size = Size::new(800, 600);
+        let bottom_left = Viewport::split(size, 2, 2)[2].rect;
+        assert_eq!(bottom_left, Rect::new(0, 300, 400, 300));
+
+        // GL is Y-up, so the bottom-left view starts at the origin.
+        assert_eq!(
+            gl_viewport(bottom_left, size, false),
+            Rect::new(0, 0, 400, 300)
+        );
+        assert_eq!(
+            gl_scissor(bottom_left, size, false),
+            Rect::new(0, 0, 400, 300)
+        );
+        assert_eq!(
+            gl_viewport(Rect::new(10, 20, 30, 40), size, false),
+            Rect::new(10, 540, 30, 40)
+        );
+
+        // When the default frame buffer is flipped, everything is drawn upside down.
+        assert_eq!(gl_viewport(bottom_left, size, true), (&bottom_left).into());
+        assert_eq!(gl_scissor(bottom_left, size, true), bottom_left);
+
+        // A viewport crossing the bottom edge starts below the frame buffer,
+        // while a scissor only covers the rows inside of it.
+        let crossing = Rect::new(0, 500, 100, 200);
+        assert_eq!(
+            gl_viewport(crossing, size, false),
+            Rect::new(0, -100, 100, 200)
+        );
+        assert_eq!(gl_scissor(crossing, size, false), Rect::new(0, 0, 100, 100));
+        assert_eq!(
+            gl_scissor(crossing, size, true),
+            Rect::new(0, 500, 100, 100)
+        );
+    }
+
+    #[test]
+    fn test_free_texture_unit() {
+        let mut units: [TextureUnit; 3] = Default::default();
+        let mut used_units = BitArray::<u32>::new(0u32);
+
+        // Two textures bound in a single draw call get distinct units.
+        let first = free_texture_unit(&units, &used_units).unwrap();
+        units[first].texture = Some(glow::NativeTexture(NonZeroU32::new(1).unwrap()));
+        used_units.set(first, true);
+
+        let second = free_texture_unit(&units, &used_units).unwrap();
+        assert_ne!(first, second);
+
+        // When all units are occupied by textures from previous draw calls,
+        // units used by the current draw call are not overridden.
+        for unit in &mut units {
+            unit.texture = Some(glow::NativeTexture(NonZeroU32::new(2).unwrap()));
         }
+        assert_eq!(free_texture_unit(&units, &used_units), Some(1));
+
+        used_units.set(1, true);
+        used_units.set(2, true);
+        assert_eq!(free_texture_unit(&units, &used_units), None);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, yapgeir_graphics_hal::uniforms::Uniforms)]
+    struct TestUniforms {
+        camera: [[f32; 3]; 3],
+        #[uniforms(kind = "FloatVec3")]
+        lights: [[f32; 3]; 3],
+        time: f32,
+        offsets: [[f32; 2]; 2],
+        transform: [f32; 16],
+        indices: [i32; 4],
     }
 
-    shader_state.uniforms_cache.0 = format;
-    shader_state.uniforms_cache.1.clear();
-    shader_state.uniforms_cache.1.extend_from_slice(uniforms);
+    #[test]
+    fn test_uniform_kinds() {
+        let kinds = TestUniforms::FORMAT.iter().map(|a| a.kind);
+        assert!(kinds.eq([
+            UniformKind::Mat3,
+            UniformKind::FloatVec3,
+            UniformKind::Float,
+            UniformKind::Mat2,
+            UniformKind::Float,
+            UniformKind::IntVec4,
+        ]));
+
+        let format = TestUniforms::FORMAT;
+        check_uniform(&format[0], UniformKind::Mat3, 4 * 9);
+        // An array of 3 vec3 has the same size as a mat3.
+        check_uniform(&format[1], UniformKind::FloatVec3, 4 * 9);
+        // Layouts of the same size are compatible, e.g. `vec2 offsets[2]` and `mat4 transform`.
+        check_uniform(&format[3], UniformKind::FloatVec2, 4 * 4);
+        check_uniform(&format[4], UniformKind::Mat4, 4 * 16);
+        check_uniform(&format[5], UniformKind::Int, 4 * 4);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Shader expects Mat4 of 64 bytes, but uniform camera is Mat3 of 36 bytes"
+    )]
+    fn test_mismatched_uniform_kind() {
+        check_uniform(&TestUniforms::FORMAT[0], UniformKind::Mat4, 4 * 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "but uniform indices is IntVec4")]
+    fn test_float_uniform_bound_to_ints() {
+        check_uniform(&TestUniforms::FORMAT[5], UniformKind::FloatVec4, 4 * 4);
+    }
 }
@@ -58,7 +58,7 @@ unsafe fn bind_texture(
 
     // Use first empty unit or a zero one.
     let unit = empty_unit.unwrap_or(0);
-    ctx.bind_texture(unit as u32, Some(texture));
+    ctx.bind_texture(unit as u32, glow::TEXTURE_2D, Some(texture));
     ctx.bind_sampler(unit as u32, sampler);
     unit
 }
@@ -94,6 +94,7 @@ const SHADER: TextShaderSource = TextShaderSource {
             gl_FragColor = texture2D(tex, v_tex_position);
         }
     "#,
+    defines: &[],
 };
 
 #[cfg(target_os = "vita")]
@@ -119,6 +120,7 @@ const SHADER: TextShaderSource = TextShaderSource {
             return tex2D(tex, v_tex_position);
         }
     "#,
+    defines: &[],
 };
 
 pub struct FallbackFramebufferBlitter {
@@ -136,6 +138,8 @@ pub struct FallbackFramebufferBlitter {
 pub enum ReadSource {
     Texture(glow::Texture),
     Unit(usize),
+    // Render buffers can't be sampled, so they can only be read with a native blit.
+    RenderBuffer,
 }
 
 pub enum BlitSourceRect {
@@ -153,7 +157,7 @@ impl FallbackFramebufferBlitter {
             BufferUsage::Static.gl_const(),
         );
 
-        let program = compile_program(&ctx.gl, &SHADER);
+        let program = compile_program(ctx.gl, &SHADER).unwrap_or_else(|e| panic!("{e}"));
         ctx.use_program(Some(program));
 
         let uv_location = ctx
@@ -233,6 +237,9 @@ impl FallbackFramebufferBlitter {
             .enable_vertex_attrib_array(self.vertex_attrib_location);
         ctx.gl
             .vertex_attrib_pointer_f32(self.vertex_attrib_location, 1, glow::FLOAT, false, 4, 0);
+        if ctx.extensions.instanced_arrays {
+            ctx.gl.vertex_attrib_divisor(self.vertex_attrib_location, 0);
+        }
 
         ctx.set_blend(None);
         ctx.set_color_mask(Rgba::all(true));
@@ -240,7 +247,7 @@ impl FallbackFramebufferBlitter {
         ctx.set_depth(None);
         ctx.set_stencil(None);
         ctx.set_scissor(None);
-        ctx.set_viewport(viewport);
+        ctx.set_viewport((&viewport).into());
         ctx.set_dithering(false);
 
         ctx.gl
@@ -304,6 +311,9 @@ impl FrameBufferBlitter {
                     bind_texture(ctx, fallback.current_texture_unit.get(), texture, filter)
                 }
                 ReadSource::Unit(unit) => unit,
+                ReadSource::RenderBuffer => {
+                    panic!("Blitting from a render buffer requires GL_EXT_framebuffer_blit!")
+                }
             };
 
             fallback.blit(ctx, fb_write, texture_unit, tex_coords, destination);
@@ -2,17 +2,19 @@ use std::rc::Rc;
 
 use buffer::GlesBuffer;
 use bytemuck::Pod;
-use context::GlesContext;
+use context::{ContextEvent, GlesContext};
 use derive_more::Deref;
 use draw_descriptor::GlesDrawDescriptor;
 use frame_buffer::GlesFrameBuffer;
+use query::GlesTimerQuery;
 use render_buffer::GlesRenderBuffer;
 use shader::GlesShader;
 use smart_default::SmartDefault;
 use texture::GlesTexture;
 use uniforms::GlesUniformBuffer;
 use yapgeir_graphics_hal::{
-    buffer::BufferUsage, render_buffer::RenderBufferFormat, Graphics, WindowBackend,
+    buffer::BufferUsage, render_buffer::RenderBufferFormat, uniforms::Uniforms, Graphics,
+    WindowBackend,
 };
 
 pub use frame_buffer::GlesReadFormat;
@@ -26,6 +28,7 @@ mod draw_descriptor;
 mod fake_default_framebuffer;
 mod frame_buffer;
 mod frame_buffer_blitter;
+mod query;
 mod render_buffer;
 mod samplers;
 mod shader;
@@ -45,6 +48,15 @@ pub struct GlesSettings {
     /// Y up for NDC, and Y down for frame buffers and textures.
     #[default(true)]
     pub flip_default_frame_buffer: bool,
+    /// If true, enables gamma-correct rendering to the default framebuffer: blending
+    /// happens in linear space, and colors are converted to sRGB when written.
+    ///
+    /// Has no effect if sRGB is unsupported (see `Graphics::supports_srgb`).
+    /// The window must be created with an sRGB-capable default framebuffer.
+    /// With `flip_default_frame_buffer` the fake default framebuffer gets an sRGB texture,
+    /// which is decoded when sampled and encoded again by the Y-flip blit, so colors
+    /// only round trip correctly if the real default framebuffer is sRGB-capable too.
+    pub srgb_default_frame_buffer: bool,
 }
 
 impl<B: WindowBackend> Gles<B> {
@@ -69,14 +81,35 @@ impl<B: WindowBackend + 'static> Graphics for Gles<B> {
     type ReadFormat = GlesReadFormat;
     type DrawDescriptor = GlesDrawDescriptor<B>;
     type FrameBuffer = GlesFrameBuffer<B>;
-    type UniformBuffer<T: Pod> = GlesUniformBuffer<T>;
+    type UniformBuffer<T: Uniforms + Pod> = GlesUniformBuffer<T>;
     type BufferUsage = BufferUsage;
     type ByteBuffer = GlesBuffer<B>;
+    type TimerQuery = GlesTimerQuery<B>;
 
     fn new(backend: B) -> Self {
         Self::new_with_settings(backend, Default::default())
     }
 
+    fn supports_instancing(&self) -> bool {
+        self.extensions.instanced_arrays
+    }
+
+    fn supports_depth_textures(&self) -> bool {
+        self.extensions.depth_texture
+    }
+
+    fn max_draw_buffers(&self) -> usize {
+        self.extensions.max_draw_buffers
+    }
+
+    fn supports_srgb(&self) -> bool {
+        self.extensions.srgb_format.is_some()
+    }
+
+    fn supports_border_clamp(&self) -> bool {
+        self.extensions.border_clamp
+    }
+
     fn swap_buffers(&self) {
         let mut ctx = self.get_ref();
 
@@ -84,12 +117,31 @@ impl<B: WindowBackend + 'static> Graphics for Gles<B> {
             unsafe {
                 fake_default_frame_buffer
                     .borrow()
-                    .blit(&mut ctx, &self.frame_buffer_blitter)
+                    .blit(&mut ctx, &self.frame_buffer_blitter.borrow())
             };
         }
 
         ctx.bind_frame_buffer(None);
+        drop(ctx);
+
         self.default_framebuffer_size.take();
         self.backend.swap_buffers();
+
+        let event = self
+            .context_loss
+            .borrow_mut()
+            .update(self.backend.is_context_lost());
+
+        if event == Some(ContextEvent::Restored) {
+            unsafe { self.restore() };
+        }
+    }
+
+    fn is_context_lost(&self) -> bool {
+        self.context_loss.borrow().lost
+    }
+
+    fn on_context_restored(&self, callback: impl FnMut() + 'static) {
+        self.restore_callbacks.borrow_mut().push(Box::new(callback));
     }
 }
@@ -0,0 +1,101 @@
+use std::{cell::Cell, time::Duration};
+
+use glow::HasContext;
+use yapgeir_graphics_hal::{query::TimerQuery, WindowBackend};
+
+use crate::Gles;
+
+// Defined by GL_EXT_disjoint_timer_query
+const GPU_DISJOINT_EXT: u32 = 0x8FBB;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum QueryState {
+    Idle,
+    Running,
+    Ended,
+}
+
+pub struct GlesTimerQuery<B: WindowBackend> {
+    ctx: Gles<B>,
+    // None if timer queries are unsupported
+    query: Option<glow::Query>,
+    state: Cell<QueryState>,
+}
+
+impl<B: WindowBackend> TimerQuery<Gles<B>> for GlesTimerQuery<B> {
+    fn new(ctx: Gles<B>) -> Self {
+        let query = match ctx.extensions.timer_query {
+            true => unsafe { ctx.gl.create_query().ok() },
+            false => None,
+        };
+
+        Self {
+            ctx,
+            query,
+            state: Cell::new(QueryState::Idle),
+        }
+    }
+
+    fn begin(&self) {
+        assert!(
+            self.state.get() != QueryState::Running,
+            "Timer query has already begun"
+        );
+
+        if let Some(query) = self.query {
+            unsafe { self.ctx.gl.begin_query(glow::TIME_ELAPSED, query) };
+        }
+        self.state.set(QueryState::Running);
+    }
+
+    fn end(&self) {
+        if self.state.get() != QueryState::Running {
+            return;
+        }
+
+        if self.query.is_some() {
+            unsafe { self.ctx.gl.end_query(glow::TIME_ELAPSED) };
+        }
+        self.state.set(QueryState::Ended);
+    }
+
+    fn poll(&self) -> Option<Duration> {
+        let query = self.query?;
+        if self.state.get() != QueryState::Ended {
+            return None;
+        }
+
+        let gl = &self.ctx.gl;
+        unsafe {
+            if gl.get_query_parameter_u32(query, glow::QUERY_RESULT_AVAILABLE) == 0 {
+                return None;
+            }
+
+            self.state.set(QueryState::Idle);
+
+            let nanoseconds = gl.get_query_parameter_u32(query, glow::QUERY_RESULT);
+
+            // The GPU has done something (e.g. changed frequency) that makes the results invalid
+            if self.ctx.extensions.disjoint_timer_query
+                && gl.get_parameter_i32(GPU_DISJOINT_EXT) != 0
+            {
+                return None;
+            }
+
+            Some(Duration::from_nanos(nanoseconds as u64))
+        }
+    }
+}
+
+impl<B: WindowBackend> Drop for GlesTimerQuery<B> {
+    fn drop(&mut self) {
+        if let Some(query) = self.query {
+            unsafe {
+                if self.state.get() == QueryState::Running {
+                    self.ctx.gl.end_query(glow::TIME_ELAPSED);
+                }
+                self.ctx.gl.delete_query(query);
+            }
+        }
+    }
+}
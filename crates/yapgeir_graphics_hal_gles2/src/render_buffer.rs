@@ -6,15 +6,29 @@ use yapgeir_graphics_hal::{
 
 use crate::{constants::GlConstant, Gles};
 
+/// Returns the number of samples a render buffer of a format is created with,
+/// downgrading to the supported number of samples, or to a single sample.
+fn supported_samples(format: RenderBufferFormat, max_samples: u8) -> u8 {
+    match format {
+        RenderBufferFormat::ColorMsaa { samples }
+        | RenderBufferFormat::DepthStencilMsaa { samples } => samples.clamp(1, max_samples),
+        _ => 1,
+    }
+}
+
 pub struct GlesRenderBuffer<B: WindowBackend> {
     pub ctx: Gles<B>,
     pub renderbuffer: glow::Renderbuffer,
+    pub size: Size<u32>,
+    pub samples: u8,
 }
 
 impl<B: WindowBackend> RenderBuffer<Gles<B>> for GlesRenderBuffer<B> {
     type Format = RenderBufferFormat;
 
     fn new(ctx: Gles<B>, size: Size<u32>, format: RenderBufferFormat) -> Self {
+        let samples = supported_samples(format, ctx.extensions.max_samples);
+
         let format = format.gl_const();
 
         let renderbuffer = unsafe {
@@ -24,13 +38,36 @@ impl<B: WindowBackend> RenderBuffer<Gles<B>> for GlesRenderBuffer<B> {
                 .create_renderbuffer()
                 .expect("unable to create a renderbuffer");
             ctx.bind_render_buffer(Some(rb));
-            ctx.gl
-                .renderbuffer_storage(glow::RENDERBUFFER, format, size.w as i32, size.h as i32);
+            if samples > 1 {
+                ctx.gl.renderbuffer_storage_multisample(
+                    glow::RENDERBUFFER,
+                    samples as i32,
+                    format,
+                    size.w as i32,
+                    size.h as i32,
+                );
+            } else {
+                ctx.gl.renderbuffer_storage(
+                    glow::RENDERBUFFER,
+                    format,
+                    size.w as i32,
+                    size.h as i32,
+                );
+            }
 
             rb
         };
 
-        Self { ctx, renderbuffer }
+        Self {
+            ctx,
+            renderbuffer,
+            size,
+            samples,
+        }
+    }
+
+    fn samples(&self) -> u8 {
+        self.samples
     }
 }
 
@@ -45,3 +82,21 @@ impl<B: WindowBackend> Drop for GlesRenderBuffer<B> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_supported_samples() {
+        let msaa = RenderBufferFormat::ColorMsaa { samples: 8 };
+        assert_eq!(supported_samples(msaa, 16), 8);
+        assert_eq!(supported_samples(msaa, 4), 4);
+        // Multisampling is unsupported.
+        assert_eq!(supported_samples(msaa, 1), 1);
+
+        let msaa = RenderBufferFormat::DepthStencilMsaa { samples: 0 };
+        assert_eq!(supported_samples(msaa, 4), 1);
+        assert_eq!(supported_samples(RenderBufferFormat::DepthStencil, 4), 1);
+    }
+}
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use glow::HasContext;
-use yapgeir_graphics_hal::sampler::SamplerState;
+use yapgeir_graphics_hal::{
+    sampler::{SamplerState, WrapFunction},
+    Size,
+};
 
 use crate::{constants::GlConstant, context::GlesContextRef};
 
@@ -9,12 +12,61 @@ use crate::{constants::GlConstant, context::GlesContextRef};
 pub struct Samplers {
     real_cache: HashMap<SamplerState, glow::Sampler>,
     fallback_cache: HashMap<glow::Texture, SamplerState>,
+    /// Textures which were already reported by `warn_npot_mipmaps`.
+    npot_warnings: HashSet<glow::Texture>,
+    /// Whether `WrapFunction::Border` falling back to `Clamp` was already reported.
+    border_warning: bool,
 }
 
 impl<'a> GlesContextRef<'a> {
     pub fn clean_texture(&mut self, texture: glow::Texture) {
         self.state.samplers.fallback_cache.remove(&texture);
+        self.state.samplers.npot_warnings.remove(&texture);
     }
+
+    /// Warns once per texture if it is sampled with mipmaps, while its size is not
+    /// a power of two, and the context can't mipmap such textures, e.g. on GLES2.
+    pub fn warn_npot_mipmaps(
+        &mut self,
+        texture: glow::Texture,
+        size: Size<u32>,
+        state: SamplerState,
+    ) {
+        if self.extensions.npot_mipmaps || !samples_npot_mipmaps(size, state) {
+            return;
+        }
+
+        if self.state.samplers.npot_warnings.insert(texture) {
+            eprintln!(
+                "Texture of size {}x{} is sampled with mipmaps, which requires a power of two size on this context",
+                size.w, size.h
+            );
+        }
+    }
+}
+
+/// Replaces `WrapFunction::Border` with `Clamp` if the context doesn't support border clamp.
+/// Returns true if the wrap function was replaced.
+fn border_fallback(state: &mut SamplerState, border_clamp: bool) -> bool {
+    let fallback = !border_clamp && matches!(state.wrap, WrapFunction::Border(_));
+    if fallback {
+        state.wrap = WrapFunction::Clamp;
+    }
+
+    fallback
+}
+
+/// Returns true if a texture which is not a power of two in size is sampled with mipmaps.
+fn samples_npot_mipmaps(size: Size<u32>, state: SamplerState) -> bool {
+    state.min_filter.is_mipmapped() && !(size.w.is_power_of_two() && size.h.is_power_of_two())
+}
+
+/// Returns the degree of anisotropic filtering that should be applied,
+/// or `None` if anisotropic filtering is unsupported.
+///
+/// If the state doesn't request anisotropy, it is explicitly disabled with 1.
+fn anisotropy(requested: Option<f32>, max: Option<f32>) -> Option<f32> {
+    max.map(|max| requested.unwrap_or(1.).clamp(1., max.max(1.)))
 }
 
 impl Samplers {
@@ -47,6 +99,19 @@ impl<'a> GlesContextRef<'a> {
             gl.sampler_parameter_i32(sampler, glow::TEXTURE_MIN_FILTER, min_filter_gl as i32);
             gl.sampler_parameter_i32(sampler, glow::TEXTURE_MAG_FILTER, mag_filter_gl as i32);
 
+            if let WrapFunction::Border(color) = state.wrap {
+                let color = <[f32; 4]>::from(color);
+                gl.sampler_parameter_f32_slice(sampler, glow::TEXTURE_BORDER_COLOR, &color);
+            }
+
+            if let Some(anisotropy) = anisotropy(state.anisotropy, self.extensions.max_anisotropy) {
+                gl.sampler_parameter_f32(sampler, glow::TEXTURE_MAX_ANISOTROPY_EXT, anisotropy);
+            }
+
+            if self.extensions.lod_bias {
+                gl.sampler_parameter_f32(sampler, glow::TEXTURE_LOD_BIAS, state.lod_bias);
+            }
+
             sampler
         };
 
@@ -69,8 +134,9 @@ impl<'a> GlesContextRef<'a> {
     }
 
     fn bind_sampling_data(&mut self, unit: u32, state: SamplerState) {
-        let texture = match self.state.texture_units[unit as usize].texture {
-            Some(texture) => texture,
+        let texture_unit = &self.state.texture_units[unit as usize];
+        let (texture, target) = match texture_unit.texture {
+            Some(texture) => (texture, texture_unit.target),
             None => return,
         };
 
@@ -87,26 +153,45 @@ impl<'a> GlesContextRef<'a> {
 
         unsafe {
             self.gl
-                .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_S, wrap_gl as i32);
+                .tex_parameter_i32(target, glow::TEXTURE_WRAP_S, wrap_gl as i32);
             self.gl
-                .tex_parameter_i32(glow::TEXTURE_2D, glow::TEXTURE_WRAP_T, wrap_gl as i32);
-            self.gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_MIN_FILTER,
-                min_filter_gl as i32,
-            );
-            self.gl.tex_parameter_i32(
-                glow::TEXTURE_2D,
-                glow::TEXTURE_MAG_FILTER,
-                mag_filter_gl as i32,
-            );
+                .tex_parameter_i32(target, glow::TEXTURE_WRAP_T, wrap_gl as i32);
+            self.gl
+                .tex_parameter_i32(target, glow::TEXTURE_MIN_FILTER, min_filter_gl as i32);
+            self.gl
+                .tex_parameter_i32(target, glow::TEXTURE_MAG_FILTER, mag_filter_gl as i32);
+
+            if let WrapFunction::Border(color) = state.wrap {
+                let color = <[f32; 4]>::from(color);
+                self.gl
+                    .tex_parameter_f32_slice(target, glow::TEXTURE_BORDER_COLOR, &color);
+            }
+
+            if let Some(anisotropy) = anisotropy(state.anisotropy, self.extensions.max_anisotropy) {
+                self.gl
+                    .tex_parameter_f32(target, glow::TEXTURE_MAX_ANISOTROPY_EXT, anisotropy);
+            }
+
+            if self.extensions.lod_bias {
+                self.gl
+                    .tex_parameter_f32(target, glow::TEXTURE_LOD_BIAS, state.lod_bias);
+            }
         }
 
         self.state.samplers.fallback_cache.insert(texture, state);
         self.state.texture_units[unit as usize].sampler = state;
     }
 
-    pub fn bind_sampler(&mut self, unit: u32, state: SamplerState) {
+    pub fn bind_sampler(&mut self, unit: u32, mut state: SamplerState) {
+        if border_fallback(&mut state, self.extensions.border_clamp)
+            && !self.state.samplers.border_warning
+        {
+            eprintln!(
+                "Border clamp is not supported on this context, falling back to clamp to edge"
+            );
+            self.state.samplers.border_warning = true;
+        }
+
         if self.extensions.sampler_objects {
             self.bind_sampler_object(unit, state);
         } else {
@@ -114,3 +199,67 @@ impl<'a> GlesContextRef<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal::Rgba;
+
+    use super::*;
+
+    #[test]
+    fn test_anisotropy_clamps_to_limit() {
+        assert_eq!(anisotropy(Some(16.), Some(8.)), Some(8.));
+        assert_eq!(anisotropy(Some(4.), Some(8.)), Some(4.));
+        assert_eq!(anisotropy(Some(0.5), Some(8.)), Some(1.));
+        assert_eq!(anisotropy(None, Some(8.)), Some(1.));
+        assert_eq!(anisotropy(Some(16.), None), None);
+    }
+
+    #[test]
+    fn test_mipmapped_min_filter() {
+        let min_filter = |state: SamplerState| state.min_filter.gl_const();
+        assert_eq!(
+            min_filter(SamplerState::linear_mipmapped()),
+            glow::LINEAR_MIPMAP_LINEAR
+        );
+        assert_eq!(
+            min_filter(SamplerState::nearest_mipmapped()),
+            glow::NEAREST_MIPMAP_NEAREST
+        );
+        assert_eq!(min_filter(SamplerState::linear()), glow::LINEAR);
+
+        // Nearest texels, blended between two mipmap levels.
+        assert_eq!(
+            min_filter(SamplerState::default()),
+            glow::NEAREST_MIPMAP_LINEAR
+        );
+    }
+
+    #[test]
+    fn test_npot_mipmaps() {
+        let mipmapped = SamplerState::linear_mipmapped();
+        assert!(samples_npot_mipmaps(Size::new(100, 64), mipmapped));
+        assert!(!samples_npot_mipmaps(Size::new(128, 64), mipmapped));
+        assert!(!samples_npot_mipmaps(
+            Size::new(100, 64),
+            SamplerState::linear()
+        ));
+    }
+
+    #[test]
+    fn test_border_fallback() {
+        let border = SamplerState {
+            wrap: WrapFunction::Border(Rgba::new(1., 0., 1., 1.)),
+            ..SamplerState::nearest()
+        };
+
+        let mut state = border;
+        assert!(!border_fallback(&mut state, true));
+        assert_eq!(state, border);
+        assert_eq!(state.wrap.gl_const(), glow::CLAMP_TO_BORDER);
+
+        assert!(border_fallback(&mut state, false));
+        assert_eq!(state, SamplerState::nearest());
+        assert!(!border_fallback(&mut state, false));
+    }
+}
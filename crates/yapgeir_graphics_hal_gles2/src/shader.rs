@@ -3,49 +3,17 @@ use std::{cell::RefCell, collections::HashMap};
 
 use glow::HasContext;
 use yapgeir_graphics_hal::{
-    shader::{Shader, TextShaderSource},
-    uniforms::{UniformAttribute, Uniforms},
+    shader::{Shader, ShaderError, ShaderStage, TextShaderSource},
+    uniforms::{UniformAttribute, UniformKind},
     WindowBackend,
 };
 
 use crate::Gles;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum UniformKind {
-    Int,
-    IntVec2,
-    IntVec3,
-    IntVec4,
-    Float,
-    FloatVec2,
-    FloatVec3,
-    FloatVec4,
-    Mat2,
-    Mat3,
-    Mat4,
-}
-
-impl UniformKind {
-    fn size(self) -> usize {
-        match self {
-            UniformKind::Int => 4 * 1,
-            UniformKind::IntVec2 => 4 * 2,
-            UniformKind::IntVec3 => 4 * 3,
-            UniformKind::IntVec4 => 4 * 4,
-            UniformKind::Float => 4 * 1,
-            UniformKind::FloatVec2 => 4 * 2,
-            UniformKind::FloatVec3 => 4 * 3,
-            UniformKind::FloatVec4 => 4 * 4,
-            UniformKind::Mat2 => 4 * 4,
-            UniformKind::Mat3 => 4 * 9,
-            UniformKind::Mat4 => 4 * 16,
-        }
-    }
-}
-
 pub struct ShaderState {
     pub sampler_attributes: HashMap<String, (glow::UniformLocation, usize)>,
-    pub uniforms_cache: (&'static [UniformAttribute], Vec<u8>),
+    /// Uniform buffers bound in the last draw call, with their values.
+    pub uniforms_cache: Vec<(&'static [UniformAttribute], Vec<u8>)>,
 }
 
 pub struct GlesShader<B: WindowBackend> {
@@ -57,58 +25,109 @@ pub struct GlesShader<B: WindowBackend> {
     pub state: RefCell<ShaderState>,
 }
 
-pub unsafe fn compile_program(gl: &glow::Context, source: &TextShaderSource) -> glow::Program {
+pub unsafe fn compile_program(
+    gl: &glow::Context,
+    source: &TextShaderSource,
+) -> Result<glow::Program, ShaderError> {
     let program = gl.create_program().expect("Cannot create program");
-    let shaders = [
+    let sources = [
         (
+            ShaderStage::Vertex,
             glow::VERTEX_SHADER,
-            &pre_process_vertex_shader(source.vertex),
+            pre_process_shader(source.vertex, source.defines, WEB),
         ),
         (
+            ShaderStage::Fragment,
             glow::FRAGMENT_SHADER,
-            &pre_process_fragment_shader(source.fragment),
+            pre_process_shader(source.fragment, source.defines, WEB),
         ),
-    ]
-    .map(|(kind, source)| {
+    ];
+
+    let mut shaders = Vec::with_capacity(sources.len());
+    let mut error = None;
+
+    for (stage, kind, source) in sources {
         let shader = gl.create_shader(kind).expect("Cannot create shader");
-        gl.shader_source(shader, source);
+        shaders.push(shader);
+
+        gl.shader_source(shader, &source);
         gl.compile_shader(shader);
         if !gl.get_shader_compile_status(shader) {
-            panic!(
-                "Error compiling shader: {}. Shader: \n {source}",
-                gl.get_shader_info_log(shader)
-            );
+            error = Some(ShaderError::Compile {
+                stage,
+                log: gl.get_shader_info_log(shader),
+                source,
+            });
+            break;
         }
         gl.attach_shader(program, shader);
-        shader
-    });
+    }
 
-    gl.link_program(program);
-    if !gl.get_program_link_status(program) {
-        panic!("{}", gl.get_program_info_log(program));
+    if error.is_none() {
+        gl.link_program(program);
+        if !gl.get_program_link_status(program) {
+            error = Some(ShaderError::Link {
+                log: gl.get_program_info_log(program),
+            });
+        }
     }
 
     for shader in shaders {
         gl.delete_shader(shader);
     }
 
-    program
+    match error {
+        None => Ok(program),
+        Some(error) => {
+            gl.delete_program(program);
+            Err(error)
+        }
+    }
 }
 
-fn pre_process_vertex_shader(code: &str) -> String {
-    #[cfg(target_os = "emscripten")]
-    return code.replace("#version 120", "#version 100");
+/// Whether shaders are compiled for WebGL, which requires GLSL ES.
+const WEB: bool = cfg!(target_os = "emscripten");
+
+/// Prepares the source of a shader stage for compilation.
+///
+/// For WebGL, GLSL 1.20 shaders are compiled as GLSL ES 1.00, with `WEB` defined
+/// so that shaders can declare the float precision required in fragment shaders.
+fn pre_process_shader(code: &str, defines: &[(&str, &str)], web: bool) -> String {
+    if !web {
+        return inject_defines(code.to_string(), defines);
+    }
 
-    #[cfg(not(target_os = "emscripten"))]
-    return code.to_string();
+    let mut web_defines = vec![("WEB", "1")];
+    web_defines.extend_from_slice(defines);
+    inject_defines(code.replace("#version 120", "#version 100"), &web_defines)
 }
 
-fn pre_process_fragment_shader(code: &str) -> String {
-    #[cfg(target_os = "emscripten")]
-    return code.replace("#version 120", "#version 100\n #define WEB\n");
+/// Adds `#define` directives right after the `#version` directive,
+/// since it must be the first one in GLSL. If there is no `#version`,
+/// e.g. in Cg shaders, the defines are added to the beginning.
+fn inject_defines(code: String, defines: &[(&str, &str)]) -> String {
+    if defines.is_empty() {
+        return code;
+    }
+
+    let position = match code.find("#version") {
+        Some(version) => code[version..]
+            .find('\n')
+            .map_or(code.len(), |end| version + end + 1),
+        None => 0,
+    };
+
+    let mut result = String::with_capacity(code.len() + defines.len() * 32);
+    result.push_str(&code[..position]);
+    if !result.is_empty() && !result.ends_with('\n') {
+        result.push('\n');
+    }
+    for (key, value) in defines {
+        result.push_str(&format!("#define {key} {value}\n"));
+    }
+    result.push_str(&code[position..]);
 
-    #[cfg(not(target_os = "emscripten"))]
-    return code.to_string();
+    result
 }
 
 unsafe fn get_uniforms(
@@ -131,13 +150,13 @@ unsafe fn get_uniforms(
             .expect("uniform location not found");
 
         enum SomeUniformKind {
-            Sampler2d,
+            Sampler,
             Uniform(UniformKind),
             Unsupported,
         }
 
         let kind = match uniform.utype {
-            glow::SAMPLER_2D => SomeUniformKind::Sampler2d,
+            glow::SAMPLER_2D | glow::SAMPLER_CUBE => SomeUniformKind::Sampler,
 
             glow::FLOAT => SomeUniformKind::Uniform(UniformKind::Float),
             glow::FLOAT_VEC2 => SomeUniformKind::Uniform(UniformKind::FloatVec2),
@@ -169,7 +188,7 @@ unsafe fn get_uniforms(
                     (location, kind, kind.size() * uniform.size as usize),
                 );
             }
-            SomeUniformKind::Sampler2d => {
+            SomeUniformKind::Sampler => {
                 samplers.insert(uniform.name.clone(), (location, 0));
             }
             SomeUniformKind::Unsupported => {
@@ -208,24 +227,24 @@ unsafe fn get_vertex_attributes(
 impl<B: WindowBackend> Shader<Gles<B>> for GlesShader<B> {
     type Source = TextShaderSource<'static>;
 
-    fn new(ctx: Gles<B>, source: &TextShaderSource) -> Self {
+    fn try_new(ctx: Gles<B>, source: &TextShaderSource) -> Result<Self, ShaderError> {
         let gl = &ctx.gl;
 
         unsafe {
-            let program = compile_program(&gl, source);
+            let program = compile_program(gl, source)?;
             let (uniform_attributes, texture_attributes) = get_uniforms(&gl, program);
             let attribute_data = get_vertex_attributes(&gl, program);
 
-            Self {
+            Ok(Self {
                 ctx,
                 program,
                 uniform_attributes,
                 attribute_data,
                 state: RefCell::new(ShaderState {
                     sampler_attributes: texture_attributes,
-                    uniforms_cache: (<()>::FORMAT, Vec::new()),
+                    uniforms_cache: Vec::new(),
                 }),
-            }
+            })
         }
     }
 }
@@ -242,3 +261,42 @@ impl<B: WindowBackend> Drop for GlesShader<B> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_inject_defines() {
+        let defines = [("TINT", "1"), ("SCALE", "2.0")];
+
+        let glsl = inject_defines("\n#version 120\nvoid main() {}".to_string(), &defines);
+        assert_eq!(
+            glsl,
+            "\n#version 120\n#define TINT 1\n#define SCALE 2.0\nvoid main() {}"
+        );
+
+        let cg = inject_defines("void main() {}".to_string(), &defines);
+        assert_eq!(cg, "#define TINT 1\n#define SCALE 2.0\nvoid main() {}");
+
+        let version_only = inject_defines("#version 120".to_string(), &defines[..1]);
+        assert_eq!(version_only, "#version 120\n#define TINT 1\n");
+    }
+
+    #[test]
+    fn test_web_define() {
+        let fragment = "#version 120\n#ifdef WEB\nprecision highp float;\n#endif\n";
+        let defines = [("TINT", "1")];
+
+        assert_eq!(
+            pre_process_shader(fragment, &defines, true),
+            "#version 100\n#define WEB 1\n#define TINT 1\n\
+            #ifdef WEB\nprecision highp float;\n#endif\n"
+        );
+        assert_eq!(
+            pre_process_shader(fragment, &defines, false),
+            "#version 120\n#define TINT 1\n#ifdef WEB\nprecision highp float;\n#endif\n"
+        );
+        assert_eq!(pre_process_shader(fragment, &[], false), fragment);
+    }
+}
@@ -1,10 +1,10 @@
 use glow::{HasContext, PixelUnpackData};
 use yapgeir_graphics_hal::{
-    texture::{PixelFormat, Texture},
+    texture::{CubemapFace, PixelFormat, Texture},
     Rect, Size, WindowBackend,
 };
 
-use crate::{constants::GlConstant, Gles};
+use crate::{constants::GlConstant, context::Extensions, Gles};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RgbLayout {
@@ -26,6 +26,18 @@ pub enum GlesPixelFormat {
     Lumia,
     Rgb(RgbLayout),
     Rgba(RgbaLayout),
+    Srgba,
+    /// Half float RGBA. Requires `GL_OES_texture_half_float` on OpenGL ES 2.
+    /// Renderable only with `GL_EXT_color_buffer_half_float` or `GL_EXT_color_buffer_float`.
+    Rgba16F,
+    /// Float RGBA. Requires `GL_OES_texture_float` on OpenGL ES 2.
+    /// Renderable only with `GL_EXT_color_buffer_float` on OpenGL ES.
+    Rgba32F,
+    /// A single float component. Sample-only on OpenGL ES 2, where it is stored
+    /// as luminance, so that the value is read from all color channels.
+    R32F,
+    Depth16,
+    Depth24,
 }
 
 impl GlesPixelFormat {
@@ -43,6 +55,12 @@ impl GlesPixelFormat {
                 RgbaLayout::U16_4_4_4_4 => 2,
                 RgbaLayout::U16_5_5_5_1 => 2,
             },
+            GlesPixelFormat::Srgba => 4,
+            GlesPixelFormat::Rgba16F => 8,
+            GlesPixelFormat::Rgba32F => 16,
+            GlesPixelFormat::R32F => 4,
+            GlesPixelFormat::Depth16 => 2,
+            GlesPixelFormat::Depth24 => 4,
         }
     }
 }
@@ -55,19 +73,62 @@ impl From<PixelFormat> for GlesPixelFormat {
             PixelFormat::Lumia => Self::Lumia,
             PixelFormat::Rgb => Self::Rgb(RgbLayout::U8),
             PixelFormat::Rgba => Self::Rgba(RgbaLayout::U8),
+            PixelFormat::Srgba => Self::Srgba,
+            PixelFormat::Depth16 => Self::Depth16,
+            PixelFormat::Depth24 => Self::Depth24,
         }
     }
 }
 
 impl GlesPixelFormat {
-    fn gl(self) -> (u32, u32) {
-        match self {
+    /// Returns the internal format, the pixel transfer format and the component type.
+    fn gl(self, extensions: &Extensions) -> (u32, u32, u32) {
+        let (format, ty) = match self {
             GlesPixelFormat::Alpha => (glow::ALPHA, glow::UNSIGNED_BYTE),
             GlesPixelFormat::Lumi => (glow::LUMINANCE, glow::UNSIGNED_BYTE),
             GlesPixelFormat::Lumia => (glow::LUMINANCE_ALPHA, glow::UNSIGNED_BYTE),
             GlesPixelFormat::Rgb(f) => (glow::RGB, f.gl_const()),
             GlesPixelFormat::Rgba(f) => (glow::RGBA, f.gl_const()),
-        }
+            GlesPixelFormat::Srgba => {
+                let transfer = extensions
+                    .srgb_format
+                    .expect("sRGB textures require GL_EXT_sRGB!");
+                return (glow::SRGB_ALPHA, transfer, glow::UNSIGNED_BYTE);
+            }
+            GlesPixelFormat::Rgba16F => {
+                let ty = extensions
+                    .half_float_type
+                    .expect("Half float textures require GL_OES_texture_half_float!");
+                return match extensions.sized_float_formats {
+                    true => (glow::RGBA16F, glow::RGBA, ty),
+                    false => (glow::RGBA, glow::RGBA, ty),
+                };
+            }
+            GlesPixelFormat::Rgba32F | GlesPixelFormat::R32F => {
+                assert!(
+                    extensions.float_texture,
+                    "Float textures require GL_OES_texture_float!"
+                );
+                return match (self, extensions.sized_float_formats) {
+                    (GlesPixelFormat::Rgba32F, true) => (glow::RGBA32F, glow::RGBA, glow::FLOAT),
+                    (GlesPixelFormat::Rgba32F, false) => (glow::RGBA, glow::RGBA, glow::FLOAT),
+                    (_, true) => (glow::R32F, glow::RED, glow::FLOAT),
+                    (_, false) => (glow::LUMINANCE, glow::LUMINANCE, glow::FLOAT),
+                };
+            }
+            GlesPixelFormat::Depth16 | GlesPixelFormat::Depth24 => {
+                assert!(
+                    extensions.depth_texture,
+                    "Depth textures require GL_OES_depth_texture!"
+                );
+                match self {
+                    GlesPixelFormat::Depth16 => (glow::DEPTH_COMPONENT, glow::UNSIGNED_SHORT),
+                    _ => (glow::DEPTH_COMPONENT, glow::UNSIGNED_INT),
+                }
+            }
+        };
+
+        (format, format, ty)
     }
 }
 
@@ -76,34 +137,50 @@ pub struct GlesTexture<B: WindowBackend> {
     format: GlesPixelFormat,
     pub size: Size<u32>,
     pub texture: glow::Texture,
+    /// `GL_TEXTURE_2D` or `GL_TEXTURE_CUBE_MAP`.
+    pub target: u32,
 }
 
-impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
-    type PixelFormat = GlesPixelFormat;
+/// Returns the length in bytes of an image with the given format and size.
+fn image_len(format: GlesPixelFormat, size: Size<u32>) -> usize {
+    size.w.saturating_mul(size.h) as usize * format.stride()
+}
 
-    fn new(ctx: Gles<B>, format: Self::PixelFormat, size: Size<u32>, bytes: Option<&[u8]>) -> Self {
-        if let Some(bytes) = bytes {
-            let stride = format.stride();
-            assert_eq!(bytes.len(), (size.w * size.h) as usize * stride);
+impl<B: WindowBackend> GlesTexture<B> {
+    /// Creates a texture with the given target, and allocates its images.
+    /// Each image is a pair of a `glTexImage2D` target and optional pixel data.
+    fn allocate(
+        ctx: Gles<B>,
+        format: GlesPixelFormat,
+        size: Size<u32>,
+        target: u32,
+        images: &[(u32, Option<&[u8]>)],
+    ) -> Self {
+        for (_, bytes) in images {
+            if let Some(bytes) = bytes {
+                assert_eq!(bytes.len(), image_len(format, size));
+            }
         }
 
         let gl = &ctx.gl;
         let texture = unsafe {
-            let (format, ty) = format.gl();
+            let (internal_format, format, ty) = format.gl(&ctx.extensions);
             let texture = gl.create_texture().expect("unable to create a texture");
 
-            ctx.get_ref().activate_texture(texture);
-            gl.tex_image_2d(
-                glow::TEXTURE_2D,
-                0,
-                format as i32,
-                size.w as i32,
-                size.h as i32,
-                0,
-                format,
-                ty,
-                bytes,
-            );
+            ctx.get_ref().activate_texture(texture, target);
+            for (image_target, bytes) in images {
+                gl.tex_image_2d(
+                    *image_target,
+                    0,
+                    internal_format as i32,
+                    size.w as i32,
+                    size.h as i32,
+                    0,
+                    format,
+                    ty,
+                    *bytes,
+                );
+            }
 
             texture
         };
@@ -113,25 +190,35 @@ impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
             format,
             size,
             texture,
+            target,
         }
     }
 
-    fn size(&self) -> Size<u32> {
-        self.size
-    }
-
-    fn write(&self, mipmap_level: u32, format: Self::PixelFormat, size: Size<u32>, bytes: &[u8]) {
-        let stride = format.stride();
-        let (format, ty) = format.gl();
-        assert_eq!(format, self.format.gl().0, "format must not change");
-        assert_eq!(bytes.len(), size.w.saturating_mul(size.h) as usize * stride);
+    fn write_image(
+        &self,
+        image_target: u32,
+        mipmap_level: u32,
+        format: GlesPixelFormat,
+        size: Size<u32>,
+        bytes: &[u8],
+    ) {
+        let len = image_len(format, size);
+        let (internal_format, format, ty) = format.gl(&self.ctx.extensions);
+        assert_eq!(
+            internal_format,
+            self.format.gl(&self.ctx.extensions).0,
+            "format must not change"
+        );
+        assert_eq!(bytes.len(), len);
 
-        self.ctx.get_ref().activate_texture(self.texture);
+        self.ctx
+            .get_ref()
+            .activate_texture(self.texture, self.target);
         unsafe {
             self.ctx.gl.tex_image_2d(
-                glow::TEXTURE_2D,
+                image_target,
                 mipmap_level as i32,
-                format as i32,
+                internal_format as i32,
                 size.w as i32,
                 size.h as i32,
                 0,
@@ -141,6 +228,62 @@ impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
             )
         };
     }
+}
+
+impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
+    type PixelFormat = GlesPixelFormat;
+
+    fn new(ctx: Gles<B>, format: Self::PixelFormat, size: Size<u32>, bytes: Option<&[u8]>) -> Self {
+        Self::allocate(
+            ctx,
+            format,
+            size,
+            glow::TEXTURE_2D,
+            &[(glow::TEXTURE_2D, bytes)],
+        )
+    }
+
+    fn new_cubemap(
+        ctx: Gles<B>,
+        format: Self::PixelFormat,
+        size: Size<u32>,
+        faces: [Option<&[u8]>; 6],
+    ) -> Self {
+        assert_eq!(size.w, size.h, "cubemap faces must be square");
+
+        let images: Vec<_> = CubemapFace::ALL
+            .iter()
+            .map(|face| face.gl_const())
+            .zip(faces)
+            .collect();
+
+        Self::allocate(ctx, format, size, glow::TEXTURE_CUBE_MAP, &images)
+    }
+
+    fn is_cubemap(&self) -> bool {
+        self.target == glow::TEXTURE_CUBE_MAP
+    }
+
+    fn size(&self) -> Size<u32> {
+        self.size
+    }
+
+    fn write(&self, mipmap_level: u32, format: Self::PixelFormat, size: Size<u32>, bytes: &[u8]) {
+        assert!(!self.is_cubemap(), "use write_face to write cubemaps");
+        self.write_image(glow::TEXTURE_2D, mipmap_level, format, size, bytes);
+    }
+
+    fn write_face(
+        &self,
+        face: CubemapFace,
+        mipmap_level: u32,
+        format: Self::PixelFormat,
+        size: Size<u32>,
+        bytes: &[u8],
+    ) {
+        assert!(self.is_cubemap(), "only cubemaps have faces");
+        self.write_image(face.gl_const(), mipmap_level, format, size, bytes);
+    }
 
     fn write_rect(
         &self,
@@ -149,12 +292,20 @@ impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
         rect: Rect<u32>,
         bytes: &[u8],
     ) {
+        assert!(!self.is_cubemap(), "use write_face to write cubemaps");
+
         let stride = format.stride();
-        let (format, ty) = format.gl();
-        assert_eq!(format, self.format.gl().0, "format must not change");
+        let (internal_format, format, ty) = format.gl(&self.ctx.extensions);
+        assert_eq!(
+            internal_format,
+            self.format.gl(&self.ctx.extensions).0,
+            "format must not change"
+        );
         assert_eq!(bytes.len(), (rect.w * rect.h) as usize * stride);
 
-        self.ctx.get_ref().activate_texture(self.texture);
+        self.ctx
+            .get_ref()
+            .activate_texture(self.texture, self.target);
         unsafe {
             self.ctx.gl.tex_sub_image_2d(
                 glow::TEXTURE_2D,
@@ -171,10 +322,12 @@ impl<B: WindowBackend> Texture<Gles<B>> for GlesTexture<B> {
     }
 
     fn generate_mipmaps(&self) {
-        self.ctx.get_ref().activate_texture(self.texture);
+        self.ctx
+            .get_ref()
+            .activate_texture(self.texture, self.target);
         unsafe {
             let gl = &self.ctx.gl;
-            gl.generate_mipmap(glow::TEXTURE_2D);
+            gl.generate_mipmap(self.target);
         }
     }
 }
@@ -191,7 +344,7 @@ impl<B: WindowBackend> Drop for GlesTexture<B> {
                 let unit = &mut ctx.state.texture_units[i];
                 if unit.texture.map_or(false, |t| t == self.texture) {
                     ctx.gl.active_texture(glow::TEXTURE0 + i as u32);
-                    ctx.gl.bind_texture(glow::TEXTURE_2D, None);
+                    ctx.gl.bind_texture(self.target, None);
                     unit.texture = None;
                     u = Some(i);
                 }
@@ -206,3 +359,99 @@ impl<B: WindowBackend> Drop for GlesTexture<B> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::HALF_FLOAT_OES;
+
+    #[test]
+    fn test_float_formats() {
+        let gles2 = Extensions {
+            float_texture: true,
+            half_float_type: Some(HALF_FLOAT_OES),
+            ..Default::default()
+        };
+        assert_eq!(
+            GlesPixelFormat::Rgba32F.gl(&gles2),
+            (glow::RGBA, glow::RGBA, glow::FLOAT)
+        );
+        assert_eq!(
+            GlesPixelFormat::Rgba16F.gl(&gles2),
+            (glow::RGBA, glow::RGBA, HALF_FLOAT_OES)
+        );
+        assert_eq!(
+            GlesPixelFormat::R32F.gl(&gles2),
+            (glow::LUMINANCE, glow::LUMINANCE, glow::FLOAT)
+        );
+
+        let desktop = Extensions {
+            float_texture: true,
+            half_float_type: Some(glow::HALF_FLOAT),
+            sized_float_formats: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            GlesPixelFormat::Rgba16F.gl(&desktop),
+            (glow::RGBA16F, glow::RGBA, glow::HALF_FLOAT)
+        );
+        assert_eq!(
+            GlesPixelFormat::R32F.gl(&desktop),
+            (glow::R32F, glow::RED, glow::FLOAT)
+        );
+
+        assert_eq!(image_len(GlesPixelFormat::Rgba32F, Size::new(2, 2)), 64);
+    }
+
+    #[test]
+    #[should_panic(expected = "GL_OES_texture_float")]
+    fn test_float_texture_requires_extension() {
+        GlesPixelFormat::Rgba32F.gl(&Extensions::default());
+    }
+
+    #[test]
+    fn test_depth_formats() {
+        let extensions = Extensions {
+            depth_texture: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            GlesPixelFormat::Depth16.gl(&extensions),
+            (
+                glow::DEPTH_COMPONENT,
+                glow::DEPTH_COMPONENT,
+                glow::UNSIGNED_SHORT
+            )
+        );
+        assert_eq!(
+            GlesPixelFormat::Depth24.gl(&extensions),
+            (
+                glow::DEPTH_COMPONENT,
+                glow::DEPTH_COMPONENT,
+                glow::UNSIGNED_INT
+            )
+        );
+
+        assert_eq!(image_len(GlesPixelFormat::Depth16, Size::new(2, 2)), 8);
+        assert_eq!(image_len(GlesPixelFormat::Depth24, Size::new(2, 2)), 16);
+    }
+
+    #[test]
+    #[should_panic(expected = "GL_OES_depth_texture")]
+    fn test_depth_texture_requires_extension() {
+        GlesPixelFormat::Depth24.gl(&Extensions::default());
+    }
+
+    #[test]
+    fn test_cubemap_faces() {
+        let targets = CubemapFace::ALL.map(|face| face.gl_const());
+        for (i, target) in targets.into_iter().enumerate() {
+            assert_eq!(target, glow::TEXTURE_CUBE_MAP_POSITIVE_X + i as u32);
+        }
+
+        let face = vec![0u8; 16 * 16 * 4];
+        let format = GlesPixelFormat::from(PixelFormat::Rgba);
+        assert_eq!(image_len(format, Size::new(16, 16)), face.len());
+        assert_eq!(image_len(GlesPixelFormat::Alpha, Size::new(16, 16)), 256);
+    }
+}
@@ -1,7 +1,10 @@
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell};
 
 use bytemuck::Pod;
-use yapgeir_graphics_hal::{uniforms::UniformBuffer, WindowBackend};
+use yapgeir_graphics_hal::{
+    uniforms::{AnyUniformBuffer, UniformAttribute, UniformBuffer, Uniforms},
+    WindowBackend,
+};
 
 use crate::Gles;
 
@@ -21,3 +24,13 @@ impl<B: WindowBackend, T: Pod> UniformBuffer<Gles<B>, T> for GlesUniformBuffer<T
         *v = *value;
     }
 }
+
+impl<B: WindowBackend, T: Uniforms + Pod> AnyUniformBuffer<Gles<B>> for GlesUniformBuffer<T> {
+    fn format(&self) -> &'static [UniformAttribute] {
+        T::FORMAT
+    }
+
+    fn bytes(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.value.borrow(), bytemuck::bytes_of)
+    }
+}
@@ -0,0 +1,105 @@
+use std::{
+    cell::{Cell, Ref, RefCell},
+    ops::Range,
+};
+
+use bytemuck::Pod;
+use yapgeir_graphics_hal::{
+    buffer::{BufferData, BufferKind, BufferUsage, ByteBuffer},
+    uniforms::{AnyUniformBuffer, UniformAttribute, UniformBuffer, Uniforms},
+};
+
+use crate::Null;
+
+pub struct NullBuffer {
+    pub kind: BufferKind,
+    data: RefCell<Vec<u8>>,
+    writes: RefCell<Vec<Range<usize>>>,
+    orphans: Cell<usize>,
+}
+
+impl NullBuffer {
+    /// Returns the contents of the buffer.
+    pub fn data(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.data.borrow(), Vec::as_slice)
+    }
+
+    /// Returns the byte ranges of all writes since the buffer was created, in order.
+    pub fn writes(&self) -> Vec<Range<usize>> {
+        self.writes.borrow().clone()
+    }
+
+    /// Returns the number of times the buffer was orphaned.
+    pub fn orphans(&self) -> usize {
+        self.orphans.get()
+    }
+}
+
+impl ByteBuffer<Null> for NullBuffer {
+    type Usage = BufferUsage;
+
+    fn new(_: Null, kind: BufferKind, _: BufferUsage, data: BufferData<u8>) -> Self {
+        let data = match data {
+            BufferData::Data(data) => data.to_vec(),
+            BufferData::Empty(len) => vec![0; len],
+        };
+
+        Self {
+            kind,
+            data: RefCell::new(data),
+            writes: RefCell::new(Vec::new()),
+            orphans: Cell::new(0),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.data.borrow().len()
+    }
+
+    fn write(&self, offset: usize, data: &[u8]) {
+        let mut buffer = self.data.borrow_mut();
+        assert!(
+            offset + data.len() <= buffer.len(),
+            "Buffer write out of bounds"
+        );
+
+        buffer[offset..offset + data.len()].copy_from_slice(data);
+        self.writes.borrow_mut().push(offset..offset + data.len());
+    }
+
+    /// The contents of an orphaned buffer are undefined, so they are zeroed
+    /// to make reads of stale data visible.
+    fn orphan(&self) {
+        self.data.borrow_mut().fill(0);
+        self.orphans.set(self.orphans.get() + 1);
+    }
+}
+
+pub struct NullUniformBuffer<T>(RefCell<T>);
+
+impl<T: Pod> NullUniformBuffer<T> {
+    /// Returns the last written value.
+    pub fn get(&self) -> T {
+        *self.0.borrow()
+    }
+}
+
+impl<T: Pod> UniformBuffer<Null, T> for NullUniformBuffer<T> {
+    fn new(_: Null, initial: &T) -> Self {
+        Self(RefCell::new(*initial))
+    }
+
+    fn write(&self, value: &T) {
+        *self.0.borrow_mut() = *value;
+    }
+}
+
+impl<T: Uniforms + Pod> AnyUniformBuffer<Null> for NullUniformBuffer<T> {
+    fn format(&self) -> &'static [UniformAttribute] {
+        T::FORMAT
+    }
+
+    fn bytes(&self) -> Ref<'_, [u8]> {
+        Ref::map(self.0.borrow(), bytemuck::bytes_of)
+    }
+}
@@ -0,0 +1,270 @@
+use std::{borrow::Borrow, cell::RefMut};
+
+use yapgeir_graphics_hal::{
+    draw_params::DrawParameters,
+    frame_buffer::{
+        Attachment, DepthStencilAttachment, DrawAttachments, FlipSource, FrameBuffer, Indices,
+        ReadFormat,
+    },
+    sampler::Filter,
+    samplers::SamplerAttribute,
+    texture::Texture,
+    uniforms::AnyUniformBuffer,
+    Rect, Rgba, Size,
+};
+
+use crate::{
+    image::Image,
+    program::{self, ProgramInputs},
+    raster,
+    shader::NullDrawDescriptor,
+    texture::NullTexture,
+    Null,
+};
+
+/// Maximum number of draw attachments, matching the minimum required by OpenGL ES 3.
+pub(crate) const MAX_DRAW_BUFFERS: usize = 4;
+
+enum Target {
+    Default,
+    Attachments(Vec<Attachment<Null>>),
+}
+
+/// A frame buffer drawing into an image in memory.
+///
+/// Clearing and blitting modify the images of all draw attachments, so the result can be read back.
+/// Draw calls are rasterized into the draw attachments if the shader has a registered
+/// `NullProgram`, and are only counted otherwise.
+///
+/// Images are laid out like in OpenGL: the first row of an attached texture is at the bottom,
+/// and `read` and `blit` use a Y-up coordinate space with (0; 0) in the bottom-left corner,
+/// while scissors and viewports are Y-down as in the rest of the API.
+pub struct NullFrameBuffer {
+    ctx: Null,
+    target: Target,
+    _depth_stencil: DepthStencilAttachment<Null>,
+}
+
+impl NullFrameBuffer {
+    fn attachments(&self) -> usize {
+        match &self.target {
+            Target::Default => 1,
+            Target::Attachments(attachments) => attachments.len(),
+        }
+    }
+
+    fn with_image<R>(&self, attachment: usize, f: impl FnOnce(&mut Image) -> R) -> R {
+        match &self.target {
+            Target::Default => {
+                assert!(
+                    attachment == 0,
+                    "Frame buffer has no draw attachment {attachment}"
+                );
+                f(&mut self.ctx.default_frame_buffer.borrow_mut())
+            }
+            Target::Attachments(attachments) => {
+                let attachment = attachments
+                    .get(attachment)
+                    .unwrap_or_else(|| panic!("Frame buffer has no draw attachment {attachment}"));
+                f(&mut attachment_image(attachment))
+            }
+        }
+    }
+
+    /// Calls `f` with the images of all draw attachments at once.
+    fn with_all_images<R>(&self, f: impl FnOnce(&mut [&mut Image]) -> R) -> R {
+        let mut images: Vec<RefMut<Image>> = match &self.target {
+            Target::Default => vec![self.ctx.default_frame_buffer.borrow_mut()],
+            Target::Attachments(attachments) => attachments.iter().map(attachment_image).collect(),
+        };
+        f(&mut images
+            .iter_mut()
+            .map(|image| &mut **image)
+            .collect::<Vec<_>>())
+    }
+
+    fn draw_impl(
+        &self,
+        draw_descriptor: &NullDrawDescriptor,
+        draw_parameters: &DrawParameters,
+        samplers: &[SamplerAttribute<Null, impl Borrow<NullTexture>>],
+        uniforms: &[&dyn AnyUniformBuffer<Null>],
+        indices: &Indices,
+        instances: usize,
+    ) {
+        let inputs = ProgramInputs {
+            uniforms: program::uniform_values(uniforms),
+            samplers: samplers
+                .iter()
+                .map(|sampler| {
+                    let texture = sampler.sampler.texture.borrow();
+                    (sampler.name, texture, sampler.sampler.state)
+                })
+                .collect(),
+            defines: &draw_descriptor.shader.defines,
+        };
+        self.ctx.record_draw();
+
+        if let Some(program) = &draw_descriptor.shader.program {
+            self.with_all_images(|images| {
+                raster::draw(
+                    images,
+                    program.as_ref(),
+                    &inputs,
+                    draw_descriptor,
+                    draw_parameters,
+                    indices,
+                    instances,
+                )
+            });
+        }
+    }
+
+    fn with_images(&self, mut f: impl FnMut(&mut Image)) {
+        for attachment in 0..self.attachments() {
+            self.with_image(attachment, &mut f);
+        }
+    }
+}
+
+fn attachment_image(attachment: &Attachment<Null>) -> RefMut<'_, Image> {
+    match attachment {
+        Attachment::Texture(texture) => {
+            assert!(
+                !texture.is_cubemap(),
+                "Cubemaps can't be attached to a frame buffer"
+            );
+            RefMut::map(texture.images.borrow_mut(), |images| &mut images[0])
+        }
+        Attachment::RenderBuffer(render_buffer) => render_buffer.image.borrow_mut(),
+    }
+}
+
+impl FrameBuffer<Null> for NullFrameBuffer {
+    type ReadFormat = ReadFormat;
+
+    fn default(ctx: Null) -> Self {
+        Self {
+            ctx,
+            target: Target::Default,
+            _depth_stencil: DepthStencilAttachment::None,
+        }
+    }
+
+    fn new(
+        ctx: Null,
+        DrawAttachments(draw): DrawAttachments<Null>,
+        depth_stencil: DepthStencilAttachment<Null>,
+    ) -> Self {
+        assert!(
+            !draw.is_empty(),
+            "A frame buffer requires at least one draw attachment"
+        );
+        assert!(
+            draw.len() <= MAX_DRAW_BUFFERS,
+            "A frame buffer with {} draw attachments was requested, \
+            but the implementation supports at most {MAX_DRAW_BUFFERS}",
+            draw.len()
+        );
+
+        let size = attachment_image(&draw[0]).size;
+        for attachment in &draw[1..] {
+            assert_eq!(
+                size,
+                attachment_image(attachment).size,
+                "All draw attachments of a frame buffer must have the same size"
+            );
+        }
+
+        Self {
+            ctx,
+            target: Target::Attachments(draw),
+            _depth_stencil: depth_stencil,
+        }
+    }
+
+    fn size(&self) -> Size<u32> {
+        self.with_image(0, |image| image.size)
+    }
+
+    fn clear(
+        &self,
+        scissor: Option<Rect<u32>>,
+        color: Option<Rgba<f32>>,
+        _depth: Option<f32>,
+        _stencil: Option<u8>,
+    ) {
+        if let Some(color) = color {
+            let color = <[f32; 4]>::from(color).map(|c| (c.clamp(0., 1.) * 255.).round() as u8);
+            self.with_images(|image| image.fill(scissor, color));
+        }
+    }
+
+    fn draw(
+        &self,
+        draw_descriptor: &NullDrawDescriptor,
+        draw_parameters: &DrawParameters,
+        samplers: &[SamplerAttribute<Null, impl Borrow<NullTexture>>],
+        uniforms: &[&dyn AnyUniformBuffer<Null>],
+        indices: &Indices,
+    ) {
+        self.draw_impl(
+            draw_descriptor,
+            draw_parameters,
+            samplers,
+            uniforms,
+            indices,
+            1,
+        );
+    }
+
+    fn draw_instanced(
+        &self,
+        draw_descriptor: &NullDrawDescriptor,
+        draw_parameters: &DrawParameters,
+        samplers: &[SamplerAttribute<Null, impl Borrow<NullTexture>>],
+        uniforms: &[&dyn AnyUniformBuffer<Null>],
+        indices: &Indices,
+        instances: usize,
+    ) {
+        assert!(
+            self.ctx.instancing,
+            "Instanced draw calls are not supported"
+        );
+        self.draw_impl(
+            draw_descriptor,
+            draw_parameters,
+            samplers,
+            uniforms,
+            indices,
+            instances,
+        );
+    }
+
+    fn blit(
+        &self,
+        read_frame_buffer: &NullFrameBuffer,
+        source: Rect<u32>,
+        destination: Rect<u32>,
+        flip_source: FlipSource,
+        _: Filter,
+    ) {
+        // The source is copied, since it may be the same image as the destination.
+        let source_image = read_frame_buffer.with_image(0, |image| image.clone());
+        self.with_images(|image| image.blit(&source_image, source, destination, &flip_source));
+    }
+
+    fn read(&self, rect: Rect<u32>, read_format: ReadFormat, target: &mut [u8]) {
+        self.read_attachment(0, rect, read_format, target);
+    }
+
+    fn read_attachment(
+        &self,
+        attachment: usize,
+        rect: Rect<u32>,
+        read_format: ReadFormat,
+        target: &mut [u8],
+    ) {
+        self.with_image(attachment, |image| image.read(rect, read_format, target));
+    }
+}
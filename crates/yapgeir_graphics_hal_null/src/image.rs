@@ -0,0 +1,155 @@
+use yapgeir_graphics_hal::{
+    frame_buffer::{FlipSource, ReadFormat},
+    texture::PixelFormat,
+    Rect, Size,
+};
+
+/// Returns the number of bytes per pixel of the given format.
+fn stride(format: PixelFormat) -> usize {
+    match format {
+        PixelFormat::Alpha | PixelFormat::Lumi => 1,
+        PixelFormat::Lumia | PixelFormat::Depth16 => 2,
+        PixelFormat::Rgb => 3,
+        PixelFormat::Rgba | PixelFormat::Srgba | PixelFormat::Depth24 => 4,
+    }
+}
+
+fn to_rgba(format: PixelFormat, pixel: &[u8]) -> [u8; 4] {
+    match format {
+        PixelFormat::Alpha => [0, 0, 0, pixel[0]],
+        PixelFormat::Lumi => [pixel[0], pixel[0], pixel[0], 255],
+        PixelFormat::Lumia => [pixel[0], pixel[0], pixel[0], pixel[1]],
+        PixelFormat::Rgb => [pixel[0], pixel[1], pixel[2], 255],
+        // sRGB is stored as is, without converting it to linear space.
+        PixelFormat::Rgba | PixelFormat::Srgba => [pixel[0], pixel[1], pixel[2], pixel[3]],
+        // Depth is not emulated.
+        PixelFormat::Depth16 | PixelFormat::Depth24 => [0; 4],
+    }
+}
+
+/// Pixels of a texture or a frame buffer stored in memory as 8 bit RGBA.
+///
+/// Like in OpenGL, the first row of a texture is at the bottom of a frame buffer
+/// it's attached to, so rows are ordered from bottom to top. Rectangles are in this
+/// Y-up coordinate space, except for `fill`.
+#[derive(Debug, Clone)]
+pub(crate) struct Image {
+    pub size: Size<u32>,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Image {
+    pub fn new(size: Size<u32>) -> Self {
+        Self {
+            size,
+            pixels: vec![[0; 4]; size.w as usize * size.h as usize],
+        }
+    }
+
+    pub fn with_bytes(format: PixelFormat, size: Size<u32>, bytes: Option<&[u8]>) -> Self {
+        let mut image = Self::new(size);
+        if let Some(bytes) = bytes {
+            image.write(format, size.into(), bytes);
+        }
+
+        image
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.size.w as usize + x as usize
+    }
+
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        self.pixels[self.index(x, y)]
+    }
+
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: [u8; 4]) {
+        let i = self.index(x, y);
+        self.pixels[i] = pixel;
+    }
+
+    fn assert_within(&self, rect: Rect<u32>) {
+        assert!(
+            rect.x + rect.w <= self.size.w && rect.y + rect.h <= self.size.h,
+            "Rectangle {rect:?} is out of bounds of an image of size {:?}",
+            self.size
+        );
+    }
+
+    fn pixels(&self, rect: Rect<u32>) -> impl Iterator<Item = (u32, u32)> {
+        (rect.y..rect.y + rect.h).flat_map(move |y| (rect.x..rect.x + rect.w).map(move |x| (x, y)))
+    }
+
+    /// Fills a rectangle in a Y-down coordinate space with (0; 0) in the top-left corner,
+    /// like scissors in `DrawParameters`.
+    pub fn fill(&mut self, rect: Option<Rect<u32>>, color: [u8; 4]) {
+        let rect = rect.unwrap_or_else(|| self.size.into());
+        self.assert_within(rect);
+        let rect = Rect::new(rect.x, self.size.h - rect.y - rect.h, rect.w, rect.h);
+
+        for (x, y) in self.pixels(rect) {
+            let i = self.index(x, y);
+            self.pixels[i] = color;
+        }
+    }
+
+    pub fn write(&mut self, format: PixelFormat, rect: Rect<u32>, bytes: &[u8]) {
+        self.assert_within(rect);
+        let stride = stride(format);
+        assert!(
+            bytes.len() >= rect.w as usize * rect.h as usize * stride,
+            "Not enough data to write {rect:?} in {format:?}"
+        );
+
+        for ((x, y), pixel) in self.pixels(rect).zip(bytes.chunks_exact(stride)) {
+            let i = self.index(x, y);
+            self.pixels[i] = to_rgba(format, pixel);
+        }
+    }
+
+    /// Reads a rectangle with rows ordered from bottom to top, like `glReadPixels`.
+    pub fn read(&self, rect: Rect<u32>, format: ReadFormat, target: &mut [u8]) {
+        self.assert_within(rect);
+
+        let mut target = target.iter_mut();
+        for (x, y) in self.pixels(rect) {
+            let pixel = self.pixels[self.index(x, y)];
+            let bytes = match format {
+                ReadFormat::Alpha => &pixel[3..],
+                ReadFormat::Rgb => &pixel[..3],
+                ReadFormat::Rgba => &pixel[..],
+            };
+
+            for byte in bytes {
+                *target.next().expect("Read target is too small") = *byte;
+            }
+        }
+    }
+
+    /// Copies a rectangle of another image, scaling it with the nearest filter.
+    pub fn blit(&mut self, source: &Image, from: Rect<u32>, to: Rect<u32>, flip: &FlipSource) {
+        source.assert_within(from);
+        self.assert_within(to);
+
+        let (flip_x, flip_y) = match flip {
+            FlipSource::None => (false, false),
+            FlipSource::X => (true, false),
+            FlipSource::Y => (false, true),
+            FlipSource::XY => (true, true),
+        };
+
+        for (x, y) in self.pixels(to) {
+            let mut dx = (x - to.x) as u64 * from.w as u64 / to.w as u64;
+            let mut dy = (y - to.y) as u64 * from.h as u64 / to.h as u64;
+            if flip_x {
+                dx = from.w as u64 - 1 - dx;
+            }
+            if flip_y {
+                dy = from.h as u64 - 1 - dy;
+            }
+
+            let i = self.index(x, y);
+            self.pixels[i] = source.pixels[source.index(from.x + dx as u32, from.y + dy as u32)];
+        }
+    }
+}
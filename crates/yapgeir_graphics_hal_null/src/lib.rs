@@ -0,0 +1,821 @@
+//! A graphics implementation without a GPU, for testing renderers without a window.
+//!
+//! Textures and frame buffers are stored in memory. Clearing, blitting and writing
+//! textures work as expected, so the results can be read back from a frame buffer.
+//! Shaders can't be compiled, so draw calls are only counted, unless a CPU implementation
+//! of the shader is registered with `Null::register_program` to rasterize triangles,
+//! see `NullProgram`.
+//! Empty textures are filled with transparent black.
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    ffi::c_void,
+    rc::Rc,
+};
+
+use bytemuck::Pod;
+use yapgeir_graphics_hal::{
+    buffer::BufferUsage, frame_buffer::ReadFormat, render_buffer::RenderBufferFormat,
+    shader::TextShaderSource, texture::PixelFormat, uniforms::Uniforms, Graphics, Size,
+    WindowBackend,
+};
+
+use image::Image;
+
+mod buffer;
+mod frame_buffer;
+mod image;
+mod program;
+mod raster;
+mod shader;
+mod texture;
+
+pub use buffer::{NullBuffer, NullUniformBuffer};
+pub use frame_buffer::NullFrameBuffer;
+pub use program::{NullProgram, ProgramInputs, VertexInputs};
+pub use shader::{NullDrawDescriptor, NullShader, NullTimerQuery};
+pub use texture::{NullRenderBuffer, NullTexture};
+
+/// A window backend without a window, with a default frame buffer of a fixed size.
+pub struct NullBackend {
+    pub size: Size<u32>,
+    /// Whether instanced draw calls are supported, which can be disabled to test fallbacks.
+    pub instancing: bool,
+    /// Whether timer queries return results, which can be disabled to test fallbacks.
+    pub timer_queries: bool,
+}
+
+impl NullBackend {
+    pub fn new(size: impl Into<Size<u32>>) -> Self {
+        Self {
+            size: size.into(),
+            instancing: true,
+            timer_queries: true,
+        }
+    }
+}
+
+impl WindowBackend for NullBackend {
+    fn swap_buffers(&self) {}
+
+    fn get_proc_address(&self, _: &str) -> *const c_void {
+        std::ptr::null()
+    }
+
+    fn default_frame_buffer_size(&self) -> Size<u32> {
+        self.size
+    }
+}
+
+pub struct NullContext {
+    default_frame_buffer: RefCell<Image>,
+    draw_calls: Cell<usize>,
+    instancing: bool,
+    timer_queries: bool,
+    programs: RefCell<HashMap<(String, String), Rc<dyn NullProgram>>>,
+}
+
+impl NullContext {
+    fn record_draw(&self) {
+        self.draw_calls.set(self.draw_calls.get() + 1);
+    }
+
+    fn program(&self, source: &TextShaderSource) -> Option<Rc<dyn NullProgram>> {
+        let key = (source.vertex.to_owned(), source.fragment.to_owned());
+        self.programs.borrow().get(&key).cloned()
+    }
+}
+
+#[derive(Clone)]
+pub struct Null(Rc<NullContext>);
+
+impl Null {
+    /// Returns the number of draw calls issued since the graphics were created.
+    pub fn draw_calls(&self) -> usize {
+        self.0.draw_calls.get()
+    }
+
+    /// Registers a CPU implementation of a shader with the given source, which is used
+    /// to rasterize draw calls with shaders created from the same source afterwards.
+    /// The same program is used for all defines, which it can read with `ProgramInputs::define`.
+    pub fn register_program(&self, source: &TextShaderSource, program: impl NullProgram + 'static) {
+        let key = (source.vertex.to_owned(), source.fragment.to_owned());
+        self.0.programs.borrow_mut().insert(key, Rc::new(program));
+    }
+}
+
+impl std::ops::Deref for Null {
+    type Target = NullContext;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Graphics for Null {
+    type Backend = NullBackend;
+    type Shader = NullShader;
+    type PixelFormat = PixelFormat;
+    type Texture = NullTexture;
+    type RenderBufferFormat = RenderBufferFormat;
+    type RenderBuffer = NullRenderBuffer;
+    type ReadFormat = ReadFormat;
+    type DrawDescriptor = NullDrawDescriptor;
+    type FrameBuffer = NullFrameBuffer;
+    type BufferUsage = BufferUsage;
+    type ByteBuffer = NullBuffer;
+    type UniformBuffer<T: Uniforms + Pod> = NullUniformBuffer<T>;
+    type TimerQuery = NullTimerQuery;
+
+    fn new(backend: NullBackend) -> Self {
+        Self(Rc::new(NullContext {
+            default_frame_buffer: RefCell::new(Image::new(backend.default_frame_buffer_size())),
+            draw_calls: Cell::new(0),
+            instancing: backend.instancing,
+            timer_queries: backend.timer_queries,
+            programs: RefCell::new(HashMap::new()),
+        }))
+    }
+
+    fn supports_instancing(&self) -> bool {
+        self.instancing
+    }
+
+    fn supports_depth_textures(&self) -> bool {
+        true
+    }
+
+    fn max_draw_buffers(&self) -> usize {
+        frame_buffer::MAX_DRAW_BUFFERS
+    }
+
+    fn supports_srgb(&self) -> bool {
+        true
+    }
+
+    fn supports_border_clamp(&self) -> bool {
+        true
+    }
+
+    fn swap_buffers(&self) {}
+
+    fn is_context_lost(&self) -> bool {
+        false
+    }
+
+    fn on_context_restored(&self, _: impl FnMut() + 'static) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use yapgeir_graphics_hal::{
+        buffer::BufferKind,
+        draw_descriptor::{AsVertexBindings, IndexBinding},
+        draw_params::{CullFaceMode, DrawParameters},
+        frame_buffer::{Attachment, DepthStencilAttachment, FlipSource, FrameBuffer, Indices},
+        index_buffer::PrimitiveMode,
+        query::TimerQuery,
+        render_buffer::RenderBuffer,
+        sampler::{Filter, Sampler, SamplerState, WrapFunction},
+        samplers::SamplerAttribute,
+        shader::TextShaderSource,
+        texture::Texture,
+        uniforms::{UniformBuffer, Uniforms},
+        vertex_buffer::Vertex,
+        viewport::Viewport,
+        Rect, Rgba,
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_clear_and_read() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        let fb = graphics.default_frame_buffer();
+        assert_eq!(fb.size(), Size::new(4, 4));
+
+        fb.clear(None, Some(Rgba::new(1., 0., 0., 1.)), None, None);
+        fb.clear(
+            Some(Rect::new(2, 0, 2, 1)),
+            Some(Rgba::new(0., 0., 1., 0.5)),
+            None,
+            None,
+        );
+
+        // Reading is Y-up, so the top row cleared by the Y-down scissor is the last one.
+        let mut pixels = [0; 4 * 4];
+        fb.read(Rect::new(0, 3, 4, 1), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(
+            pixels,
+            [255, 0, 0, 255, 255, 0, 0, 255, 0, 0, 255, 128, 0, 0, 255, 128]
+        );
+
+        let mut pixels = [0; 3];
+        fb.read(Rect::new(3, 0, 1, 1), ReadFormat::Rgb, &mut pixels);
+        assert_eq!(pixels, [255, 0, 0]);
+    }
+
+    #[test]
+    fn test_viewport_clear() {
+        let graphics = Null::new(NullBackend::new((4, 2)));
+        let fb = graphics.default_frame_buffer();
+        fb.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+
+        // Clearing the right view of a split screen leaves the left one intact.
+        let views = Viewport::split(fb.size(), 2, 1);
+        views[1].clear::<Null>(&fb, Some(Rgba::new(1., 1., 1., 1.)), None, None);
+
+        let mut pixels = [0; 4 * 2 * 3];
+        fb.read(Rect::new(0, 0, 4, 2), ReadFormat::Rgb, &mut pixels);
+        let red: Vec<_> = pixels.iter().step_by(3).copied().collect();
+        assert_eq!(red, [0, 0, 255, 255, 0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_blit_texture() {
+        let graphics = Null::new(NullBackend::new((2, 2)));
+        let texture =
+            Rc::new(graphics.new_texture(PixelFormat::Lumi, Size::new(1, 2), Some(&[10, 20])));
+        let texture_fb = graphics.new_frame_buffer(texture, DepthStencilAttachment::None);
+
+        let fb = graphics.default_frame_buffer();
+        fb.blit(
+            &texture_fb,
+            Rect::new(0, 0, 1, 2),
+            Rect::new(0, 0, 2, 2),
+            FlipSource::Y,
+            Filter::Nearest,
+        );
+
+        let mut pixels = [0; 4];
+        fb.read(Rect::new(0, 0, 2, 2), ReadFormat::Alpha, &mut pixels);
+        assert_eq!(pixels, [255; 4]);
+
+        let mut pixels = [0; 2 * 2 * 3];
+        fb.read(Rect::new(0, 0, 2, 2), ReadFormat::Rgb, &mut pixels);
+        // The first row of a texture is at the bottom, so flipping it moves the row to the top.
+        assert_eq!(pixels, [20, 20, 20, 20, 20, 20, 10, 10, 10, 10, 10, 10]);
+    }
+
+    #[test]
+    fn test_resolve_msaa_render_buffer() {
+        let graphics = Null::new(NullBackend::new((2, 2)));
+        let render_buffer = Rc::new(graphics.new_render_buffer(
+            Size::new(2, 2),
+            RenderBufferFormat::ColorMsaa { samples: 4 },
+        ));
+        // Multisampling is not emulated, so the render buffer is downgraded to a single sample.
+        assert_eq!(render_buffer.samples(), 1);
+
+        let msaa_fb = graphics.new_frame_buffer(
+            Attachment::RenderBuffer(render_buffer),
+            DepthStencilAttachment::None,
+        );
+        msaa_fb.clear(None, Some(Rgba::new(1., 0.5, 0., 1.)), None, None);
+
+        let texture = Rc::new(graphics.new_texture_empty(PixelFormat::Rgba, Size::new(2, 2)));
+        let texture_fb = graphics.new_frame_buffer(texture.clone(), DepthStencilAttachment::None);
+        texture_fb.blit(
+            &msaa_fb,
+            Rect::new(0, 0, 2, 2),
+            Rect::new(0, 0, 2, 2),
+            FlipSource::None,
+            Filter::Nearest,
+        );
+
+        let mut pixels = [0; 2 * 2 * 4];
+        texture_fb.read(Rect::new(0, 0, 2, 2), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(pixels, [255, 128, 0, 255].repeat(4)[..]);
+    }
+
+    const GBUFFER: TextShaderSource = TextShaderSource {
+        vertex: "gbuffer",
+        fragment: "gbuffer",
+        defines: &[],
+    };
+
+    /// Writes a color and a normal into separate draw attachments, like a deferred renderer.
+    struct GBufferProgram;
+
+    impl NullProgram for GBufferProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let [x, y] = vertex.get("position");
+            ([x, y, 0., 1.], Vec::new())
+        }
+
+        fn fragment(&self, _: &ProgramInputs, _: &[f32]) -> Option<Rgba<f32>> {
+            Some(Rgba::new(1., 0.5, 0., 1.))
+        }
+
+        fn fragment_data(
+            &self,
+            inputs: &ProgramInputs,
+            varyings: &[f32],
+        ) -> Option<Vec<Rgba<f32>>> {
+            let albedo = self.fragment(inputs, varyings)?;
+            Some(vec![albedo, Rgba::new(0.5, 0.5, 1., 1.)])
+        }
+    }
+
+    #[test]
+    fn test_multiple_render_targets() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        graphics.register_program(&GBUFFER, GBufferProgram);
+
+        let texture = |pixel: [u8; 4]| {
+            Rc::new(graphics.new_texture(
+                PixelFormat::Rgba,
+                Size::new(2, 1),
+                Some(&pixel.repeat(2)),
+            ))
+        };
+        let (albedo, normals) = (texture([1, 2, 3, 4]), texture([5, 6, 7, 8]));
+        let fb = graphics.new_frame_buffer([albedo, normals], DepthStencilAttachment::None);
+        assert_eq!(fb.size(), Size::new(2, 1));
+
+        // A strip covering the left pixel.
+        let vertices = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[[-1., 1.], [-1., -1.], [0., 1.], [0., -1.]]
+                .map(|position| PositionVertex { position }),
+        );
+        let draw_descriptor = graphics.new_draw_descriptor(
+            Rc::new(graphics.new_shader(&GBUFFER)),
+            IndexBinding::None,
+            &[vertices.bindings()],
+        );
+        fb.draw(
+            &draw_descriptor,
+            &Default::default(),
+            &[] as &[SamplerAttribute<Null, NullTexture>],
+            &[],
+            &Indices::new(PrimitiveMode::TriangleStrip, 0, 4),
+        );
+
+        // Each attachment is read back independently, with the outputs of the draw call
+        // in the left pixel and the initial contents in the right one.
+        let read = |attachment| {
+            let mut pixels = [0; 2 * 4];
+            fb.read_attachment(
+                attachment,
+                Rect::new(0, 0, 2, 1),
+                ReadFormat::Rgba,
+                &mut pixels,
+            );
+            pixels
+        };
+        assert_eq!(read(0), [255, 128, 0, 255, 1, 2, 3, 4]);
+        assert_eq!(read(1), [128, 128, 255, 255, 5, 6, 7, 8]);
+
+        // Clearing affects all attachments.
+        fb.clear(
+            Some(Rect::new(1, 0, 1, 1)),
+            Some(Rgba::new(1., 1., 1., 1.)),
+            None,
+            None,
+        );
+        assert_eq!(read(0), [255, 128, 0, 255, 255, 255, 255, 255]);
+        assert_eq!(read(1), [128, 128, 255, 255, 255, 255, 255, 255]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have the same size")]
+    fn test_render_targets_of_different_sizes() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let texture = |size| Rc::new(graphics.new_texture(PixelFormat::Rgba, size, None));
+        graphics.new_frame_buffer(
+            [texture(Size::new(2, 2)), texture(Size::new(1, 1))],
+            DepthStencilAttachment::None,
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "has no draw attachment 1")]
+    fn test_read_missing_attachment_of_default_frame_buffer() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let mut pixels = [0; 4];
+        graphics.default_frame_buffer().read_attachment(
+            1,
+            Rect::new(0, 0, 1, 1),
+            ReadFormat::Rgba,
+            &mut pixels,
+        );
+    }
+
+    #[test]
+    fn test_orphan_then_write() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let buffer = graphics.new_buffer(BufferKind::Vertex, BufferUsage::Stream, &[1u8, 2, 3, 4]);
+
+        buffer.orphan();
+        buffer.write(1, &[5, 6]);
+
+        // The written contents are kept, while the rest of the buffer is discarded.
+        assert_eq!(*buffer.bytes.data(), [0, 5, 6, 0]);
+        assert_eq!(buffer.bytes.orphans(), 1);
+        assert_eq!(buffer.len(), 4);
+    }
+
+    #[test]
+    fn test_write_rect_to_empty_texture() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let texture = Rc::new(graphics.new_texture_empty(PixelFormat::Rgba, Size::new(4, 4)));
+        texture.write_rect(
+            0,
+            PixelFormat::Rgba,
+            Rect::new(2, 0, 2, 2),
+            &[255; 2 * 2 * 4],
+        );
+
+        let fb = graphics.new_frame_buffer(texture, DepthStencilAttachment::None);
+        let mut pixels = [0; 4 * 4];
+        fb.read(Rect::new(0, 0, 4, 4), ReadFormat::Alpha, &mut pixels);
+
+        // Texture rows and read rows both start at the bottom.
+        for row in pixels[..8].chunks(4) {
+            assert_eq!(row, [0, 0, 255, 255]);
+        }
+        // The rest of the empty texture is transparent black.
+        assert_eq!(pixels[8..], [0; 8]);
+    }
+
+    #[test]
+    fn test_border_clamp() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        assert!(graphics.supports_border_clamp());
+
+        #[rustfmt::skip]
+        let pixels = [
+            255, 0, 0, 255,   0, 255, 0, 255,
+            0, 0, 255, 255,   255, 255, 255, 255,
+        ];
+        let texture = graphics.new_texture(PixelFormat::Rgba, Size::new(2, 2), Some(&pixels));
+        let state = SamplerState {
+            wrap: WrapFunction::Border(Rgba::new(1., 0., 1., 0.5)),
+            ..SamplerState::nearest()
+        };
+
+        assert_eq!(
+            texture.sample(state, [0.25, 0.75]),
+            Rgba::new(0, 0, 255, 255)
+        );
+        assert_eq!(
+            texture.sample(state, [1., 1.]),
+            Rgba::new(255, 255, 255, 255)
+        );
+        // Outside of [0; 1] the border color is sampled, instead of the edge texels.
+        let border = Rgba::new(255, 0, 255, 128);
+        assert_eq!(texture.sample(state, [1.25, 0.25]), border);
+        assert_eq!(texture.sample(state, [0.5, -0.01]), border);
+
+        let clamp = SamplerState::nearest();
+        assert_eq!(
+            texture.sample(clamp, [1.25, 0.25]),
+            Rgba::new(0, 255, 0, 255)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 4")]
+    fn test_too_many_render_targets() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let textures = [(); 5]
+            .map(|_| Rc::new(graphics.new_texture_empty(PixelFormat::Rgba, Size::new(1, 1))));
+        graphics.new_frame_buffer(textures, DepthStencilAttachment::None);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Uniforms)]
+    struct CameraUniforms {
+        camera: [[f32; 3]; 3],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Uniforms)]
+    struct ObjectUniforms {
+        tint: [f32; 4],
+    }
+
+    const CAMERA: TextShaderSource = TextShaderSource {
+        vertex: "camera",
+        fragment: "camera",
+        defines: &[],
+    };
+
+    /// Transforms positions by a `camera` matrix and fills triangles with a `tint` color.
+    struct CameraProgram;
+
+    impl NullProgram for CameraProgram {
+        fn vertex(&self, inputs: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let camera: [[f32; 3]; 3] = inputs.uniform("camera");
+            let [x, y] = vertex.get("position");
+            let [x, y] = [0, 1].map(|r| camera[0][r] * x + camera[1][r] * y + camera[2][r]);
+            ([x, y, 0., 1.], Vec::new())
+        }
+
+        fn fragment(&self, inputs: &ProgramInputs, _: &[f32]) -> Option<Rgba<f32>> {
+            Some(inputs.uniform::<[f32; 4]>("tint").into())
+        }
+    }
+
+    #[test]
+    fn test_draw_with_multiple_uniform_buffers() {
+        let graphics = Null::new(NullBackend::new((2, 1)));
+        graphics.register_program(&CAMERA, CameraProgram);
+
+        // A unit square, which the camera maps to the left half of the frame buffer.
+        let vertices = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[[0., 1.], [0., 0.], [1., 1.], [1., 0.]].map(|position| PositionVertex { position }),
+        );
+        let draw_descriptor = graphics.new_draw_descriptor(
+            Rc::new(graphics.new_shader(&CAMERA)),
+            IndexBinding::None,
+            &[vertices.bindings()],
+        );
+
+        let camera = graphics.new_uniform_buffer(&CameraUniforms {
+            camera: [[1., 0., 0.], [0., 2., 0.], [-1., -1., 1.]],
+        });
+        let object = graphics.new_uniform_buffer(&ObjectUniforms { tint: [1.; 4] });
+        object.write(&ObjectUniforms {
+            tint: [0.5, 0.25, 1., 1.],
+        });
+
+        let fb = graphics.default_frame_buffer();
+        fb.draw(
+            &draw_descriptor,
+            &Default::default(),
+            &[] as &[SamplerAttribute<Null, NullTexture>],
+            &[&camera, &object],
+            &Indices::new(PrimitiveMode::TriangleStrip, 0, 4),
+        );
+
+        // Each buffer supplies its own uniforms.
+        let mut pixels = [0; 2 * 4];
+        fb.read(Rect::new(0, 0, 2, 1), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(pixels, [128, 64, 255, 255, 0, 0, 0, 0]);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+    struct TexturedVertex {
+        position: [f32; 2],
+        uv: [f32; 2],
+    }
+
+    const TEXTURED: TextShaderSource = TextShaderSource {
+        vertex: "textured",
+        fragment: "textured",
+        defines: &[],
+    };
+
+    struct TexturedProgram;
+
+    impl NullProgram for TexturedProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let [x, y] = vertex.get("position");
+            ([x, y, 0., 1.], vertex.get::<2>("uv").to_vec())
+        }
+
+        fn fragment(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+            Some(inputs.sample("image", [varyings[0], varyings[1]]))
+        }
+    }
+
+    #[test]
+    fn test_draw_textured_quad() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        graphics.register_program(&TEXTURED, TexturedProgram);
+
+        #[rustfmt::skip]
+        let texels = [
+            255, 0, 0, 255,   0, 255, 0, 255,
+            0, 0, 255, 255,   255, 255, 255, 255,
+        ];
+        let texture = graphics.new_texture(PixelFormat::Rgba, Size::new(2, 2), Some(&texels));
+        let sampler = Sampler::nearest(&texture);
+
+        let vertices = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[
+                ([-1., 1.], [0., 0.]),
+                ([-1., -1.], [0., 1.]),
+                ([1., -1.], [1., 1.]),
+                ([1., 1.], [1., 0.]),
+            ]
+            .map(|(position, uv)| TexturedVertex { position, uv }),
+        );
+        let indices = graphics.new_buffer(
+            BufferKind::Index,
+            BufferUsage::Static,
+            &[0u16, 1, 2, 0, 2, 3],
+        );
+        let draw_descriptor = graphics.new_draw_descriptor(
+            Rc::new(graphics.new_shader(&TEXTURED)),
+            Some(&indices),
+            &[vertices.bindings()],
+        );
+
+        let fb = graphics.default_frame_buffer();
+        let draw = |draw_parameters: &DrawParameters| {
+            fb.draw(
+                &draw_descriptor,
+                draw_parameters,
+                &SamplerAttribute::named([("image", &sampler)]),
+                &[],
+                &Indices::new(PrimitiveMode::Triangles, 0, 6),
+            );
+        };
+
+        // The quad is counter-clockwise, so it's culled as a back face by default.
+        draw(&DrawParameters {
+            cull_face: Some(CullFaceMode::Back),
+            ..Default::default()
+        });
+        let mut pixels = [0; 4 * 4 * 3];
+        fb.read(Rect::new(0, 0, 4, 4), ReadFormat::Rgb, &mut pixels);
+        assert_eq!(pixels, [0; 4 * 4 * 3]);
+
+        draw(&Default::default());
+        let mut row = [0; 4 * 3];
+        fb.read(Rect::new(0, 3, 4, 1), ReadFormat::Rgb, &mut row);
+        assert_eq!(row, [255, 0, 0, 255, 0, 0, 0, 255, 0, 0, 255, 0]);
+        fb.read(Rect::new(0, 0, 4, 1), ReadFormat::Rgb, &mut row);
+        assert_eq!(row, [0, 0, 255, 0, 0, 255, 255, 255, 255, 255, 255, 255]);
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+    struct PositionVertex {
+        position: [f32; 2],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+    #[vertex(divisor = 1)]
+    struct OffsetInstance {
+        offset: [f32; 2],
+    }
+
+    const FILL: TextShaderSource = TextShaderSource {
+        vertex: "fill",
+        fragment: "fill",
+        defines: &[],
+    };
+
+    struct FillProgram;
+
+    impl NullProgram for FillProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let ([x, y], [dx, dy]) = (vertex.get("position"), vertex.get("offset"));
+            ([x + dx, y + dy, 0., 1.], Vec::new())
+        }
+
+        fn fragment(&self, inputs: &ProgramInputs, _: &[f32]) -> Option<Rgba<f32>> {
+            Some(inputs.uniform::<[f32; 4]>("tint").into())
+        }
+    }
+
+    #[test]
+    fn test_draw_instanced() {
+        let graphics = Null::new(NullBackend::new((4, 1)));
+        graphics.register_program(&FILL, FillProgram);
+
+        // A strip covering the first pixel, repeated with an offset of two pixels.
+        let vertices = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[[-1., 1.], [-1., -1.], [-0.5, 1.], [-0.5, -1.]]
+                .map(|position| PositionVertex { position }),
+        );
+        let instances = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[[0., 0.], [1., 0.]].map(|offset| OffsetInstance { offset }),
+        );
+        let draw_descriptor = graphics.new_draw_descriptor(
+            Rc::new(graphics.new_shader(&FILL)),
+            IndexBinding::None,
+            &[vertices.bindings(), instances.bindings()],
+        );
+        let tint = graphics.new_uniform_buffer(&ObjectUniforms {
+            tint: [1., 0.5, 0., 1.],
+        });
+
+        let fb = graphics.default_frame_buffer();
+        fb.draw_instanced(
+            &draw_descriptor,
+            &Default::default(),
+            &[] as &[SamplerAttribute<Null, NullTexture>],
+            &[&tint],
+            &Indices::new(PrimitiveMode::TriangleStrip, 0, 4),
+            2,
+        );
+
+        let mut pixels = [0; 4 * 4];
+        fb.read(Rect::new(0, 0, 4, 1), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(
+            pixels,
+            [255, 128, 0, 255, 0, 0, 0, 0, 255, 128, 0, 255, 0, 0, 0, 0]
+        );
+    }
+
+    const BRANCH: TextShaderSource = TextShaderSource {
+        vertex: "branch",
+        fragment: "branch",
+        defines: &[],
+    };
+
+    /// Fills the primitive with red if `RED` is defined, and with white otherwise.
+    struct BranchProgram;
+
+    impl NullProgram for BranchProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let [x, y] = vertex.get("position");
+            ([x, y, 0., 1.], Vec::new())
+        }
+
+        fn fragment(&self, inputs: &ProgramInputs, _: &[f32]) -> Option<Rgba<f32>> {
+            match inputs.define("RED") {
+                Some(_) => Some(Rgba::new(1., 0., 0., 1.)),
+                None => Some(Rgba::all(1.)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_define_changes_output() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        graphics.register_program(&BRANCH, BranchProgram);
+
+        let vertices = graphics.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &[[-1., 1.], [-1., -1.], [1., 1.], [1., -1.]]
+                .map(|position| PositionVertex { position }),
+        );
+        let fb = graphics.default_frame_buffer();
+        let draw = |defines| {
+            let source = TextShaderSource { defines, ..BRANCH };
+            let draw_descriptor = graphics.new_draw_descriptor(
+                Rc::new(graphics.new_shader(&source)),
+                IndexBinding::None,
+                &[vertices.bindings()],
+            );
+            fb.draw(
+                &draw_descriptor,
+                &Default::default(),
+                &[] as &[SamplerAttribute<Null, NullTexture>],
+                &[],
+                &Indices::new(PrimitiveMode::TriangleStrip, 0, 4),
+            );
+
+            let mut pixel = [0; 4];
+            fb.read(Rect::new(0, 0, 1, 1), ReadFormat::Rgba, &mut pixel);
+            pixel
+        };
+
+        assert_eq!(draw(&[]), [255, 255, 255, 255]);
+        assert_eq!(draw(&[("RED", "1")]), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_timer_query() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        let query = graphics.new_timer_query();
+        assert_eq!(query.poll(), None);
+
+        query.begin();
+        graphics
+            .default_frame_buffer()
+            .clear(None, Some(Rgba::all(1.)), None, None);
+        assert_eq!(query.poll(), None);
+        query.end();
+
+        // The result is reported once per measurement.
+        assert!(query.poll().is_some());
+        assert_eq!(query.poll(), None);
+
+        let mut backend = NullBackend::new((4, 4));
+        backend.timer_queries = false;
+        let query = Null::new(backend).new_timer_query();
+        query.begin();
+        query.end();
+        assert_eq!(query.poll(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Timer query has already begun")]
+    fn test_timer_query_begin_twice() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        let query = graphics.new_timer_query();
+        query.begin();
+        query.begin();
+    }
+}
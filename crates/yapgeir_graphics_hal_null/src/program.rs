@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use bytemuck::Pod;
+use yapgeir_graphics_hal::{sampler::SamplerState, uniforms::AnyUniformBuffer, Rgba};
+
+use crate::{texture::NullTexture, Null};
+
+/// A CPU implementation of a shader, which allows the null backend to rasterize draw calls.
+///
+/// Register it for a shader source with `Null::register_program` before the shader is created.
+pub trait NullProgram {
+    /// Returns the clip space position of a vertex, and the varyings which are interpolated
+    /// across the primitive and passed to `fragment`.
+    fn vertex(&self, inputs: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>);
+
+    /// Returns the color of a fragment, or `None` to discard it.
+    fn fragment(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>>;
+
+    /// Returns the size of a point in pixels like `gl_PointSize`, when drawing points.
+    fn point_size(&self, _inputs: &ProgramInputs, _vertex: &VertexInputs) -> f32 {
+        1.
+    }
+
+    /// Returns the colors of a fragment for each draw attachment like `gl_FragData`,
+    /// or `None` to discard it. Only the first attachment is written to by default.
+    fn fragment_data(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Vec<Rgba<f32>>> {
+        self.fragment(inputs, varyings).map(|color| vec![color])
+    }
+}
+
+/// Returns the values of all uniforms provided by the uniform buffers, by their names.
+pub(crate) fn uniform_values(
+    uniforms: &[&dyn AnyUniformBuffer<Null>],
+) -> HashMap<&'static str, Vec<u8>> {
+    let mut values = HashMap::new();
+    for buffer in uniforms {
+        let bytes = buffer.bytes();
+        for attribute in buffer.format() {
+            let value = &bytes[attribute.offset..attribute.offset + attribute.size];
+            values.insert(attribute.name, value.to_vec());
+        }
+    }
+
+    values
+}
+
+/// Uniforms and samplers bound by a draw call.
+pub struct ProgramInputs<'a> {
+    pub(crate) uniforms: HashMap<&'static str, Vec<u8>>,
+    pub(crate) samplers: Vec<(&'static str, &'a NullTexture, SamplerState)>,
+    pub(crate) defines: &'a [(String, String)],
+}
+
+impl ProgramInputs<'_> {
+    /// Returns the value of a define the shader was created with, like `#ifdef` in a shader.
+    pub fn define(&self, name: &str) -> Option<&str> {
+        self.defines
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the value of a uniform. Panics if none of the bound uniform buffers provides it.
+    pub fn uniform<T: Pod>(&self, name: &str) -> T {
+        let bytes = self
+            .uniforms
+            .get(name)
+            .unwrap_or_else(|| panic!("Uniform {name} is not bound"));
+        bytemuck::pod_read_unaligned(bytes)
+    }
+
+    /// Samples the texture bound to a sampler uniform at a normalized position
+    /// with (0; 0) in the top-left corner, see `NullTexture::sample`.
+    pub fn sample(&self, name: &str, position: [f32; 2]) -> Rgba<f32> {
+        let (_, texture, state) = self
+            .samplers
+            .iter()
+            .find(|(sampler, ..)| *sampler == name)
+            .unwrap_or_else(|| panic!("Sampler {name} is not bound"));
+
+        let texel: [u8; 4] = texture.sample(*state, position).into();
+        texel.map(|c| c as f32 / u8::MAX as f32).into()
+    }
+}
+
+/// Attributes of a vertex, converted to floats like in a shader.
+pub struct VertexInputs(pub(crate) Vec<(&'static str, Vec<f32>)>);
+
+impl VertexInputs {
+    /// Returns the components of an attribute, with missing components set to zero.
+    /// Panics if the vertex has no attribute with the name.
+    pub fn get<const N: usize>(&self, name: &str) -> [f32; N] {
+        let (_, value) = self
+            .0
+            .iter()
+            .find(|(attribute, _)| *attribute == name)
+            .unwrap_or_else(|| panic!("Vertex attribute {name} is not bound"));
+
+        std::array::from_fn(|i| value.get(i).copied().unwrap_or_default())
+    }
+}
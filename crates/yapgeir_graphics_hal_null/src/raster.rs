@@ -0,0 +1,306 @@
+use yapgeir_graphics_hal::{
+    draw_descriptor::IndexBinding,
+    draw_params::DrawParameters,
+    frame_buffer::Indices,
+    index_buffer::{IndexKind, PrimitiveMode},
+    vertex_buffer::AttributeKind,
+    Rect, Rgba,
+};
+
+use crate::{
+    image::Image,
+    program::{NullProgram, ProgramInputs, VertexInputs},
+    shader::NullDrawDescriptor,
+};
+
+/// A vertex after the vertex stage, in Y-down pixel coordinates of the frame buffer.
+struct Fragment {
+    ndc: [f32; 2],
+    position: [f32; 2],
+    varyings: Vec<f32>,
+}
+
+fn read_index(bytes: &[u8], kind: IndexKind, i: usize) -> usize {
+    let at = i * kind.size();
+    match kind {
+        IndexKind::U8 => bytes[at] as usize,
+        IndexKind::U16 => u16::from_ne_bytes([bytes[at], bytes[at + 1]]) as usize,
+        IndexKind::U32 => u32::from_ne_bytes(bytes[at..at + 4].try_into().unwrap()) as usize,
+    }
+}
+
+fn read_component(bytes: &[u8], kind: AttributeKind, i: usize) -> f32 {
+    match kind {
+        AttributeKind::I8 => bytes[i] as i8 as f32,
+        AttributeKind::U8 => bytes[i] as f32,
+        AttributeKind::I16 => i16::from_ne_bytes([bytes[i * 2], bytes[i * 2 + 1]]) as f32,
+        AttributeKind::U16 => u16::from_ne_bytes([bytes[i * 2], bytes[i * 2 + 1]]) as f32,
+        AttributeKind::F32 => f32::from_ne_bytes(bytes[i * 4..i * 4 + 4].try_into().unwrap()),
+    }
+}
+
+fn attribute_size(kind: AttributeKind) -> usize {
+    match kind {
+        AttributeKind::I8 | AttributeKind::U8 => 1,
+        AttributeKind::I16 | AttributeKind::U16 => 2,
+        AttributeKind::F32 => 4,
+    }
+}
+
+/// Reads the attributes of a vertex of an instance from the bound vertex buffers.
+/// Attributes are not normalized, like in the GLES2 implementation.
+fn vertex_inputs(descriptor: &NullDrawDescriptor, vertex: usize, instance: usize) -> VertexInputs {
+    let mut inputs = Vec::new();
+    for (buffer, attributes, stride) in &descriptor.vertices {
+        let data = buffer.data();
+        for attribute in attributes {
+            let element = match attribute.divisor {
+                0 => vertex,
+                divisor => instance / divisor as usize,
+            };
+
+            let size = attribute.size.size();
+            let offset = element * stride + attribute.offset;
+            let bytes = &data[offset..offset + size * attribute_size(attribute.kind)];
+            let value = (0..size)
+                .map(|i| read_component(bytes, attribute.kind, i))
+                .collect();
+            inputs.push((attribute.name, value));
+        }
+    }
+
+    VertexInputs(inputs)
+}
+
+/// Returns the indices of the vertices of the triangles, with the winding
+/// of triangle strips fixed up the same way as by OpenGL.
+fn triangles(mode: PrimitiveMode, vertices: &[usize]) -> Vec<[usize; 3]> {
+    match mode {
+        PrimitiveMode::Triangles => vertices
+            .chunks_exact(3)
+            .map(|t| [t[0], t[1], t[2]])
+            .collect(),
+        PrimitiveMode::TriangleStrip => vertices
+            .windows(3)
+            .enumerate()
+            .map(|(i, t)| match i % 2 {
+                0 => [t[0], t[1], t[2]],
+                _ => [t[1], t[0], t[2]],
+            })
+            .collect(),
+        PrimitiveMode::TriangleFan => vertices
+            .windows(2)
+            .skip(1)
+            .map(|t| [vertices[0], t[0], t[1]])
+            .collect(),
+        PrimitiveMode::Points
+        | PrimitiveMode::Lines
+        | PrimitiveMode::LineStrip
+        | PrimitiveMode::LineLoop => Vec::new(),
+    }
+}
+
+fn intersect(a: Rect<u32>, b: Rect<u32>) -> Rect<u32> {
+    let (x, y) = (a.x.max(b.x), a.y.max(b.y));
+    let right = (a.x + a.w).min(b.x + b.w).max(x);
+    let bottom = (a.y + a.h).min(b.y + b.h).max(y);
+    Rect::new(x, y, right - x, bottom - y)
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`.
+fn edge(a: [f32; 2], b: [f32; 2], p: [f32; 2]) -> f32 {
+    (b[0] - a[0]) * (p[1] - a[1]) - (b[1] - a[1]) * (p[0] - a[0])
+}
+
+/// Whether pixel centers exactly on the edge from `a` to `b` are covered.
+/// Holds for exactly one of the directions of an edge, so a pixel on an edge shared
+/// by two triangles is drawn once.
+fn owns_edge(a: [f32; 2], b: [f32; 2]) -> bool {
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    dy > 0. || (dy == 0. && dx < 0.)
+}
+
+/// Draws triangles and points into the images of draw attachments by running a program on the CPU.
+///
+/// Lines are skipped, and primitives with vertices behind the viewer are skipped
+/// as a whole instead of being clipped. Varyings are interpolated
+/// linearly in screen space. Depth and stencil tests are not emulated.
+pub(crate) fn draw(
+    images: &mut [&mut Image],
+    program: &dyn NullProgram,
+    inputs: &ProgramInputs,
+    descriptor: &NullDrawDescriptor,
+    parameters: &DrawParameters,
+    indices: &Indices,
+    instances: usize,
+) {
+    let vertices: Vec<usize> = match &descriptor.indices {
+        IndexBinding::None => (indices.offset..indices.offset + indices.len).collect(),
+        IndexBinding::Some { buffer, kind } => {
+            let data = buffer.data();
+            (indices.offset..indices.offset + indices.len)
+                .map(|i| read_index(&data, *kind, i))
+                .collect()
+        }
+    };
+
+    let size = images[0].size;
+    let viewport = parameters.viewport.unwrap_or(size.into());
+    // Primitives are not clipped, so the viewport clips fragments instead.
+    let mut bounds = intersect(size.into(), viewport);
+    if let Some(scissor) = parameters.scissor {
+        bounds = intersect(bounds, scissor);
+    }
+
+    for instance in 0..instances {
+        let fragment = |vertex: &VertexInputs| {
+            let ([x, y, _, w], varyings) = program.vertex(inputs, vertex);
+            (w > 0.).then(|| {
+                let ndc = [x / w, y / w];
+                Fragment {
+                    ndc,
+                    position: [
+                        viewport.x as f32 + (ndc[0] + 1.) / 2. * viewport.w as f32,
+                        viewport.y as f32 + (1. - ndc[1]) / 2. * viewport.h as f32,
+                    ],
+                    varyings,
+                }
+            })
+        };
+
+        if indices.mode == PrimitiveMode::Points {
+            for &vertex in &vertices {
+                let vertex = vertex_inputs(descriptor, vertex, instance);
+                if let Some(point) = fragment(&vertex) {
+                    let size = program.point_size(inputs, &vertex);
+                    draw_point(images, program, inputs, parameters, bounds, point, size);
+                }
+            }
+        }
+
+        for triangle in triangles(indices.mode, &vertices) {
+            let [Some(a), Some(b), Some(c)] =
+                triangle.map(|vertex| fragment(&vertex_inputs(descriptor, vertex, instance)))
+            else {
+                continue;
+            };
+
+            if let Some(cull_face) = parameters.cull_face {
+                let front = parameters.front_face.is_front([a.ndc, b.ndc, c.ndc]);
+                if cull_face.culls(front) {
+                    continue;
+                }
+            }
+
+            draw_triangle(images, program, inputs, parameters, bounds, [a, b, c]);
+        }
+    }
+}
+
+/// Draws a square point of `size` pixels centered at its position, covering
+/// the pixels with centers inside of it.
+fn draw_point(
+    images: &mut [&mut Image],
+    program: &dyn NullProgram,
+    inputs: &ProgramInputs,
+    parameters: &DrawParameters,
+    bounds: Rect<u32>,
+    point: Fragment,
+    size: f32,
+) {
+    let half = size.max(1.) / 2.;
+    let range = |i: usize, start: u32, len: u32| {
+        let from = (point.position[i] - half - 0.5).ceil().max(start as f32) as u32;
+        let to = (point.position[i] + half - 0.5).ceil().max(0.) as u32;
+        from..to.min(start + len)
+    };
+
+    let Some(colors) = program.fragment_data(inputs, &point.varyings) else {
+        return;
+    };
+
+    for y in range(1, bounds.y, bounds.h) {
+        for x in range(0, bounds.x, bounds.w) {
+            for (image, color) in images.iter_mut().zip(&colors) {
+                write_fragment(image, parameters, [x, y], *color);
+            }
+        }
+    }
+}
+
+fn draw_triangle(
+    images: &mut [&mut Image],
+    program: &dyn NullProgram,
+    inputs: &ProgramInputs,
+    parameters: &DrawParameters,
+    bounds: Rect<u32>,
+    [a, mut b, mut c]: [Fragment; 3],
+) {
+    let mut area = edge(a.position, b.position, c.position);
+    if area == 0. {
+        return;
+    }
+    if area < 0. {
+        std::mem::swap(&mut b, &mut c);
+        area = -area;
+    }
+
+    let edges = [(&b, &c), (&c, &a), (&a, &b)];
+    let min = |i: usize| a.position[i].min(b.position[i]).min(c.position[i]).floor();
+    let max = |i: usize| a.position[i].max(b.position[i]).max(c.position[i]).ceil();
+    let x_range = (min(0).max(bounds.x as f32) as u32)..(max(0) as u32).min(bounds.x + bounds.w);
+    let y_range = (min(1).max(bounds.y as f32) as u32)..(max(1) as u32).min(bounds.y + bounds.h);
+
+    for y in y_range {
+        for x in x_range.clone() {
+            let p = [x as f32 + 0.5, y as f32 + 0.5];
+            let weights = edges.map(|(from, to)| edge(from.position, to.position, p));
+            let covered = weights.iter().zip(edges).all(|(weight, (from, to))| {
+                *weight > 0. || (*weight == 0. && owns_edge(from.position, to.position))
+            });
+            if !covered {
+                continue;
+            }
+
+            let varyings: Vec<f32> = (0..a.varyings.len())
+                .map(|i| {
+                    (weights[0] * a.varyings[i]
+                        + weights[1] * b.varyings[i]
+                        + weights[2] * c.varyings[i])
+                        / area
+                })
+                .collect();
+
+            let Some(colors) = program.fragment_data(inputs, &varyings) else {
+                continue;
+            };
+
+            for (image, color) in images.iter_mut().zip(colors) {
+                write_fragment(image, parameters, [x, y], color);
+            }
+        }
+    }
+}
+
+fn write_fragment(
+    image: &mut Image,
+    parameters: &DrawParameters,
+    [x, y]: [u32; 2],
+    color: Rgba<f32>,
+) {
+    let color = <[f32; 4]>::from(color).map(|c| c.clamp(0., 1.));
+    // Images are stored bottom-up, while positions are Y-down.
+    let row = image.size.h - 1 - y;
+    let destination = image.pixel(x, row).map(|c| c as f32 / u8::MAX as f32);
+    let color: [f32; 4] = match &parameters.blend {
+        Some(blend) => blend.apply(color.into(), destination.into()).into(),
+        None => color,
+    };
+
+    let mask: [bool; 4] = parameters.color_mask.into();
+    let pixel = std::array::from_fn(|i| match mask[i] {
+        true => (color[i] * u8::MAX as f32).round() as u8,
+        false => image.pixel(x, row)[i],
+    });
+    image.set_pixel(x, row, pixel);
+}
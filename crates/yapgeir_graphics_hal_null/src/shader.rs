@@ -0,0 +1,120 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use yapgeir_graphics_hal::{
+    draw_descriptor::{DrawDescriptor, IndexBinding, VertexBindings},
+    query::TimerQuery,
+    shader::{Shader, ShaderError, TextShaderSource},
+    vertex_buffer::VertexAttribute,
+};
+
+use crate::{buffer::NullBuffer, program::NullProgram, Null};
+
+/// A shader which is never compiled, so any source is accepted.
+///
+/// Draw calls with a shader are rasterized if a program was registered
+/// for its source with `Null::register_program`, and only counted otherwise.
+pub struct NullShader {
+    pub(crate) program: Option<Rc<dyn NullProgram>>,
+    pub(crate) defines: Vec<(String, String)>,
+}
+
+impl Shader<Null> for NullShader {
+    type Source = ();
+
+    fn try_new(ctx: Null, source: &TextShaderSource) -> Result<Self, ShaderError> {
+        Ok(NullShader {
+            program: ctx.program(source),
+            defines: source
+                .defines
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        })
+    }
+}
+
+pub struct NullDrawDescriptor {
+    pub shader: Rc<NullShader>,
+    pub indices: IndexBinding<Null>,
+    pub(crate) vertices: Vec<(Rc<NullBuffer>, Vec<VertexAttribute>, usize)>,
+}
+
+impl DrawDescriptor<Null> for NullDrawDescriptor {
+    fn new(
+        ctx: Null,
+        shader: Rc<NullShader>,
+        indices: IndexBinding<Null>,
+        vertices: &[VertexBindings<Null>],
+    ) -> Self {
+        let vertices = vertices
+            .iter()
+            .map(|binding| {
+                let mut attributes = binding.attributes.to_vec();
+                // Without instancing support divisors are ignored, like in the GLES2 implementation.
+                if !ctx.instancing {
+                    attributes.iter_mut().for_each(|a| a.divisor = 0);
+                }
+                (binding.buffer.clone(), attributes, binding.stride)
+            })
+            .collect();
+
+        Self {
+            shader,
+            indices,
+            vertices,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum QueryState {
+    Idle,
+    Running(Instant),
+    Ended(Duration),
+}
+
+/// A timer query measuring the CPU time between `begin` and `end`, since the null
+/// implementation executes commands immediately. The result is available right after `end`.
+///
+/// If timer queries are disabled in `NullBackend`, the query never returns a result.
+pub struct NullTimerQuery {
+    supported: bool,
+    state: Cell<QueryState>,
+}
+
+impl TimerQuery<Null> for NullTimerQuery {
+    fn new(ctx: Null) -> Self {
+        NullTimerQuery {
+            supported: ctx.timer_queries,
+            state: Cell::new(QueryState::Idle),
+        }
+    }
+
+    fn begin(&self) {
+        assert!(
+            !matches!(self.state.get(), QueryState::Running(_)),
+            "Timer query has already begun"
+        );
+        self.state.set(QueryState::Running(Instant::now()));
+    }
+
+    fn end(&self) {
+        if let QueryState::Running(start) = self.state.get() {
+            self.state.set(QueryState::Ended(start.elapsed()));
+        }
+    }
+
+    fn poll(&self) -> Option<Duration> {
+        match self.state.get() {
+            QueryState::Ended(duration) if self.supported => {
+                self.state.set(QueryState::Idle);
+                Some(duration)
+            }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::cell::{Cell, RefCell};
+
+use yapgeir_graphics_hal::{
+    render_buffer::{RenderBuffer, RenderBufferFormat},
+    sampler::{SamplerState, WrapFunction},
+    texture::{CubemapFace, PixelFormat, Texture},
+    Rect, Rgba, Size,
+};
+
+use crate::{image::Image, Null};
+
+pub struct NullTexture {
+    format: PixelFormat,
+    cubemap: bool,
+    /// A single image for 2D textures, or six faces for cubemaps. Mipmaps are not stored.
+    pub(crate) images: RefCell<Vec<Image>>,
+    size_queries: Cell<usize>,
+}
+
+impl Texture<Null> for NullTexture {
+    type PixelFormat = PixelFormat;
+
+    fn new(_: Null, format: PixelFormat, size: Size<u32>, bytes: Option<&[u8]>) -> Self {
+        Self {
+            format,
+            cubemap: false,
+            images: RefCell::new(vec![Image::with_bytes(format, size, bytes)]),
+            size_queries: Cell::new(0),
+        }
+    }
+
+    fn new_cubemap(
+        _: Null,
+        format: PixelFormat,
+        size: Size<u32>,
+        faces: [Option<&[u8]>; 6],
+    ) -> Self {
+        Self {
+            format,
+            cubemap: true,
+            images: RefCell::new(
+                faces
+                    .iter()
+                    .map(|bytes| Image::with_bytes(format, size, *bytes))
+                    .collect(),
+            ),
+            size_queries: Cell::new(0),
+        }
+    }
+
+    fn is_cubemap(&self) -> bool {
+        self.cubemap
+    }
+
+    fn write_face(
+        &self,
+        face: CubemapFace,
+        mipmap_level: u32,
+        format: PixelFormat,
+        size: Size<u32>,
+        bytes: &[u8],
+    ) {
+        assert!(self.cubemap, "Only faces of cubemaps can be written");
+        if mipmap_level == 0 {
+            let face = CubemapFace::ALL.iter().position(|f| *f == face).unwrap();
+            self.images.borrow_mut()[face] = Image::with_bytes(format, size, Some(bytes));
+        }
+    }
+
+    fn size(&self) -> Size<u32> {
+        self.size_queries.set(self.size_queries.get() + 1);
+        self.images.borrow()[0].size
+    }
+
+    fn write(&self, mipmap_level: u32, format: PixelFormat, size: Size<u32>, bytes: &[u8]) {
+        assert!(!self.cubemap, "Cubemaps must be written with write_face");
+        if mipmap_level == 0 {
+            self.images.borrow_mut()[0] = Image::with_bytes(format, size, Some(bytes));
+        }
+    }
+
+    fn write_rect(&self, mipmap_level: u32, format: PixelFormat, rect: Rect<u32>, bytes: &[u8]) {
+        assert!(!self.cubemap, "Cubemaps must be written with write_face");
+        if mipmap_level == 0 {
+            self.images.borrow_mut()[0].write(format, rect, bytes);
+        }
+    }
+
+    fn generate_mipmaps(&self) {}
+}
+
+impl NullTexture {
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+
+    /// Returns the number of times the size of the texture was queried.
+    pub fn size_queries(&self) -> usize {
+        self.size_queries.get()
+    }
+
+    /// Samples a 2D texture at a normalized position with (0; 0) in the top-left corner.
+    ///
+    /// Only wrapping is emulated, texels are always filtered as `Filter::Nearest`.
+    pub(crate) fn sample(&self, state: SamplerState, position: [f32; 2]) -> Rgba<u8> {
+        let image = &self.images.borrow()[0];
+        let (Some(x), Some(y)) = (state.wrap.wrap(position[0]), state.wrap.wrap(position[1]))
+        else {
+            let WrapFunction::Border(color) = state.wrap else {
+                unreachable!("Only the border wrap function samples outside of the texture");
+            };
+            return <[f32; 4]>::from(color)
+                .map(|c| (c.clamp(0., 1.) * 255.).round() as u8)
+                .into();
+        };
+
+        let texel = |c: f32, size: u32| ((c * size as f32) as u32).min(size.saturating_sub(1));
+        image
+            .pixel(texel(x, image.size.w), texel(y, image.size.h))
+            .into()
+    }
+}
+
+pub struct NullRenderBuffer {
+    pub(crate) image: RefCell<Image>,
+}
+
+impl RenderBuffer<Null> for NullRenderBuffer {
+    type Format = RenderBufferFormat;
+
+    fn new(_: Null, size: Size<u32>, _: RenderBufferFormat) -> Self {
+        Self {
+            image: RefCell::new(Image::new(size)),
+        }
+    }
+
+    /// Multisampling is not emulated.
+    fn samples(&self) -> u8 {
+        1
+    }
+}
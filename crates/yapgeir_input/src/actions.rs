@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    controller::{GamepadAxis, GamepadButton},
+    keyboard::ScanCode,
+    mouse::MouseButton,
+    Input,
+};
+
+/// Axis values with a magnitude above this threshold count as a pressed action.
+const AXIS_PRESS_THRESHOLD: f32 = 0.5;
+
+/// Picks the value with the largest magnitude.
+fn strongest(a: f32, b: f32) -> f32 {
+    if b.abs() > a.abs() {
+        b
+    } else {
+        a
+    }
+}
+
+/// A physical input that can trigger an action.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(ScanCode),
+    MouseButton(MouseButton),
+    /// A button on any of the connected gamepads.
+    GamepadButton(GamepadButton),
+    /// An axis on any of the connected gamepads.
+    GamepadAxis(GamepadAxis),
+    /// A pair of keys acting as an axis, e.g. left and right arrows.
+    KeyAxis {
+        negative: ScanCode,
+        positive: ScanCode,
+    },
+}
+
+impl Binding {
+    fn value(&self, input: &Input) -> f32 {
+        let down = |pressed: bool| if pressed { 1. } else { 0. };
+
+        match *self {
+            Binding::Key(key) => down(input.keyboard.down(key)),
+            Binding::MouseButton(button) => down(input.mouse.buttons.down(button)),
+            Binding::GamepadButton(button) => {
+                down(input.gamepads.values().any(|g| g.buttons.down(button)))
+            }
+            Binding::GamepadAxis(axis) => input
+                .gamepads
+                .values()
+                .map(|g| g.axis(axis))
+                .fold(0., strongest),
+            Binding::KeyAxis { negative, positive } => {
+                down(input.keyboard.down(positive)) - down(input.keyboard.down(negative))
+            }
+        }
+    }
+
+    fn just_pressed(&self, input: &Input) -> bool {
+        match *self {
+            Binding::Key(key) => input.keyboard.just_pressed(key),
+            Binding::MouseButton(button) => input.mouse.buttons.just_pressed(button),
+            Binding::GamepadButton(button) => input
+                .gamepads
+                .values()
+                .any(|g| g.buttons.just_pressed(button)),
+            Binding::KeyAxis { negative, positive } => {
+                input.keyboard.just_pressed(negative) || input.keyboard.just_pressed(positive)
+            }
+            // Previous axis values are not tracked.
+            Binding::GamepadAxis(_) => false,
+        }
+    }
+}
+
+/// Maps named actions to one or more bindings, decoupling game logic from the hardware.
+///
+/// Actions are evaluated against the current `Input` state, and can be deserialized
+/// from a config file as a map of action names to lists of bindings.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ActionMap {
+    actions: HashMap<String, Vec<Binding>>,
+}
+
+impl ActionMap {
+    /// Add a binding to an action.
+    pub fn bind(&mut self, action: impl Into<String>, binding: Binding) -> &mut Self {
+        self.actions.entry(action.into()).or_default().push(binding);
+        self
+    }
+
+    fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns true if any of the action bindings is down.
+    pub fn is_pressed(&self, input: &Input, action: &str) -> bool {
+        self.bindings(action)
+            .iter()
+            .any(|b| b.value(input).abs() > AXIS_PRESS_THRESHOLD)
+    }
+
+    /// Returns true if any of the action button bindings was pressed during the last frame.
+    pub fn just_pressed(&self, input: &Input, action: &str) -> bool {
+        self.bindings(action).iter().any(|b| b.just_pressed(input))
+    }
+
+    /// Returns the value of the action in [-1, 1]. Buttons have a value of 1 when down.
+    /// If multiple bindings are active, the one with the largest magnitude wins.
+    pub fn axis(&self, input: &Input, action: &str) -> f32 {
+        self.bindings(action)
+            .iter()
+            .map(|b| b.value(input))
+            .fold(0., strongest)
+            .clamp(-1., 1.)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_any_binding_presses_action() {
+        let mut actions = ActionMap::default();
+        actions
+            .bind("jump", Binding::Key(ScanCode::Space))
+            .bind("jump", Binding::Key(ScanCode::W));
+
+        let mut input = Input::default();
+        assert!(!actions.is_pressed(&input, "jump"));
+
+        input.keyboard.current_state.set(ScanCode::W as usize, true);
+        assert!(actions.is_pressed(&input, "jump"));
+        assert!(!actions.is_pressed(&input, "unbound"));
+    }
+
+    #[test]
+    fn test_key_axis() {
+        let mut actions = ActionMap::default();
+        actions.bind(
+            "move_x",
+            Binding::KeyAxis {
+                negative: ScanCode::Left,
+                positive: ScanCode::Right,
+            },
+        );
+
+        let mut input = Input::default();
+        input
+            .keyboard
+            .current_state
+            .set(ScanCode::Left as usize, true);
+        assert_eq!(actions.axis(&input, "move_x"), -1.);
+    }
+}
@@ -1,4 +1,5 @@
 use derive_more::Constructor;
+use serde::{Deserialize, Serialize};
 use strum::EnumCount;
 
 use crate::{
@@ -9,7 +10,7 @@ use crate::{
 #[derive(Constructor, PartialEq, Eq, Hash, Clone, Copy)]
 pub struct GamepadId(pub u32);
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize)]
 pub enum GamepadButton {
     A,
     B,
@@ -43,6 +44,33 @@ impl CastToUsize for GamepadButton {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    TriggerLeft,
+    TriggerRight,
+}
+
+/// Normalizes a raw axis value to [-1, 1].
+pub fn normalize_axis(value: i16) -> f32 {
+    (value as f32 / i16::MAX as f32).clamp(-1., 1.)
+}
+
+/// Applies a radial deadzone to a stick position, rescaling the rest of the range,
+/// so that the stick moves smoothly from 0 when leaving the deadzone.
+fn apply_deadzone(stick: Axial<f32>, deadzone: f32) -> Axial<f32> {
+    let magnitude = (stick.x * stick.x + stick.y * stick.y).sqrt();
+    if magnitude <= deadzone {
+        return Axial::default();
+    }
+
+    let scale = (magnitude.min(1.) - deadzone) / (1. - deadzone) / magnitude;
+    Axial::new(stick.x * scale, stick.y * scale)
+}
+
 #[derive(Default)]
 pub struct Gamepad {
     //// Current button states.
@@ -59,4 +87,68 @@ pub struct Gamepad {
 
     /// Right trigger state. Normalized to [0, 1]. Depressed is 0.
     pub right_trigger: f32,
+
+    /// Radial deadzone of both sticks in [0, 1).
+    /// A stick within the deadzone is reported as centered. Disabled by default.
+    pub deadzone: f32,
+
+    raw_left_stick: Axial<f32>,
+    raw_right_stick: Axial<f32>,
+}
+
+impl Gamepad {
+    /// Returns the current value of an axis, with the deadzone applied to the sticks.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        match axis {
+            GamepadAxis::LeftX => self.left_stick.x,
+            GamepadAxis::LeftY => self.left_stick.y,
+            GamepadAxis::RightX => self.right_stick.x,
+            GamepadAxis::RightY => self.right_stick.y,
+            GamepadAxis::TriggerLeft => self.left_trigger,
+            GamepadAxis::TriggerRight => self.right_trigger,
+        }
+    }
+
+    /// Update an axis from a raw value reported by the device.
+    pub fn set_axis(&mut self, axis: GamepadAxis, value: i16) {
+        let value = normalize_axis(value);
+        match axis {
+            GamepadAxis::LeftX => self.raw_left_stick.x = value,
+            GamepadAxis::LeftY => self.raw_left_stick.y = value,
+            GamepadAxis::RightX => self.raw_right_stick.x = value,
+            GamepadAxis::RightY => self.raw_right_stick.y = value,
+            GamepadAxis::TriggerLeft => self.left_trigger = value.max(0.),
+            GamepadAxis::TriggerRight => self.right_trigger = value.max(0.),
+        }
+
+        self.left_stick = apply_deadzone(self.raw_left_stick, self.deadzone);
+        self.right_stick = apply_deadzone(self.raw_right_stick, self.deadzone);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_axis_normalization() {
+        assert!((normalize_axis(i16::MAX) - 1.).abs() < 1e-6);
+        assert_eq!(normalize_axis(i16::MIN), -1.);
+        assert_eq!(normalize_axis(0), 0.);
+    }
+
+    #[test]
+    fn test_stick_deadzone() {
+        let mut gamepad = Gamepad {
+            deadzone: 0.2,
+            ..Default::default()
+        };
+
+        gamepad.set_axis(GamepadAxis::LeftX, i16::MAX / 10);
+        assert_eq!(gamepad.left_stick, Axial::new(0., 0.));
+
+        gamepad.set_axis(GamepadAxis::LeftX, i16::MAX);
+        assert!((gamepad.left_stick.x - 1.).abs() < 1e-6);
+        assert_eq!(gamepad.left_stick.y, 0.);
+    }
 }
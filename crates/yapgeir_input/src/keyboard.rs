@@ -1,9 +1,10 @@
 use derive_more::{Deref, DerefMut};
+use serde::{Deserialize, Serialize};
 use strum::EnumCount;
 
 use crate::buttons::{u32_blocks, Buttons, CastToUsize};
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize)]
 pub enum ScanCode {
     A = 4,
     B = 5,
@@ -259,3 +260,8 @@ const BLOCKS: usize = u32_blocks(ScanCode::COUNT);
 
 #[derive(Default, Deref, DerefMut)]
 pub struct Keyboard(Buttons<BLOCKS, ScanCode>);
+
+/// Composed text entered by the user, e.g. with an IME.
+/// Unlike scancodes, it accounts for the keyboard layout and dead keys.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TextInputEvent(pub String);
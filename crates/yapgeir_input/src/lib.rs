@@ -1,14 +1,17 @@
 use controller::{Gamepad, GamepadId};
 use derive_more::Constructor;
 use indexmap::IndexMap;
-use keyboard::Keyboard;
+use keyboard::{Keyboard, TextInputEvent};
 use mouse::{Mouse, MouseButtonEvent};
-use yapgeir_realm::{Realm, ResMut};
+use touch::{TouchEvent, Touches};
+use yapgeir_realm::{stage, Realm, ResMut};
 
+pub mod actions;
 pub mod buttons;
 pub mod controller;
 pub mod keyboard;
 pub mod mouse;
+pub mod touch;
 
 #[derive(Constructor, Default, Debug, Clone, Copy, PartialEq, Hash)]
 pub struct Axial<T> {
@@ -20,12 +23,18 @@ pub struct Axial<T> {
 pub struct Input {
     pub mouse: Mouse,
     pub keyboard: Keyboard,
+    pub touches: Touches,
     pub gamepads: IndexMap<GamepadId, Gamepad>,
 }
 
+/// Flushes the per-frame input state. Runs before the backend input system,
+/// so relative values such as mouse wheel and motion are zero in frames without events.
 fn update(mut input: ResMut<Input>) {
     input.keyboard.flush();
     input.mouse.buttons.flush();
+    input.mouse.wheel = Axial::default();
+    input.mouse.motion = Axial::default();
+    input.touches.flush();
     for (_, gamepad) in input.gamepads.iter_mut() {
         gamepad.buttons.flush();
     }
@@ -35,5 +44,33 @@ pub fn plugin(realm: &mut Realm) {
     realm
         .initialize_resource::<Input>()
         .add_plugin(yapgeir_events::plugin::<MouseButtonEvent>)
-        .add_system(update);
+        .add_plugin(yapgeir_events::plugin::<TouchEvent>)
+        .add_plugin(yapgeir_events::plugin::<TextInputEvent>)
+        .add_system_to_stage(stage::INPUT, update);
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_realm::Res;
+
+    use super::*;
+
+    #[test]
+    fn test_mouse_deltas_reset_between_frames() {
+        let mut realm = Realm::default();
+        realm
+            .add_plugin(plugin)
+            // Frame 1: the backend reports a scroll and a motion.
+            .run_system(update)
+            .run_system(|mut input: ResMut<Input>| {
+                input.mouse.wheel = Axial::new(0, 3);
+                input.mouse.motion = Axial::new(5, -2);
+            })
+            // Frame 2: no events.
+            .run_system(update)
+            .run_system(|input: Res<Input>| {
+                assert_eq!(input.mouse.wheel, Axial::new(0, 0));
+                assert_eq!(input.mouse.motion, Axial::new(0, 0));
+            });
+    }
 }
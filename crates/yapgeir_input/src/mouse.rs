@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 use strum::EnumCount;
 
 use crate::{
@@ -5,7 +6,7 @@ use crate::{
     Axial,
 };
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, EnumCount, Serialize, Deserialize)]
 pub enum MouseButton {
     Left,
     Right,
@@ -0,0 +1,115 @@
+use indexmap::IndexMap;
+
+use crate::{buttons::ButtonAction, Axial};
+
+/// An identifier of a finger, unique while the finger is touching the screen.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct FingerId(pub i64);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Touch {
+    /// Current finger position in pixels relative to window.
+    pub position: Axial<i32>,
+
+    /// Normalized pressure in [0, 1].
+    pub pressure: f32,
+
+    /// True if the finger has touched the screen since the last frame.
+    pub pressed: bool,
+
+    /// True if the finger is still touching the screen.
+    /// Released fingers are removed at the beginning of the next frame.
+    pub down: bool,
+}
+
+/// Fingers touching the screen, with the same flush semantics as buttons.
+#[derive(Default)]
+pub struct Touches {
+    fingers: IndexMap<FingerId, Touch>,
+}
+
+impl Touches {
+    #[inline]
+    pub(crate) fn flush(&mut self) {
+        self.fingers.retain(|_, touch| touch.down);
+        for touch in self.fingers.values_mut() {
+            touch.pressed = false;
+        }
+    }
+
+    pub fn get(&self, finger: FingerId) -> Option<&Touch> {
+        self.fingers.get(&finger)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (FingerId, &Touch)> {
+        self.fingers.iter().map(|(id, touch)| (*id, touch))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fingers.is_empty()
+    }
+
+    /// Register a finger touching the screen.
+    pub fn press(&mut self, finger: FingerId, position: Axial<i32>, pressure: f32) {
+        self.fingers.insert(
+            finger,
+            Touch {
+                position,
+                pressure,
+                pressed: true,
+                down: true,
+            },
+        );
+    }
+
+    /// Update the position of a finger touching the screen.
+    pub fn move_to(&mut self, finger: FingerId, position: Axial<i32>, pressure: f32) {
+        if let Some(touch) = self.fingers.get_mut(&finger) {
+            touch.position = position;
+            touch.pressure = pressure;
+        }
+    }
+
+    /// Register a finger leaving the screen.
+    pub fn release(&mut self, finger: FingerId, position: Axial<i32>) {
+        if let Some(touch) = self.fingers.get_mut(&finger) {
+            touch.position = position;
+            touch.down = false;
+        }
+    }
+}
+
+/// A touch event, that keeps the coordinate of the place where the finger
+/// touched or left the screen. See `MouseButtonEvent` for the rationale.
+#[derive(Debug, Clone, PartialEq, Hash)]
+pub struct TouchEvent {
+    pub coordinate: Axial<i32>,
+    pub finger: FingerId,
+    pub action: ButtonAction,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_finger_down_and_up() {
+        let mut touches = Touches::default();
+        let finger = FingerId(7);
+
+        touches.press(finger, Axial::new(10, 20), 0.5);
+        let touch = touches.get(finger).unwrap();
+        assert!(touch.pressed && touch.down);
+        assert_eq!(touch.position, Axial::new(10, 20));
+
+        touches.flush();
+        let touch = touches.get(finger).unwrap();
+        assert!(!touch.pressed && touch.down);
+
+        touches.release(finger, Axial::new(15, 20));
+        assert!(!touches.get(finger).unwrap().down);
+
+        touches.flush();
+        assert!(touches.is_empty());
+    }
+}
@@ -0,0 +1,278 @@
+use egui::{Color32, Id, Pos2, Rect, Sense, Stroke};
+use nalgebra::Vector2;
+use yapgeir_core::WindowSize;
+use yapgeir_world_2d::Transform;
+use yapgeir_world_2d_sprites::camera::Camera2D;
+
+/// The area of the screen showing the world through a `Camera2D`.
+#[derive(Debug, Clone, Copy)]
+pub struct GameView {
+    pub camera: Camera2D,
+    /// Screen area the camera renders to, in points.
+    pub rect: Rect,
+    pub pixels_per_point: f32,
+}
+
+impl GameView {
+    fn window(&self) -> WindowSize {
+        let size = self.rect.size() * self.pixels_per_point;
+        WindowSize::new(size.x.round() as u32, size.y.round() as u32)
+    }
+
+    pub fn world_to_screen(&self, world: [f32; 2]) -> Pos2 {
+        let [x, y] = self.camera.world_to_screen(world, self.window());
+        self.rect.min + egui::vec2(x, y) / self.pixels_per_point
+    }
+
+    /// Returns `None` if the camera is degenerate and can't be inverted.
+    pub fn screen_to_world(&self, screen: Pos2) -> Option<[f32; 2]> {
+        let pixel = (screen - self.rect.min) * self.pixels_per_point;
+        self.camera
+            .screen_to_world([pixel.x, pixel.y], self.window())
+    }
+}
+
+/// A handle drawn over the game view, which moves the translation of a `Transform` when dragged.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformGizmo {
+    /// Radius of the handle in points.
+    pub radius: f32,
+    /// Size of the grid to which the translation is snapped while dragging.
+    pub snap: Option<f32>,
+}
+
+impl Default for TransformGizmo {
+    fn default() -> Self {
+        Self {
+            radius: 6.,
+            snap: None,
+        }
+    }
+}
+
+impl TransformGizmo {
+    pub fn with_snap(self, grid: f32) -> Self {
+        Self {
+            snap: Some(grid),
+            ..self
+        }
+    }
+
+    fn snapped(&self, world: [f32; 2]) -> [f32; 2] {
+        match self.snap {
+            Some(grid) if grid > 0. => world.map(|v| (v / grid).round() * grid),
+            _ => world,
+        }
+    }
+
+    /// Draws the handle at the translation of the transform, and writes back the new
+    /// translation while it is dragged. Returns true if the translation was changed.
+    ///
+    /// The translation follows the pointer keeping the offset it had when the drag started,
+    /// so grabbing the handle off-centre doesn't make the entity jump.
+    pub fn show(&self, ctx: &egui::Context, view: &GameView, transform: &mut Transform) -> bool {
+        let translation = transform.isometry.translation.vector;
+        let center = view.world_to_screen(translation.into());
+        let rect = Rect::from_center_size(center, egui::Vec2::splat(self.radius * 2.));
+
+        let response = egui::Area::new(Id::new("transform_gizmo"))
+            .order(egui::Order::Foreground)
+            .fixed_pos(rect.min)
+            .show(ctx, |ui| {
+                let (rect, response) = ui.allocate_exact_size(rect.size(), Sense::drag());
+
+                let visuals = ui.style().interact(&response);
+                let stroke = Stroke::new(visuals.fg_stroke.width.max(1.), visuals.fg_stroke.color);
+                let painter = ui.painter();
+                painter.circle(rect.center(), self.radius, visuals.bg_fill, stroke);
+                painter.line_segment([rect.left_center(), rect.right_center()], stroke);
+                painter.line_segment([rect.center_top(), rect.center_bottom()], stroke);
+
+                response
+            })
+            .inner;
+
+        let Some(pointer) = response
+            .interact_pointer_pos()
+            .filter(|_| response.dragged())
+            .and_then(|pointer| view.screen_to_world(pointer))
+        else {
+            ctx.data_mut(|data| data.remove::<[f32; 2]>(response.id));
+            return false;
+        };
+
+        // Offset of the translation from the pointer, stored when the drag starts.
+        let offset = ctx.data_mut(|data| {
+            *data.get_temp_mut_or_insert_with(response.id, || {
+                [translation.x - pointer[0], translation.y - pointer[1]]
+            })
+        });
+
+        let world = self.snapped([pointer[0] + offset[0], pointer[1] + offset[1]]);
+        if world == <[f32; 2]>::from(translation) {
+            return false;
+        }
+
+        transform.isometry.translation.vector = Vector2::from(world);
+        true
+    }
+}
+
+/// Draws lines of a grid with a given size over the screen, e.g. to show the gizmo snapping.
+pub fn draw_grid(ctx: &egui::Context, view: &GameView, grid: f32, color: Color32) {
+    let screen = view.rect;
+    let corners = [
+        screen.left_top(),
+        screen.right_top(),
+        screen.left_bottom(),
+        screen.right_bottom(),
+    ]
+    .map(|p| view.screen_to_world(p));
+    let Some(corners) = corners.into_iter().collect::<Option<Vec<_>>>() else {
+        return;
+    };
+
+    let min = corners
+        .iter()
+        .fold([f32::MAX; 2], |a, c| [a[0].min(c[0]), a[1].min(c[1])]);
+    let max = corners
+        .iter()
+        .fold([f32::MIN; 2], |a, c| [a[0].max(c[0]), a[1].max(c[1])]);
+
+    // Too many lines would be just noise, and would take a long time to draw.
+    const MAX_LINES: f32 = 256.;
+    let lines = [max[0] - min[0], max[1] - min[1]].map(|extent| extent / grid);
+    if grid <= 0. || lines.iter().any(|lines| *lines > MAX_LINES) {
+        return;
+    }
+
+    let painter = ctx.layer_painter(egui::LayerId::background());
+    let stroke = Stroke::new(1., color);
+    let line = |a: [f32; 2], b: [f32; 2]| {
+        painter.line_segment([view.world_to_screen(a), view.world_to_screen(b)], stroke);
+    };
+
+    let mut x = (min[0] / grid).ceil() * grid;
+    while x <= max[0] {
+        line([x, min[1]], [x, max[1]]);
+        x += grid;
+    }
+
+    let mut y = (min[1] / grid).ceil() * grid;
+    while y <= max[1] {
+        line([min[0], y], [max[0], y]);
+        y += grid;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_screen_world_round_trip() {
+        // Zoomed in twice, looking at (5; 10) in world space.
+        let view = GameView {
+            camera: Camera2D {
+                position: [5., 10.],
+                zoom: 2.,
+                rotation: 0.,
+            },
+            rect: Rect::from_min_size(Pos2::new(100., 50.), egui::vec2(400., 300.)),
+            pixels_per_point: 2.,
+        };
+
+        assert_eq!(view.world_to_screen([5., 10.]), Pos2::new(300., 200.));
+        // One world unit is two pixels, which is one point, and Y goes down on the screen.
+        assert_eq!(view.world_to_screen([6., 11.]), Pos2::new(301., 199.));
+
+        let world = view.screen_to_world(Pos2::new(123., 45.)).unwrap();
+        let screen = view.world_to_screen(world);
+        assert!(
+            (screen - Pos2::new(123., 45.)).length() < 1e-4,
+            "{screen:?}"
+        );
+
+        let degenerate = GameView {
+            camera: Camera2D {
+                zoom: 0.,
+                ..view.camera
+            },
+            ..view
+        };
+        assert!(degenerate.screen_to_world(Pos2::ZERO).is_none());
+    }
+
+    #[test]
+    fn test_drag_keeps_pointer_offset() {
+        // One world unit is one point, with the world origin in the center of the view.
+        let view = GameView {
+            camera: Camera2D::default(),
+            rect: Rect::from_min_size(Pos2::ZERO, egui::vec2(200., 200.)),
+            pixels_per_point: 1.,
+        };
+        let gizmo = TransformGizmo::default();
+        let mut transform = Transform::default();
+
+        let ctx = egui::Context::default();
+        let frame = |transform: &mut Transform, events: Vec<egui::Event>| {
+            let input = egui::RawInput {
+                events,
+                ..Default::default()
+            };
+            let mut changed = false;
+            let _ = ctx.run(input, |ctx| changed = gizmo.show(ctx, &view, transform));
+            changed
+        };
+        let button = |pos, pressed| egui::Event::PointerButton {
+            pos,
+            button: egui::PointerButton::Primary,
+            pressed,
+            modifiers: Default::default(),
+        };
+
+        // Grab the handle 3 points right and 2 points below its center.
+        let grab = Pos2::new(103., 102.);
+        frame(&mut transform, vec![]);
+        assert!(!frame(
+            &mut transform,
+            vec![egui::Event::PointerMoved(grab), button(grab, true)]
+        ));
+
+        let release = Pos2::new(123., 92.);
+        assert!(frame(
+            &mut transform,
+            vec![egui::Event::PointerMoved(release)]
+        ));
+        frame(&mut transform, vec![button(release, false)]);
+
+        // Moved by the pointer delta, with Y going up in the world.
+        assert_eq!(
+            transform.isometry.translation.vector,
+            Vector2::new(20., 10.)
+        );
+
+        // A new drag doesn't reuse the offset of the previous one.
+        let grab = Pos2::new(118., 88.);
+        frame(
+            &mut transform,
+            vec![egui::Event::PointerMoved(grab), button(grab, true)],
+        );
+        frame(
+            &mut transform,
+            vec![egui::Event::PointerMoved(Pos2::new(128., 88.))],
+        );
+        frame(&mut transform, vec![button(Pos2::new(128., 88.), false)]);
+        assert_eq!(
+            transform.isometry.translation.vector,
+            Vector2::new(30., 10.)
+        );
+    }
+
+    #[test]
+    fn test_snap() {
+        let gizmo = TransformGizmo::default().with_snap(16.);
+        assert_eq!(gizmo.snapped([7., -9.]), [0., -16.]);
+        assert_eq!(gizmo.snapped([25., 40.]), [32., 48.]);
+    }
+}
@@ -1,3 +1,6 @@
+pub use gizmo::*;
+
+mod gizmo;
 mod primitives;
 
 use std::{
@@ -8,26 +11,99 @@ use std::{
     time::Duration,
 };
 
-use egui::{CollapsingHeader, Grid, Ui};
-use hecs::EntityRef;
+use egui::{collapsing_header::CollapsingState, CollapsingHeader, Grid, Ui};
+use hecs::{Entity, EntityRef, World};
 use yapgeir_core::Named;
-use yapgeir_realm::{Realm, ResMut};
+use yapgeir_geometry::Rgba;
+use yapgeir_realm::{Plugin, Realm, ResMut};
 use yapgeir_reflection::{
     bevy_reflect::{
         self, std_traits::ReflectDefault, Array, DynamicEnum, DynamicStruct, DynamicTuple,
-        DynamicVariant, Enum, List, Map, Reflect, ReflectMut, Struct, Tuple, TupleStruct, TypeInfo,
-        TypeRegistry, VariantInfo, VariantType,
+        DynamicVariant, Enum, FromType, List, Map, Reflect, ReflectMut, Struct, Tuple, TupleStruct,
+        TypeInfo, TypeRegistry, VariantInfo, VariantType,
     },
     RealmExtensions, Reflection,
 };
+use yapgeir_world_2d::{DrawQuad, Isometry, Vector};
 
 type GuiElementMutFn = fn(value: &mut dyn Any, ui: &mut egui::Ui, id: egui::Id);
+type RangedGuiElementMutFn =
+    fn(value: &mut dyn Any, ui: &mut egui::Ui, id: egui::Id, range: &InspectorRange);
 
 #[derive(Clone)]
 pub struct GuiElement {
     pub fn_mut: GuiElementMutFn,
 }
 
+/// A drawing implementation for numbers within an `InspectorRange`.
+#[derive(Clone)]
+pub struct RangedGuiElement {
+    pub fn_mut: RangedGuiElementMutFn,
+}
+
+/// Marks a type as read-only in the inspector, so that its values are displayed
+/// with disabled widgets. Nested fields of a read-only value are read-only as well.
+///
+/// Can be registered with `TypeRegistry::register_type_data::<T, ReflectReadOnly>()`,
+/// or with `#[reflect(ReadOnly)]` when deriving `Reflect`.
+#[derive(Clone)]
+pub struct ReflectReadOnly;
+
+impl<T> FromType<T> for ReflectReadOnly {
+    fn from_type() -> Self {
+        Self
+    }
+}
+
+/// Bounds of numbers, which are drawn with a slider instead of an unbounded drag value.
+///
+/// When registered as type data, the range applies to the numeric fields of a type,
+/// e.g. to `f32` in `struct Health(f32)`, but not to the fields of its nested values.
+/// Edits are clamped to the range, while values set elsewhere are kept until edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InspectorRange {
+    pub min: f64,
+    pub max: f64,
+    /// Slider increment, continuous if `None`.
+    pub step: Option<f64>,
+}
+
+impl InspectorRange {
+    /// Panics if `min` is greater than `max`.
+    pub fn new(min: f64, max: f64) -> Self {
+        assert!(min <= max, "Invalid inspector range: {min} > {max}");
+        Self {
+            min,
+            max,
+            step: None,
+        }
+    }
+
+    pub fn with_step(self, step: f64) -> Self {
+        Self {
+            step: Some(step),
+            ..self
+        }
+    }
+}
+
+/// Options inherited by nested values while drawing.
+#[derive(Debug, Clone, Copy, Default)]
+struct DrawOptions {
+    read_only: bool,
+    /// Range of the type the value is a field of, see `InspectorRange`.
+    range: Option<InspectorRange>,
+}
+
+impl DrawOptions {
+    fn read_only(read_only: bool) -> Self {
+        Self {
+            read_only,
+            range: None,
+        }
+    }
+}
+
 fn maybe_grid(i: usize, ui: &mut egui::Ui, id: egui::Id, mut f: impl FnMut(&mut egui::Ui, bool)) {
     match i {
         0 => {}
@@ -134,31 +210,251 @@ fn construct_default_variant(
     Ok(dynamic_enum)
 }
 
-pub fn draw_entity(reflection: &Reflection, ui: &mut Ui, entity: EntityRef) {
+/// A change of entity components requested from the inspector UI.
+///
+/// The UI only has access to an `EntityRef`, so the commands are returned
+/// from `draw_entity` and should be applied after the world is no longer borrowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComponentCommand {
+    /// Insert a default value of a component type.
+    Insert(TypeId),
+    /// Remove a component of a given type.
+    Remove(TypeId),
+}
+
+impl ComponentCommand {
+    pub fn apply(self, reflection: &Reflection, world: &mut World, entity: Entity) {
+        match self {
+            ComponentCommand::Insert(type_id) => {
+                if let Some(visitor) = reflection.component_visitors.get(&type_id) {
+                    visitor.insert_default(&reflection.type_registry, world, entity);
+                }
+            }
+            ComponentCommand::Remove(type_id) => {
+                if let Some(visitor) = reflection.component_visitors.get(&type_id) {
+                    visitor.remove(world, entity);
+                }
+            }
+        }
+    }
+}
+
+/// Returns registered component types, which are not present on the entity,
+/// and can be constructed with a default value.
+pub fn insertable_components(reflection: &Reflection, entity: EntityRef) -> Vec<TypeId> {
+    let mut types: Vec<TypeId> = reflection
+        .component_visitors
+        .keys()
+        .filter(|type_id| !entity.component_types().any(|t| t == **type_id))
+        .filter(|type_id| {
+            reflection
+                .type_registry
+                .get_type_data::<ReflectDefault>(**type_id)
+                .is_some()
+        })
+        .copied()
+        .collect();
+
+    types.sort_by_cached_key(|type_id| type_short_name(reflection, *type_id).to_owned());
+    types
+}
+
+fn type_short_name(reflection: &Reflection, type_id: TypeId) -> &str {
+    reflection
+        .type_registry
+        .get(type_id)
+        .map_or("Unregistered", |ty| ty.short_name())
+}
+
+/// Draws the entity with all of its components, and controls to add and remove components.
+#[must_use]
+pub fn draw_entity(
+    reflection: &Reflection,
+    ui: &mut Ui,
+    entity: EntityRef,
+) -> Option<ComponentCommand> {
+    draw_entity_with(reflection, ui, entity, false)
+}
+
+/// Draws the entity with all of its components, without allowing to change them.
+pub fn draw_entity_readonly(reflection: &Reflection, ui: &mut Ui, entity: EntityRef) {
+    let _ = draw_entity_with(reflection, ui, entity, true);
+}
+
+fn draw_entity_with(
+    reflection: &Reflection,
+    ui: &mut Ui,
+    entity: EntityRef,
+    read_only: bool,
+) -> Option<ComponentCommand> {
     let name = entity
         .get::<&Named>()
         .map(|n| format!("{} ({:?})", n.0, entity.entity()))
         .unwrap_or_else(|| format!("Unnamed {:?}", entity.entity()));
 
+    let mut command = None;
+
     CollapsingHeader::new(&name)
         .default_open(true)
         .id_source(entity.entity())
         .show(ui, |ui| {
             for type_id in entity.component_types() {
-                draw_component(reflection, ui, entity, type_id);
+                if draw_component_with(reflection, ui, entity, type_id, read_only) {
+                    command = Some(ComponentCommand::Remove(type_id));
+                }
+            }
+
+            if read_only {
+                return;
+            }
+
+            if let Some(type_id) = draw_add_component(reflection, ui, entity) {
+                command = Some(ComponentCommand::Insert(type_id));
+            }
+        });
+
+    command
+}
+
+/// Draws a component of the entity. Returns true if the remove button was clicked.
+pub fn draw_component(
+    reflection: &Reflection,
+    ui: &mut Ui,
+    entity: EntityRef,
+    type_id: TypeId,
+) -> bool {
+    draw_component_with(reflection, ui, entity, type_id, false)
+}
+
+fn draw_component_with(
+    reflection: &Reflection,
+    ui: &mut Ui,
+    entity: EntityRef,
+    type_id: TypeId,
+    read_only: bool,
+) -> bool {
+    let Some(ty) = reflection.type_registry.get(type_id) else {
+        ui.label(format!("Unregistered type: {:?}", type_id));
+        return false;
+    };
+
+    let mut remove = false;
+    let id = ui.make_persistent_id((entity.entity(), type_id));
+
+    CollapsingState::load_with_default_open(ui.ctx(), id, true)
+        .show_header(ui, |ui| {
+            ui.label(ty.short_name());
+            if !read_only {
+                remove = ui
+                    .small_button("🗑")
+                    .on_hover_text("Remove component")
+                    .clicked();
+            }
+        })
+        .body(|ui| {
+            if let Some(visitor) = reflection.component_visitors.get(&type_id) {
+                visitor.visit(
+                    entity,
+                    Box::new(|r| {
+                        let id = ui.next_auto_id();
+                        let options = DrawOptions::read_only(read_only);
+                        ui_for_reflect_with(&reflection.type_registry, r, ui, id, options);
+                    }),
+                );
             }
         });
+
+    remove
+}
+
+/// Draws a combo box with the components that can be added to the entity.
+/// Returns a type of the component if the add button was clicked.
+fn draw_add_component(reflection: &Reflection, ui: &mut Ui, entity: EntityRef) -> Option<TypeId> {
+    let types = insertable_components(reflection, entity);
+    if types.is_empty() {
+        return None;
+    }
+
+    let id = ui.make_persistent_id((entity.entity(), "add_component"));
+    let mut selected = ui
+        .data_mut(|data| data.get_temp::<TypeId>(id))
+        .filter(|type_id| types.contains(type_id))
+        .unwrap_or(types[0]);
+
+    let mut add = false;
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_source(id)
+            .selected_text(type_short_name(reflection, selected))
+            .show_ui(ui, |ui| {
+                for type_id in &types {
+                    ui.selectable_value(
+                        &mut selected,
+                        *type_id,
+                        type_short_name(reflection, *type_id),
+                    );
+                }
+            });
+
+        add = ui.button("Add").clicked();
+    });
+
+    ui.data_mut(|data| data.insert_temp(id, selected));
+    add.then_some(selected)
+}
+
+/// Returns true if the entity name or id contains the lowercase `filter`, ignoring case.
+fn entity_matches(entity: EntityRef, filter: &str) -> bool {
+    if filter.is_empty() {
+        return true;
+    }
+
+    let name_matches = entity
+        .get::<&Named>()
+        .map_or(false, |n| n.to_lowercase().contains(filter));
+
+    name_matches || format!("{:?}", entity.entity()).contains(filter)
 }
 
-pub fn draw_component(reflection: &Reflection, ui: &mut Ui, entity: EntityRef, type_id: TypeId) {
-    draw_collapsing_type(reflection, ui, type_id, |ui| {
-        reflection.component_visitors.get(&type_id).unwrap().visit(
-            entity,
-            Box::new(|r| {
-                ui_for_reflect(&reflection.type_registry, r, ui, ui.next_auto_id());
-            }),
-        );
+/// Draws a list of all entities in the world, filtered by a text box.
+///
+/// The filter is matched against the `Named` component and the entity id,
+/// and is applied anew on every frame. Returns component commands requested
+/// for the drawn entities, to be applied with `ComponentCommand::apply`.
+#[must_use]
+pub fn entity_browser(
+    reflection: &Reflection,
+    ui: &mut Ui,
+    world: &World,
+) -> Vec<(Entity, ComponentCommand)> {
+    let id = ui.make_persistent_id("entity_browser_filter");
+    let mut filter = ui
+        .data_mut(|data| data.get_temp::<String>(id))
+        .unwrap_or_default();
+
+    ui.horizontal(|ui| {
+        ui.label("Filter");
+        ui.text_edit_singleline(&mut filter);
     });
+
+    let lowercase = filter.to_lowercase();
+    ui.data_mut(|data| data.insert_temp(id, filter));
+
+    let entities: Vec<EntityRef> = world
+        .iter()
+        .filter(|entity| entity_matches(*entity, &lowercase))
+        .collect();
+
+    ui.label(format!("{} of {} entities", entities.len(), world.len()));
+    ui.separator();
+
+    let mut commands = Vec::new();
+    for entity in entities {
+        if let Some(command) = draw_entity(reflection, ui, entity) {
+            commands.push((entity.entity(), command));
+        }
+    }
+
+    commands
 }
 
 pub fn draw_collapsing_type(
@@ -187,20 +483,70 @@ pub fn ui_for_reflect(
     ui: &mut egui::Ui,
     id: egui::Id,
 ) {
+    ui_for_reflect_with(type_registry, value, ui, id, DrawOptions::default());
+}
+
+/// Draw UI for any value that implements Reflect, with all widgets disabled.
+pub fn ui_for_reflect_readonly(
+    type_registry: &TypeRegistry,
+    value: &mut dyn Reflect,
+    ui: &mut egui::Ui,
+    id: egui::Id,
+) {
+    ui_for_reflect_with(type_registry, value, ui, id, DrawOptions::read_only(true));
+}
+
+fn ui_for_reflect_with(
+    type_registry: &TypeRegistry,
+    value: &mut dyn Reflect,
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    options: DrawOptions,
+) {
+    let type_id = Any::type_id(value);
+    let range = type_registry
+        .get_type_data::<InspectorRange>(type_id)
+        .copied();
+    let options = DrawOptions {
+        read_only: options.read_only
+            || type_registry
+                .get_type_data::<ReflectReadOnly>(type_id)
+                .is_some(),
+        range: range.or(options.range),
+    };
+
+    // Only leaf widgets are disabled, so that nested headers can still be collapsed.
+    if let (Some(range), Some(s)) = (
+        options.range,
+        type_registry.get_type_data::<RangedGuiElement>(type_id),
+    ) {
+        ui.add_enabled_ui(!options.read_only, |ui| {
+            (s.fn_mut)(value.as_any_mut(), ui, id, &range)
+        });
+        return;
+    }
+
+    // Fields only inherit the range of their own type, not of the types it is nested in.
+    let options = DrawOptions { range, ..options };
+
     // There are specific drawing implementations for primitives, check them first
-    if let Some(s) = type_registry.get_type_data::<GuiElement>(Any::type_id(value)) {
-        (s.fn_mut)(value.as_any_mut(), ui, id);
+    if let Some(s) = type_registry.get_type_data::<GuiElement>(type_id) {
+        ui.add_enabled_ui(!options.read_only, |ui| {
+            (s.fn_mut)(value.as_any_mut(), ui, id)
+        });
         return;
     }
 
     match value.reflect_mut() {
-        ReflectMut::Struct(value) => ui_for_struct(type_registry, value, ui, id),
-        ReflectMut::TupleStruct(value) => ui_for_tuple_struct(type_registry, value, ui, id),
-        ReflectMut::Tuple(value) => ui_for_tuple(type_registry, value, ui, id),
-        ReflectMut::List(value) => ui_for_list(type_registry, value, ui, id),
-        ReflectMut::Array(value) => ui_for_array(type_registry, value, ui, id),
-        ReflectMut::Map(value) => ui_for_reflect_map(type_registry, value, ui, id),
-        ReflectMut::Enum(value) => ui_for_enum(type_registry, value, ui, id),
+        ReflectMut::Struct(value) => ui_for_struct(type_registry, value, ui, id, options),
+        ReflectMut::TupleStruct(value) => {
+            ui_for_tuple_struct(type_registry, value, ui, id, options)
+        }
+        ReflectMut::Tuple(value) => ui_for_tuple(type_registry, value, ui, id, options),
+        ReflectMut::List(value) => ui_for_list(type_registry, value, ui, id, options),
+        ReflectMut::Array(value) => ui_for_array(type_registry, value, ui, id, options),
+        ReflectMut::Map(value) => ui_for_reflect_map(type_registry, value, ui, id, options),
+        ReflectMut::Enum(value) => ui_for_enum(type_registry, value, ui, id, options),
         ReflectMut::Value(_) => {
             // Values should be processed by s.fn_mut, if we get here,
             // it means we are processing a data type for which ui representation
@@ -209,13 +555,88 @@ pub fn ui_for_reflect(
     };
 }
 
-fn ui_for_list(type_registry: &TypeRegistry, list: &mut dyn List, ui: &mut egui::Ui, id: egui::Id) {
+/// A change of a list requested from the inspector UI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListEdit {
+    /// Append a default item, or a copy of the last item if the item type has no default.
+    Push,
+    Remove(usize),
+    /// Swap an item with the previous one.
+    MoveUp(usize),
+    /// Swap an item with the next one.
+    MoveDown(usize),
+}
+
+fn apply_list_edit(type_registry: &TypeRegistry, list: &mut dyn List, edit: ListEdit) {
+    fn swap_with_next(list: &mut dyn List, i: usize) {
+        let item = list.remove(i);
+        list.insert(i + 1, item);
+    }
+
+    let len = list.len();
+    match edit {
+        ListEdit::Push => {
+            let default = list
+                .get_represented_type_info()
+                .and_then(|info| match info {
+                    TypeInfo::List(info) => {
+                        get_default_value_for(type_registry, info.item_type_id())
+                    }
+                    _ => None,
+                })
+                .or_else(|| {
+                    let last = len.checked_sub(1)?;
+                    Some(Reflect::clone_value(list.get(last)?))
+                });
+
+            if let Some(new_value) = default {
+                list.push(new_value);
+            }
+        }
+        ListEdit::Remove(i) if i < len => {
+            list.remove(i);
+        }
+        ListEdit::MoveUp(i) if i > 0 && i < len => swap_with_next(list, i - 1),
+        ListEdit::MoveDown(i) if i + 1 < len => swap_with_next(list, i),
+        _ => {}
+    }
+}
+
+fn ui_for_list(
+    type_registry: &TypeRegistry,
+    list: &mut dyn List,
+    ui: &mut egui::Ui,
+    id: egui::Id,
+    options: DrawOptions,
+) {
+    let mut edit = None;
+
     ui.vertical(|ui| {
         let len = list.len();
         for i in 0..len {
             let val = list.get_mut(i).unwrap();
             ui.horizontal(|ui| {
-                ui_for_reflect(type_registry, val, ui, id.with(i));
+                ui_for_reflect_with(type_registry, val, ui, id.with(i), options);
+
+                if options.read_only {
+                    return;
+                }
+
+                if ui
+                    .add_enabled(i > 0, egui::Button::new("⬆").small())
+                    .clicked()
+                {
+                    edit = Some(ListEdit::MoveUp(i));
+                }
+                if ui
+                    .add_enabled(i + 1 < len, egui::Button::new("⬇").small())
+                    .clicked()
+                {
+                    edit = Some(ListEdit::MoveDown(i));
+                }
+                if ui.small_button("×").on_hover_text("Remove item").clicked() {
+                    edit = Some(ListEdit::Remove(i));
+                }
             });
 
             if i != len - 1 {
@@ -223,24 +644,21 @@ fn ui_for_list(type_registry: &TypeRegistry, list: &mut dyn List, ui: &mut egui:
             }
         }
 
-        let Some(TypeInfo::List(info)) = list.get_represented_type_info() else {
+        if options.read_only {
             return;
-        };
+        }
 
         ui.vertical_centered_justified(|ui| {
             if ui.button("+").clicked() {
-                let default =
-                    get_default_value_for(type_registry, info.item_type_id()).or_else(|| {
-                        let last = len.checked_sub(1)?;
-                        Some(Reflect::clone_value(list.get(last)?))
-                    });
-
-                if let Some(new_value) = default {
-                    list.push(new_value);
-                }
+                edit = Some(ListEdit::Push);
             }
         });
     });
+
+    // Items are borrowed while drawn, so the edit is applied after the whole list.
+    if let Some(edit) = edit {
+        apply_list_edit(type_registry, list, edit);
+    }
 }
 
 fn ui_for_array(
@@ -248,13 +666,14 @@ fn ui_for_array(
     array: &mut dyn Array,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
     ui.vertical(|ui| {
         let len = array.len();
         for i in 0..len {
             let val = array.get_mut(i).unwrap();
             ui.horizontal(|ui| {
-                ui_for_reflect(type_registry, val, ui, id.with(i));
+                ui_for_reflect_with(type_registry, val, ui, id.with(i), options);
             });
 
             if i != len - 1 {
@@ -264,23 +683,113 @@ fn ui_for_array(
     });
 }
 
+/// A change of a map requested from the inspector UI.
+enum MapEdit {
+    /// Insert an entry with a default key and value, unless the default key is taken.
+    Insert,
+    Remove(Box<dyn Reflect>),
+}
+
+/// Returns default values of the map key and value types, if both have `ReflectDefault`.
+fn default_map_entry(
+    type_registry: &TypeRegistry,
+    map: &dyn Map,
+) -> Option<(Box<dyn Reflect>, Box<dyn Reflect>)> {
+    let Some(TypeInfo::Map(info)) = map.get_represented_type_info() else {
+        return None;
+    };
+
+    Some((
+        get_default_value_for(type_registry, info.key_type_id())?,
+        get_default_value_for(type_registry, info.value_type_id())?,
+    ))
+}
+
+fn apply_map_edit(type_registry: &TypeRegistry, map: &mut dyn Map, edit: MapEdit) {
+    match edit {
+        MapEdit::Insert => {
+            let entry = default_map_entry(type_registry, map);
+            if let Some((key, value)) = entry.filter(|(key, _)| map.get(key.as_ref()).is_none()) {
+                map.insert_boxed(key, value);
+            }
+        }
+        MapEdit::Remove(key) => {
+            map.remove(key.as_ref());
+        }
+    }
+}
+
 fn ui_for_reflect_map(
     type_registry: &TypeRegistry,
     map: &mut dyn Map,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
+    // Keys are cloned up front, since editing a key requires re-inserting the entry.
+    let keys: Vec<Box<dyn Reflect>> = map.iter().map(|(key, _)| key.clone_value()).collect();
+    let mut edit = None;
+
     egui::Grid::new(id).show(ui, |ui| {
-        for (i, (key, value)) in map.iter().enumerate() {
-            // FIXME: get change tracking back
-            let mut key = key.clone_value();
-            let mut value = value.clone_value();
+        for (i, original_key) in keys.iter().enumerate() {
+            let mut key = original_key.clone_value();
+            ui_for_reflect_with(
+                type_registry,
+                key.as_mut(),
+                ui,
+                id.with((i, "key")),
+                options,
+            );
+
+            if let Some(value) = map.get_mut(original_key.as_ref()) {
+                ui_for_reflect_with(type_registry, value, ui, id.with((i, "value")), options);
+            }
+
+            if !options.read_only && ui.small_button("×").on_hover_text("Remove entry").clicked() {
+                edit = Some(MapEdit::Remove(original_key.clone_value()));
+            }
+
+            let key_changed = !key
+                .reflect_partial_eq(original_key.as_ref())
+                .unwrap_or(true);
+
+            if key_changed {
+                if map.get(key.as_ref()).is_some() {
+                    // Renaming would overwrite another entry, so leave the map unchanged.
+                    ui.colored_label(ui.visuals().error_fg_color, "Duplicate key");
+                } else if let Some(value) = map.remove(original_key.as_ref()) {
+                    map.insert_boxed(key, value);
+                }
+            }
 
-            ui_for_reflect(type_registry, key.as_mut(), ui, id.with(i));
-            ui_for_reflect(type_registry, value.as_mut(), ui, id.with(i));
             ui.end_row();
         }
     });
+
+    // Only the "add entry" row is skipped for maps without a default entry,
+    // other edits are still applied below.
+    let key_taken = match options.read_only {
+        true => None,
+        false => {
+            default_map_entry(type_registry, map).map(|(key, _)| map.get(key.as_ref()).is_some())
+        }
+    };
+
+    if let Some(key_taken) = key_taken {
+        ui.vertical_centered_justified(|ui| {
+            let add = ui
+                .add_enabled(!key_taken, egui::Button::new("+"))
+                .on_disabled_hover_text("An entry with the default key already exists");
+            if add.clicked() {
+                edit = Some(MapEdit::Insert);
+            }
+        });
+    }
+
+    // Entries are borrowed while drawn, so the edit is applied after the whole map.
+    if let Some(edit) = edit {
+        apply_map_edit(type_registry, map, edit);
+    }
 }
 
 fn ui_for_enum(
@@ -288,6 +797,7 @@ fn ui_for_enum(
     value: &mut dyn Enum,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
     let Some(type_info) = value.get_represented_type_info() else {
         ui.label("Unrepresentable");
@@ -302,8 +812,11 @@ fn ui_for_enum(
     let mut changed = false;
 
     ui.vertical(|ui| {
-        let changed_variant =
-            ui_for_enum_variant_select(type_registry, id, ui, value.variant_index(), type_info);
+        let changed_variant = ui
+            .add_enabled_ui(!options.read_only, |ui| {
+                ui_for_enum_variant_select(type_registry, id, ui, value.variant_index(), type_info)
+            })
+            .inner;
         if let Some((_new_variant, dynamic_enum)) = changed_variant {
             changed = true;
             value.apply(&dynamic_enum);
@@ -323,7 +836,7 @@ fn ui_for_enum(
                 let field_value = value
                     .field_at_mut(i)
                     .expect("invalid reflect impl: field len");
-                ui_for_reflect(type_registry, field_value, ui, id.with(i));
+                ui_for_reflect_with(type_registry, field_value, ui, id.with(i), options);
                 ui.end_row();
             })
         });
@@ -375,6 +888,7 @@ fn ui_for_tuple_struct(
     value: &mut dyn TupleStruct,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
     (0..value.field_len()).for_each(|i| {
         ui.horizontal(|ui| {
@@ -382,7 +896,7 @@ fn ui_for_tuple_struct(
                 ui.label(format!("{i}:"));
             }
             let field = value.field_mut(i).unwrap();
-            ui_for_reflect(type_registry, field, ui, id.with(i));
+            ui_for_reflect_with(type_registry, field, ui, id.with(i), options);
         });
     })
 }
@@ -392,6 +906,7 @@ fn ui_for_tuple(
     value: &mut dyn Tuple,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
     maybe_grid(value.field_len(), ui, id, |ui, label| {
         (0..value.field_len()).for_each(|i| {
@@ -399,7 +914,7 @@ fn ui_for_tuple(
                 ui.label(i.to_string());
             }
             let field = value.field_mut(i).unwrap();
-            let changed = ui_for_reflect(type_registry, field, ui, id.with(i));
+            let changed = ui_for_reflect_with(type_registry, field, ui, id.with(i), options);
             ui.end_row();
             changed
         });
@@ -411,6 +926,7 @@ fn ui_for_struct(
     value: &mut dyn Struct,
     ui: &mut egui::Ui,
     id: egui::Id,
+    options: DrawOptions,
 ) {
     for i in 0..value.field_len() {
         CollapsingHeader::new(value.name_at(i).unwrap())
@@ -418,7 +934,7 @@ fn ui_for_struct(
             .id_source(i)
             .show(ui, |ui| {
                 let field = value.field_at_mut(i).unwrap();
-                ui_for_reflect(&type_registry, field, ui, id.with(i));
+                ui_for_reflect_with(&type_registry, field, ui, id.with(i), options);
             });
     }
 }
@@ -430,6 +946,16 @@ fn add<T: 'static>(type_registry: &mut TypeRegistry, fn_mut: GuiElementMutFn) {
         .insert(GuiElement { fn_mut });
 }
 
+fn add_number<T: egui::emath::Numeric>(type_registry: &mut TypeRegistry) {
+    add::<T>(type_registry, primitives::number_ui::<T>);
+    type_registry
+        .get_mut(TypeId::of::<T>())
+        .unwrap_or_else(|| panic!("Type {:?} not registered", type_name::<T>()))
+        .insert(RangedGuiElement {
+            fn_mut: primitives::ranged_number_ui::<T>,
+        });
+}
+
 fn initialize(mut reflection: ResMut<Reflection>) {
     let tr = &mut reflection.type_registry;
 
@@ -437,26 +963,70 @@ fn initialize(mut reflection: ResMut<Reflection>) {
     add::<Cow<str>>(tr, primitives::cow_str_ui);
 
     add::<bool>(tr, primitives::bool_ui);
-    add::<f32>(tr, primitives::number_ui::<f32>);
-    add::<f64>(tr, primitives::number_ui::<f64>);
-    add::<i8>(tr, primitives::number_ui::<i8>);
-    add::<i16>(tr, primitives::number_ui::<i16>);
-    add::<i32>(tr, primitives::number_ui::<i32>);
-    add::<i64>(tr, primitives::number_ui::<i64>);
-    add::<isize>(tr, primitives::number_ui::<isize>);
-    add::<u8>(tr, primitives::number_ui::<u8>);
-    add::<u16>(tr, primitives::number_ui::<u16>);
-    add::<u32>(tr, primitives::number_ui::<u32>);
-    add::<u64>(tr, primitives::number_ui::<u64>);
-    add::<usize>(tr, primitives::number_ui::<usize>);
+    add::<char>(tr, primitives::char_ui);
+    add_number::<f32>(tr);
+    add_number::<f64>(tr);
+    add_number::<i8>(tr);
+    add_number::<i16>(tr);
+    add_number::<i32>(tr);
+    add_number::<i64>(tr);
+    add_number::<isize>(tr);
+    add_number::<u8>(tr);
+    add_number::<u16>(tr);
+    add_number::<u32>(tr);
+    add_number::<u64>(tr);
+    add_number::<usize>(tr);
     add::<[[f32; 2]; 4]>(tr, primitives::quad_ui);
     add::<[f32; 2]>(tr, primitives::num_vector_ui::<f32, 2>);
     add::<[f32; 3]>(tr, primitives::num_vector_ui::<f32, 3>);
     add::<[u32; 2]>(tr, primitives::num_vector_ui::<u32, 2>);
     add::<[u32; 3]>(tr, primitives::num_vector_ui::<u32, 3>);
+    add::<Rgba<f32>>(tr, primitives::rgba_f32_ui);
+    add::<Rgba<u8>>(tr, primitives::rgba_u8_ui);
+    add::<Isometry>(tr, primitives::isometry_ui);
+    add::<Vector>(tr, primitives::vector_ui);
+
+    // Draw quads are calculated from transforms on every frame.
+    tr.register_type_data::<DrawQuad, ReflectReadOnly>();
+}
+
+/// Settings of the inspector plugin, see `plugin_with_settings`.
+#[derive(Debug, Default, Clone)]
+pub struct InspectorSettings {
+    ranges: Vec<(TypeId, &'static str, InspectorRange)>,
+}
+
+impl InspectorSettings {
+    /// Draws the numeric fields of `T` with a slider within `range`.
+    ///
+    /// `T` must be registered before the inspector plugin is added.
+    pub fn with_range<T: 'static>(mut self, range: InspectorRange) -> Self {
+        self.ranges
+            .push((TypeId::of::<T>(), type_name::<T>(), range));
+        self
+    }
 }
 
 pub fn plugin(realm: &mut Realm) {
+    realm.add_plugin(plugin_with_settings(InspectorSettings::default()));
+}
+
+pub fn plugin_with_settings(settings: InspectorSettings) -> impl Plugin {
+    move |realm: &mut Realm| {
+        register_types(realm);
+        realm.run_system(move |mut reflection: ResMut<Reflection>| {
+            for (type_id, type_name, range) in &settings.ranges {
+                reflection
+                    .type_registry
+                    .get_mut(*type_id)
+                    .unwrap_or_else(|| panic!("Type {type_name:?} not registered"))
+                    .insert(*range);
+            }
+        });
+    }
+}
+
+fn register_types(realm: &mut Realm) {
     realm
         .add_plugin(yapgeir_reflection::plugin)
         .register_type::<PathBuf>()
@@ -472,5 +1042,369 @@ pub fn plugin(realm: &mut Realm) {
         .register_type::<[f32; 3]>()
         .register_type::<[u32; 2]>()
         .register_type::<[u32; 3]>()
+        .register_type::<Rgba<f32>>()
+        .register_type::<Rgba<u8>>()
+        .register_type::<Isometry>()
+        .register_type::<Vector>()
+        .register_type::<DrawQuad>()
         .run_system(initialize);
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, collections::HashMap};
+
+    use super::*;
+
+    thread_local! {
+        static SEEN: RefCell<Vec<u32>> = RefCell::new(Vec::new());
+        static ENABLED: RefCell<Vec<(u32, bool)>> = RefCell::new(Vec::new());
+    }
+
+    fn record_u32_ui(value: &mut dyn Any, _: &mut egui::Ui, _: egui::Id) {
+        let value = value.downcast_mut::<u32>().unwrap();
+        SEEN.with(|seen| seen.borrow_mut().push(*value));
+    }
+
+    fn record_enabled_u32_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
+        let value = value.downcast_mut::<u32>().unwrap();
+        ENABLED.with(|enabled| enabled.borrow_mut().push((*value, ui.is_enabled())));
+    }
+
+    #[derive(Default, Reflect)]
+    struct Stats {
+        health: u32,
+        computed: Computed,
+    }
+
+    #[derive(Default, Reflect)]
+    #[reflect(ReadOnly)]
+    struct Computed {
+        total: u32,
+    }
+
+    fn enabled_widgets(read_only: bool) -> Vec<(u32, bool)> {
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<Stats>();
+        type_registry.register::<Computed>();
+        type_registry.register::<u32>();
+        add::<u32>(&mut type_registry, record_enabled_u32_ui);
+
+        let mut stats = Stats {
+            health: 1,
+            computed: Computed { total: 2 },
+        };
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let id = egui::Id::new("stats");
+                let options = DrawOptions::read_only(read_only);
+                ui_for_reflect_with(&type_registry, &mut stats, ui, id, options);
+            });
+        });
+
+        ENABLED.with(|enabled| enabled.take())
+    }
+
+    fn edited(mut list: Vec<u32>, edits: &[ListEdit]) -> Vec<u32> {
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<u32>();
+
+        for edit in edits {
+            apply_list_edit(&type_registry, &mut list, *edit);
+        }
+
+        list
+    }
+
+    #[test]
+    fn test_list_edits() {
+        use ListEdit::*;
+
+        let list = edited(vec![1, 2, 3], &[Remove(1), MoveDown(0), Push]);
+        assert_eq!(list, vec![3, 1, 0]);
+
+        // Moves past the ends and removals out of bounds are ignored.
+        let list = edited(list, &[MoveUp(2), MoveUp(0), MoveDown(2), Remove(3)]);
+        assert_eq!(list, vec![3, 0, 1]);
+
+        let list = edited(list, &[Remove(0), Remove(0), Remove(0)]);
+        assert!(list.is_empty());
+
+        // An empty list still gets a default item.
+        let list = edited(list, &[Push, Push]);
+        assert_eq!(list, vec![0, 0]);
+        assert_eq!(list.len(), 2);
+    }
+
+    #[test]
+    fn test_map_insert_and_remove() {
+        let type_registry = TypeRegistry::new();
+        let mut map = HashMap::from([("one".to_string(), 1i32)]);
+
+        apply_map_edit(&type_registry, &mut map, MapEdit::Insert);
+        assert_eq!(map, HashMap::from([("one".into(), 1), (String::new(), 0)]));
+
+        // The default key is already taken, so nothing is inserted.
+        apply_map_edit(&type_registry, &mut map, MapEdit::Insert);
+        assert_eq!(map.len(), 2);
+
+        let one = Box::new("one".to_string());
+        apply_map_edit(&type_registry, &mut map, MapEdit::Remove(one));
+        assert_eq!(map, HashMap::from([(String::new(), 0)]));
+    }
+
+    /// Drives `ui_for_reflect` for a value with injected input, like a user would.
+    struct UiDriver {
+        ctx: egui::Context,
+        type_registry: TypeRegistry,
+    }
+
+    impl UiDriver {
+        fn new() -> Self {
+            let mut type_registry = TypeRegistry::new();
+            type_registry.register::<String>();
+            type_registry.register::<i32>();
+            type_registry.register::<u32>();
+            add::<String>(&mut type_registry, primitives::string_ui);
+            add_number::<i32>(&mut type_registry);
+            add_number::<u32>(&mut type_registry);
+
+            Self {
+                ctx: egui::Context::default(),
+                type_registry,
+            }
+        }
+
+        /// Runs a frame, returning all drawn texts with the centers of their rects.
+        fn run(
+            &self,
+            value: &mut dyn Reflect,
+            events: Vec<egui::Event>,
+        ) -> Vec<(String, egui::Pos2)> {
+            let input = egui::RawInput {
+                events,
+                ..Default::default()
+            };
+            let output = self.ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    ui_for_reflect(&self.type_registry, value, ui, egui::Id::new("value"));
+                });
+            });
+
+            output
+                .shapes
+                .iter()
+                .filter_map(|shape| match &shape.1 {
+                    egui::Shape::Text(text) => Some((
+                        text.galley.text().to_string(),
+                        text.pos + text.galley.rect.center().to_vec2(),
+                    )),
+                    _ => None,
+                })
+                .collect()
+        }
+
+        /// Returns the positions of the texts after the layout of grids has settled.
+        fn texts(&self, value: &mut dyn Reflect, text: &str) -> Vec<egui::Pos2> {
+            self.run(value, vec![]);
+            self.run(value, vec![])
+                .into_iter()
+                .filter(|(t, _)| t == text)
+                .map(|(_, pos)| pos)
+                .collect()
+        }
+
+        fn click(&self, value: &mut dyn Reflect, pos: egui::Pos2) {
+            let button = |pressed| egui::Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed,
+                modifiers: Default::default(),
+            };
+            self.run(value, vec![egui::Event::PointerMoved(pos), button(true)]);
+            self.run(value, vec![button(false)]);
+        }
+
+        /// Clicks the `nth` text from the top.
+        fn click_text(&self, value: &mut dyn Reflect, text: &str, nth: usize) {
+            let pos = self.texts(value, text)[nth];
+            self.click(value, pos);
+        }
+    }
+
+    #[test]
+    fn test_list_edits_through_ui() {
+        let ui = UiDriver::new();
+        let mut list = vec![1u32, 2, 3];
+
+        ui.click_text(&mut list, "×", 1);
+        assert_eq!(list, vec![1, 3]);
+
+        ui.click_text(&mut list, "+", 0);
+        assert_eq!(list, vec![1, 3, 0]);
+
+        ui.click_text(&mut list, "⬇", 0);
+        assert_eq!(list, vec![3, 1, 0]);
+
+        ui.click_text(&mut list, "⬆", 2);
+        assert_eq!(list, vec![3, 0, 1]);
+    }
+
+    #[test]
+    fn test_map_edits_through_ui() {
+        let ui = UiDriver::new();
+        let mut map = HashMap::from([("a".to_string(), 1i32), ("ab".to_string(), 2)]);
+
+        ui.click_text(&mut map, "+", 0);
+        assert_eq!(map.get(""), Some(&0));
+        assert_eq!(map.len(), 3);
+
+        // The default key is taken now, so the button is disabled.
+        ui.click_text(&mut map, "+", 0);
+        assert_eq!(map.len(), 3);
+
+        // Renaming "a" to "ab" would overwrite the other entry.
+        ui.click_text(&mut map, "a", 0);
+        let end = egui::Event::Key {
+            key: egui::Key::End,
+            pressed: true,
+            repeat: false,
+            modifiers: Default::default(),
+        };
+        let texts = ui.run(&mut map, vec![end, egui::Event::Text("b".into())]);
+        assert!(texts.iter().any(|(text, _)| text == "Duplicate key"));
+        assert_eq!(map.get("a"), Some(&1));
+        assert_eq!(map.get("ab"), Some(&2));
+
+        // The remove button in the row of "ab".
+        let row = ui.texts(&mut map, "ab")[0];
+        let remove = ui
+            .texts(&mut map, "×")
+            .into_iter()
+            .min_by(|a, b| (a.y - row.y).abs().total_cmp(&(b.y - row.y).abs()))
+            .unwrap();
+        ui.click(&mut map, remove);
+        assert_eq!(map, HashMap::from([("a".into(), 1), (String::new(), 0)]));
+    }
+
+    thread_local! {
+        static RANGES: RefCell<Vec<(u32, Option<f64>)>> = RefCell::new(Vec::new());
+    }
+
+    fn record_range_u32_ui(value: &mut dyn Any, _: &mut egui::Ui, _: egui::Id) {
+        let value = value.downcast_mut::<u32>().unwrap();
+        RANGES.with(|ranges| ranges.borrow_mut().push((*value, None)));
+    }
+
+    fn record_ranged_u32_ui(
+        value: &mut dyn Any,
+        _: &mut egui::Ui,
+        _: egui::Id,
+        range: &InspectorRange,
+    ) {
+        let value = value.downcast_mut::<u32>().unwrap();
+        RANGES.with(|ranges| ranges.borrow_mut().push((*value, Some(range.max))));
+    }
+
+    #[derive(Default, Reflect)]
+    struct Level {
+        current: u32,
+        stats: Stats,
+    }
+
+    #[test]
+    fn test_range_applies_to_own_fields() {
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<Level>();
+        type_registry.register::<Stats>();
+        type_registry.register::<Computed>();
+        type_registry.register::<u32>();
+        add::<u32>(&mut type_registry, record_range_u32_ui);
+        type_registry
+            .get_mut(TypeId::of::<u32>())
+            .unwrap()
+            .insert(RangedGuiElement {
+                fn_mut: record_ranged_u32_ui,
+            });
+        type_registry
+            .get_mut(TypeId::of::<Level>())
+            .unwrap()
+            .insert(InspectorRange::new(0., 10.));
+
+        let mut level = Level {
+            current: 1,
+            stats: Stats {
+                health: 2,
+                computed: Computed { total: 3 },
+            },
+        };
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui_for_reflect(&type_registry, &mut level, ui, egui::Id::new("level"));
+            });
+        });
+
+        // Fields of nested values are drawn without the range.
+        let ranges = RANGES.with(|ranges| ranges.take());
+        assert_eq!(ranges, vec![(1, Some(10.)), (2, None), (3, None)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid inspector range")]
+    fn test_inverted_range() {
+        InspectorRange::new(100., 0.);
+    }
+
+    #[test]
+    fn test_read_only_field_is_disabled() {
+        assert_eq!(enabled_widgets(false), vec![(1, true), (2, false)]);
+        assert_eq!(enabled_widgets(true), vec![(1, false), (2, false)]);
+    }
+
+    #[test]
+    fn test_map_shows_values() {
+        let mut type_registry = TypeRegistry::new();
+        type_registry.register::<String>();
+        type_registry.register::<u32>();
+        add::<String>(&mut type_registry, primitives::string_ui);
+        add::<u32>(&mut type_registry, record_u32_ui);
+
+        let mut map = HashMap::from([("one".to_string(), 1u32), ("two".to_string(), 2u32)]);
+
+        let ctx = egui::Context::default();
+        let _ = ctx.run(Default::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                ui_for_reflect(&type_registry, &mut map, ui, egui::Id::new("map"));
+            });
+        });
+
+        let mut seen = SEEN.with(|seen| seen.take());
+        seen.sort();
+        assert_eq!(seen, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_entity_filter_is_case_insensitive() {
+        let mut world = World::new();
+        let player = world.spawn((Named::new("Player"),));
+        let enemy = world.spawn((Named::new("Enemy"),));
+        let unnamed = world.spawn(());
+
+        let matches = |filter: &str| -> Vec<Entity> {
+            world
+                .iter()
+                .filter(|e| entity_matches(*e, &filter.to_lowercase()))
+                .map(|e| e.entity())
+                .collect()
+        };
+
+        assert_eq!(matches("PLAY"), vec![player]);
+        assert_eq!(matches("").len(), 3);
+        assert_eq!(matches(&format!("{:?}", unnamed)), vec![unnamed]);
+        assert_eq!(matches("enemy"), vec![enemy]);
+    }
+}
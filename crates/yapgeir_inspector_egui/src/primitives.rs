@@ -1,6 +1,11 @@
 use std::{any::Any, borrow::Cow};
 
-use egui::{emath::Numeric, DragValue};
+use egui::{color_picker::Alpha, emath::Numeric, Color32, DragValue, Slider};
+use nalgebra::{Isometry2, UnitComplex, Vector2};
+use yapgeir_geometry::Rgba;
+use yapgeir_world_2d::{Isometry, Vector};
+
+use crate::InspectorRange;
 
 pub fn num_row_ui<T: Numeric, const N: usize>(value: &mut [T; N], ui: &mut egui::Ui, _: egui::Id) {
     for i in 0..N {
@@ -32,11 +37,95 @@ pub fn num_vector_ui<T: Numeric, const N: usize>(
     ui.horizontal(|ui| num_row_ui(value, ui, id));
 }
 
+pub fn rgba_f32_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
+    let value = value.downcast_mut::<Rgba<f32>>().unwrap();
+    let mut color = egui::Rgba::from_rgba_unmultiplied(value.r, value.g, value.b, value.a);
+
+    // Only write back on change, since premultiplication is lossy for low alpha values.
+    if egui::color_picker::color_edit_button_rgba(ui, &mut color, Alpha::OnlyBlend).changed() {
+        *value = color.to_rgba_unmultiplied().into();
+    }
+}
+
+pub fn rgba_u8_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
+    let value = value.downcast_mut::<Rgba<u8>>().unwrap();
+    let mut color = Color32::from_rgba_unmultiplied(value.r, value.g, value.b, value.a);
+
+    if egui::color_picker::color_edit_button_srgba(ui, &mut color, Alpha::OnlyBlend).changed() {
+        *value = color.to_srgba_unmultiplied().into();
+    }
+}
+
+/// Editable representation of an isometry: translation and rotation angle in degrees.
+fn isometry_fields(isometry: &Isometry2<f32>) -> ([f32; 2], f32) {
+    let translation = isometry.translation.vector;
+    (
+        [translation.x, translation.y],
+        isometry.rotation.angle().to_degrees(),
+    )
+}
+
+fn apply_isometry_fields(isometry: &mut Isometry2<f32>, (translation, angle): ([f32; 2], f32)) {
+    isometry.translation.vector = Vector2::from(translation);
+    isometry.rotation = UnitComplex::new(angle.to_radians());
+}
+
+pub fn isometry_ui(value: &mut dyn Any, ui: &mut egui::Ui, id: egui::Id) {
+    let value = value.downcast_mut::<Isometry>().unwrap();
+    let (mut translation, mut angle) = isometry_fields(value);
+
+    let changed = egui::Grid::new(id)
+        .show(ui, |ui| {
+            ui.label("translation");
+            let mut changed = false;
+            for v in &mut translation {
+                changed |= ui.add(DragValue::new(v).speed(0.1)).changed();
+            }
+            ui.end_row();
+
+            ui.label("angle");
+            changed |= ui
+                .add(DragValue::new(&mut angle).speed(1.).suffix("°"))
+                .changed();
+            ui.end_row();
+
+            changed
+        })
+        .inner;
+
+    // Only write back on change, to avoid drifting the rotation by converting it back and forth.
+    if changed {
+        apply_isometry_fields(value, (translation, angle));
+    }
+}
+
+pub fn vector_ui(value: &mut dyn Any, ui: &mut egui::Ui, id: egui::Id) {
+    let value = value.downcast_mut::<Vector>().unwrap();
+    let mut fields: [f32; 2] = value.0.into();
+
+    ui.horizontal(|ui| num_row_ui(&mut fields, ui, id));
+    *value = Vector(Vector2::from(fields));
+}
+
 pub fn bool_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
     let value = value.downcast_mut::<bool>().unwrap();
     ui.checkbox(value, "");
 }
 
+pub fn char_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
+    let value = value.downcast_mut::<char>().unwrap();
+    let mut text = value.to_string();
+
+    let edit = egui::TextEdit::singleline(&mut text).desired_width(ui.spacing().interact_size.x);
+    // The last character wins, so typing after the current one replaces it,
+    // while clearing the field keeps the previous value.
+    if ui.add(edit).changed() {
+        if let Some(c) = text.chars().last() {
+            *value = c;
+        }
+    }
+}
+
 pub fn number_ui<T: egui::emath::Numeric>(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
     let value = value.downcast_mut::<T>().unwrap();
 
@@ -45,6 +134,22 @@ pub fn number_ui<T: egui::emath::Numeric>(value: &mut dyn Any, ui: &mut egui::Ui
     ui.add(widget);
 }
 
+pub fn ranged_number_ui<T: Numeric>(
+    value: &mut dyn Any,
+    ui: &mut egui::Ui,
+    _: egui::Id,
+    range: &InspectorRange,
+) {
+    let value = value.downcast_mut::<T>().unwrap();
+
+    // The slider clamps the values it sets, so values set elsewhere are only clamped when edited.
+    let mut slider = Slider::new(value, T::from_f64(range.min)..=T::from_f64(range.max));
+    if let Some(step) = range.step {
+        slider = slider.step_by(step);
+    }
+    ui.add(slider);
+}
+
 pub fn string_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
     let value = value.downcast_mut::<String>().unwrap();
 
@@ -69,3 +174,148 @@ pub fn cow_str_ui(value: &mut dyn Any, ui: &mut egui::Ui, _: egui::Id) {
         *value = Cow::Owned(clone);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use egui::{Event, PointerButton, Pos2, RawInput, Rect};
+
+    use super::*;
+
+    /// Runs a frame with the bool widget, returning its rect.
+    fn run_bool_ui(ctx: &egui::Context, value: &mut bool, events: Vec<Event>) -> Rect {
+        let mut rect = Rect::NOTHING;
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                rect = ui.scope(|ui| bool_ui(value, ui, ui.id())).response.rect;
+            });
+        });
+
+        rect
+    }
+
+    fn click(pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_bool_toggle() {
+        let ctx = egui::Context::default();
+        let mut value = false;
+
+        let pos = run_bool_ui(&ctx, &mut value, vec![]).center();
+        run_bool_ui(
+            &ctx,
+            &mut value,
+            vec![Event::PointerMoved(pos), click(pos, true)],
+        );
+        run_bool_ui(&ctx, &mut value, vec![click(pos, false)]);
+        assert!(value);
+
+        run_bool_ui(&ctx, &mut value, vec![click(pos, true)]);
+        run_bool_ui(&ctx, &mut value, vec![click(pos, false)]);
+        assert!(!value);
+    }
+
+    /// Runs a frame with a ranged slider, returning its rect.
+    fn run_ranged_ui(
+        ctx: &egui::Context,
+        value: &mut f32,
+        range: &InspectorRange,
+        events: Vec<Event>,
+    ) -> Rect {
+        let mut rect = Rect::NOTHING;
+        let input = RawInput {
+            events,
+            ..Default::default()
+        };
+
+        let _ = ctx.run(input, |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                rect = ui
+                    .scope(|ui| ranged_number_ui::<f32>(value, ui, ui.id(), range))
+                    .response
+                    .rect;
+            });
+        });
+
+        rect
+    }
+
+    #[test]
+    fn test_ranged_number_clamps_edits() {
+        let ctx = egui::Context::default();
+        let range = InspectorRange::new(0., 100.).with_step(1.);
+
+        // Values set elsewhere are kept while they are not edited.
+        let mut value = 150.;
+        let rect = run_ranged_ui(&ctx, &mut value, &range, vec![]);
+        assert_eq!(value, 150.);
+
+        // Dragging the slider past its end sets the maximum.
+        let start = rect.left_center() + egui::vec2(4., 0.);
+        let end = start + egui::vec2(1000., 0.);
+        run_ranged_ui(
+            &ctx,
+            &mut value,
+            &range,
+            vec![Event::PointerMoved(start), click(start, true)],
+        );
+        run_ranged_ui(&ctx, &mut value, &range, vec![Event::PointerMoved(end)]);
+        run_ranged_ui(&ctx, &mut value, &range, vec![click(end, false)]);
+        assert_eq!(value, 100.);
+    }
+
+    #[test]
+    fn test_vector_drag_edits_one_component() {
+        let ctx = egui::Context::default();
+        let mut value = Vector(Vector2::new(1., 2.));
+        let mut run = |events| {
+            let mut rect = Rect::NOTHING;
+            let input = RawInput {
+                events,
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default().show(ctx, |ui| {
+                    rect = ui
+                        .scope(|ui| vector_ui(&mut value, ui, ui.id()))
+                        .response
+                        .rect;
+                });
+            });
+            rect
+        };
+
+        // Dragging the first field to the right increases x.
+        let start = run(vec![]).left_center() + egui::vec2(4., 0.);
+        let end = start + egui::vec2(50., 0.);
+        run(vec![Event::PointerMoved(start), click(start, true)]);
+        run(vec![Event::PointerMoved(end)]);
+        run(vec![click(end, false)]);
+
+        assert!(value.x > 1., "{value:?}");
+        assert_eq!(value.y, 2.);
+    }
+
+    #[test]
+    fn test_translation_edit_updates_isometry() {
+        let mut isometry = Isometry2::new(Vector2::new(1., 2.), 90f32.to_radians());
+
+        let (mut translation, angle) = isometry_fields(&isometry);
+        translation[0] += 10.;
+        apply_isometry_fields(&mut isometry, (translation, angle));
+
+        assert_eq!(isometry.translation.vector, Vector2::new(11., 2.));
+        assert!((isometry.rotation.angle() - 90f32.to_radians()).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,34 @@
+use hecs::World;
+use yapgeir_core::Named;
+use yapgeir_inspector_egui::{insertable_components, ComponentCommand};
+use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_reflection::{RealmExtensions, Reflection};
+
+#[test]
+fn test_insert_and_remove_component() {
+    let mut realm = Realm::default();
+    realm
+        .add_plugin(yapgeir_reflection::plugin)
+        .register_type::<Named>()
+        .initialize_resource::<World>()
+        .run_system(|reflection: Res<Reflection>, mut world: ResMut<World>| {
+            let entity = world.spawn(());
+
+            let insertable = insertable_components(&reflection, world.entity(entity).unwrap());
+            assert!(insertable.contains(&std::any::TypeId::of::<Named>()));
+
+            ComponentCommand::Insert(std::any::TypeId::of::<Named>()).apply(
+                &reflection,
+                &mut world,
+                entity,
+            );
+            assert!(world.get::<&Named>(entity).is_ok());
+
+            ComponentCommand::Remove(std::any::TypeId::of::<Named>()).apply(
+                &reflection,
+                &mut world,
+                entity,
+            );
+            assert!(world.get::<&Named>(entity).is_err());
+        });
+}
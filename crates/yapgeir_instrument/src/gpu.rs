@@ -0,0 +1,125 @@
+use by_address::ByAddress;
+use indexmap::IndexMap;
+use std::time::Duration;
+use yapgeir_graphics_hal::{query::TimerQuery, Graphics};
+use yapgeir_realm::{Realm, Res};
+
+/// GPU results become available a few frames later, so each pass
+/// keeps a small ring of queries in flight.
+const QUERIES_PER_PASS: usize = 4;
+
+#[derive(Default, Debug)]
+pub struct GpuValues {
+    pub samples: u64,
+    pub last: Duration,
+    pub total: Duration,
+}
+
+pub struct GpuPass<G: Graphics> {
+    queries: Vec<G::TimerQuery>,
+    next: usize,
+    pub values: GpuValues,
+}
+
+impl<G: Graphics> GpuPass<G> {
+    fn poll(&mut self) {
+        for query in &self.queries {
+            if let Some(duration) = query.poll() {
+                self.values.samples += 1;
+                self.values.last = duration;
+                self.values.total += duration;
+            }
+        }
+    }
+}
+
+/// A resource that measures GPU time of render passes with timer queries.
+///
+/// If timer queries are not supported by the graphics implementation,
+/// no samples are ever recorded.
+pub struct GpuInstrumentation<G: Graphics> {
+    ctx: G,
+    pub data: IndexMap<ByAddress<&'static str>, GpuPass<G>>,
+}
+
+pub struct GpuInstrumentationGuard<'a, G: Graphics> {
+    query: &'a G::TimerQuery,
+}
+
+impl<'a, G: Graphics> Drop for GpuInstrumentationGuard<'a, G> {
+    fn drop(&mut self) {
+        self.query.end();
+    }
+}
+
+impl<G: Graphics> GpuInstrumentation<G> {
+    pub fn new(ctx: G) -> Self {
+        Self {
+            ctx,
+            data: Default::default(),
+        }
+    }
+
+    /// Measure GPU time of the draw calls issued while the guard is alive.
+    ///
+    /// Guards can't be nested, since only one timer query can be active at a time.
+    pub fn guard<'a>(&'a mut self, pass: &'static str) -> GpuInstrumentationGuard<'a, G> {
+        let ctx = &self.ctx;
+        let pass = self.data.entry(ByAddress(pass)).or_insert_with(|| GpuPass {
+            queries: (0..QUERIES_PER_PASS)
+                .map(|_| ctx.new_timer_query())
+                .collect(),
+            next: 0,
+            values: Default::default(),
+        });
+
+        pass.poll();
+
+        let query = &pass.queries[pass.next];
+        pass.next = (pass.next + 1) % pass.queries.len();
+
+        query.begin();
+        GpuInstrumentationGuard { query }
+    }
+}
+
+pub fn plugin<G: Graphics>(realm: &mut Realm) {
+    realm.initialize_resource_with(|ctx: Res<G>| GpuInstrumentation::new(ctx.clone()));
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+
+    #[test]
+    fn test_record_pass() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        let mut instrumentation = GpuInstrumentation::new(graphics);
+        let pass = "render";
+
+        drop(instrumentation.guard(pass));
+        assert_eq!(instrumentation.data[&ByAddress(pass)].values.samples, 0);
+
+        // Results of earlier frames are collected by the next guard of the pass.
+        drop(instrumentation.guard(pass));
+        drop(instrumentation.guard(pass));
+        let values = &instrumentation.data[&ByAddress(pass)].values;
+        assert_eq!(values.samples, 2);
+        assert!(values.total >= values.last);
+    }
+
+    #[test]
+    fn test_unsupported_timer_queries() {
+        let mut backend = NullBackend::new((4, 4));
+        backend.timer_queries = false;
+        let mut instrumentation = GpuInstrumentation::new(Null::new(backend));
+        let pass = "render";
+
+        for _ in 0..10 {
+            drop(instrumentation.guard(pass));
+        }
+        assert_eq!(instrumentation.data[&ByAddress(pass)].values.samples, 0);
+    }
+}
@@ -1,6 +1,10 @@
 use by_address::ByAddress;
 use indexmap::IndexMap;
-use std::time::{Duration, Instant};
+use serde_json::{json, Value};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 use yapgeir_core::Frame;
 use yapgeir_realm::{Realm, Res, ResMut};
 
@@ -9,6 +13,12 @@ pub use yapgeir_instrument_macro::instrument;
 #[cfg(feature = "allocations")]
 mod allocator;
 
+#[cfg(feature = "gpu")]
+pub mod gpu;
+
+#[cfg(feature = "egui")]
+pub mod overlay;
+
 #[derive(Default, Debug)]
 pub struct Values {
     pub invocations: u64,
@@ -16,23 +26,89 @@ pub struct Values {
     pub duration: Duration,
 }
 
+/// Number of frames for which per-frame allocation counts are kept.
+const ALLOCATION_WINDOW: usize = 120;
+
 #[derive(Default, Debug)]
 pub struct System {
     last_frame: u64,
     pub total: Values,
     pub current_frame: Values,
+    /// Allocation counts of the last frames the system ran in, excluding the current one.
+    allocation_history: VecDeque<u64>,
+    peak_allocations: u64,
+}
+
+impl System {
+    fn record(&mut self, frame: u64, duration: Duration, allocations: u64) {
+        if frame != self.last_frame {
+            if self.current_frame.invocations > 0 {
+                if self.allocation_history.len() == ALLOCATION_WINDOW {
+                    self.allocation_history.pop_front();
+                }
+                self.allocation_history
+                    .push_back(self.current_frame.allocations);
+            }
+
+            self.last_frame = frame;
+            self.current_frame = Values::default();
+        }
+
+        self.current_frame.invocations += 1;
+        self.current_frame.duration += duration;
+        self.current_frame.allocations += allocations;
+
+        self.total.invocations += 1;
+        self.total.duration += duration;
+        self.total.allocations += allocations;
+
+        self.peak_allocations = self.peak_allocations.max(self.current_frame.allocations);
+    }
+
+    /// The largest number of allocations the system has made during a single frame.
+    pub fn peak_allocations(&self) -> u64 {
+        self.peak_allocations
+    }
+
+    /// Average number of allocations per frame over the last completed frames the system ran in.
+    pub fn recent_allocations(&self) -> f32 {
+        if self.allocation_history.is_empty() {
+            return 0.;
+        }
+
+        self.allocation_history.iter().sum::<u64>() as f32 / self.allocation_history.len() as f32
+    }
+}
+
+/// A single recorded system invocation.
+#[derive(Debug, Clone, Copy)]
+pub struct TraceEvent {
+    pub system: &'static str,
+    /// Start of the invocation, relative to the start of the capture.
+    pub start: Duration,
+    pub duration: Duration,
+}
+
+#[derive(Debug)]
+struct Capture {
+    start: Instant,
+    end_frame: u64,
+    events: Vec<TraceEvent>,
 }
 
 #[derive(Default, Debug)]
 pub struct Instrumentation {
     pub frame: u64,
     pub data: IndexMap<ByAddress<&'static str>, System>,
+    capture: Option<Capture>,
 }
 
 pub struct InstrumentationGuard<'a> {
     time: Instant,
     frame: u64,
+    name: &'static str,
     system: &'a mut System,
+    capture: Option<&'a mut Capture>,
     #[cfg(feature = "allocations")]
     allocations: allocator::Counter,
 }
@@ -41,38 +117,87 @@ impl<'a> Drop for InstrumentationGuard<'a> {
     fn drop(&mut self) {
         let duration = self.time.elapsed();
 
-        if self.frame != self.system.last_frame {
-            self.system.last_frame = self.frame;
-            self.system.current_frame = Values::default();
-        }
-
-        self.system.current_frame.invocations += 1;
-        self.system.current_frame.duration += duration;
+        #[cfg(feature = "allocations")]
+        let allocations = self.allocations.count();
+        #[cfg(not(feature = "allocations"))]
+        let allocations = 0;
 
-        self.system.total.invocations += 1;
-        self.system.total.duration += duration;
+        self.system.record(self.frame, duration, allocations);
 
-        #[cfg(feature = "allocations")]
-        {
-            let allocations = self.allocations.count();
-            self.system.current_frame.allocations += allocations;
-            self.system.total.allocations += allocations;
+        if let Some(capture) = &mut self.capture {
+            capture.events.push(TraceEvent {
+                system: self.name,
+                start: self.time.saturating_duration_since(capture.start),
+                duration,
+            });
         }
     }
 }
 
 impl Instrumentation {
-    pub fn guard<'a>(&'a mut self, system: &'static str) -> InstrumentationGuard<'a> {
-        let system = self.data.entry(ByAddress(system)).or_default();
+    pub fn guard<'a>(&'a mut self, name: &'static str) -> InstrumentationGuard<'a> {
+        let system = self.data.entry(ByAddress(name)).or_default();
+        let frame = self.frame;
+        let capture = self
+            .capture
+            .as_mut()
+            .filter(|capture| frame < capture.end_frame);
 
         InstrumentationGuard {
             time: Instant::now(),
-            frame: self.frame,
+            frame,
+            name,
             system,
+            capture,
             #[cfg(feature = "allocations")]
             allocations: allocator::CountingAllocator::counter(),
         }
     }
+
+    /// Start recording individual invocations of all systems for `frames` frames,
+    /// starting with the current one. Events of a previous capture are discarded.
+    pub fn start_capture(&mut self, frames: u64) {
+        self.capture = Some(Capture {
+            start: Instant::now(),
+            end_frame: self.frame + frames,
+            events: Vec::new(),
+        });
+    }
+
+    /// Returns true if invocations of the current frame are being recorded.
+    pub fn is_capturing(&self) -> bool {
+        self.capture
+            .as_ref()
+            .is_some_and(|capture| self.frame < capture.end_frame)
+    }
+
+    /// Invocations recorded by the last capture.
+    pub fn captured(&self) -> &[TraceEvent] {
+        self.capture
+            .as_ref()
+            .map_or(&[], |capture| capture.events.as_slice())
+    }
+
+    /// Serializes the recorded invocations in the Chrome Trace Event Format,
+    /// which can be loaded in `chrome://tracing` or Perfetto.
+    pub fn to_chrome_trace(&self) -> String {
+        let events: Vec<Value> = self
+            .captured()
+            .iter()
+            .map(|event| {
+                json!({
+                    "name": event.system,
+                    "ph": "X",
+                    "ts": event.start.as_secs_f64() * 1e6,
+                    "dur": event.duration.as_secs_f64() * 1e6,
+                    "pid": 1,
+                    "tid": 1,
+                })
+            })
+            .collect();
+
+        json!({ "traceEvents": events, "displayTimeUnit": "ms" }).to_string()
+    }
 }
 
 pub fn update(mut instrumentation: ResMut<Instrumentation>, frame: Res<Frame>) {
@@ -84,3 +209,57 @@ pub fn plugin(realm: &mut Realm) {
         .initialize_resource::<Instrumentation>()
         .add_system(update);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allocation_spikes() {
+        let mut system = System::default();
+        for frame in 0..200 {
+            let allocations = if frame % 50 == 0 { 100 } else { 1 };
+            system.record(frame, Duration::ZERO, allocations);
+        }
+
+        assert_eq!(system.peak_allocations(), 100);
+        // The window holds frames 79..199, two of which are spikes.
+        let average = (118. + 200.) / 120.;
+        assert!((system.recent_allocations() - average).abs() < 1e-4);
+
+        // Frames with multiple invocations are accounted as a whole.
+        system.record(200, Duration::ZERO, 40);
+        system.record(200, Duration::ZERO, 70);
+        assert_eq!(system.current_frame.allocations, 110);
+        assert_eq!(system.peak_allocations(), 110);
+        assert_eq!(system.total.allocations, 4 * 100 + 196 + 110);
+    }
+
+    #[test]
+    fn test_chrome_trace() {
+        let mut instrumentation = Instrumentation::default();
+        drop(instrumentation.guard("before_capture"));
+
+        instrumentation.start_capture(1);
+        assert!(instrumentation.is_capturing());
+        drop(instrumentation.guard("physics"));
+        drop(instrumentation.guard("physics"));
+        drop(instrumentation.guard("render \"main\""));
+
+        instrumentation.frame += 1;
+        assert!(!instrumentation.is_capturing());
+        drop(instrumentation.guard("physics"));
+
+        assert_eq!(instrumentation.captured().len(), 3);
+        let trace: Value = serde_json::from_str(&instrumentation.to_chrome_trace()).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        let names: Vec<_> = events.iter().map(|e| e["name"].as_str().unwrap()).collect();
+        assert_eq!(names, ["physics", "physics", "render \"main\""]);
+
+        for event in events {
+            assert_eq!(event["ph"], "X");
+            assert!(event["ts"].as_f64().unwrap() >= 0.);
+            assert!(event["dur"].as_f64().unwrap() >= 0.);
+        }
+    }
+}
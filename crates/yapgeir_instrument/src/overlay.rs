@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use egui::{Grid, Id, Ui};
+
+use crate::{Instrumentation, Values};
+
+/// A column of the instrumentation table the rows are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortBy {
+    Name,
+    FrameDuration,
+    TotalDuration,
+    Invocations,
+    #[cfg(feature = "allocations")]
+    Allocations,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Sort {
+    by: SortBy,
+    descending: bool,
+}
+
+impl Default for Sort {
+    fn default() -> Self {
+        Self {
+            by: SortBy::FrameDuration,
+            descending: true,
+        }
+    }
+}
+
+struct Row<'a> {
+    name: &'static str,
+    /// Values of the current frame, empty if the system did not run during it.
+    frame: Values,
+    total: &'a Values,
+}
+
+fn sorted_rows(instrumentation: &Instrumentation, sort: Sort) -> Vec<Row<'_>> {
+    let mut rows: Vec<_> = instrumentation
+        .data
+        .iter()
+        .map(|(name, system)| Row {
+            name: name.0,
+            frame: match system.last_frame == instrumentation.frame {
+                true => Values {
+                    invocations: system.current_frame.invocations,
+                    allocations: system.current_frame.allocations,
+                    duration: system.current_frame.duration,
+                },
+                false => Values::default(),
+            },
+            total: &system.total,
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        let ordering = match sort.by {
+            SortBy::Name => a.name.cmp(b.name),
+            SortBy::FrameDuration => a.frame.duration.cmp(&b.frame.duration),
+            SortBy::TotalDuration => a.total.duration.cmp(&b.total.duration),
+            SortBy::Invocations => a.total.invocations.cmp(&b.total.invocations),
+            #[cfg(feature = "allocations")]
+            SortBy::Allocations => a.frame.allocations.cmp(&b.frame.allocations),
+        };
+
+        match sort.descending {
+            true => ordering.reverse(),
+            false => ordering,
+        }
+    });
+
+    rows
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3}", duration.as_secs_f64() * 1000.)
+}
+
+fn header(ui: &mut Ui, sort: &mut Sort, by: SortBy, label: &str) {
+    let label = match (sort.by == by, sort.descending) {
+        (true, true) => format!("{label} ⏷"),
+        (true, false) => format!("{label} ⏶"),
+        (false, _) => label.to_owned(),
+    };
+
+    if ui.selectable_label(sort.by == by, label).clicked() {
+        match sort.by == by {
+            true => sort.descending = !sort.descending,
+            false => {
+                sort.by = by;
+                sort.descending = by != SortBy::Name;
+            }
+        }
+    }
+}
+
+/// Draws a table of all instrumented systems, sortable by clicking on the column headers.
+///
+/// Rows are sorted by the current frame duration in descending order by default,
+/// so the most expensive systems are on top. Durations are in milliseconds.
+pub fn instrumentation_table(instrumentation: &Instrumentation, ui: &mut Ui) {
+    let id = Id::new("yapgeir_instrumentation_table");
+    let mut sort = ui.data_mut(|d| *d.get_temp_mut_or_default::<Sort>(id));
+
+    Grid::new(id).striped(true).show(ui, |ui| {
+        header(ui, &mut sort, SortBy::Name, "System");
+        header(ui, &mut sort, SortBy::FrameDuration, "Frame");
+        header(ui, &mut sort, SortBy::TotalDuration, "Total");
+        header(ui, &mut sort, SortBy::Invocations, "Invocations");
+        #[cfg(feature = "allocations")]
+        header(ui, &mut sort, SortBy::Allocations, "Allocations");
+        ui.end_row();
+
+        for row in sorted_rows(instrumentation, sort) {
+            ui.label(row.name);
+            ui.label(millis(row.frame.duration));
+            ui.label(millis(row.total.duration));
+            ui.label(row.total.invocations.to_string());
+            #[cfg(feature = "allocations")]
+            ui.label(row.frame.allocations.to_string());
+            ui.end_row();
+        }
+    });
+
+    ui.data_mut(|d| d.insert_temp(id, sort));
+}
+
+#[cfg(test)]
+mod tests {
+    use egui::{CentralPanel, Context, RawInput};
+
+    use super::*;
+
+    fn populated() -> Instrumentation {
+        let mut instrumentation = Instrumentation::default();
+        for (name, frame) in [("physics", 0), ("render", 1), ("input", 1)] {
+            instrumentation.frame = frame;
+            drop(instrumentation.guard(name));
+        }
+
+        instrumentation
+    }
+
+    #[test]
+    fn test_rows() {
+        let instrumentation = populated();
+
+        let rows = sorted_rows(&instrumentation, Sort::default());
+        assert_eq!(rows.len(), 3);
+        // Physics did not run during the current frame.
+        assert_eq!(rows[2].name, "physics");
+        assert_eq!(rows[2].frame.invocations, 0);
+        assert_eq!(rows[2].total.invocations, 1);
+
+        let sort = Sort {
+            by: SortBy::Name,
+            descending: false,
+        };
+        let names: Vec<_> = sorted_rows(&instrumentation, sort)
+            .iter()
+            .map(|row| row.name)
+            .collect();
+        assert_eq!(names, ["input", "physics", "render"]);
+    }
+
+    #[test]
+    fn test_table_renders_all_rows() {
+        let instrumentation = populated();
+        let ctx = Context::default();
+
+        let run = || {
+            ctx.run(RawInput::default(), |ctx| {
+                CentralPanel::default().show(ctx, |ui| instrumentation_table(&instrumentation, ui));
+            })
+        };
+
+        // Grids are only measured during the first frame, and are visible since the second one.
+        run();
+        let output = run();
+
+        let texts = output
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape.1, egui::Shape::Text(_)))
+            .count();
+        // A header row and a row per system.
+        let columns = 4 + cfg!(feature = "allocations") as usize;
+        assert_eq!(texts, columns * 4);
+    }
+}
@@ -163,7 +163,6 @@ pub fn plugin(realm: &mut Realm) {
         .register_type::<Acceleration<X>>()
         .register_type::<Acceleration<Y>>();
 
-    realm
-        .add_system(update_axis::<X>)
-        .add_system(update_axis::<Y>);
+    realm.add_system(update_axis::<X>);
+    realm.add_system(update_axis::<Y>);
 }
@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+
+use hecs::Entity;
+use nalgebra::{Point2, Vector2};
+
+/// An axis-aligned bounding box in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub center: Vector2<f32>,
+    pub half_extents: Vector2<f32>,
+}
+
+impl Aabb {
+    pub fn min(&self) -> Vector2<f32> {
+        self.center - self.half_extents
+    }
+
+    pub fn max(&self) -> Vector2<f32> {
+        self.center + self.half_extents
+    }
+
+    /// Returns the minimum translation vector that moves `other` out of `self`,
+    /// or `None` if the boxes don't overlap.
+    ///
+    /// The vector is always parallel to one of the axes.
+    pub fn penetration(&self, other: &Aabb) -> Option<Vector2<f32>> {
+        let distance = other.center - self.center;
+        let overlap = self.half_extents + other.half_extents - distance.abs();
+        if overlap.x <= 0. || overlap.y <= 0. {
+            return None;
+        }
+
+        let sign = |v: f32| if v < 0. { -1. } else { 1. };
+        Some(if overlap.x < overlap.y {
+            Vector2::new(overlap.x * sign(distance.x), 0.)
+        } else {
+            Vector2::new(0., overlap.y * sign(distance.y))
+        })
+    }
+
+    pub fn contains(&self, point: Point2<f32>) -> bool {
+        let (min, max) = (self.min(), self.max());
+        (min.x..=max.x).contains(&point.x) && (min.y..=max.y).contains(&point.y)
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        let (min, max) = (self.min(), self.max());
+        let (other_min, other_max) = (other.min(), other.max());
+        min.x <= other_max.x && other_min.x <= max.x && min.y <= other_max.y && other_min.y <= max.y
+    }
+}
+
+/// Finds pairs of bounding boxes that may overlap, so that only these pairs
+/// need to be tested precisely.
+pub trait Broadphase {
+    /// Fills `pairs` with indices of candidate pairs in `aabbs`.
+    /// Every pair is reported once, with the lower index first.
+    fn candidate_pairs(
+        &mut self,
+        entities: &[Entity],
+        aabbs: &[Aabb],
+        pairs: &mut Vec<(usize, usize)>,
+    );
+}
+
+/// Reports every pair of bounding boxes as a candidate.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct BruteForce;
+
+impl Broadphase for BruteForce {
+    fn candidate_pairs(&mut self, _: &[Entity], aabbs: &[Aabb], pairs: &mut Vec<(usize, usize)>) {
+        pairs.clear();
+        for a in 0..aabbs.len() {
+            for b in a + 1..aabbs.len() {
+                pairs.push((a, b));
+            }
+        }
+    }
+}
+
+/// A uniform grid, bucketing entities by the cells their bounding boxes overlap.
+///
+/// Only the entities sharing a cell are reported as candidate pairs. The cell size
+/// should be close to the size of a typical collider.
+///
+/// The `simple` physics plugin adds it as a resource, and rebuilds it from
+/// the `AabbCollider`s on every frame, so it can also be used for spatial queries.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    entries: Vec<(Entity, Aabb)>,
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0., "cell size must be positive");
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    fn cell(&self, point: Vector2<f32>) -> (i32, i32) {
+        (
+            (point.x / self.cell_size).floor() as i32,
+            (point.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn cells(&self, aabb: &Aabb) -> impl Iterator<Item = (i32, i32)> {
+        let (min, max) = (self.cell(aabb.min()), self.cell(aabb.max()));
+        (min.0..=max.0).flat_map(move |x| (min.1..=max.1).map(move |y| (x, y)))
+    }
+
+    /// Remove all entities, keeping the allocations of the cells which were occupied.
+    ///
+    /// Cells left empty since the previous `clear` are dropped, so that the map doesn't
+    /// grow with every cell ever touched by a moving entity.
+    pub fn clear(&mut self) {
+        self.cells.retain(|_, cell| {
+            let occupied = !cell.is_empty();
+            cell.clear();
+            occupied
+        });
+        self.entries.clear();
+    }
+
+    pub fn insert(&mut self, entity: Entity, aabb: Aabb) {
+        let index = self.entries.len();
+        self.entries.push((entity, aabb));
+
+        for cell in self.cells(&aabb) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+
+    /// Returns every entity with a bounding box intersecting the region.
+    pub fn query_region(&self, region: Aabb) -> impl Iterator<Item = Entity> + '_ {
+        let mut indices: Vec<usize> = self
+            .cells(&region)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|&i| self.entries[i].1.intersects(&region))
+            .collect();
+
+        indices.sort_unstable();
+        indices.dedup();
+        indices.into_iter().map(|i| self.entries[i].0)
+    }
+
+    /// Returns every entity with a bounding box containing the point.
+    pub fn query_point(&self, point: Point2<f32>) -> impl Iterator<Item = Entity> + '_ {
+        self.cells
+            .get(&self.cell(point.coords))
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.entries[i])
+            .filter(move |(_, aabb)| aabb.contains(point))
+            .map(|(entity, _)| *entity)
+    }
+}
+
+impl Broadphase for SpatialHash {
+    fn candidate_pairs(
+        &mut self,
+        entities: &[Entity],
+        aabbs: &[Aabb],
+        pairs: &mut Vec<(usize, usize)>,
+    ) {
+        self.clear();
+        for (entity, aabb) in entities.iter().zip(aabbs) {
+            self.insert(*entity, *aabb);
+        }
+
+        pairs.clear();
+        for cell in self.cells.values() {
+            for (i, a) in cell.iter().enumerate() {
+                pairs.extend(cell[i + 1..].iter().map(|b| (*a, *b)));
+            }
+        }
+
+        pairs.sort_unstable();
+        pairs.dedup();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hecs::World;
+
+    use super::*;
+
+    fn aabb(x: f32, y: f32) -> Aabb {
+        Aabb {
+            center: Vector2::new(x, y),
+            half_extents: Vector2::new(4., 4.),
+        }
+    }
+
+    #[test]
+    fn test_spatial_hash_candidates() {
+        let mut world = World::new();
+        let entities: Vec<Entity> = (0..3).map(|_| world.spawn(())).collect();
+        let aabbs = [aabb(0., 0.), aabb(6., 0.), aabb(1000., 1000.)];
+
+        let mut hash = SpatialHash::new(16.);
+        let mut pairs = Vec::new();
+        hash.candidate_pairs(&entities, &aabbs, &mut pairs);
+
+        assert!(pairs.contains(&(0, 1)));
+        assert!(pairs.iter().all(|&(a, b)| a != 2 && b != 2));
+
+        let point: Vec<Entity> = hash.query_point(Point2::new(1000., 1000.)).collect();
+        assert_eq!(point, vec![entities[2]]);
+
+        let region: Vec<Entity> = hash.query_region(aabb(3., 0.)).collect();
+        assert_eq!(region, vec![entities[0], entities[1]]);
+    }
+
+    #[test]
+    fn test_spatial_hash_drops_empty_cells() {
+        let entity = World::new().spawn(());
+        let mut hash = SpatialHash::new(16.);
+        let mut pairs = Vec::new();
+
+        // A box overlapping up to four cells, moving across a thousand of them.
+        for x in 0..1000 {
+            let aabbs = [aabb(x as f32 * 16. + 8., 0.)];
+            hash.candidate_pairs(&[entity], &aabbs, &mut pairs);
+            assert!(hash.cells.len() <= 8, "{} cells", hash.cells.len());
+        }
+
+        let point: Vec<Entity> = hash.query_point(Point2::new(999. * 16. + 8., 0.)).collect();
+        assert_eq!(point, vec![entity]);
+    }
+}
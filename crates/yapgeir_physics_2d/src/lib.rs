@@ -1,3 +1,4 @@
 pub mod acceleration;
+pub mod broadphase;
 pub mod rapier;
 pub mod simple;
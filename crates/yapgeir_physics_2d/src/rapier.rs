@@ -99,7 +99,7 @@ fn update(mut rapier: ResMut<Rapier>, world: Res<World>, delta: Res<Delta>) {
         if rigid_body.user_data != 0 {
             let entity = Entity::from_bits(rigid_body.user_data as u64).unwrap();
             if let Ok(mut t) = world.get::<&mut Transform>(entity) {
-                t.isometry = rigid_body.position().clone();
+                *t.isometry = rigid_body.position().clone();
             }
         }
     }
@@ -1,25 +1,186 @@
-use derive_more::Constructor;
-use hecs::World;
+use derive_more::{Constructor, Deref};
+use hecs::{Entity, World};
 use nalgebra::Vector2;
 use yapgeir_core::Delta;
+use yapgeir_events::Events;
 use yapgeir_realm::{Realm, Res, ResMut};
 use yapgeir_world_2d::Transform;
 
+use crate::broadphase::{Aabb, Broadphase, SpatialHash};
+
 #[derive(Constructor, Default, Clone, Debug)]
 pub struct KinematicBody {
     pub velocity: Vector2<f32>,
-    pub force: Vector2<f32>,
+    pub acceleration: Vector2<f32>,
+    /// If true, `Gravity` is added to the acceleration of the body.
+    pub affected_by_gravity: bool,
 }
 
-fn update(mut world: ResMut<World>, delta: Res<Delta>) {
+/// Global acceleration applied to all kinematic bodies affected by gravity.
+#[derive(Default, Clone, Copy, Debug, Deref)]
+pub struct Gravity(pub Vector2<f32>);
+
+fn integrate(
+    body: &mut KinematicBody,
+    translation: &mut Vector2<f32>,
+    gravity: Vector2<f32>,
+    delta: f32,
+) {
+    let mut acceleration = body.acceleration;
+    if body.affected_by_gravity {
+        acceleration += gravity;
+    }
+
+    body.velocity += acceleration * delta;
+    *translation += body.velocity * delta;
+}
+
+fn update(mut world: ResMut<World>, delta: Res<Delta>, gravity: Option<Res<Gravity>>) {
     let delta = **delta;
+    let gravity = gravity.map(|g| **g).unwrap_or_default();
 
     for (_, (b, t)) in world.query_mut::<(&mut KinematicBody, &mut Transform)>() {
-        b.velocity += b.force * delta;
-        t.isometry.translation.vector += b.velocity * delta;
+        integrate(b, &mut t.isometry.translation.vector, gravity, delta);
+    }
+}
+
+/// An axis-aligned box collider centered at the `Transform` translation.
+/// Rotation and scale of the transform are ignored.
+#[derive(Constructor, Default, Clone, Copy, Debug)]
+pub struct AabbCollider {
+    pub half_extents: [f32; 2],
+    /// If true, overlapping solid colliders are pushed apart.
+    pub solid: bool,
+}
+
+/// An event emitted for every pair of overlapping colliders.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Collision {
+    pub a: Entity,
+    pub b: Entity,
+    /// The minimum translation that moves `b` out of `a`.
+    pub penetration: [f32; 2],
+}
+
+fn find_collisions(world: &World, broadphase: &mut impl Broadphase) -> Vec<Collision> {
+    let (entities, aabbs): (Vec<Entity>, Vec<Aabb>) = world
+        .query::<(&AabbCollider, &Transform)>()
+        .iter()
+        .map(|(e, (collider, transform))| {
+            let aabb = Aabb {
+                center: transform.isometry.translation.vector,
+                half_extents: collider.half_extents.into(),
+            };
+            (e, aabb)
+        })
+        .unzip();
+
+    let mut pairs = Vec::new();
+    broadphase.candidate_pairs(&entities, &aabbs, &mut pairs);
+
+    pairs
+        .into_iter()
+        .filter_map(|(a, b)| {
+            let penetration = aabbs[a].penetration(&aabbs[b])?;
+            Some(Collision {
+                a: entities[a],
+                b: entities[b],
+                penetration: penetration.into(),
+            })
+        })
+        .collect()
+}
+
+/// Moves both colliders by a half of the penetration in opposite directions,
+/// if both of them are solid.
+fn push_apart(world: &mut World, collision: &Collision) {
+    let solid = |e: Entity| world.get::<&AabbCollider>(e).map_or(false, |c| c.solid);
+    if !solid(collision.a) || !solid(collision.b) {
+        return;
+    }
+
+    let half = Vector2::from(collision.penetration) * 0.5;
+    if let Ok(mut t) = world.get::<&mut Transform>(collision.a) {
+        t.isometry.translation.vector -= half;
+    }
+    if let Ok(mut t) = world.get::<&mut Transform>(collision.b) {
+        t.isometry.translation.vector += half;
     }
 }
 
+fn collide(
+    mut world: ResMut<World>,
+    mut events: ResMut<Events<Collision>>,
+    mut spatial_hash: ResMut<SpatialHash>,
+) {
+    let collisions = find_collisions(&world, &mut *spatial_hash);
+
+    for collision in &collisions {
+        push_apart(&mut world, collision);
+    }
+
+    events.extend(collisions);
+}
+
 pub fn plugin(realm: &mut Realm) {
+    realm
+        .add_plugin(yapgeir_events::plugin::<Collision>)
+        .add_resource(SpatialHash::new(64.));
     realm.add_system(update);
+    realm.add_system(collide);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::broadphase::BruteForce;
+
+    use super::*;
+
+    #[test]
+    fn test_gravity_integration() {
+        let gravity = Vector2::new(0., -9.8);
+        let mut body = KinematicBody::new(Vector2::zeros(), Vector2::zeros(), true);
+        let mut still = KinematicBody::new(Vector2::zeros(), Vector2::zeros(), false);
+        let mut translation = Vector2::zeros();
+        let mut still_translation = Vector2::zeros();
+
+        for _ in 0..10 {
+            integrate(&mut body, &mut translation, gravity, 0.1);
+            integrate(&mut still, &mut still_translation, gravity, 0.1);
+        }
+
+        // Semi-implicit Euler: the position is updated with the new velocity on every step.
+        assert!((body.velocity - Vector2::new(0., -9.8)).norm() < 1e-4);
+        assert!((translation - Vector2::new(0., -0.098 * 55.)).norm() < 1e-4);
+        assert_eq!(still.velocity, Vector2::zeros());
+        assert_eq!(still_translation, Vector2::zeros());
+    }
+
+    #[test]
+    fn test_overlapping_boxes_collide() {
+        let mut world = World::new();
+        let collider = AabbCollider::new([1., 1.], true);
+        let a = world.spawn((collider, Transform::default()));
+        let b = world.spawn((
+            collider,
+            Transform::new(nalgebra::Isometry2::translation(1.5, 0.5), None),
+        ));
+        world.spawn((
+            collider,
+            Transform::new(nalgebra::Isometry2::translation(10., 0.), None),
+        ));
+
+        let collisions = find_collisions(&world, &mut BruteForce);
+        assert_eq!(
+            collisions,
+            vec![Collision {
+                a,
+                b,
+                penetration: [0.5, 0.],
+            }]
+        );
+
+        push_apart(&mut world, &collisions[0]);
+        assert!(find_collisions(&world, &mut BruteForce).is_empty());
+    }
 }
@@ -0,0 +1,128 @@
+use std::time::Instant;
+
+use derive_more::Deref;
+
+use crate::{Exit, IntoSystem, Resources, System, SystemId, SystemRunner};
+
+/// Duration of a fixed step in seconds.
+/// Available to the systems in the `stage::FIXED_UPDATE` stage.
+#[derive(Debug, Clone, Copy, PartialEq, Deref)]
+pub struct FixedDelta(pub f32);
+
+/// Runs systems zero or more times per frame with a fixed time step,
+/// accumulating real elapsed time between frames.
+pub struct FixedTimestep {
+    /// Duration of a single step in seconds.
+    pub step: f32,
+    /// Maximum number of steps per frame. After a long stall the time that
+    /// doesn't fit into this number of steps is dropped, so that catching up
+    /// doesn't take longer than the stall itself.
+    pub max_steps: u32,
+
+    accumulator: f32,
+    previous: Option<Instant>,
+    systems: SystemRunner,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(1. / 60., 5)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step: f32, max_steps: u32) -> Self {
+        assert!(step > 0., "step must be positive");
+        Self {
+            step,
+            max_steps,
+            accumulator: 0.,
+            previous: None,
+            systems: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn push<I, S: System<()> + 'static>(
+        &mut self,
+        system: impl IntoSystem<I, (), System = S>,
+    ) -> SystemId {
+        self.systems.push(system)
+    }
+
+    #[inline]
+    pub fn remove(&mut self, id: SystemId) -> bool {
+        self.systems.remove(id)
+    }
+
+    /// Accumulate `elapsed` seconds and run as many fixed steps as fit into the accumulated time.
+    /// Returns the number of steps that were run.
+    pub fn advance(&mut self, elapsed: f32, resources: &mut Resources) -> u32 {
+        self.accumulator += elapsed;
+        resources.insert(FixedDelta(self.step));
+
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+
+            if !self.systems.run(resources) {
+                break;
+            }
+        }
+
+        if steps == self.max_steps {
+            self.accumulator %= self.step;
+        }
+
+        steps
+    }
+
+    /// Advance by the real time elapsed since the previous run.
+    pub fn run(&mut self, resources: &mut Resources) -> bool {
+        let now = Instant::now();
+        let elapsed = self
+            .previous
+            .map_or(0., |previous| (now - previous).as_secs_f32());
+        self.previous = Some(now);
+
+        self.advance(elapsed, resources);
+        !resources.get::<Exit>().is_some_and(|e| **e)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ResMut;
+
+    use super::*;
+
+    fn count(mut n: ResMut<u32>) {
+        *n += 1;
+    }
+
+    #[test]
+    fn test_fixed_steps() {
+        let mut resources = Resources::default();
+        resources.insert(0u32);
+
+        let mut fixed = FixedTimestep::new(0.25, 4);
+        fixed.push(count);
+
+        assert_eq!(fixed.advance(0.1, &mut resources), 0);
+        assert_eq!(fixed.advance(0.2, &mut resources), 1);
+        assert_eq!(fixed.advance(0.5, &mut resources), 2);
+        assert_eq!(*resources.get::<u32>().unwrap(), 3);
+        assert_eq!(**resources.get::<FixedDelta>().unwrap(), 0.25);
+
+        // A long stall is capped, and the backlog is dropped.
+        assert_eq!(fixed.advance(10., &mut resources), 4);
+        assert_eq!(fixed.advance(0., &mut resources), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "step must be positive")]
+    fn test_zero_step() {
+        crate::Realm::default().set_fixed_timestep(0., 4);
+    }
+}
@@ -1,20 +1,30 @@
+use std::hash::Hash;
+
+use state::{ApplyTransition, StateHooks};
+
 mod commands;
+mod fixed_timestep;
 mod initializer;
 mod plugin;
 mod resources;
+pub mod stage;
+mod state;
 mod systems;
 
 pub use commands::*;
+pub use fixed_timestep::*;
 pub use initializer::*;
 pub use plugin::*;
 pub use resources::*;
+pub use stage::{Stages, SystemHandle};
+pub use state::{in_state, NextState, OnEnter, OnExit, State, StateHook};
 pub use systems::*;
 
 pub use yapgeir_realm_macro::*;
 
 pub struct Realm {
     resources: Resources,
-    systems: SystemRunner,
+    systems: Stages,
 }
 
 impl Default for Realm {
@@ -74,12 +84,88 @@ impl Realm {
         self
     }
 
+    /// Add a system to the `stage::UPDATE` stage.
+    ///
+    /// Returns a handle that can be used to remove the system with `remove_system`.
     #[inline]
     pub fn add_system<I, S: System<()> + 'static>(
         &mut self,
         system: impl IntoSystem<I, (), System = S>,
+    ) -> SystemHandle {
+        self.add_system_to_stage(stage::UPDATE, system)
+    }
+
+    /// Add a system to a stage. Panics if the stage does not exist.
+    ///
+    /// Returns a handle that can be used to remove the system with `remove_system`.
+    #[inline]
+    pub fn add_system_to_stage<I, S: System<()> + 'static>(
+        &mut self,
+        stage: &str,
+        system: impl IntoSystem<I, (), System = S>,
+    ) -> SystemHandle {
+        self.systems.push(stage, system)
+    }
+
+    /// Remove a system by the handle returned when it was added, returning true if it was found.
+    #[inline]
+    pub fn remove_system(&mut self, handle: SystemHandle) -> bool {
+        self.systems.remove(handle)
+    }
+
+    /// Register a state machine `S` starting in the `initial` state.
+    ///
+    /// Adds the `State<S>` and `NextState<S>` resources. Transitions requested with `NextState`
+    /// are applied in the `stage::FIRST` stage of the next frame.
+    pub fn add_state<S: Clone + Eq + Hash + 'static>(&mut self, initial: S) -> &mut Self {
+        self.resources.insert(State::new(initial));
+        self.resources.insert(NextState::<S>::default());
+        self.resources.insert(StateHooks::<S>::default());
+        self.add_system_to_stage(stage::FIRST, ApplyTransition::<S>::default());
+        self
+    }
+
+    /// Add a system to the `stage::UPDATE` stage, which only runs while the state machine is in `state`.
+    pub fn add_system_in_state<S: PartialEq + 'static, I, Sy: System<()> + 'static>(
+        &mut self,
+        state: S,
+        system: impl IntoSystem<I, (), System = Sy>,
+    ) -> SystemHandle {
+        self.add_system(system.run_if(in_state(state)))
+    }
+
+    /// Add a system running once on a state transition, e.g. `OnEnter(GameState::Menu)`.
+    /// Panics if the state machine is not registered with `add_state`.
+    pub fn add_system_on<S: Eq + Hash + 'static, I, Sy: System<()> + 'static>(
+        &mut self,
+        hook: impl Into<StateHook<S>>,
+        system: impl IntoSystem<I, (), System = Sy>,
     ) -> &mut Self {
-        self.systems.push(system);
+        self.resources
+            .get_mut::<StateHooks<S>>()
+            .expect("State is not registered, use Realm::add_state")
+            .push(hook.into(), system);
+        self
+    }
+
+    /// Configure the time step of the `stage::FIXED_UPDATE` stage. Panics if `step` is not positive.
+    pub fn set_fixed_timestep(&mut self, step: f32, max_steps: u32) -> &mut Self {
+        assert!(step > 0., "step must be positive");
+        let fixed = self.systems.fixed_timestep_mut();
+        fixed.step = step;
+        fixed.max_steps = max_steps;
+        self
+    }
+
+    /// Add a new stage, running right before an existing one.
+    pub fn add_stage_before(&mut self, before: &str, stage: &'static str) -> &mut Self {
+        self.systems.add_stage_before(before, stage);
+        self
+    }
+
+    /// Add a new stage, running right after an existing one.
+    pub fn add_stage_after(&mut self, after: &str, stage: &'static str) -> &mut Self {
+        self.systems.add_stage_after(after, stage);
         self
     }
 
@@ -97,6 +183,15 @@ impl Realm {
         self
     }
 
+    /// Remove a resource, returning it if it existed.
+    ///
+    /// Useful to release GPU resources deterministically, since they hold
+    /// a reference to the graphics context.
+    #[inline]
+    pub fn remove_resource<T: 'static>(&mut self) -> Option<T> {
+        self.resources.remove::<T>()
+    }
+
     #[inline]
     pub fn initialize_resource<T: FromResources + 'static>(&mut self) -> &mut Self {
         self.resources.insert(T::from(&self.resources));
@@ -33,6 +33,7 @@ impl Resources {
             .map(|res| RefMut::map(res, |r| r.downcast_mut::<T>().expect("Downcast failed")))
     }
 
+    /// Remove a resource, returning it if it existed.
     pub fn remove<T: 'static>(&mut self) -> Option<T> {
         self.resources
             .remove(&TypeId::of::<T>())
@@ -87,4 +88,15 @@ mod tests {
         let number = *resources.get::<u32>().unwrap();
         assert_eq!(number, 15);
     }
+
+    #[test]
+    fn test_remove() {
+        let mut resources = Resources::default();
+        resources.insert(String::from("texture"));
+
+        assert_eq!(resources.remove::<String>().as_deref(), Some("texture"));
+        assert!(resources.get::<String>().is_none());
+        assert!(!resources.contains::<String>());
+        assert!(resources.remove::<String>().is_none());
+    }
 }
@@ -0,0 +1,181 @@
+use crate::{FixedTimestep, IntoSystem, Resources, System, SystemId, SystemRunner};
+
+/// Frame setup: clearing events from the previous frame and updating frame timing.
+pub const FIRST: &str = "first";
+/// Polling window events and updating input state.
+pub const INPUT: &str = "input";
+/// Systems running with a fixed time step, zero or more times per frame, e.g. physics.
+/// See `FixedTimestep`.
+pub const FIXED_UPDATE: &str = "fixed_update";
+/// Game logic. This is the default stage for `Realm::add_system`.
+pub const UPDATE: &str = "update";
+/// Rendering and presenting the frame.
+pub const RENDER: &str = "render";
+
+enum Stage {
+    /// Runs once per frame.
+    Frame(SystemRunner),
+    Fixed(FixedTimestep),
+}
+
+impl Stage {
+    fn push<I, S: System<()> + 'static>(
+        &mut self,
+        system: impl IntoSystem<I, (), System = S>,
+    ) -> SystemId {
+        match self {
+            Stage::Frame(systems) => systems.push(system),
+            Stage::Fixed(fixed) => fixed.push(system),
+        }
+    }
+
+    fn remove(&mut self, id: SystemId) -> bool {
+        match self {
+            Stage::Frame(systems) => systems.remove(id),
+            Stage::Fixed(fixed) => fixed.remove(id),
+        }
+    }
+
+    fn run(&mut self, resources: &mut Resources) -> bool {
+        match self {
+            Stage::Frame(systems) => systems.run(resources),
+            Stage::Fixed(fixed) => fixed.run(resources),
+        }
+    }
+}
+
+/// A handle of a system added to a stage, used to remove the system later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemHandle {
+    stage: &'static str,
+    id: SystemId,
+}
+
+/// An ordered list of named stages, each running its systems in the order they were added.
+pub struct Stages {
+    stages: Vec<(&'static str, Stage)>,
+}
+
+impl Default for Stages {
+    fn default() -> Self {
+        let frame = |label| (label, Stage::Frame(SystemRunner::default()));
+
+        Self {
+            stages: vec![
+                frame(FIRST),
+                frame(INPUT),
+                (FIXED_UPDATE, Stage::Fixed(FixedTimestep::default())),
+                frame(UPDATE),
+                frame(RENDER),
+            ],
+        }
+    }
+}
+
+impl Stages {
+    fn position(&self, label: &str) -> usize {
+        self.stages
+            .iter()
+            .position(|(l, _)| *l == label)
+            .unwrap_or_else(|| panic!("Stage {label} does not exist"))
+    }
+
+    fn insert(&mut self, index: usize, label: &'static str) {
+        assert!(
+            self.stages.iter().all(|(l, _)| *l != label),
+            "Stage {label} already exists"
+        );
+        self.stages
+            .insert(index, (label, Stage::Frame(SystemRunner::default())));
+    }
+
+    pub fn add_stage_before(&mut self, before: &str, label: &'static str) {
+        self.insert(self.position(before), label);
+    }
+
+    pub fn add_stage_after(&mut self, after: &str, label: &'static str) {
+        self.insert(self.position(after) + 1, label);
+    }
+
+    #[inline]
+    pub fn push<I, S: System<()> + 'static>(
+        &mut self,
+        label: &str,
+        system: impl IntoSystem<I, (), System = S>,
+    ) -> SystemHandle {
+        let index = self.position(label);
+        let (stage, systems) = &mut self.stages[index];
+        SystemHandle {
+            stage,
+            id: systems.push(system),
+        }
+    }
+
+    /// Remove a system, returning true if it was found.
+    pub fn remove(&mut self, handle: SystemHandle) -> bool {
+        self.stages
+            .iter_mut()
+            .find(|(label, _)| *label == handle.stage)
+            .is_some_and(|(_, systems)| systems.remove(handle.id))
+    }
+
+    /// Returns the fixed time step scheduler of the `FIXED_UPDATE` stage.
+    pub fn fixed_timestep_mut(&mut self) -> &mut FixedTimestep {
+        let index = self.position(FIXED_UPDATE);
+        match &mut self.stages[index].1 {
+            Stage::Fixed(fixed) => fixed,
+            Stage::Frame(_) => unreachable!("{FIXED_UPDATE} is always a fixed stage"),
+        }
+    }
+
+    pub fn run(&mut self, resources: &mut Resources) -> bool {
+        self.stages
+            .iter_mut()
+            .all(|(_, stage)| stage.run(resources))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Realm, ResMut};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Order(Vec<&'static str>);
+
+    #[test]
+    fn test_stage_order() {
+        let mut realm = Realm::default();
+        realm
+            .initialize_resource::<Order>()
+            .add_stage_after(UPDATE, "late_update");
+        realm.add_system_to_stage(RENDER, |mut o: ResMut<Order>| o.0.push(RENDER));
+        realm.add_system_to_stage("late_update", |mut o: ResMut<Order>| {
+            o.0.push("late_update")
+        });
+        realm.add_system(|mut o: ResMut<Order>| o.0.push(UPDATE));
+        realm.add_system_to_stage(INPUT, |mut o: ResMut<Order>| o.0.push(INPUT));
+
+        realm.systems.run(&mut realm.resources);
+
+        let order = realm.resources.get::<Order>().unwrap();
+        assert_eq!(order.0, vec![INPUT, UPDATE, "late_update", RENDER]);
+    }
+
+    #[test]
+    fn test_remove_system() {
+        let mut realm = Realm::default();
+        realm.initialize_resource::<Order>();
+        let first = realm.add_system(|mut o: ResMut<Order>| o.0.push("first"));
+        let second = realm.add_system(|mut o: ResMut<Order>| o.0.push("second"));
+
+        assert!(realm.remove_system(first));
+        assert!(!realm.remove_system(first));
+        realm.systems.run(&mut realm.resources);
+
+        let order = realm.resources.get::<Order>().unwrap();
+        assert_eq!(order.0, vec!["second"]);
+        assert_ne!(first, second);
+    }
+}
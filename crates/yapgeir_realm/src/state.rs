@@ -0,0 +1,210 @@
+use std::{collections::HashMap, hash::Hash, marker::PhantomData};
+
+use derive_more::Deref;
+
+use crate::{IntoSystem, Res, Resources, System, SystemRunner};
+
+/// The current state of a state machine `S`, e.g. an enum of
+/// "menu", "playing" and "paused" game states.
+///
+/// Added by `Realm::add_state`. To switch to a different state use `NextState`.
+#[derive(Debug, Clone, PartialEq, Eq, Deref)]
+pub struct State<S>(S);
+
+impl<S> State<S> {
+    pub(crate) fn new(state: S) -> Self {
+        Self(state)
+    }
+
+    pub fn get(&self) -> &S {
+        &self.0
+    }
+}
+
+/// A requested transition of the state machine `S`.
+/// The transition is applied at the beginning of the next frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NextState<S>(Option<S>);
+
+impl<S> Default for NextState<S> {
+    fn default() -> Self {
+        Self(None)
+    }
+}
+
+impl<S> NextState<S> {
+    pub fn set(&mut self, state: S) {
+        self.0 = Some(state);
+    }
+}
+
+/// Systems added with `OnEnter` run once, when the state machine enters the state.
+pub struct OnEnter<S>(pub S);
+
+/// Systems added with `OnExit` run once, when the state machine leaves the state.
+pub struct OnExit<S>(pub S);
+
+pub enum StateHook<S> {
+    Enter(S),
+    Exit(S),
+}
+
+impl<S> From<OnEnter<S>> for StateHook<S> {
+    fn from(hook: OnEnter<S>) -> Self {
+        StateHook::Enter(hook.0)
+    }
+}
+
+impl<S> From<OnExit<S>> for StateHook<S> {
+    fn from(hook: OnExit<S>) -> Self {
+        StateHook::Exit(hook.0)
+    }
+}
+
+/// Enter and exit systems of the state machine `S`.
+pub(crate) struct StateHooks<S> {
+    on_enter: HashMap<S, SystemRunner>,
+    on_exit: HashMap<S, SystemRunner>,
+}
+
+impl<S> Default for StateHooks<S> {
+    fn default() -> Self {
+        Self {
+            on_enter: Default::default(),
+            on_exit: Default::default(),
+        }
+    }
+}
+
+impl<S: Eq + Hash> StateHooks<S> {
+    pub fn push<I, Sy: System<()> + 'static>(
+        &mut self,
+        hook: StateHook<S>,
+        system: impl IntoSystem<I, (), System = Sy>,
+    ) {
+        let (hooks, state) = match hook {
+            StateHook::Enter(state) => (&mut self.on_enter, state),
+            StateHook::Exit(state) => (&mut self.on_exit, state),
+        };
+
+        hooks.entry(state).or_default().push(system);
+    }
+
+    fn run(hooks: &mut HashMap<S, SystemRunner>, state: &S, resources: &mut Resources) {
+        if let Some(systems) = hooks.get_mut(state) {
+            systems.run(resources);
+        }
+    }
+}
+
+/// Applies a requested `NextState`, running exit systems of the previous state
+/// and enter systems of the next one.
+///
+/// Enter systems of the initial state are run on the first frame.
+pub(crate) struct ApplyTransition<S> {
+    entered: bool,
+    state: PhantomData<fn() -> S>,
+}
+
+impl<S> Default for ApplyTransition<S> {
+    fn default() -> Self {
+        Self {
+            entered: false,
+            state: Default::default(),
+        }
+    }
+}
+
+impl<S: Clone + Eq + Hash + 'static> System<()> for ApplyTransition<S> {
+    fn run(&mut self, resources: &mut Resources) {
+        let next = resources
+            .get_mut::<NextState<S>>()
+            .and_then(|mut next| next.0.take());
+
+        let Some(mut hooks) = resources.remove::<StateHooks<S>>() else {
+            return;
+        };
+
+        let current = resources
+            .get::<State<S>>()
+            .map(|state| state.0.clone())
+            .expect("State is not registered, use Realm::add_state");
+
+        if !self.entered {
+            self.entered = true;
+            StateHooks::run(&mut hooks.on_enter, &current, resources);
+        }
+
+        if let Some(next) = next.filter(|next| *next != current) {
+            StateHooks::run(&mut hooks.on_exit, &current, resources);
+            resources.insert(State(next.clone()));
+            StateHooks::run(&mut hooks.on_enter, &next, resources);
+        }
+
+        resources.insert(hooks);
+    }
+}
+
+/// A run condition, which is true while the state machine is in the given state.
+pub fn in_state<S: PartialEq + 'static>(state: S) -> impl System<bool> {
+    (move |current: Option<Res<State<S>>>| current.is_some_and(|current| current.0 == state))
+        .system()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{IntoFilteredSystem, Realm, ResMut};
+
+    use super::*;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    enum GameState {
+        Menu,
+        Playing,
+    }
+
+    #[derive(Default)]
+    struct Log(Vec<&'static str>);
+
+    #[test]
+    fn test_state_transition() {
+        let mut realm = Realm::default();
+        realm
+            .add_state(GameState::Menu)
+            .add_resource(Log::default())
+            .add_system_on(OnEnter(GameState::Menu), |mut log: ResMut<Log>| {
+                log.0.push("enter menu")
+            })
+            .add_system_on(OnExit(GameState::Menu), |mut log: ResMut<Log>| {
+                log.0.push("exit menu")
+            })
+            .add_system_on(OnEnter(GameState::Playing), |mut log: ResMut<Log>| {
+                log.0.push("enter playing")
+            });
+        realm.add_system_in_state(GameState::Menu, |mut log: ResMut<Log>| log.0.push("menu"));
+        realm.add_system_in_state(GameState::Playing, |mut log: ResMut<Log>| {
+            log.0.push("playing")
+        });
+        realm.add_system(
+            (|mut next: ResMut<NextState<GameState>>| next.set(GameState::Playing))
+                .run_if(in_state(GameState::Menu)),
+        );
+
+        for _ in 0..3 {
+            realm.systems.run(&mut realm.resources);
+        }
+
+        let log = realm.resources.get::<Log>().unwrap();
+        assert_eq!(
+            log.0,
+            [
+                "enter menu",
+                "menu",
+                "exit menu",
+                "enter playing",
+                "playing",
+                "playing"
+            ]
+        );
+    }
+}
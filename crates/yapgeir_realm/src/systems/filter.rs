@@ -26,6 +26,22 @@ pub trait IntoFilteredSystem<Args, R>: IntoSystem<Args, R> {
         let system = self.system();
         FilteredSystem { system, predicate }
     }
+
+    /// Run the system only when `condition` returns true.
+    /// The condition is a system itself, and is evaluated before every run.
+    ///
+    /// An alias to `filter`, e.g. `update.run_if(|paused: Res<Paused>| !paused.0)`.
+    #[inline]
+    fn run_if<ConditionArgs, C: System<bool> + 'static>(
+        self,
+        condition: impl IntoSystem<ConditionArgs, bool, System = C>,
+    ) -> FilteredSystem<Self::System, C>
+    where
+        Self: Sized,
+        <Self as IntoSystem<Args, R>>::System: System<()>,
+    {
+        self.filter(condition)
+    }
 }
 
 impl<Args, R, T> IntoFilteredSystem<Args, R> for T where T: IntoSystem<Args, R> + Sized {}
@@ -34,6 +50,25 @@ pub fn resource_exists<R: 'static>() -> impl System<bool> {
     (|r: Option<Res<R>>| r.is_some()).system()
 }
 
+/// A condition that is true on the first run, and then on every `n`-th run.
+pub struct EveryNth {
+    n: usize,
+    runs: usize,
+}
+
+impl System<bool> for EveryNth {
+    fn run(&mut self, _: &mut Resources) -> bool {
+        let run = self.runs.is_multiple_of(self.n);
+        self.runs += 1;
+        run
+    }
+}
+
+pub fn every_nth(n: usize) -> EveryNth {
+    assert!(n > 0, "n must be positive");
+    EveryNth { n, runs: 0 }
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::*;
@@ -72,4 +107,47 @@ mod tests {
             assert_eq!(message, "Hello, world!");
         }
     }
+
+    struct Paused(bool);
+
+    fn count_system(mut n: ResMut<u32>) {
+        *n += 1;
+    }
+
+    #[test]
+    fn test_run_if() {
+        let mut resources = Resources::default();
+        resources.insert(Paused(false));
+        resources.insert(0u32);
+
+        let mut system_runner = SystemRunner::default();
+        system_runner.push(count_system.run_if(|p: Res<Paused>| !p.0));
+
+        system_runner.run(&mut resources);
+        assert_eq!(*resources.get::<u32>().unwrap(), 1);
+
+        resources.get_mut::<Paused>().unwrap().0 = true;
+        system_runner.run(&mut resources);
+        assert_eq!(*resources.get::<u32>().unwrap(), 1);
+
+        resources.get_mut::<Paused>().unwrap().0 = false;
+        system_runner.run(&mut resources);
+        assert_eq!(*resources.get::<u32>().unwrap(), 2);
+    }
+
+    #[test]
+    fn test_every_nth() {
+        let mut resources = Resources::default();
+        resources.insert(0u32);
+
+        let mut system_runner = SystemRunner::default();
+        system_runner.push(count_system.run_if(every_nth(3)));
+
+        for _ in 0..7 {
+            system_runner.run(&mut resources);
+        }
+
+        // Runs 0, 3 and 6
+        assert_eq!(*resources.get::<u32>().unwrap(), 3);
+    }
 }
@@ -19,9 +19,15 @@ pub trait System<R = ()> {
     fn run(&mut self, resources: &mut Resources) -> R;
 }
 
+/// A stable identifier of a system in a `SystemRunner`.
+/// Identifiers are never reused, so they stay valid after other systems are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SystemId(u64);
+
 #[derive(Default)]
 pub struct SystemRunner {
-    systems: Vec<Box<dyn System<()>>>,
+    systems: Vec<(SystemId, Box<dyn System<()>>)>,
+    next_id: u64,
 }
 
 impl SystemRunner {
@@ -29,19 +35,26 @@ impl SystemRunner {
     pub fn push<I, S: System<()> + 'static>(
         &mut self,
         system: impl IntoSystem<I, (), System = S>,
-    ) -> usize {
-        let len = self.systems.len();
-        self.systems.push(Box::new(system.system()));
-        len
+    ) -> SystemId {
+        let id = SystemId(self.next_id);
+        self.next_id += 1;
+        self.systems.push((id, Box::new(system.system())));
+        id
     }
 
-    #[inline]
-    pub fn remove(&mut self, index: usize) {
-        self.systems.remove(index);
+    /// Remove a system, returning true if it was found.
+    pub fn remove(&mut self, id: SystemId) -> bool {
+        match self.systems.iter().position(|(i, _)| *i == id) {
+            Some(index) => {
+                self.systems.remove(index);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn run(&mut self, resources: &mut Resources) -> bool {
-        for system in &mut self.systems {
+        for (_, system) in &mut self.systems {
             system.run(resources);
             if resources.get::<Exit>().is_some_and(|e| e.0) {
                 return false;
@@ -2,28 +2,93 @@ use std::{any::TypeId, collections::HashMap, marker::PhantomData};
 
 use bevy_reflect::{std_traits::ReflectDefault, GetTypeRegistration, Reflect, TypeRegistry};
 use derive_more::Deref;
-use hecs::{Component, EntityRef};
+use hecs::{Component, Entity, EntityBuilder, EntityRef, World};
 use yapgeir_realm::{resource_exists, IntoFilteredSystem, Realm, ResMut};
 
 pub use bevy_reflect;
 
+pub mod prefab;
+pub mod save;
+
 #[derive(Default)]
 pub struct Reflection {
     pub type_registry: TypeRegistry,
     pub component_visitors: ComponentVisitors,
 }
 
+impl Reflection {
+    /// Registers a component type, so that it can be visited on entities.
+    pub fn register_component<T: GetTypeRegistration + Reflect + Component>(&mut self) {
+        self.type_registry.register::<T>();
+        self.component_visitors.0.insert(
+            TypeId::of::<T>(),
+            Box::new(TypedComponentVisitor::<T>(Default::default())),
+        );
+    }
+}
+
 pub trait ComponentVisitor {
     fn visit<'a>(&self, entity: EntityRef, visitor: Box<dyn FnMut(&mut dyn Reflect) + 'a>);
+
+    /// Inserts a default value of the component into the entity.
+    ///
+    /// Returns false if the component type has no `ReflectDefault` registered,
+    /// or the entity doesn't exist.
+    fn insert_default(
+        &self,
+        type_registry: &TypeRegistry,
+        world: &mut World,
+        entity: Entity,
+    ) -> bool;
+
+    /// Adds a component value to an entity which is being built.
+    ///
+    /// Returns false if the value is not of the component type.
+    fn add(&self, builder: &mut EntityBuilder, component: Box<dyn Reflect>) -> bool;
+
+    /// Removes the component from the entity, if it is present.
+    fn remove(&self, world: &mut World, entity: Entity);
 }
 
 struct TypedComponentVisitor<T>(PhantomData<T>);
 
-impl<T: Reflect> ComponentVisitor for TypedComponentVisitor<T> {
+impl<T: Reflect + Component> ComponentVisitor for TypedComponentVisitor<T> {
     fn visit<'a>(&self, entity: EntityRef, mut visitor: Box<dyn FnMut(&mut dyn Reflect) + 'a>) {
         let mut component = entity.get::<&mut T>().unwrap();
         visitor(component.as_reflect_mut());
     }
+
+    fn insert_default(
+        &self,
+        type_registry: &TypeRegistry,
+        world: &mut World,
+        entity: Entity,
+    ) -> bool {
+        let Some(reflect_default) =
+            type_registry.get_type_data::<ReflectDefault>(TypeId::of::<T>())
+        else {
+            return false;
+        };
+
+        match reflect_default.default().take::<T>() {
+            Ok(component) => world.insert_one(entity, component).is_ok(),
+            Err(_) => false,
+        }
+    }
+
+    fn add(&self, builder: &mut EntityBuilder, component: Box<dyn Reflect>) -> bool {
+        match component.take::<T>() {
+            Ok(component) => {
+                builder.add(component);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    fn remove(&self, world: &mut World, entity: Entity) {
+        let _ = world.remove_one::<T>(entity);
+    }
 }
 
 #[derive(Default, Deref)]
@@ -32,11 +97,7 @@ pub struct ComponentVisitors(HashMap<TypeId, Box<dyn ComponentVisitor>>);
 fn register_non_default<'a, T: GetTypeRegistration + Reflect + Component>(
     mut reflection: ResMut<Reflection>,
 ) {
-    reflection.type_registry.register::<T>();
-    reflection.component_visitors.0.insert(
-        TypeId::of::<T>(),
-        Box::new(TypedComponentVisitor::<T>(Default::default())),
-    );
+    reflection.register_component::<T>();
 }
 
 fn register_default<'a, T: GetTypeRegistration + Reflect + Default + Component>(
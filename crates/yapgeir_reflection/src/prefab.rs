@@ -0,0 +1,197 @@
+use std::fmt;
+
+use bevy_reflect::{serde::TypedReflectDeserializer, DynamicStruct, Reflect, TypeRegistry};
+use hecs::{Entity, EntityBuilder, World};
+use serde::de::{DeserializeSeed, Error, MapAccess, Visitor};
+
+use crate::{save::add_component, Reflection};
+
+/// A reusable template of an entity, holding reflected values of its components.
+///
+/// Prefabs are written in RON as a map of component type names to their values:
+///
+/// ```ron
+/// {
+///     "game::Health": (current: 5, max: 5),
+///     "game::Position": (0.0, 0.0),
+/// }
+/// ```
+#[derive(Default)]
+pub struct Prefab {
+    pub components: Vec<Box<dyn Reflect>>,
+}
+
+impl Prefab {
+    /// Parses a prefab from RON. All component types must be registered.
+    pub fn from_ron(ron: &str, type_registry: &TypeRegistry) -> Result<Self, ron::Error> {
+        let mut deserializer = ron::Deserializer::from_str(ron).map_err(|e| e.code)?;
+        let components = PrefabVisitor { type_registry }.deserialize(&mut deserializer)?;
+        deserializer.end()?;
+
+        Ok(Self { components })
+    }
+}
+
+struct PrefabVisitor<'a> {
+    type_registry: &'a TypeRegistry,
+}
+
+impl<'a, 'de> DeserializeSeed<'de> for PrefabVisitor<'a> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'a, 'de> Visitor<'de> for PrefabVisitor<'a> {
+    type Value = Vec<Box<dyn Reflect>>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map of component type names to component values")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let mut components = Vec::new();
+
+        while let Some(type_name) = map.next_key::<String>()? {
+            let registration = self
+                .type_registry
+                .get_with_name(&type_name)
+                .ok_or_else(|| Error::custom(format!("Unregistered component {type_name}")))?;
+
+            components.push(map.next_value_seed(TypedReflectDeserializer::new(
+                registration,
+                self.type_registry,
+            ))?);
+        }
+
+        Ok(components)
+    }
+}
+
+/// Spawns an entity with the components of a prefab, see `spawn_prefab_with_overrides`.
+pub fn spawn_prefab(world: &mut World, reflection: &Reflection, prefab: &Prefab) -> Entity {
+    spawn_prefab_with_overrides(world, reflection, prefab, &[])
+}
+
+/// Spawns an entity with the components of a prefab.
+///
+/// Components are constructed with their `ReflectDefault`, and then the prefab values are
+/// applied to them. Each override is a patch with some of the fields of a component,
+/// which is applied on top of the prefab value of a component with the same represented type.
+///
+/// Components which are not registered, or don't have `ReflectDefault`, are skipped with a warning.
+/// The entity is spawned only once all of its components are constructed, so an override which
+/// panics when applied, e.g. because of a mismatched field type, doesn't leave a partial entity.
+pub fn spawn_prefab_with_overrides(
+    world: &mut World,
+    reflection: &Reflection,
+    prefab: &Prefab,
+    overrides: &[DynamicStruct],
+) -> Entity {
+    let mut builder = EntityBuilder::new();
+
+    for value in &prefab.components {
+        let type_name = value.type_name();
+        let Some(registration) = reflection.type_registry.get_with_name(type_name) else {
+            eprintln!("Skipped spawning an unregistered component {type_name}");
+            continue;
+        };
+
+        let overrides = overrides.iter().filter(|o| o.type_name() == type_name);
+        add_component(
+            &mut builder,
+            reflection,
+            registration.type_id(),
+            type_name,
+            |component| {
+                component.apply(value.as_ref());
+                for patch in overrides {
+                    component.apply(patch);
+                }
+            },
+        );
+    }
+
+    world.spawn(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::AssertUnwindSafe;
+
+    use bevy_reflect::{std_traits::ReflectDefault, Typed};
+
+    use super::*;
+
+    #[derive(Debug, Default, Clone, PartialEq, Reflect)]
+    #[reflect(Default)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Reflect)]
+    #[reflect(Default)]
+    struct Position(f32, f32);
+
+    const PREFAB: &str = r#"{
+        "yapgeir_reflection::prefab::tests::Health": (current: 5, max: 5),
+        "yapgeir_reflection::prefab::tests::Position": (1.0, 2.0),
+    }"#;
+
+    #[test]
+    fn test_spawn_with_override() {
+        let mut reflection = Reflection::default();
+        reflection.register_component::<Health>();
+        reflection.register_component::<Position>();
+
+        let prefab = Prefab::from_ron(PREFAB, &reflection.type_registry).unwrap();
+        assert_eq!(prefab.components.len(), 2);
+
+        let mut wounded = DynamicStruct::default();
+        wounded.set_represented_type(Some(Health::type_info()));
+        wounded.insert("current", 2u32);
+
+        let mut world = World::new();
+        let healthy = spawn_prefab(&mut world, &reflection, &prefab);
+        let wounded = spawn_prefab_with_overrides(&mut world, &reflection, &prefab, &[wounded]);
+
+        let health = |e: Entity| world.get::<&Health>(e).unwrap().clone();
+        assert_eq!(health(healthy), Health { current: 5, max: 5 });
+        assert_eq!(health(wounded), Health { current: 2, max: 5 });
+        assert_eq!(*world.get::<&Position>(wounded).unwrap(), Position(1., 2.));
+    }
+
+    #[test]
+    fn test_failed_override_spawns_nothing() {
+        let mut reflection = Reflection::default();
+        reflection.register_component::<Health>();
+        reflection.register_component::<Position>();
+
+        let prefab = Prefab::from_ron(PREFAB, &reflection.type_registry).unwrap();
+
+        // Position is a tuple struct, so a struct patch can't be applied to it.
+        let mut invalid = DynamicStruct::default();
+        invalid.set_represented_type(Some(Position::type_info()));
+        invalid.insert("x", 3f32);
+
+        let mut world = World::new();
+        let spawned = std::panic::catch_unwind(AssertUnwindSafe(|| {
+            spawn_prefab_with_overrides(&mut world, &reflection, &prefab, &[invalid])
+        }));
+
+        assert!(spawned.is_err());
+        assert_eq!(world.len(), 0);
+    }
+
+    #[test]
+    fn test_unregistered_component() {
+        let reflection = Reflection::default();
+        assert!(Prefab::from_ron(PREFAB, &reflection.type_registry).is_err());
+    }
+}
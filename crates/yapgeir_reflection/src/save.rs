@@ -0,0 +1,209 @@
+use std::{any::TypeId, collections::HashSet};
+
+use bevy_reflect::{
+    serde::{ReflectSerializer, UntypedReflectDeserializer},
+    std_traits::ReflectDefault,
+    Reflect,
+};
+use hecs::{Entity, EntityBuilder, World};
+use serde::de::DeserializeSeed;
+use serde_json::{Map, Value};
+
+use crate::Reflection;
+
+/// Serializes all entities of the world to JSON, as a list of entities,
+/// each being a map of component type names to their reflected values.
+///
+/// Only components registered in `Reflection` are saved, other components
+/// are skipped with a warning. Entity ids are not preserved, so components
+/// referencing other entities will not be valid after loading.
+pub fn save_world(world: &World, reflection: &Reflection) -> Vec<u8> {
+    let mut skipped = HashSet::new();
+
+    let entities = world
+        .iter()
+        .map(|entity| {
+            let mut components = Map::new();
+
+            for type_id in entity.component_types() {
+                let (Some(visitor), Some(registration)) = (
+                    reflection.component_visitors.get(&type_id),
+                    reflection.type_registry.get(type_id),
+                ) else {
+                    skipped.insert(type_id);
+                    continue;
+                };
+
+                visitor.visit(
+                    entity,
+                    Box::new(|component| {
+                        let serializer =
+                            ReflectSerializer::new(component, &reflection.type_registry);
+                        match serde_json::to_value(serializer) {
+                            Ok(Value::Object(value)) => components.extend(value),
+                            Ok(_) => unreachable!("Reflected values are serialized as maps"),
+                            Err(e) => eprintln!(
+                                "Unable to serialize component {}: {e}",
+                                registration.type_name()
+                            ),
+                        }
+                    }),
+                );
+            }
+
+            Value::Object(components)
+        })
+        .collect();
+
+    for type_id in skipped {
+        eprintln!("Skipped saving an unregistered component {type_id:?}");
+    }
+
+    serde_json::to_vec(&Value::Array(entities)).expect("Unable to serialize the world")
+}
+
+/// Spawns entities saved with `save_world`, returning them in the saved order.
+///
+/// Components are constructed with their `ReflectDefault`, and then the saved values
+/// are applied to them. Unregistered components and components without
+/// `ReflectDefault` are skipped with a warning.
+///
+/// All entities are deserialized before any of them is spawned,
+/// so the world is left unchanged if an error is returned.
+pub fn load_world(
+    world: &mut World,
+    reflection: &Reflection,
+    json: &[u8],
+) -> serde_json::Result<Vec<Entity>> {
+    let entities: Vec<Map<String, Value>> = serde_json::from_slice(json)?;
+    let registry = &reflection.type_registry;
+
+    let mut builders = entities
+        .into_iter()
+        .map(|components| {
+            let mut builder = EntityBuilder::new();
+
+            for (type_name, value) in components {
+                let Some(type_id) = registry.get_with_name(&type_name).map(|r| r.type_id()) else {
+                    eprintln!("Skipped loading an unregistered component {type_name}");
+                    continue;
+                };
+
+                let value = Value::Object(Map::from_iter([(type_name.clone(), value)]));
+                let value = UntypedReflectDeserializer::new(registry).deserialize(value)?;
+                add_component(&mut builder, reflection, type_id, &type_name, |component| {
+                    component.apply(value.as_ref())
+                });
+            }
+
+            Ok(builder)
+        })
+        .collect::<serde_json::Result<Vec<_>>>()?;
+
+    Ok(builders
+        .iter_mut()
+        .map(|builder| world.spawn(builder.build()))
+        .collect())
+}
+
+/// Adds a default value of a component to the builder, modified with `apply`.
+pub(crate) fn add_component(
+    builder: &mut EntityBuilder,
+    reflection: &Reflection,
+    type_id: TypeId,
+    type_name: &str,
+    apply: impl FnOnce(&mut dyn Reflect),
+) {
+    let visitor = reflection.component_visitors.get(&type_id);
+    let reflect_default = reflection
+        .type_registry
+        .get_type_data::<ReflectDefault>(type_id);
+
+    let added = match (visitor, reflect_default) {
+        (Some(visitor), Some(reflect_default)) => {
+            let mut component = reflect_default.default();
+            apply(component.as_mut());
+            visitor.add(builder, component)
+        }
+        _ => false,
+    };
+
+    if !added {
+        eprintln!("Skipped component {type_name} without a default value");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_reflect::{std_traits::ReflectDefault, Reflect};
+
+    use super::*;
+
+    #[derive(Debug, Default, Clone, PartialEq, Reflect)]
+    #[reflect(Default)]
+    struct Health {
+        current: u32,
+        max: u32,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Reflect)]
+    #[reflect(Default)]
+    struct Position(f32, f32);
+
+    /// A component which is not registered.
+    struct Cached;
+
+    #[test]
+    fn test_round_trip() {
+        let mut reflection = Reflection::default();
+        reflection.register_component::<Health>();
+        reflection.register_component::<Position>();
+
+        let mut world = World::new();
+        let health = Health { current: 3, max: 5 };
+        world.spawn((health.clone(), Position(1., -2.), Cached));
+        world.spawn((Position(10., 20.),));
+
+        let saved = save_world(&world, &reflection);
+        world.clear();
+
+        let entities = load_world(&mut world, &reflection, &saved).unwrap();
+        assert_eq!(entities.len(), 2);
+        assert_eq!(world.len(), 2);
+
+        let mut loaded: Vec<_> = world
+            .query::<(&Position, Option<&Health>)>()
+            .iter()
+            .map(|(_, (position, health))| (position.clone(), health.cloned()))
+            .collect();
+        loaded.sort_by(|a, b| a.0 .0.total_cmp(&b.0 .0));
+
+        assert_eq!(
+            loaded,
+            [
+                (Position(1., -2.), Some(health)),
+                (Position(10., 20.), None)
+            ]
+        );
+        assert_eq!(world.query::<&Cached>().iter().count(), 0);
+    }
+
+    #[test]
+    fn test_corrupt_entity_leaves_world_unchanged() {
+        let mut reflection = Reflection::default();
+        reflection.register_component::<Health>();
+        reflection.register_component::<Position>();
+
+        let mut world = World::new();
+        world.spawn((Position(1., 2.), Health { current: 1, max: 2 }));
+        world.spawn((Position(3., 4.), Health { current: 3, max: 4 }));
+
+        let mut saved: Value = serde_json::from_slice(&save_world(&world, &reflection)).unwrap();
+        saved[1]["yapgeir_reflection::save::tests::Health"]["current"] = "many".into();
+        let saved = serde_json::to_vec(&saved).unwrap();
+
+        assert!(load_world(&mut world, &reflection, &saved).is_err());
+        assert_eq!(world.len(), 2);
+        assert_eq!(world.query::<&Position>().iter().count(), 2);
+    }
+}
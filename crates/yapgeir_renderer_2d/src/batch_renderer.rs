@@ -1,5 +1,10 @@
 use bytemuck::Pod;
-use std::{borrow::Borrow, iter::repeat_with, marker::PhantomData, rc::Rc};
+use std::{
+    borrow::Borrow,
+    iter::{repeat_n, repeat_with},
+    marker::PhantomData,
+    rc::Rc,
+};
 use yapgeir_graphics_hal::{
     buffer::{Buffer, BufferKind, BufferUsage},
     draw_descriptor::{AsVertexBindings, IndexBinding},
@@ -87,19 +92,28 @@ where
         }
     }
 
+    /// Vertices drawn since the last flush.
+    #[cfg(test)]
+    pub(crate) fn unflushed(&self) -> &[V] {
+        &self.renderer.unflushed
+    }
+
     fn flush(&mut self) {
         if self.renderer.unflushed.is_empty() {
             return;
         }
 
         let current = &self.renderer.vertices[self.renderer.current_buffer];
+        if self.renderer.vertices.len() == 1 {
+            current.0.orphan();
+        }
         current.0.write(0, &self.renderer.unflushed);
 
         self.fb.draw(
             &current.1,
             &self.draw_parameters,
             self.textures.borrow(),
-            Some(&self.renderer.uniform_buffer),
+            &[&*self.renderer.uniform_buffer],
             &self.renderer.indices.indices(self.renderer.unflushed.len()),
         );
 
@@ -134,6 +148,14 @@ where
     V: Vertex + Pod,
     U: Uniforms + Pod,
 {
+    /// Create a new batch renderer with `buffer_count` vertex buffers of `buffer_size` vertices.
+    ///
+    /// Buffers are used in turns, so that writing to a buffer doesn't stall
+    /// on a draw call still reading from it. With a single buffer, the buffer is orphaned
+    /// before every write instead, letting the driver allocate fresh storage.
+    /// Which approach is faster depends on the driver: orphaning saves memory and is
+    /// well optimized by desktop drivers, while some mobile drivers handle it poorly.
+    /// The `buffer_streaming` example measures both approaches on the device it runs on.
     pub fn new<'a>(
         ctx: &G,
         shader: Rc<G::Shader>,
@@ -196,3 +218,366 @@ where
         }
     }
 }
+
+pub struct InstancedBatch<
+    'a,
+    G,
+    V,
+    I,
+    U,
+    T = <G as Graphics>::Texture,
+    S = [SamplerAttribute<G, T>; 0],
+> where
+    G: Graphics,
+    V: Vertex + Pod,
+    I: Vertex + Pod,
+    U: Uniforms + Pod,
+    T: Borrow<G::Texture>,
+    S: Borrow<[SamplerAttribute<G, T>]>,
+{
+    fb: &'a G::FrameBuffer,
+    textures: S,
+    renderer: &'a mut InstancedBatchRenderer<G, V, I, U>,
+    draw_parameters: &'a DrawParameters,
+
+    _t: PhantomData<T>,
+}
+
+impl<'a, G, V, I, U, T, S> Drop for InstancedBatch<'a, G, V, I, U, T, S>
+where
+    G: Graphics,
+    V: Vertex + Pod,
+    I: Vertex + Pod,
+    U: Uniforms + Pod,
+    T: Borrow<G::Texture>,
+    S: Borrow<[SamplerAttribute<G, T>]>,
+{
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+impl<'a, G, V, I, U, T, S> InstancedBatch<'a, G, V, I, U, T, S>
+where
+    G: Graphics,
+    V: Vertex + Pod,
+    I: Vertex + Pod,
+    U: Uniforms + Pod,
+    T: Borrow<G::Texture>,
+    S: Borrow<[SamplerAttribute<G, T>]>,
+{
+    pub fn draw(&mut self, instance: I) {
+        if self.renderer.unflushed.len() == self.renderer.unflushed.capacity() {
+            self.flush();
+        }
+
+        self.renderer.unflushed.push(instance);
+    }
+
+    fn flush(&mut self) {
+        if self.renderer.unflushed.is_empty() {
+            return;
+        }
+
+        let renderer = &mut *self.renderer;
+        let current = &renderer.instances[renderer.current_buffer];
+        let mesh_len = renderer.mesh_len;
+
+        if renderer.instances.len() == 1 {
+            current.0.orphan();
+        }
+
+        if renderer.instanced {
+            current.0.write(0, &renderer.unflushed);
+
+            self.fb.draw_instanced(
+                &current.1,
+                self.draw_parameters,
+                self.textures.borrow(),
+                &[&*renderer.uniform_buffer],
+                &renderer.indices.indices(mesh_len),
+                renderer.unflushed.len(),
+            );
+        } else {
+            // Without instancing support the mesh buffer contains the mesh repeated for each
+            // instance, so it's enough to repeat each instance for every vertex of the mesh.
+            renderer.expanded.clear();
+            for instance in &renderer.unflushed {
+                renderer.expanded.extend(repeat_n(*instance, mesh_len));
+            }
+            current.0.write(0, &renderer.expanded);
+
+            self.fb.draw(
+                &current.1,
+                self.draw_parameters,
+                self.textures.borrow(),
+                &[&*renderer.uniform_buffer],
+                &renderer
+                    .indices
+                    .indices(mesh_len * renderer.unflushed.len()),
+            );
+        }
+
+        renderer.unflushed.clear();
+        renderer.current_buffer = (renderer.current_buffer + 1) % renderer.instances.len();
+    }
+}
+
+/// A batch renderer that draws the same mesh multiple times with different
+/// per-instance data, e.g. a quad for tiles or particles.
+///
+/// Per-instance vertex type `I` should have a non-zero divisor,
+/// e.g. by deriving `Vertex` with `#[vertex(divisor = 1)]`.
+///
+/// If the graphics implementation doesn't support instancing, the renderer falls back
+/// to expanding each instance to a mesh on CPU and drawing all of them in a single draw call.
+pub struct InstancedBatchRenderer<G, V, I, U = ()>
+where
+    G: Graphics,
+    V: Vertex + Pod,
+    I: Vertex + Pod,
+    U: Uniforms + Pod,
+{
+    uniform_buffer: Rc<G::UniformBuffer<U>>,
+    instanced: bool,
+
+    // The mesh is kept alive by draw descriptors.
+    _mesh: Buffer<G, V>,
+    mesh_len: usize,
+
+    // This will store our instances until they are flushed to GPU.
+    unflushed: Vec<I>,
+    // Only used without instancing support, to repeat each instance for every vertex of the mesh.
+    expanded: Vec<I>,
+
+    // Keep multiple instance buffers, and use them as a ring buffer
+    // moving to the next one when instances are flushed.
+    instances: Vec<(Buffer<G, I>, G::DrawDescriptor)>,
+    current_buffer: usize,
+
+    indices: BatchIndices<G>,
+}
+
+impl<G, V, I, U> InstancedBatchRenderer<G, V, I, U>
+where
+    G: Graphics,
+    V: Vertex + Pod,
+    I: Vertex + Pod,
+    U: Uniforms + Pod,
+{
+    /// Create a new instanced batch renderer with `buffer_count` instance buffers.
+    ///
+    /// Has the same buffering semantics as `BatchRenderer::new`.
+    pub fn new(
+        ctx: &G,
+        shader: Rc<G::Shader>,
+        mesh: &[V],
+        indices: BatchIndices<G>,
+        uniforms: Rc<G::UniformBuffer<U>>,
+        (buffer_size, buffer_count): (usize, usize),
+    ) -> Self {
+        let instanced = ctx.supports_instancing();
+
+        let (mesh_buffer, instance_buffer_size) = if instanced {
+            let mesh = ctx.new_buffer(BufferKind::Vertex, BufferUsage::Static, mesh);
+            (mesh, buffer_size)
+        } else {
+            let mesh: Vec<V> = repeat_n(mesh, buffer_size).flatten().copied().collect();
+            let mesh_buffer = ctx.new_buffer(BufferKind::Vertex, BufferUsage::Static, &mesh);
+            (mesh_buffer, mesh.len())
+        };
+
+        let instances = repeat_with(|| {
+            let instances = ctx.new_buffer(
+                BufferKind::Vertex,
+                BufferUsage::Stream,
+                instance_buffer_size,
+            );
+
+            let descriptor = ctx.new_draw_descriptor(
+                shader.clone(),
+                match &indices {
+                    BatchIndices::Quad(quad) => quad.bindings(),
+                    BatchIndices::Primitive(_) => IndexBinding::None,
+                },
+                &[mesh_buffer.bindings(), instances.bindings()],
+            );
+
+            (instances, descriptor)
+        })
+        .take(buffer_count)
+        .collect();
+
+        Self {
+            uniform_buffer: uniforms,
+            instanced,
+
+            _mesh: mesh_buffer,
+            mesh_len: mesh.len(),
+
+            unflushed: Vec::with_capacity(buffer_size),
+            expanded: match instanced {
+                true => Vec::new(),
+                false => Vec::with_capacity(instance_buffer_size),
+            },
+
+            instances,
+            current_buffer: 0,
+
+            indices,
+        }
+    }
+
+    pub fn start_batch<'a, T, S>(
+        &'a mut self,
+        fb: &'a G::FrameBuffer,
+        draw_parameters: &'a DrawParameters,
+        uniforms: &U,
+        textures: S,
+    ) -> InstancedBatch<'a, G, V, I, U, T, S>
+    where
+        T: Borrow<G::Texture>,
+        S: Borrow<[SamplerAttribute<G, T>]>,
+    {
+        self.uniform_buffer.write(uniforms);
+
+        InstancedBatch {
+            fb,
+            textures,
+            renderer: self,
+            draw_parameters,
+
+            _t: PhantomData,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytemuck::Zeroable;
+    use yapgeir_graphics_hal::{
+        frame_buffer::ReadFormat, shader::TextShaderSource, Rect, Rgba, Size,
+    };
+    use yapgeir_graphics_hal_null::{
+        Null, NullBackend, NullProgram, NullTexture, ProgramInputs, VertexInputs,
+    };
+
+    use super::*;
+
+    const SIZE: u32 = 100;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+    struct MeshVertex {
+        position: [f32; 2],
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Zeroable, Pod, Vertex)]
+    #[vertex(divisor = 1)]
+    struct Instance {
+        offset: [f32; 2],
+        color: [f32; 4],
+    }
+
+    const SHADER: TextShaderSource = TextShaderSource {
+        vertex: "instance",
+        fragment: "instance",
+        defines: &[],
+    };
+
+    /// Draws the mesh in pixels, moved by the instance offset and filled with the instance color.
+    struct InstanceProgram;
+
+    impl NullProgram for InstanceProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let ([x, y], [dx, dy]) = (vertex.get("position"), vertex.get("offset"));
+            let ndc = |px: f32| px / SIZE as f32 * 2. - 1.;
+            (
+                [ndc(x + dx), -ndc(y + dy), 0., 1.],
+                vertex.get::<4>("color").to_vec(),
+            )
+        }
+
+        fn fragment(&self, _: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+            Some(Rgba::new(
+                varyings[0],
+                varyings[1],
+                varyings[2],
+                varyings[3],
+            ))
+        }
+    }
+
+    /// Draws a quad over every pixel of the frame buffer and reads it back.
+    /// Returns the pixels, the number of draw calls and the number of orphaned buffers.
+    fn draw_quads(instancing: bool, buffers: usize) -> (Vec<u8>, usize, usize) {
+        let graphics = Null::new(NullBackend {
+            instancing,
+            ..NullBackend::new((SIZE, SIZE))
+        });
+        graphics.register_program(&SHADER, InstanceProgram);
+
+        let mut renderer = InstancedBatchRenderer::<_, _, Instance>::new(
+            &graphics,
+            Rc::new(graphics.new_shader(&SHADER)),
+            &[[0., 0.], [1., 0.], [1., 1.], [0., 1.]].map(|position| MeshVertex { position }),
+            BatchIndices::Quad(QuadIndexBuffer::with_quads(&graphics, 1024)),
+            Rc::new(graphics.new_uniform_buffer(&())),
+            (1024, buffers),
+        );
+
+        let fb = graphics.default_frame_buffer();
+        let draw_parameters = DrawParameters::default();
+        let mut batch = renderer.start_batch::<NullTexture, _>(&fb, &draw_parameters, &(), []);
+        for i in 0..SIZE * SIZE {
+            let [x, y] = [i % SIZE, i / SIZE].map(|c| c as f32);
+            batch.draw(Instance {
+                offset: [x, y],
+                color: [x / SIZE as f32, y / SIZE as f32, 0.5, 1.],
+            });
+        }
+        drop(batch);
+
+        let mut pixels = vec![0; (SIZE * SIZE * 4) as usize];
+        fb.read(Rect::new(0, 0, SIZE, SIZE), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(fb.size(), Size::new(SIZE, SIZE));
+
+        let orphans = renderer
+            .instances
+            .iter()
+            .map(|(buffer, _)| buffer.bytes.orphans())
+            .sum();
+        (pixels, graphics.draw_calls(), orphans)
+    }
+
+    #[test]
+    fn test_instanced_matches_expanded() {
+        let (instanced, instanced_draw_calls, _) = draw_quads(true, 2);
+        let (expanded, expanded_draw_calls, _) = draw_quads(false, 2);
+
+        // Both paths flush the same batches.
+        assert_eq!(instanced_draw_calls, 10);
+        assert_eq!(expanded_draw_calls, 10);
+
+        // Every pixel is covered by its own quad.
+        assert!(instanced.chunks(4).all(|pixel| pixel[3] == 255));
+        let top_right = ((SIZE * (SIZE - 1) + SIZE - 1) * 4) as usize;
+        assert_eq!(instanced[top_right..top_right + 4], [252, 0, 128, 255]);
+
+        assert!(instanced == expanded);
+    }
+
+    #[test]
+    fn test_single_buffer_is_orphaned() {
+        let (ring, _, ring_orphans) = draw_quads(true, 2);
+        assert_eq!(ring_orphans, 0);
+
+        // A single buffer is orphaned before every flush, and still draws the same image.
+        for instancing in [true, false] {
+            let (orphaned, draw_calls, orphans) = draw_quads(instancing, 1);
+            assert_eq!(orphans, draw_calls);
+            assert!(orphaned == ring);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use yapgeir_core::WindowSize;
+use yapgeir_geometry::{Rect, Size};
+use yapgeir_realm::{Plugin, Realm, Res, ResMut};
+
+use crate::post_process::BlitArea;
+
+/// Places an image of an internal resolution on the window, upscaled by the largest
+/// integer factor that fits, so that all pixels of the image have the same size.
+///
+/// The image is centered, letterboxed on the sides which don't fit another step of the scale.
+/// If the window is smaller than the internal resolution, the image is not scaled and is cropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntegerScaleCamera {
+    internal: Size<u32>,
+    window: Size<u32>,
+    scale: u32,
+    destination: Rect<u32>,
+}
+
+impl IntegerScaleCamera {
+    pub fn new(internal: Size<u32>, window: Size<u32>) -> Self {
+        let scale = (window.w / internal.w.max(1))
+            .min(window.h / internal.h.max(1))
+            .max(1);
+
+        let (w, h) = (internal.w * scale, internal.h * scale);
+        Self {
+            internal,
+            window,
+            scale,
+            destination: Rect::new(
+                window.w.saturating_sub(w) / 2,
+                window.h.saturating_sub(h) / 2,
+                w,
+                h,
+            ),
+        }
+    }
+
+    /// Recalculates the scale for a new window size.
+    pub fn resize(&mut self, window: Size<u32>) {
+        if window != self.window {
+            *self = Self::new(self.internal, window);
+        }
+    }
+
+    /// Size of the offscreen frame buffer the scene should be rendered to.
+    pub fn internal_size(&self) -> Size<u32> {
+        self.internal
+    }
+
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Rectangle of the window covered by the upscaled image, with (0; 0) in the top-left corner.
+    pub fn destination(&self) -> Rect<u32> {
+        self.destination
+    }
+
+    /// Returns the area to blit the rendered image to, e.g. with `PostProcess::finish`.
+    pub fn blit_area(&self) -> BlitArea {
+        BlitArea::Custom(self.destination)
+    }
+}
+
+fn update_integer_scale(window: Res<WindowSize>, mut camera: ResMut<IntegerScaleCamera>) {
+    camera.resize(Size::new(window.w, window.h));
+}
+
+/// Adds an `IntegerScaleCamera` for the `internal` resolution, updated when `WindowSize` changes.
+pub fn plugin(internal: Size<u32>) -> impl Plugin {
+    move |realm: &mut Realm| {
+        realm
+            .initialize_resource_with(move |window: Option<Res<WindowSize>>| {
+                let window = window.map_or(internal, |window| Size::new(window.w, window.h));
+                IntegerScaleCamera::new(internal, window)
+            })
+            .add_system(update_integer_scale);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_integer_scale() {
+        let camera = IntegerScaleCamera::new(Size::new(320, 180), Size::new(1280, 720));
+        assert_eq!(camera.scale(), 4);
+        assert_eq!(camera.destination(), Rect::new(0, 0, 1280, 720));
+
+        // 6x doesn't fit, so the 5x image is letterboxed on all sides.
+        let mut camera = camera;
+        camera.resize(Size::new(1700, 1000));
+        assert_eq!(camera.scale(), 5);
+        assert_eq!(camera.destination(), Rect::new(50, 50, 1600, 900));
+        assert_eq!(
+            camera.blit_area(),
+            BlitArea::Custom(Rect::new(50, 50, 1600, 900))
+        );
+    }
+
+    #[test]
+    fn test_window_smaller_than_internal() {
+        let camera = IntegerScaleCamera::new(Size::new(320, 180), Size::new(300, 200));
+        assert_eq!(camera.scale(), 1);
+        assert_eq!(camera.destination(), Rect::new(0, 10, 320, 180));
+    }
+}
@@ -1,12 +1,20 @@
-use quad_index_buffer::QuadIndexBuffer;
+use quad_index_buffer::{QuadIndexBuffer, QuadIndexBufferSettings};
 use yapgeir_geometry::Size;
 use yapgeir_graphics_hal::Graphics;
 use yapgeir_realm::{Realm, Res};
 
 pub mod batch_renderer;
+pub mod integer_scale;
+pub mod light_renderer;
+pub mod post_process;
 pub mod primitive_renderer;
 pub mod quad_index_buffer;
 pub mod sprite_renderer;
+pub mod text_renderer;
+pub mod tilemap_renderer;
+
+#[cfg(test)]
+mod test_programs;
 
 pub enum NdcProjection {
     Center,
@@ -19,10 +27,10 @@ impl NdcProjection {
         match self {
             Self::Center => (
                 [0., 0.],
-                [1. / (size.w / 2) as f32, 1. / (size.h / 2) as f32],
+                [1. / (size.w as f32 / 2.), 1. / (size.h as f32 / 2.)],
             ),
             Self::TopLeft => {
-                let offset = [-((size.w / 2) as f32), ((size.h / 2) as f32)];
+                let offset = [-(size.w as f32 / 2.), size.h as f32 / 2.];
                 let scale = [-1. / offset[0], 1. / offset[1]];
                 (offset, scale)
             }
@@ -31,6 +39,49 @@ impl NdcProjection {
     }
 }
 
+/// Adds a `QuadIndexBuffer`, sized by the `QuadIndexBufferSettings` resource if it exists.
 pub fn plugin<G: Graphics>(realm: &mut Realm) {
-    realm.initialize_resource_with(|ctx: Res<G>| QuadIndexBuffer::<G>::new(&ctx, 65532u16));
+    realm.initialize_resource_with(
+        |ctx: Res<G>, settings: Option<Res<QuadIndexBufferSettings>>| {
+            let settings = settings.map_or_else(Default::default, |settings| *settings);
+            match QuadIndexBuffer::<G>::try_new(&ctx, settings.indices, settings.kind) {
+                Ok(buffer) => buffer,
+                Err(e) => panic!("{e}"),
+            }
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_odd_size_projection() {
+        let size = Size::new(601, 401);
+
+        let (offset, scale) = NdcProjection::Center.offset_and_scale(size);
+        assert_eq!(offset, [0., 0.]);
+        assert_eq!(scale, [1. / 300.5, 1. / 200.5]);
+
+        let (offset, scale) = NdcProjection::TopLeft.offset_and_scale(size);
+        assert_eq!(offset, [-300.5, 200.5]);
+        assert_eq!(scale, [1. / 300.5, 1. / 200.5]);
+    }
+
+    #[test]
+    #[should_panic(expected = "use U32 indices")]
+    fn test_plugin_rejects_index_overflow() {
+        use yapgeir_graphics_hal::index_buffer::IndexKind;
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let mut realm = Realm::default();
+        realm
+            .add_resource(Null::new(NullBackend::new((1, 1))))
+            .add_resource(QuadIndexBufferSettings {
+                indices: 100_000,
+                kind: IndexKind::U16,
+            })
+            .add_plugin(plugin::<Null>);
+    }
 }
@@ -0,0 +1,334 @@
+use std::rc::Rc;
+
+use yapgeir_geometry::Rect;
+use yapgeir_graphics_hal::{
+    buffer::{BufferKind, BufferUsage},
+    draw_descriptor::{AsVertexBindings, IndexBinding},
+    draw_params::{Blend, DrawParameters},
+    frame_buffer::{DepthStencilAttachment, FrameBuffer, Indices},
+    index_buffer::PrimitiveMode,
+    sampler::{Sampler, SamplerState},
+    samplers::SamplerAttribute,
+    shader::TextShaderSource,
+    texture::PixelFormat,
+    Graphics, Rgba, Size,
+};
+
+use crate::{
+    post_process::{POST_PROCESS_VERTEX_SHADER, SCREEN_QUAD},
+    quad_index_buffer::QuadIndexBuffer,
+    sprite_renderer::{DrawRegion, SpriteRenderer, TextureRegion},
+    NdcProjection,
+};
+
+/// Draws the light buffer over the whole target.
+const LIGHT_BUFFER_SHADER: TextShaderSource = TextShaderSource {
+    vertex: POST_PROCESS_VERTEX_SHADER,
+    fragment: r#"
+        #version 120
+
+        #ifdef WEB
+        precision highp float;
+        #endif
+
+        uniform sampler2D tex;
+        varying vec2 v_tex_position;
+
+        void main() {
+            gl_FragColor = texture2D(tex, v_tex_position);
+        }
+    "#,
+    defines: &[],
+};
+
+/// Size of the generated falloff texture in pixels.
+const FALLOFF_SIZE: u32 = 64;
+
+/// A radial light in world space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub position: [f32; 2],
+    /// Distance from the position at which the light fades out completely.
+    pub radius: f32,
+    /// Color of the light, with alpha being its intensity.
+    pub color: Rgba<u8>,
+}
+
+/// Intensity of a light at a distance relative to its radius.
+fn falloff(distance: f32) -> f32 {
+    let d = (1. - distance).max(0.);
+    d * d
+}
+
+/// White RGBA pixels with the falloff from the center of the texture in the alpha channel.
+fn falloff_pixels() -> Vec<u8> {
+    let center = FALLOFF_SIZE as f32 / 2.;
+
+    (0..FALLOFF_SIZE * FALLOFF_SIZE)
+        .flat_map(|i| {
+            let x = (i % FALLOFF_SIZE) as f32 + 0.5 - center;
+            let y = (i / FALLOFF_SIZE) as f32 + 0.5 - center;
+            let alpha = (falloff(x.hypot(y) / center) * u8::MAX as f32).round() as u8;
+            [u8::MAX, u8::MAX, u8::MAX, alpha]
+        })
+        .collect()
+}
+
+struct LightBuffer<G: Graphics> {
+    texture: Rc<G::Texture>,
+    frame_buffer: G::FrameBuffer,
+}
+
+impl<G: Graphics> LightBuffer<G> {
+    fn new(ctx: &G, size: Size<u32>) -> Self {
+        let texture = Rc::new(ctx.new_texture_empty(PixelFormat::Rgba, size));
+        let frame_buffer = ctx.new_frame_buffer(texture.clone(), DepthStencilAttachment::None);
+
+        Self {
+            texture,
+            frame_buffer,
+        }
+    }
+}
+
+/// Renders 2D lighting: lights are added together in an offscreen light buffer,
+/// which is then multiplied over the scene.
+///
+/// The light buffer has 8 bits per channel, so the accumulated light saturates at full brightness
+/// and can only darken the scene.
+pub struct LightRenderer<G: Graphics> {
+    ctx: G,
+    sprites: SpriteRenderer<G>,
+    falloff: G::Texture,
+
+    light_buffer: LightBuffer<G>,
+    size: Size<u32>,
+
+    draw_descriptor: G::DrawDescriptor,
+    draw_parameters: DrawParameters,
+}
+
+impl<G: Graphics> LightRenderer<G> {
+    /// Create a light renderer with a light buffer of a given size, which should be
+    /// the size of the scene frame buffer.
+    pub fn new(ctx: &G, quad_index_buffer: QuadIndexBuffer<G>, size: Size<u32>) -> Self {
+        let mut sprites = SpriteRenderer::new(ctx, quad_index_buffer);
+        sprites.set_blend(Some(Blend::additive()));
+
+        let falloff = ctx.new_texture(
+            PixelFormat::Rgba,
+            Size::new(FALLOFF_SIZE, FALLOFF_SIZE),
+            Some(&falloff_pixels()),
+        );
+
+        let vertices = ctx.new_buffer(BufferKind::Vertex, BufferUsage::Static, &SCREEN_QUAD);
+        let draw_descriptor = ctx.new_draw_descriptor(
+            Rc::new(ctx.new_shader(&LIGHT_BUFFER_SHADER)),
+            IndexBinding::None,
+            &[vertices.bindings()],
+        );
+
+        Self {
+            ctx: ctx.clone(),
+            sprites,
+            falloff,
+            light_buffer: LightBuffer::new(ctx, size),
+            size,
+            draw_descriptor,
+            draw_parameters: DrawParameters {
+                blend: Some(Blend::multiply()),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns the frame buffer lights are accumulated in.
+    pub fn light_buffer(&self) -> &G::FrameBuffer {
+        &self.light_buffer.frame_buffer
+    }
+
+    /// Clear the light buffer to the `ambient` light, and add the lights to it.
+    ///
+    /// The light buffer is recreated if `size` has changed, e.g. when `WindowSize` changes.
+    /// See `SpriteRenderer::start_batch` for the description of `view_camera` and `projection`,
+    /// which should be the same as for the scene sprites.
+    pub fn draw_lights(
+        &mut self,
+        size: Size<u32>,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+        ambient: Rgba<f32>,
+        lights: impl IntoIterator<Item = Light>,
+    ) {
+        if size != self.size {
+            self.light_buffer = LightBuffer::new(&self.ctx, size);
+            self.size = size;
+        }
+
+        let frame_buffer = &self.light_buffer.frame_buffer;
+        frame_buffer.clear(None, Some(ambient), None, None);
+
+        let sampler = Sampler::new(&self.falloff, SamplerState::linear());
+        let mut batch = self
+            .sprites
+            .start_batch(frame_buffer, view_camera, projection, sampler);
+
+        for light in lights {
+            let [x, y] = light.position;
+            let diameter = light.radius * 2.;
+            let region = Rect::new(x - light.radius, y - light.radius, diameter, diameter);
+            batch.draw_sprite_tinted(
+                DrawRegion::Rect(region),
+                TextureRegion::Full,
+                0,
+                light.color,
+            );
+        }
+    }
+
+    /// Multiply the scene rendered to `target` by the light buffer.
+    pub fn finish(&self, target: &G::FrameBuffer) {
+        target.draw(
+            &self.draw_descriptor,
+            &self.draw_parameters,
+            &[SamplerAttribute {
+                name: "tex",
+                location: 0,
+                sampler: Sampler::new(&*self.light_buffer.texture, SamplerState::nearest()),
+            }],
+            &[],
+            &Indices {
+                mode: PrimitiveMode::TriangleStrip,
+                offset: 0,
+                len: SCREEN_QUAD.len(),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal::frame_buffer::ReadFormat;
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+    use crate::test_programs;
+
+    const CAMERA: [[f32; 3]; 3] = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+    fn renderer(graphics: &Null, size: Size<u32>) -> LightRenderer<Null> {
+        test_programs::register_sprites(graphics);
+        test_programs::register_copy(graphics, &LIGHT_BUFFER_SHADER);
+        LightRenderer::new(graphics, QuadIndexBuffer::with_quads(graphics, 16), size)
+    }
+
+    /// Reads a pixel of the light buffer at a pixel position with (0; 0) in the top-left corner.
+    fn read_light(renderer: &LightRenderer<Null>, [x, y]: [u32; 2]) -> [u8; 4] {
+        let light_buffer = renderer.light_buffer();
+        let y = light_buffer.size().h - 1 - y;
+
+        let mut pixel = [0; 4];
+        light_buffer.read(Rect::new(x, y, 1, 1), ReadFormat::Rgba, &mut pixel);
+        pixel
+    }
+
+    #[test]
+    fn test_overlapping_lights_sum() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+        let size = Size::new(20, 2);
+        let mut renderer = renderer(&graphics, size);
+
+        let ambient = Rgba::new(0.1, 0.1, 0.1, 1.);
+        let light = Light {
+            position: [-4., 0.],
+            radius: 10.,
+            color: Rgba::new(255, 102, 0, 255),
+        };
+        let other = Light {
+            position: [5., 0.],
+            ..light
+        };
+
+        // The top-left pixel of the center of the buffer is at (0.5; -0.5) in world space,
+        // so it's at 0.45 of the radius of both lights.
+        let mut light_at = |lights: &[Light], pixel| {
+            renderer.draw_lights(
+                size,
+                CAMERA,
+                NdcProjection::Center,
+                ambient,
+                lights.to_vec(),
+            );
+            read_light(&renderer, pixel).map(|c| c as i32)
+        };
+        let [r, g, b, a] = light_at(&[light], [10, 0]);
+        assert_eq!([b, a], [26, 255]);
+        // The light is added with its falloff, within the precision of the falloff texture.
+        let added = |c: f32| (c * falloff(0.45) * 255.) as i32;
+        assert!((r - 26 - added(1.)).abs() <= 3, "{r}");
+        assert!((g - 26 - added(0.4)).abs() <= 3, "{g}");
+
+        // Overlapping lights are summed, up to rounding of each of them.
+        let other_only = light_at(&[other], [10, 0]);
+        let both = light_at(&[light, other], [10, 0]);
+        let sum = [r + other_only[0] - 26, g + other_only[1] - 26, 26, 255];
+        assert!(
+            both.iter().zip(sum).all(|(a, b)| (a - b).abs() <= 1),
+            "{both:?}"
+        );
+
+        // Lights don't reach beyond their radius.
+        assert_eq!(light_at(&[other], [0, 0]), [26, 26, 26, 255]);
+    }
+
+    #[test]
+    fn test_falloff_texture() {
+        let pixels = falloff_pixels();
+        let alpha = |x: u32, y: u32| pixels[((y * FALLOFF_SIZE + x) * 4 + 3) as usize];
+
+        let center = FALLOFF_SIZE / 2;
+        assert!(alpha(center, center) > 240);
+        assert_eq!(alpha(0, 0), 0);
+        assert_eq!(alpha(center, 0), alpha(0, center));
+    }
+
+    #[test]
+    fn test_draw_lights() {
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        let mut renderer = renderer(&graphics, Size::new(4, 4));
+
+        let light = Light {
+            position: [-1., -1.],
+            radius: 1.,
+            color: Rgba::all(255),
+        };
+        let ambient = Rgba::new(0.2, 0.4, 0.6, 1.);
+        renderer.draw_lights(
+            Size::new(2, 2),
+            CAMERA,
+            NdcProjection::Center,
+            ambient,
+            [light, light],
+        );
+
+        // Both lights are drawn in a single batch.
+        assert_eq!(graphics.draw_calls(), 1);
+
+        // Light buffer is recreated with the new size. The lights only cover the top-left pixel.
+        let light_buffer = renderer.light_buffer();
+        assert_eq!(light_buffer.size(), Size::new(2, 2));
+        let lit = read_light(&renderer, [0, 0]);
+        assert!(lit[..3] > [51, 102, 153][..], "{lit:?}");
+        assert_eq!(read_light(&renderer, [1, 1]), [51, 102, 153, 255]);
+
+        // The scene is multiplied by the light buffer.
+        let fb = graphics.default_frame_buffer();
+        fb.clear(None, Some(Rgba::new(1., 0.5, 1., 1.)), None, None);
+        renderer.finish(&fb);
+        assert_eq!(graphics.draw_calls(), 2);
+
+        let mut pixel = [0; 4];
+        fb.read(Rect::new(3, 0, 1, 1), ReadFormat::Rgba, &mut pixel);
+        assert_eq!(pixel, [51, 51, 153, 255]);
+    }
+}
@@ -0,0 +1,341 @@
+use std::rc::Rc;
+
+use bytemuck::{Pod, Zeroable};
+use yapgeir_geometry::Rect;
+use yapgeir_graphics_hal::{
+    buffer::{Buffer, BufferKind, BufferUsage},
+    draw_descriptor::{AsVertexBindings, IndexBinding},
+    draw_params::DrawParameters,
+    frame_buffer::{Attachment, DepthStencilAttachment, FlipSource, FrameBuffer, Indices},
+    index_buffer::PrimitiveMode,
+    render_buffer::RenderBufferFormat,
+    sampler::{Filter, Sampler, SamplerState},
+    samplers::SamplerAttribute,
+    shader::TextShaderSource,
+    texture::PixelFormat,
+    uniforms::Uniforms,
+    vertex_buffer::Vertex,
+    Graphics, Size,
+};
+
+/// A vertex shader for post processing passes, drawing a full screen quad.
+///
+/// Passes texture coordinates of the screen to the fragment shader in `v_tex_position`.
+/// The fragment shader should sample the previous pass result from a `tex` sampler.
+pub const POST_PROCESS_VERTEX_SHADER: &str = r#"
+    #version 120
+
+    attribute vec2 position;
+    varying vec2 v_tex_position;
+
+    void main() {
+        v_tex_position = position * 0.5 + vec2(0.5);
+        gl_Position = vec4(position, 0.0, 1.0);
+    }
+"#;
+
+#[repr(C)]
+#[derive(Copy, Clone, Default, Zeroable, Pod, Vertex)]
+pub(crate) struct ScreenVertex {
+    position: [f32; 2],
+}
+
+pub(crate) const SCREEN_QUAD: [ScreenVertex; 4] = [
+    ScreenVertex {
+        position: [-1., -1.],
+    },
+    ScreenVertex {
+        position: [-1., 1.],
+    },
+    ScreenVertex {
+        position: [1., -1.],
+    },
+    ScreenVertex { position: [1., 1.] },
+];
+
+/// Defines how the post processed image is placed on the target frame buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlitArea {
+    /// Stretch the image over the whole target.
+    Stretch,
+    /// Scale the image to fit the target, centering it and leaving the rest of the target untouched.
+    PreserveAspectRatio,
+    /// Stretch the image over a rectangle of the target in pixels, with (0; 0) in the top-left corner.
+    Custom(Rect<u32>),
+}
+
+impl BlitArea {
+    /// Returns the destination rectangle of a `source` image on a `target` frame buffer,
+    /// with (0; 0) in the bottom-left corner like in `FrameBuffer::blit`.
+    pub fn destination(self, source: Size<u32>, target: Size<u32>) -> Rect<u32> {
+        match self {
+            BlitArea::Stretch => target.into(),
+            BlitArea::Custom(rect) => Rect::new(
+                rect.x,
+                target.h.saturating_sub(rect.y + rect.h),
+                rect.w,
+                rect.h,
+            ),
+            BlitArea::PreserveAspectRatio => {
+                let scale =
+                    (target.w as f32 / source.w as f32).min(target.h as f32 / source.h as f32);
+                let w = ((source.w as f32 * scale) as u32).min(target.w);
+                let h = ((source.h as f32 * scale) as u32).min(target.h);
+                Rect::new((target.w - w) / 2, (target.h - h) / 2, w, h)
+            }
+        }
+    }
+}
+
+trait Pass<G: Graphics> {
+    fn draw(&self, target: &G::FrameBuffer, source: &G::Texture, draw_parameters: &DrawParameters);
+}
+
+struct ShaderPass<G: Graphics, U: Uniforms + Pod> {
+    draw_descriptor: G::DrawDescriptor,
+    uniforms: Rc<G::UniformBuffer<U>>,
+}
+
+impl<G: Graphics, U: Uniforms + Pod> Pass<G> for ShaderPass<G, U> {
+    fn draw(&self, target: &G::FrameBuffer, source: &G::Texture, draw_parameters: &DrawParameters) {
+        target.draw(
+            &self.draw_descriptor,
+            draw_parameters,
+            &[SamplerAttribute {
+                name: "tex",
+                location: 0,
+                sampler: Sampler::new(source, SamplerState::nearest()),
+            }],
+            &[&*self.uniforms],
+            &Indices {
+                mode: PrimitiveMode::TriangleStrip,
+                offset: 0,
+                len: SCREEN_QUAD.len(),
+            },
+        );
+    }
+}
+
+/// Returns `(read, write)` buffer indices of each pass, starting by reading the scene from the first buffer.
+fn ping_pong(passes: usize) -> impl Iterator<Item = (usize, usize)> {
+    (0..passes).map(|i| (i % 2, (i + 1) % 2))
+}
+
+struct Target<G: Graphics> {
+    texture: Rc<G::Texture>,
+    frame_buffer: G::FrameBuffer,
+}
+
+impl<G: Graphics> Target<G> {
+    fn new(ctx: &G, size: Size<u32>) -> Self {
+        let texture = Rc::new(ctx.new_texture_empty(PixelFormat::Rgba, size));
+        let depth_stencil = Rc::new(ctx.new_render_buffer(size, RenderBufferFormat::DepthStencil));
+        let frame_buffer = ctx.new_frame_buffer(
+            texture.clone(),
+            DepthStencilAttachment::DepthStencil(Attachment::RenderBuffer(depth_stencil)),
+        );
+
+        Self {
+            texture,
+            frame_buffer,
+        }
+    }
+}
+
+/// Renders a scene into an offscreen frame buffer, and applies a chain of full screen
+/// shader passes to it, e.g. bloom or a CRT effect.
+///
+/// Passes render into a ping-pong pair of textures, each reading the result of the previous pass.
+/// The final result is blitted to the target frame buffer.
+pub struct PostProcess<G: Graphics> {
+    ctx: G,
+    targets: [Target<G>; 2],
+    size: Size<u32>,
+
+    vertices: Buffer<G, ScreenVertex>,
+    passes: Vec<Box<dyn Pass<G>>>,
+    draw_parameters: DrawParameters,
+}
+
+impl<G: Graphics> PostProcess<G> {
+    pub fn new(ctx: &G, size: Size<u32>) -> Self {
+        Self {
+            ctx: ctx.clone(),
+            targets: [Target::new(ctx, size), Target::new(ctx, size)],
+            size,
+            vertices: ctx.new_buffer(BufferKind::Vertex, BufferUsage::Static, &SCREEN_QUAD),
+            passes: Vec::new(),
+            draw_parameters: Default::default(),
+        }
+    }
+
+    /// Add a full screen shader pass, running after all previously added passes.
+    ///
+    /// The shader should use `POST_PROCESS_VERTEX_SHADER` as the vertex shader.
+    /// Returns the uniform buffer of the pass, which can be updated between frames.
+    pub fn add_pass<U: Uniforms + Pod>(
+        &mut self,
+        shader: &TextShaderSource,
+        uniforms: U,
+    ) -> Rc<G::UniformBuffer<U>> {
+        let uniforms = Rc::new(self.ctx.new_uniform_buffer(&uniforms));
+        let draw_descriptor = self.ctx.new_draw_descriptor(
+            Rc::new(self.ctx.new_shader(shader)),
+            IndexBinding::None,
+            &[self.vertices.bindings()],
+        );
+
+        self.passes.push(Box::new(ShaderPass::<G, U> {
+            draw_descriptor,
+            uniforms: uniforms.clone(),
+        }));
+
+        uniforms
+    }
+
+    /// Returns the frame buffer the scene should be rendered to.
+    ///
+    /// Frame buffers are recreated if `size` has changed, e.g. when `WindowSize` changes.
+    pub fn frame_buffer(&mut self, size: Size<u32>) -> &G::FrameBuffer {
+        if size != self.size {
+            self.targets = [Target::new(&self.ctx, size), Target::new(&self.ctx, size)];
+            self.size = size;
+        }
+
+        &self.targets[0].frame_buffer
+    }
+
+    /// Run all passes over the rendered scene, and blit the result to `target`.
+    pub fn finish(&self, target: &G::FrameBuffer, area: BlitArea) {
+        let mut result = 0;
+        for (pass, (read, write)) in self.passes.iter().zip(ping_pong(self.passes.len())) {
+            pass.draw(
+                &self.targets[write].frame_buffer,
+                &self.targets[read].texture,
+                &self.draw_parameters,
+            );
+            result = write;
+        }
+
+        target.blit(
+            &self.targets[result].frame_buffer,
+            self.size.into(),
+            area.destination(self.size, target.size()),
+            FlipSource::None,
+            Filter::Linear,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal::{frame_buffer::ReadFormat, Rgba};
+    use yapgeir_graphics_hal_null::{Null, NullBackend, NullProgram, ProgramInputs, VertexInputs};
+
+    use super::*;
+
+    #[test]
+    fn test_ping_pong() {
+        let passes: Vec<_> = ping_pong(2).collect();
+        assert_eq!(passes, [(0, 1), (1, 0)]);
+
+        assert_eq!(ping_pong(3).last(), Some((0, 1)));
+        assert_eq!(ping_pong(0).count(), 0);
+    }
+
+    /// A pass adding a color to the result of the previous pass.
+    struct AddProgram(Rgba<f32>);
+
+    impl NullProgram for AddProgram {
+        fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+            let [x, y] = vertex.get("position");
+            ([x, y, 0., 1.], vec![x * 0.5 + 0.5, y * 0.5 + 0.5])
+        }
+
+        fn fragment(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+            let source: [f32; 4] = inputs.sample("tex", [varyings[0], varyings[1]]).into();
+            let add: [f32; 4] = self.0.into();
+            Some(std::array::from_fn::<_, 4, _>(|i| (source[i] + add[i]).min(1.)).into())
+        }
+    }
+
+    #[test]
+    fn test_two_passes_are_chained() {
+        let pass = |fragment| TextShaderSource {
+            vertex: POST_PROCESS_VERTEX_SHADER,
+            fragment,
+            defines: &[],
+        };
+        let (red, green) = (pass("add red"), pass("add green"));
+
+        let graphics = Null::new(NullBackend::new((4, 4)));
+        graphics.register_program(&red, AddProgram(Rgba::new(1., 0., 0., 1.)));
+        graphics.register_program(&green, AddProgram(Rgba::new(0., 1., 0., 1.)));
+        let target = graphics.default_frame_buffer();
+        target.clear(None, Some(Rgba::new(0., 0., 1., 1.)), None, None);
+
+        let mut post_process = PostProcess::new(&graphics, Size::new(4, 4));
+        post_process.add_pass(&red, ());
+        post_process.add_pass(&green, ());
+        let scene = post_process.frame_buffer(Size::new(4, 4));
+        scene.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+        post_process.finish(&target, BlitArea::Stretch);
+
+        // The second pass read the result of the first one, and its result was blitted.
+        let mut pixels = vec![0; 4 * 4 * 4];
+        target.read(Rect::new(0, 0, 4, 4), ReadFormat::Rgba, &mut pixels);
+        for pixel in pixels.chunks_exact(4) {
+            assert_eq!(pixel, [255, 255, 0, 255]);
+        }
+    }
+
+    #[test]
+    fn test_preserve_aspect_ratio() {
+        let area = BlitArea::PreserveAspectRatio;
+        assert_eq!(
+            area.destination(Size::new(320, 180), Size::new(800, 600)),
+            Rect::new(0, 75, 800, 450)
+        );
+        assert_eq!(
+            area.destination(Size::new(100, 100), Size::new(800, 600)),
+            Rect::new(100, 0, 600, 600)
+        );
+        assert_eq!(
+            BlitArea::Stretch.destination(Size::new(100, 100), Size::new(800, 600)),
+            Rect::new(0, 0, 800, 600)
+        );
+    }
+
+    #[test]
+    fn test_custom_area() {
+        let area = BlitArea::Custom(Rect::new(10, 20, 30, 40));
+        assert_eq!(
+            area.destination(Size::new(100, 100), Size::new(800, 600)),
+            Rect::new(10, 540, 30, 40)
+        );
+    }
+
+    #[test]
+    fn test_blit_to_custom_area() {
+        let graphics = Null::new(NullBackend::new((8, 6)));
+        let target = graphics.default_frame_buffer();
+        target.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+
+        let mut post_process = PostProcess::new(&graphics, Size::new(4, 4));
+        let scene = post_process.frame_buffer(Size::new(4, 4));
+        scene.clear(None, Some(Rgba::new(1., 0., 0., 1.)), None, None);
+        post_process.finish(&target, BlitArea::Custom(Rect::new(1, 1, 3, 2)));
+
+        // Rows are read bottom-up, so the top-left rectangle is at rows 3 and 4.
+        let mut pixels = vec![0; 8 * 6 * 4];
+        target.read(Rect::new(0, 0, 8, 6), ReadFormat::Rgba, &mut pixels);
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let (x, row) = (i % 8, i / 8);
+            let expected = match (1..4).contains(&x) && (3..5).contains(&row) {
+                true => [255, 0, 0, 255],
+                false => [0, 0, 0, 255],
+            };
+            assert_eq!(pixel, expected, "({x}; {row})");
+        }
+    }
+}
@@ -2,18 +2,29 @@ use std::rc::Rc;
 
 use crate::batch_renderer::{Batch, BatchIndices, BatchRenderer};
 use bytemuck::{Pod, Zeroable};
-use yapgeir_geometry::Rect;
+use yapgeir_geometry::{Rect, Size};
 use yapgeir_graphics_hal::{
-    draw_params::DrawParameters, index_buffer::PrimitiveMode, shader::TextShaderSource,
-    uniforms::Uniforms, vertex_buffer::Vertex, Graphics, Rgba,
+    buffer::{Buffer, BufferKind, BufferUsage},
+    draw_descriptor::AsVertexBindings,
+    draw_params::DrawParameters,
+    frame_buffer::{FrameBuffer, Indices},
+    index_buffer::PrimitiveMode,
+    samplers::SamplerAttribute,
+    shader::TextShaderSource,
+    uniforms::{UniformBuffer, Uniforms},
+    vertex_buffer::Vertex,
+    Graphics, Rgba,
 };
 
+use crate::NdcProjection;
+
 #[cfg(not(target_os = "vita"))]
-const SHADER: TextShaderSource = TextShaderSource {
+pub(crate) const SHADER: TextShaderSource = TextShaderSource {
     vertex: r#"
         #version 120
         
         uniform mat3 view_projection;
+        uniform float point_size;
 
         attribute vec2 position;
         attribute vec4 color;
@@ -23,6 +34,7 @@ const SHADER: TextShaderSource = TextShaderSource {
         void main() {
             o_color = color;
             gl_Position = vec4(view_projection * vec3(position, 1.0), 1.0);
+            gl_PointSize = point_size;
 
             // Flip Y axis in the UV
             gl_Position.y = -gl_Position.y;
@@ -41,21 +53,25 @@ const SHADER: TextShaderSource = TextShaderSource {
             gl_FragColor = o_color;
         }
     "#,
+    defines: &[],
 };
 
 #[cfg(target_os = "vita")]
-const SHADER: TextShaderSource = TextShaderSource {
+pub(crate) const SHADER: TextShaderSource = TextShaderSource {
     vertex: r#"
         uniform float3x3 view_projection;
+        uniform float point_size;
 
         void main(
             float2 position,
             float4 color,
             float4 out o_color : COLOR1,
-            float4 out gl_Position : POSITION
+            float4 out gl_Position : POSITION,
+            float out gl_PointSize : PSIZE
         ) {
             o_color = color;
             gl_Position = float4(mul(view_projection, float3(position, 1.0f)), 1.0f);
+            gl_PointSize = point_size;
         }
     "#,
     fragment: r#"
@@ -63,6 +79,7 @@ const SHADER: TextShaderSource = TextShaderSource {
             return o_color;
         }
     "#,
+    defines: &[],
 };
 
 #[repr(C)]
@@ -76,6 +93,8 @@ pub struct PrimitiveVertex {
 #[derive(Copy, Clone, Default, Zeroable, Pod, Uniforms)]
 pub struct PrimitiveUniforms {
     pub view_projection: [[f32; 3]; 3],
+    /// Size of points in pixels, only used when drawing `PrimitiveMode::Points`.
+    pub point_size: f32,
 }
 
 pub struct PrimitiveBatch<'a, G: Graphics> {
@@ -108,24 +127,43 @@ impl<G: Graphics> PrimitiveBatch<'_, G> {
     }
 }
 
+pub struct PointBatch<'a, G: Graphics> {
+    batch: Batch<'a, G, PrimitiveVertex, PrimitiveUniforms>,
+}
+
+impl<G: Graphics> PointBatch<'_, G> {
+    pub fn draw_point(&mut self, position: [f32; 2], color: Rgba<f32>) {
+        self.batch.draw(&[PrimitiveVertex {
+            position,
+            color: color.into(),
+        }]);
+    }
+}
+
 pub struct PrimitiveRenderer<G: Graphics> {
     renderer: BatchRenderer<G, PrimitiveVertex, PrimitiveUniforms>,
+    points: BatchRenderer<G, PrimitiveVertex, PrimitiveUniforms>,
 }
 
 impl<G: Graphics> PrimitiveRenderer<G> {
     pub fn new<'a>(ctx: &G) -> Self {
-        let shader = Rc::new(ctx.new_shader(&SHADER.into()));
+        let shader = Rc::new(ctx.new_shader(&SHADER));
         let uniforms = Rc::new(ctx.new_uniform_buffer(&PrimitiveUniforms::default()));
 
-        let renderer = BatchRenderer::new(
-            ctx,
-            shader.clone(),
-            BatchIndices::Primitive(PrimitiveMode::Lines),
-            uniforms.clone(),
-            (u16::MAX as usize, 1),
-        );
+        let renderer = |mode| {
+            BatchRenderer::new(
+                ctx,
+                shader.clone(),
+                BatchIndices::Primitive(mode),
+                uniforms.clone(),
+                (u16::MAX as usize, 1),
+            )
+        };
 
-        Self { renderer }
+        Self {
+            renderer: renderer(PrimitiveMode::Lines),
+            points: renderer(PrimitiveMode::Points),
+        }
     }
 
     pub fn start_batch<'a>(
@@ -138,7 +176,31 @@ impl<G: Graphics> PrimitiveRenderer<G> {
             batch: self.renderer.start_batch(
                 frame_buffer,
                 &draw_parameters,
-                &PrimitiveUniforms { view_projection },
+                &PrimitiveUniforms {
+                    view_projection,
+                    point_size: 1.,
+                },
+                [],
+            ),
+        }
+    }
+
+    /// Create a new batch drawing square points of `point_size` pixels.
+    pub fn start_point_batch<'a>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_projection: [[f32; 3]; 3],
+        draw_parameters: &'a DrawParameters,
+        point_size: f32,
+    ) -> PointBatch<'a, G> {
+        PointBatch {
+            batch: self.points.start_batch(
+                frame_buffer,
+                draw_parameters,
+                &PrimitiveUniforms {
+                    view_projection,
+                    point_size,
+                },
                 [],
             ),
         }
@@ -156,3 +218,351 @@ impl<G: Graphics> PrimitiveRenderer<G> {
         draw(&mut batch);
     }
 }
+
+/// Combine a camera matrix transforming world space to pixel space with an NDC projection.
+fn view_projection(
+    view_camera: [[f32; 3]; 3],
+    projection: NdcProjection,
+    size: Size<u32>,
+) -> [[f32; 3]; 3] {
+    let ([ox, oy], [sx, sy]) = projection.offset_and_scale(size);
+
+    // Matrices are column-major, and the projection is `(px + offset) * scale`.
+    view_camera.map(|[x, y, z]| [(x + ox * z) * sx, (y + oy * z) * sy, z])
+}
+
+/// Shapes buffered for the next `DebugShapes::draw` call.
+#[derive(Default)]
+struct ShapeBuffer {
+    /// Pairs of line segment vertices.
+    lines: Vec<PrimitiveVertex>,
+    /// Vertices of all closed line loops.
+    loops: Vec<PrimitiveVertex>,
+    /// Index in `loops` where each loop ends.
+    loop_ends: Vec<usize>,
+}
+
+impl ShapeBuffer {
+    fn line(&mut self, start: [f32; 2], end: [f32; 2], color: Rgba<f32>) {
+        let color = color.into();
+        self.lines.extend([
+            PrimitiveVertex {
+                position: start,
+                color,
+            },
+            PrimitiveVertex {
+                position: end,
+                color,
+            },
+        ]);
+    }
+
+    fn line_loop(&mut self, points: impl IntoIterator<Item = [f32; 2]>, color: Rgba<f32>) {
+        let color = color.into();
+        self.loops.extend(
+            points
+                .into_iter()
+                .map(|position| PrimitiveVertex { position, color }),
+        );
+        self.loop_ends.push(self.loops.len());
+    }
+
+    fn circle(&mut self, center: [f32; 2], radius: f32, segments: usize, color: Rgba<f32>) {
+        let step = std::f32::consts::TAU / segments as f32;
+        let points = (0..segments).map(|i| {
+            let (sin, cos) = (i as f32 * step).sin_cos();
+            [center[0] + radius * cos, center[1] + radius * sin]
+        });
+
+        self.line_loop(points, color);
+    }
+
+    /// Splits the loops into chunks of at most `max_vertices` vertices, and calls `draw`
+    /// with the vertices of each chunk and the indices of line segments connecting them.
+    fn for_each_loop_chunk(
+        &self,
+        max_vertices: usize,
+        indices: &mut Vec<u16>,
+        mut draw: impl FnMut(&[PrimitiveVertex], &[u16]),
+    ) {
+        indices.clear();
+        let (mut chunk_start, mut loop_start) = (0, 0);
+
+        for &end in &self.loop_ends {
+            let len = end - loop_start;
+            assert!(
+                len <= max_vertices,
+                "A line loop of {len} vertices exceeds the limit of {max_vertices}"
+            );
+
+            if end - chunk_start > max_vertices {
+                draw(&self.loops[chunk_start..loop_start], indices);
+                indices.clear();
+                chunk_start = loop_start;
+            }
+
+            let base = loop_start - chunk_start;
+            for i in 0..len {
+                indices.extend([base + i, base + (i + 1) % len].map(|i| i as u16));
+            }
+            loop_start = end;
+        }
+
+        if !indices.is_empty() {
+            draw(&self.loops[chunk_start..loop_start], indices);
+        }
+    }
+
+    fn clear(&mut self) {
+        self.lines.clear();
+        self.loops.clear();
+        self.loop_ends.clear();
+    }
+}
+
+/// Maximum number of line loop vertices drawn with a single draw call,
+/// so that they can be addressed by `u16` indices.
+const MAX_LOOP_VERTICES: usize = u16::MAX as usize;
+
+/// An immediate mode helper for debug drawing, e.g. collider AABBs or velocity vectors.
+///
+/// Shapes are buffered in world space, and drawn on top of everything else with `draw`.
+pub struct DebugShapes<G: Graphics> {
+    shapes: ShapeBuffer,
+    uniforms: Rc<G::UniformBuffer<PrimitiveUniforms>>,
+    lines: BatchRenderer<G, PrimitiveVertex, PrimitiveUniforms>,
+
+    // Line loops are drawn as indexed lines, so that all of them fit a single draw call.
+    loop_vertices: Buffer<G, PrimitiveVertex>,
+    loop_indices: Buffer<G, u16>,
+    loop_draw_descriptor: G::DrawDescriptor,
+    indices: Vec<u16>,
+
+    draw_parameters: DrawParameters,
+}
+
+impl<G: Graphics> DebugShapes<G> {
+    pub fn new(ctx: &G) -> Self {
+        let shader = Rc::new(ctx.new_shader(&SHADER));
+        let uniforms = Rc::new(ctx.new_uniform_buffer(&PrimitiveUniforms::default()));
+
+        let lines = BatchRenderer::new(
+            ctx,
+            shader.clone(),
+            BatchIndices::Primitive(PrimitiveMode::Lines),
+            uniforms.clone(),
+            (u16::MAX as usize, 1),
+        );
+
+        let loop_vertices =
+            ctx.new_buffer(BufferKind::Vertex, BufferUsage::Stream, MAX_LOOP_VERTICES);
+        let loop_indices = ctx.new_buffer(
+            BufferKind::Index,
+            BufferUsage::Stream,
+            MAX_LOOP_VERTICES * 2,
+        );
+        let loop_draw_descriptor =
+            ctx.new_draw_descriptor(shader, Some(&loop_indices), &[loop_vertices.bindings()]);
+
+        Self {
+            shapes: Default::default(),
+            uniforms,
+            lines,
+            loop_vertices,
+            loop_indices,
+            loop_draw_descriptor,
+            indices: Vec::new(),
+            draw_parameters: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn line(&mut self, start: [f32; 2], end: [f32; 2], color: Rgba<f32>) {
+        self.shapes.line(start, end, color);
+    }
+
+    #[inline]
+    pub fn rect(&mut self, rect: Rect<f32>, color: Rgba<f32>) {
+        self.shapes.line_loop(rect.points(), color);
+    }
+
+    /// Buffer a circle, approximated with a line loop of `segments` vertices.
+    #[inline]
+    pub fn circle(&mut self, center: [f32; 2], radius: f32, segments: usize, color: Rgba<f32>) {
+        self.shapes.circle(center, radius, segments, color);
+    }
+
+    /// Discard all buffered shapes.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.shapes.clear();
+    }
+
+    /// Draw and clear all buffered shapes.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_buffer` - Frame buffer to draw to.
+    /// * `view_camera` - A camera matrix that will transform world space to pixel space,
+    ///   e.g. a `WorldCamera`.
+    /// * `projection` - Describes how pixels are projected to normalized display coordinates.
+    pub fn draw(
+        &mut self,
+        frame_buffer: &G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+    ) {
+        let uniforms = PrimitiveUniforms {
+            view_projection: view_projection(view_camera, projection, frame_buffer.size()),
+            point_size: 1.,
+        };
+
+        if !self.shapes.lines.is_empty() {
+            let mut batch: Batch<G, PrimitiveVertex, PrimitiveUniforms> =
+                self.lines
+                    .start_batch(frame_buffer, &self.draw_parameters, &uniforms, []);
+            batch.draw(&self.shapes.lines);
+        }
+
+        self.uniforms.write(&uniforms);
+        self.shapes.for_each_loop_chunk(
+            MAX_LOOP_VERTICES,
+            &mut self.indices,
+            |vertices, indices| {
+                self.loop_vertices.orphan();
+                self.loop_vertices.write(0, vertices);
+                self.loop_indices.orphan();
+                self.loop_indices.write(0, indices);
+
+                frame_buffer.draw(
+                    &self.loop_draw_descriptor,
+                    &self.draw_parameters,
+                    &[] as &[SamplerAttribute<G, G::Texture>],
+                    &[&*self.uniforms],
+                    &Indices::new(PrimitiveMode::Lines, 0, indices.len()),
+                );
+            },
+        );
+
+        self.shapes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal::frame_buffer::ReadFormat;
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+    use crate::test_programs;
+
+    #[test]
+    fn test_circle_vertices() {
+        let mut shapes = ShapeBuffer::default();
+        shapes.circle([10., 0.], 2., 16, Rgba::all(1.));
+        shapes.line_loop(Rect::new(0., 0., 1., 1.).points(), Rgba::all(1.));
+
+        assert_eq!(shapes.loop_ends, [16, 20]);
+        assert_eq!(shapes.loops[0].position, [12., 0.]);
+        assert!(shapes.lines.is_empty());
+
+        shapes.clear();
+        assert!(shapes.loops.is_empty());
+    }
+
+    #[test]
+    fn test_loop_chunks() {
+        let mut shapes = ShapeBuffer::default();
+        shapes.line_loop([[0., 0.], [1., 0.], [1., 1.]], Rgba::all(1.));
+        shapes.line_loop([[2., 0.], [3., 0.]], Rgba::all(1.));
+        shapes.line_loop([[4., 0.], [5., 0.], [5., 1.]], Rgba::all(1.));
+
+        let mut chunks = Vec::new();
+        shapes.for_each_loop_chunk(5, &mut Vec::new(), |vertices, indices| {
+            chunks.push((vertices[0].position, vertices.len(), indices.to_vec()))
+        });
+
+        // Loops are closed, and don't connect to each other.
+        assert_eq!(
+            chunks,
+            [
+                ([0., 0.], 5, vec![0, 1, 1, 2, 2, 0, 3, 4, 4, 3]),
+                ([4., 0.], 3, vec![0, 1, 1, 2, 2, 0]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_loops_single_draw_call() {
+        let graphics = Null::new(NullBackend::new((10, 10)));
+        let mut shapes = DebugShapes::new(&graphics);
+        let identity = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+        for i in 0..100 {
+            shapes.rect(Rect::new(i as f32, 0., 1., 1.), Rgba::all(1.));
+            shapes.circle([i as f32, 0.], 1., 16, Rgba::all(1.));
+        }
+        shapes.line([0., 0.], [1., 1.], Rgba::all(1.));
+        shapes.draw(
+            &graphics.default_frame_buffer(),
+            identity,
+            NdcProjection::Center,
+        );
+
+        // One draw call for the lines, and one for all the loops.
+        assert_eq!(graphics.draw_calls(), 2);
+    }
+
+    #[test]
+    fn test_point_size_uniform() {
+        let point_size = PrimitiveUniforms::FORMAT
+            .iter()
+            .find(|u| u.name == "point_size")
+            .unwrap();
+
+        assert_eq!(point_size.offset, std::mem::size_of::<[[f32; 3]; 3]>());
+        assert_eq!(point_size.size, std::mem::size_of::<f32>());
+    }
+
+    #[test]
+    fn test_draw_large_point() {
+        let graphics = Null::new(NullBackend::new((8, 8)));
+        test_programs::register_primitives(&graphics);
+        let fb = graphics.default_frame_buffer();
+        fb.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+
+        let mut renderer = PrimitiveRenderer::new(&graphics);
+        let identity = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let view_projection = view_projection(identity, NdcProjection::Center, Size::new(8, 8));
+        let draw_parameters = DrawParameters::default();
+        renderer
+            .start_point_batch(&fb, view_projection, &draw_parameters, 4.)
+            .draw_point([0., 0.], Rgba::new(1., 0., 0., 1.));
+
+        // The point covers the 4x4 pixels around its position.
+        let mut pixels = vec![0; 8 * 8 * 4];
+        fb.read(Rect::new(0, 0, 8, 8), ReadFormat::Rgba, &mut pixels);
+        for (i, pixel) in pixels.chunks_exact(4).enumerate() {
+            let (x, y) = (i % 8, i / 8);
+            let expected = match (2..6).contains(&x) && (2..6).contains(&y) {
+                true => [255, 0, 0, 255],
+                false => [0, 0, 0, 255],
+            };
+            assert_eq!(pixel, expected, "({x}; {y})");
+        }
+    }
+
+    #[test]
+    fn test_view_projection() {
+        let identity = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let m = view_projection(identity, NdcProjection::TopLeft, Size::new(200, 100));
+
+        // The top-left pixel is projected to the top-left corner of NDC (before Y flip).
+        let (x, y) = (0., 0.);
+        let ndc = [
+            m[0][0] * x + m[1][0] * y + m[2][0],
+            m[0][1] * x + m[1][1] * y + m[2][1],
+        ];
+        assert_eq!(ndc, [-1., 1.]);
+    }
+}
@@ -1,12 +1,71 @@
-use std::{fmt::Debug, num::TryFromIntError, rc::Rc};
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    num::TryFromIntError,
+    rc::Rc,
+};
 
 use yapgeir_graphics_hal::{
-    buffer::{BufferKind, BufferUsage},
+    buffer::{BufferKind, BufferUsage, ByteBuffer},
     draw_descriptor::IndexBinding,
     index_buffer::{Index, IndexKind},
     Graphics,
 };
 
+/// Size of the `QuadIndexBuffer` created by the renderer plugin.
+///
+/// Add this resource before the plugin to change the defaults.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuadIndexBufferSettings {
+    /// Number of indices, six per quad.
+    pub indices: usize,
+    pub kind: IndexKind,
+}
+
+impl Default for QuadIndexBufferSettings {
+    fn default() -> Self {
+        Self {
+            indices: 65532,
+            kind: IndexKind::U16,
+        }
+    }
+}
+
+/// An error returned when the vertices of the requested quads can't be addressed
+/// by the index type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuadIndexOverflow {
+    pub indices: usize,
+    pub kind: IndexKind,
+}
+
+impl Display for QuadIndexOverflow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { indices, kind } = self;
+        write!(
+            f,
+            "{indices} quad indices reference more vertices than {kind:?} indices can address, \
+            use U32 indices for larger quad buffers"
+        )
+    }
+}
+
+impl Error for QuadIndexOverflow {}
+
+/// Number of vertices which can be addressed by the index type.
+fn max_vertices(kind: IndexKind) -> u64 {
+    match kind {
+        IndexKind::U8 => u8::MAX as u64 + 1,
+        IndexKind::U16 => u16::MAX as u64 + 1,
+        IndexKind::U32 => u32::MAX as u64 + 1,
+    }
+}
+
+/// Maximum number of quad indices which can be addressed by the index type.
+fn max_quad_indices(kind: IndexKind) -> u64 {
+    max_vertices(kind) / 4 * 6
+}
+
 pub struct QuadIndexBuffer<G: Graphics> {
     pub buffer: Rc<G::ByteBuffer>,
     pub kind: IndexKind,
@@ -19,6 +78,17 @@ impl<G: Graphics> QuadIndexBuffer<G> {
             kind: self.kind,
         }
     }
+
+    /// Number of quads the buffer has indices for.
+    pub fn quads(&self) -> usize {
+        self.buffer.len() / self.kind.size() / 6
+    }
+
+    /// Number of vertices which can be addressed by the indices of the buffer,
+    /// which limits the size of a single batch.
+    pub fn max_vertices(&self) -> usize {
+        max_vertices(self.kind).min(usize::MAX as u64) as usize
+    }
 }
 
 impl<G: Graphics> Clone for QuadIndexBuffer<G> {
@@ -53,16 +123,110 @@ where
 }
 
 impl<G: Graphics> QuadIndexBuffer<G> {
+    /// Creates a buffer with `size` indices, panicking if they overflow the index type.
     pub fn new<I: Index + Into<usize> + TryFrom<usize, Error = TryFromIntError> + Debug>(
         ctx: &G,
         size: I,
     ) -> Self {
-        let indices = create_quad_indices::<I>(size.into()).expect("Unable to create quad indices");
-        let buffer = ctx.new_buffer(BufferKind::Index, BufferUsage::Static, &indices);
+        match Self::try_new(ctx, size.into(), I::KIND) {
+            Ok(buffer) => buffer,
+            Err(e) => panic!("{e}"),
+        }
+    }
 
-        Self {
-            buffer: buffer.bytes,
-            kind: I::KIND,
+    /// Creates a buffer with a given number of indices, six per quad.
+    ///
+    /// Returns an error if quad vertices can't be addressed by the `kind` of indices,
+    /// e.g. more than 16384 quads with `U16` indices.
+    pub fn try_new(ctx: &G, indices: usize, kind: IndexKind) -> Result<Self, QuadIndexOverflow> {
+        if indices as u64 > max_quad_indices(kind) {
+            return Err(QuadIndexOverflow { indices, kind });
+        }
+
+        let buffer = match kind {
+            IndexKind::U8 => Self::create_buffer::<u8>(ctx, indices),
+            IndexKind::U16 => Self::create_buffer::<u16>(ctx, indices),
+            IndexKind::U32 => Self::create_buffer::<u32>(ctx, indices),
+        };
+
+        Ok(Self { buffer, kind })
+    }
+
+    /// Creates a buffer for a given number of quads, with `U16` indices if they fit,
+    /// or `U32` indices otherwise.
+    pub fn with_quads(ctx: &G, quads: usize) -> Self {
+        let indices = quads * 6;
+        let kind = match indices as u64 <= max_quad_indices(IndexKind::U16) {
+            true => IndexKind::U16,
+            false => IndexKind::U32,
+        };
+
+        match Self::try_new(ctx, indices, kind) {
+            Ok(buffer) => buffer,
+            Err(e) => panic!("{e}"),
         }
     }
+
+    fn create_buffer<I>(ctx: &G, indices: usize) -> Rc<G::ByteBuffer>
+    where
+        I: Index + TryFrom<usize, Error = TryFromIntError>,
+    {
+        let indices = create_quad_indices::<I>(indices).expect("Indices were checked to fit");
+        ctx.new_buffer(BufferKind::Index, BufferUsage::Static, &indices)
+            .bytes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+
+    #[test]
+    fn test_index_overflow() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+
+        // 16384 quads have 65536 vertices, which is the whole u16 range.
+        let buffer = QuadIndexBuffer::try_new(&graphics, 16384 * 6, IndexKind::U16).unwrap();
+        assert_eq!(buffer.buffer.len(), 16384 * 6 * 2);
+
+        let error = QuadIndexBuffer::try_new(&graphics, 16385 * 6, IndexKind::U16).err();
+        assert_eq!(
+            error,
+            Some(QuadIndexOverflow {
+                indices: 16385 * 6,
+                kind: IndexKind::U16
+            })
+        );
+        assert!(QuadIndexBuffer::try_new(&graphics, 16385 * 6, IndexKind::U32).is_ok());
+    }
+
+    #[test]
+    fn test_u32_indices() {
+        let graphics = Null::new(NullBackend::new((1, 1)));
+
+        assert_eq!(
+            QuadIndexBuffer::with_quads(&graphics, 16384).kind,
+            IndexKind::U16
+        );
+
+        let buffer = QuadIndexBuffer::with_quads(&graphics, 100_000);
+        assert_eq!(buffer.kind, IndexKind::U32);
+        assert_eq!(buffer.quads(), 100_000);
+
+        let indices: Vec<u32> = buffer
+            .buffer
+            .data()
+            .chunks_exact(4)
+            .map(bytemuck::pod_read_unaligned)
+            .collect();
+        assert_eq!(indices[..6], [0, 1, 2, 0, 2, 3]);
+
+        let last = 99_999 * 4;
+        assert_eq!(
+            indices[indices.len() - 6..],
+            [last, last + 1, last + 2, last, last + 2, last + 3]
+        );
+    }
 }
@@ -1,17 +1,16 @@
 use bytemuck::{Pod, Zeroable};
-use std::rc::Rc;
+use std::{cmp::Reverse, ops::Range, rc::Rc};
 use yapgeir_geometry::{Box2D, Rect};
 use yapgeir_graphics_hal::{
-    buffer::ByteBuffer,
-    draw_params::{Depth as DrawDepth, DepthStencilTest, DrawParameters},
+    draw_params::{Blend, Depth as DrawDepth, DepthStencilTest, DrawParameters},
     frame_buffer::FrameBuffer,
-    sampler::Sampler,
+    sampler::{Sampler, SamplerState},
     samplers::SamplerAttribute,
     shader::TextShaderSource,
     texture::Texture,
     uniforms::Uniforms,
     vertex_buffer::Vertex,
-    Graphics, Size,
+    Graphics, Rgba, Size,
 };
 
 use crate::{
@@ -23,7 +22,7 @@ use crate::{
 use super::batch_renderer::BatchRenderer;
 
 #[cfg(not(target_os = "vita"))]
-const SHADER: TextShaderSource = TextShaderSource {
+pub(crate) const SHADER: TextShaderSource = TextShaderSource {
     vertex: r#"
         #version 120
 
@@ -34,8 +33,10 @@ const SHADER: TextShaderSource = TextShaderSource {
         attribute vec2 position;
         attribute vec2 tex_position;
         attribute float depth;
+        attribute vec4 color; // 0-255 RGBA
 
         varying vec2 v_tex_position;
+        varying vec4 v_color;
 
         vec2 round(vec2 value) { 
             return floor(value + vec2(0.5));
@@ -43,6 +44,7 @@ const SHADER: TextShaderSource = TextShaderSource {
 
         void main() {
             v_tex_position = tex_position;
+            v_color = color / 255.0;
             vec2 px = round((view_camera * vec3(position, 1.0)).xy);
             vec2 uv = (px + projection_offset) * projection_scale;
             gl_Position = vec4(uv, depth, 1.0);
@@ -61,16 +63,18 @@ const SHADER: TextShaderSource = TextShaderSource {
         uniform sampler2D tex;
 
         varying vec2 v_tex_position;
+        varying vec4 v_color;
 
         void main() {
-            gl_FragColor = texture2D(tex, v_tex_position);
+            gl_FragColor = texture2D(tex, v_tex_position) * v_color;
             if (gl_FragColor.a == 0.0) discard;
         }
     "#,
+    defines: &[],
 };
 
 #[cfg(target_os = "vita")]
-const SHADER: TextShaderSource = TextShaderSource {
+pub(crate) const SHADER: TextShaderSource = TextShaderSource {
     vertex: r#"
         uniform float3x3 view_camera;
         uniform float2 projection_scale;
@@ -80,11 +84,14 @@ const SHADER: TextShaderSource = TextShaderSource {
             float2 position,
             float2 tex_position,
             float depth,
+            float4 color, // 0-255 RGBA
 
             float2 out v_tex_position: TEXCOORD0,
+            float4 out v_color: TEXCOORD1,
             float4 out gl_Position : POSITION
         ) {
             v_tex_position = tex_position;
+            v_color = color / 255.0;
             float2 px = round((mul(view_camera, float3(position, 1.0f))).xy);
             float2 uv = (px + projection_offset) * projection_scale;
             gl_Position = float4(uv, depth, 1.0f);
@@ -97,14 +104,16 @@ const SHADER: TextShaderSource = TextShaderSource {
         uniform sampler2D tex: TEXUNIT0;
 
         float4 main(
-            float2 v_tex_position: TEXCOORD0
+            float2 v_tex_position: TEXCOORD0,
+            float4 v_color: TEXCOORD1
         ) {
-            float4 gl_FragColor = tex2D(tex, v_tex_position);
+            float4 gl_FragColor = tex2D(tex, v_tex_position) * v_color;
             if (gl_FragColor.a == 0.0) discard;
 
             return gl_FragColor;
         }
     "#,
+    defines: &[],
 };
 
 #[repr(C)]
@@ -113,15 +122,16 @@ pub struct SpriteVertex {
     pub position: [f32; 2],
     pub tex_position: [f32; 2],
     pub depth: f32,
+    pub color: [u8; 4],
 }
 
 impl SpriteVertex {
-    pub fn new(position: [f32; 2], tex_position: [f32; 2], depth: f32) -> Self {
+    pub fn new(position: [f32; 2], tex_position: [f32; 2], depth: f32, color: [u8; 4]) -> Self {
         Self {
             position,
             tex_position,
             depth,
-            ..Default::default()
+            color,
         }
     }
 }
@@ -134,7 +144,12 @@ pub struct SpriteUniforms {
     pub projection_scale: [f32; 2],
 }
 
-pub struct SpriteBatch<'a, G>
+/// A batch of sprites sharing the same `N` textures.
+///
+/// The first texture is the main one, its size is used to resolve
+/// `DrawRegion::Point` and `TextureRegion::Pixels`. Other textures, e.g. a normal map,
+/// are sampled by the shader with the same texture coordinates.
+pub struct SpriteBatch<'a, G, const N: usize = 1>
 where
     G: Graphics,
 {
@@ -144,9 +159,10 @@ where
         SpriteVertex,
         SpriteUniforms,
         &'a G::Texture,
-        [SamplerAttribute<G, &'a G::Texture>; 1],
+        [SamplerAttribute<G, &'a G::Texture>; N],
     >,
-    texture: &'a G::Texture,
+    /// Size of the main texture, which doesn't change for the lifetime of the batch.
+    texture_size: Size<u32>,
 }
 
 pub enum DrawRegion {
@@ -163,6 +179,15 @@ pub enum DrawRegion {
     ///
     /// Passing non-convex quads is undefined behavior.
     Quad([[f32; 2]; 4]),
+
+    /// A sprite of a given size in world space, scaled and rotated around its center.
+    /// Rotation is in radians, counter-clockwise.
+    Transformed {
+        center: [f32; 2],
+        size: [f32; 2],
+        rotation: f32,
+        scale: [f32; 2],
+    },
 }
 
 impl DrawRegion {
@@ -184,6 +209,24 @@ impl DrawRegion {
             }
             DrawRegion::Rect(rect) => rect.points(),
             DrawRegion::Quad(quad) => quad,
+            DrawRegion::Transformed {
+                center,
+                size,
+                rotation,
+                scale,
+            } => {
+                let half_size = (size[0] * scale[0] / 2., size[1] * scale[1] / 2.);
+                let (sin, cos) = rotation.sin_cos();
+
+                // Same winding as for the Point variant, but each corner is rotated around the center.
+                [
+                    [-half_size.0, -half_size.1],
+                    [-half_size.0, half_size.1],
+                    [half_size.0, half_size.1],
+                    [half_size.0, -half_size.1],
+                ]
+                .map(|[x, y]| [center[0] + x * cos - y * sin, center[1] + x * sin + y * cos])
+            }
         }
     }
 }
@@ -256,29 +299,321 @@ impl TextureRegion {
     }
 }
 
-impl<'a, G> SpriteBatch<'a, G>
+/// Border widths of a nine-slice sprite in texture pixels.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NineSliceInsets {
+    pub left: f32,
+    pub right: f32,
+    pub top: f32,
+    pub bottom: f32,
+}
+
+impl NineSliceInsets {
+    pub fn all(inset: f32) -> Self {
+        Self {
+            left: inset,
+            right: inset,
+            top: inset,
+            bottom: inset,
+        }
+    }
+}
+
+/// Scales two borders down proportionally if they don't fit into `size`.
+fn fit_borders(a: f32, b: f32, size: f32) -> (f32, f32) {
+    let total = a + b;
+    match total > size && total > 0. {
+        true => (a * size / total, b * size / total),
+        false => (a, b),
+    }
+}
+
+/// Split a nine-slice sprite into world space quads and texture regions,
+/// row by row from the bottom-left corner.
+///
+/// Corners keep the size of their source region, edges are stretched along one axis
+/// and the center along both. If the destination is smaller than the borders,
+/// the borders are shrunk proportionally.
+pub(crate) fn nine_slice(
+    destination: Rect<f32>,
+    source: Box2D<f32>,
+    insets: NineSliceInsets,
+    texture_size: Size<u32>,
+) -> [(Rect<f32>, Box2D<f32>); 9] {
+    let (tw, th) = (texture_size.w as f32, texture_size.h as f32);
+
+    let (left, right) = fit_borders(insets.left, insets.right, destination.w);
+    let (bottom, top) = fit_borders(insets.bottom, insets.top, destination.h);
+
+    // World space is Y-up, while texture space is Y-down, so rows go from the bottom
+    // of the texture region to its top.
+    let xs = [
+        destination.x,
+        destination.x + left,
+        destination.x + destination.w - right,
+        destination.x + destination.w,
+    ];
+    let ys = [
+        destination.y,
+        destination.y + bottom,
+        destination.y + destination.h - top,
+        destination.y + destination.h,
+    ];
+    let us = [
+        source.a[0],
+        source.a[0] + insets.left / tw,
+        source.b[0] - insets.right / tw,
+        source.b[0],
+    ];
+    let vs = [
+        source.b[1],
+        source.b[1] - insets.bottom / th,
+        source.a[1] + insets.top / th,
+        source.a[1],
+    ];
+
+    std::array::from_fn(|i| {
+        let (column, row) = (i % 3, i / 3);
+        let quad = Rect::new(
+            xs[column],
+            ys[row],
+            xs[column + 1] - xs[column],
+            ys[row + 1] - ys[row],
+        );
+        let region = Box2D::new([us[column], vs[row + 1]], [us[column + 1], vs[row]]);
+        (quad, region)
+    })
+}
+
+impl<'a, G, const N: usize> SpriteBatch<'a, G, N>
 where
     G: Graphics,
 {
     pub fn draw_sprite(&mut self, sprite: DrawRegion, texture_region: TextureRegion, depth: u16) {
-        let quad = sprite.quad(&texture_region, self.texture.size());
-        let texture_region = texture_region.to_texel_quad(self.texture.size());
-
-        let depth = (depth as f32 - 32768.) / u16::MAX as f32;
-
-        // Correctly map the UV to the texture region.
-        // Since texture and NDC space have different Y axis directions,
-        // we must flip the Y axis for the texture region.
-        //
-        //   1---2                                      0---3
-        //   | / | in NDC should be mapped to a texture | \ |
-        //   0---3                                      1---2
-        self.batch.draw(&[
-            SpriteVertex::new(quad[0].into(), texture_region[1].into(), depth),
-            SpriteVertex::new(quad[1].into(), texture_region[0].into(), depth),
-            SpriteVertex::new(quad[2].into(), texture_region[3].into(), depth),
-            SpriteVertex::new(quad[3].into(), texture_region[2].into(), depth),
-        ])
+        self.draw_sprite_tinted(sprite, texture_region, depth, Rgba::all(u8::MAX));
+    }
+
+    /// Draw a sprite with each texel multiplied by the `tint` color.
+    pub fn draw_sprite_tinted(
+        &mut self,
+        sprite: DrawRegion,
+        texture_region: TextureRegion,
+        depth: u16,
+        tint: Rgba<u8>,
+    ) {
+        let vertices = sprite_vertices(sprite, texture_region, depth, tint, self.texture_size);
+        self.batch.draw(&vertices);
+    }
+
+    /// Draw many sprites at once, see `draw_sprite`.
+    pub fn draw_sprites<I>(&mut self, sprites: I)
+    where
+        I: IntoIterator<Item = (DrawRegion, TextureRegion, u16)>,
+    {
+        let tint = Rgba::all(u8::MAX);
+
+        for (sprite, texture_region, depth) in sprites {
+            let vertices = sprite_vertices(sprite, texture_region, depth, tint, self.texture_size);
+            self.batch.draw(&vertices);
+        }
+    }
+
+    /// Draw a sprite scaled to the `destination` rectangle, while keeping its borders intact,
+    /// e.g. a UI panel.
+    ///
+    /// `source` is the sprite region in texture space, and `insets` are the widths
+    /// of its borders in texture pixels.
+    pub fn draw_nine_slice(
+        &mut self,
+        destination: Rect<f32>,
+        source: Box2D<f32>,
+        insets: NineSliceInsets,
+        depth: u16,
+        tint: Rgba<u8>,
+    ) {
+        for (quad, region) in nine_slice(destination, source, insets, self.texture_size) {
+            self.batch.draw(&quad_vertices(
+                quad.points(),
+                region.points(),
+                depth,
+                tint.into(),
+            ));
+        }
+    }
+}
+
+/// Build the vertices of a sprite drawn from a texture of a given size.
+fn sprite_vertices(
+    sprite: DrawRegion,
+    texture_region: TextureRegion,
+    depth: u16,
+    tint: Rgba<u8>,
+    texture_size: Size<u32>,
+) -> [SpriteVertex; 4] {
+    let quad = sprite.quad(&texture_region, texture_size);
+    let texture_region = texture_region.to_texel_quad(texture_size);
+    quad_vertices(quad, texture_region, depth, tint.into())
+}
+
+/// Build the vertices of a sprite quad from world space and texel space quads.
+pub(crate) fn quad_vertices(
+    quad: [[f32; 2]; 4],
+    texture_region: [[f32; 2]; 4],
+    depth: u16,
+    color: [u8; 4],
+) -> [SpriteVertex; 4] {
+    let depth = (depth as f32 - 32768.) / u16::MAX as f32;
+
+    // Correctly map the UV to the texture region.
+    // Since texture and NDC space have different Y axis directions,
+    // we must flip the Y axis for the texture region.
+    //
+    //   1---2                                      0---3
+    //   | / | in NDC should be mapped to a texture | \ |
+    //   0---3                                      1---2
+    [
+        SpriteVertex::new(quad[0], texture_region[1], depth, color),
+        SpriteVertex::new(quad[1], texture_region[0], depth, color),
+        SpriteVertex::new(quad[2], texture_region[3], depth, color),
+        SpriteVertex::new(quad[3], texture_region[2], depth, color),
+    ]
+}
+
+/// A pass of the sprite renderer, which defines how sprites are sorted and blended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SpritePass {
+    /// Sprites are sorted by the depth buffer, and fully transparent texels are discarded.
+    Opaque,
+    /// Sprites are sorted back-to-front on the CPU and alpha blended, without writing depth.
+    Transparent,
+}
+
+/// A sprite queued in a `SortedSpriteBatch`.
+struct SpriteCommand {
+    /// Index of the texture in `SortedSpriteBatch::textures`.
+    texture: usize,
+    depth: u16,
+    vertices: [SpriteVertex; 4],
+}
+
+/// Sort commands for a pass, returning ranges of consecutive commands sharing a texture.
+///
+/// Opaque commands are sorted by texture and then by depth. Transparent commands are sorted
+/// back-to-front, i.e. by descending depth, keeping the order of sprites with the same depth.
+fn sort_commands(commands: &mut [SpriteCommand], pass: SpritePass) -> Vec<Range<usize>> {
+    match pass {
+        SpritePass::Opaque => commands.sort_by_key(|c| (c.texture, c.depth)),
+        SpritePass::Transparent => commands.sort_by_key(|c| Reverse(c.depth)),
+    }
+
+    let mut runs = Vec::new();
+    let mut start = 0;
+    for i in 1..=commands.len() {
+        if i == commands.len() || commands[i].texture != commands[start].texture {
+            runs.push(start..i);
+            start = i;
+        }
+    }
+
+    runs
+}
+
+/// A sprite batch which draws sprites from multiple textures.
+///
+/// Unlike `SpriteBatch`, which is bound to a single texture, draw calls are accumulated
+/// until the batch is dropped, and then sorted by texture and depth, so that each texture
+/// is bound only once.
+///
+/// In the transparent pass (see `SpriteRenderer::start_transparent_batch`) sprites are sorted
+/// back-to-front instead, and a texture is bound once per run of consecutive sprites using it.
+pub struct SortedSpriteBatch<'a, G>
+where
+    G: Graphics,
+{
+    renderer: &'a mut SpriteRenderer<G>,
+    pass: SpritePass,
+    frame_buffer: &'a G::FrameBuffer,
+    view_camera: [[f32; 3]; 3],
+    projection: ([f32; 2], [f32; 2]),
+
+    textures: Vec<(&'a G::Texture, SamplerState)>,
+    commands: Vec<SpriteCommand>,
+}
+
+impl<'a, G> SortedSpriteBatch<'a, G>
+where
+    G: Graphics,
+{
+    fn texture_index(&mut self, sampler: Sampler<G, &'a G::Texture>) -> usize {
+        let found = self.textures.iter().position(|(texture, state)| {
+            std::ptr::eq(*texture, sampler.texture) && *state == sampler.state
+        });
+
+        found.unwrap_or_else(|| {
+            self.textures.push((sampler.texture, sampler.state));
+            self.textures.len() - 1
+        })
+    }
+
+    pub fn draw_sprite(
+        &mut self,
+        sprite: DrawRegion,
+        texture_region: TextureRegion,
+        sampler: Sampler<G, &'a G::Texture>,
+        depth: u16,
+    ) {
+        self.draw_sprite_tinted(sprite, texture_region, sampler, depth, Rgba::all(u8::MAX));
+    }
+
+    /// Queue a sprite with each texel multiplied by the `tint` color.
+    pub fn draw_sprite_tinted(
+        &mut self,
+        sprite: DrawRegion,
+        texture_region: TextureRegion,
+        sampler: Sampler<G, &'a G::Texture>,
+        depth: u16,
+        tint: Rgba<u8>,
+    ) {
+        let texture_size = sampler.texture.size();
+        let quad = sprite.quad(&texture_region, texture_size);
+        let texture_region = texture_region.to_texel_quad(texture_size);
+
+        let texture = self.texture_index(sampler);
+        self.commands.push(SpriteCommand {
+            texture,
+            depth,
+            vertices: quad_vertices(quad, texture_region, depth, tint.into()),
+        });
+    }
+}
+
+impl<'a, G> Drop for SortedSpriteBatch<'a, G>
+where
+    G: Graphics,
+{
+    fn drop(&mut self) {
+        let mut commands = std::mem::take(&mut self.commands);
+
+        for run in sort_commands(&mut commands, self.pass) {
+            let (texture, state) = self.textures[commands[run.start].texture];
+            let (offset, scale) = self.projection;
+
+            let mut batch = self.renderer.start_pass_batch(
+                self.pass,
+                self.frame_buffer,
+                self.view_camera,
+                NdcProjection::Custom { offset, scale },
+                [SamplerAttribute {
+                    name: "tex",
+                    location: 0,
+                    sampler: Sampler::new(texture, state),
+                }],
+            );
+
+            for command in &commands[run] {
+                batch.batch.draw(&command.vertices);
+            }
+        }
     }
 }
 
@@ -288,6 +623,7 @@ where
 {
     renderer: BatchRenderer<G, SpriteVertex, SpriteUniforms>,
     draw_parameters: DrawParameters,
+    transparent_draw_parameters: DrawParameters,
 }
 
 impl<G> SpriteRenderer<G>
@@ -295,12 +631,23 @@ where
     G: Graphics,
 {
     pub fn new<'a>(ctx: &G, quad_index_buffer: QuadIndexBuffer<G>) -> Self {
-        let shader = Rc::new(ctx.new_shader(&SHADER.into()));
+        Self::with_shader(ctx, quad_index_buffer, &SHADER)
+    }
+
+    /// Create a sprite renderer with a custom shader, e.g. one sampling multiple textures
+    /// with `start_multi_texture_batch`.
+    ///
+    /// The shader must have the same vertex attributes and uniforms as the default sprite shader.
+    pub fn with_shader(
+        ctx: &G,
+        quad_index_buffer: QuadIndexBuffer<G>,
+        shader: &TextShaderSource,
+    ) -> Self {
+        let shader = Rc::new(ctx.new_shader(shader));
         let uniforms = Rc::new(ctx.new_uniform_buffer(&SpriteUniforms::default()));
 
-        let index_count = quad_index_buffer.buffer.len() / quad_index_buffer.kind.size();
-        let max_quads = index_count / 6;
-        let batch_size = max_quads.min(u16::MAX as usize);
+        // Quad indices were checked to address only the vertices within the index type range.
+        let batch_size = quad_index_buffer.quads() * 4;
 
         Self {
             renderer: BatchRenderer::new(
@@ -312,7 +659,8 @@ where
             ),
             draw_parameters: DrawParameters {
                 // Use depth buffer to "sort" sprites by their depth on GPU.
-                // This won't work for semi-transparent pixels (such as light)
+                // This won't work for semi-transparent pixels (such as light),
+                // which should be drawn in the transparent pass.
                 depth: Some(DrawDepth {
                     test: DepthStencilTest::Less,
                     write: true,
@@ -321,9 +669,28 @@ where
                 }),
                 ..Default::default()
             },
+            transparent_draw_parameters: DrawParameters {
+                // Transparent sprites are hidden behind opaque ones, but don't hide each other,
+                // since they are already sorted.
+                depth: Some(DrawDepth {
+                    test: DepthStencilTest::Less,
+                    write: false,
+                    range: (-1., 1.),
+                }),
+                blend: Some(Blend::straight_alpha()),
+                ..Default::default()
+            },
         }
     }
 
+    /// Set the blending of sprites drawn in the following batches, e.g. `Blend::additive()`
+    /// for lights. Sprites are not blended by default.
+    ///
+    /// Doesn't affect the transparent pass, which always uses alpha blending.
+    pub fn set_blend(&mut self, blend: Option<Blend>) {
+        self.draw_parameters.blend = blend;
+    }
+
     /// Create a new sprite draw batch.
     ///
     /// Batch will be flushed on drop, so ensure that it is dropped before swap_buffers is called.
@@ -348,29 +715,127 @@ where
         projection: NdcProjection,
         sampler: Sampler<G, &'a G::Texture>,
     ) -> SpriteBatch<'a, G> {
-        let size = frame_buffer.size();
+        self.start_multi_texture_batch(
+            frame_buffer,
+            view_camera,
+            projection,
+            [SamplerAttribute {
+                name: "tex",
+                location: 0,
+                sampler,
+            }],
+        )
+    }
+
+    /// Create a new sprite draw batch, binding multiple textures to the shader
+    /// sampler uniforms named in `samplers`.
+    ///
+    /// Has the same semantics as `start_batch`. The first sampler is the main texture
+    /// of the sprites, see `SpriteBatch`.
+    pub fn start_multi_texture_batch<'a, const N: usize>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+        samplers: [SamplerAttribute<G, &'a G::Texture>; N],
+    ) -> SpriteBatch<'a, G, N> {
+        self.start_pass_batch(
+            SpritePass::Opaque,
+            frame_buffer,
+            view_camera,
+            projection,
+            samplers,
+        )
+    }
 
+    fn start_pass_batch<'a, const N: usize>(
+        &'a mut self,
+        pass: SpritePass,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+        samplers: [SamplerAttribute<G, &'a G::Texture>; N],
+    ) -> SpriteBatch<'a, G, N> {
+        let draw_parameters = match pass {
+            SpritePass::Opaque => &self.draw_parameters,
+            SpritePass::Transparent => &self.transparent_draw_parameters,
+        };
+
+        let texture = samplers
+            .first()
+            .expect("At least one sampler is required")
+            .sampler
+            .texture;
+
+        let size = frame_buffer.size();
         let (projection_offset, projection_scale) = projection.offset_and_scale(size);
 
         SpriteBatch {
-            texture: sampler.texture,
+            texture_size: texture.size(),
             batch: self.renderer.start_batch(
                 frame_buffer,
-                &self.draw_parameters,
+                draw_parameters,
                 &SpriteUniforms {
                     view_camera,
                     projection_offset,
                     projection_scale,
                 },
-                [SamplerAttribute {
-                    name: "tex",
-                    location: 0,
-                    sampler,
-                }],
+                samplers,
             ),
         }
     }
 
+    /// Create a new sprite draw batch, which can draw sprites from multiple textures.
+    ///
+    /// Sprites are drawn when the batch is dropped, with one draw call per texture.
+    /// See `start_batch` for the description of arguments.
+    pub fn start_sorted_batch<'a>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+    ) -> SortedSpriteBatch<'a, G> {
+        self.start_sorted_pass_batch(SpritePass::Opaque, frame_buffer, view_camera, projection)
+    }
+
+    /// Create a new sprite draw batch for semi-transparent sprites, e.g. particles or glass.
+    ///
+    /// Sprites are sorted back-to-front by their depth and alpha blended when the batch
+    /// is dropped, without writing to the depth buffer. This pass should be drawn after
+    /// the opaque sprites, which will hide transparent sprites behind them.
+    /// See `start_batch` for the description of arguments.
+    pub fn start_transparent_batch<'a>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+    ) -> SortedSpriteBatch<'a, G> {
+        self.start_sorted_pass_batch(
+            SpritePass::Transparent,
+            frame_buffer,
+            view_camera,
+            projection,
+        )
+    }
+
+    fn start_sorted_pass_batch<'a>(
+        &'a mut self,
+        pass: SpritePass,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+    ) -> SortedSpriteBatch<'a, G> {
+        SortedSpriteBatch {
+            renderer: self,
+            pass,
+            frame_buffer,
+            view_camera,
+            projection: projection.offset_and_scale(frame_buffer.size()),
+            textures: Vec::new(),
+            commands: Vec::new(),
+        }
+    }
+
     /// Create a new sprite draw batch and execute draw calls with it.
     ///
     /// # Arguments
@@ -397,3 +862,344 @@ where
         draw(&mut batch);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transformed_quad_rotation() {
+        let quad = DrawRegion::Transformed {
+            center: [0., 0.],
+            size: [10., 10.],
+            rotation: std::f32::consts::FRAC_PI_2,
+            scale: [1., 1.],
+        }
+        .quad(&TextureRegion::Full, Size::new(10, 10));
+
+        let expected = [[5., -5.], [-5., -5.], [-5., 5.], [5., 5.]];
+        for (point, expected) in quad.iter().zip(expected) {
+            assert!((point[0] - expected[0]).abs() < 1e-4, "{quad:?}");
+            assert!((point[1] - expected[1]).abs() < 1e-4, "{quad:?}");
+        }
+    }
+
+    #[test]
+    fn test_nine_slice() {
+        // A 32x32 sprite in the top-left corner of a 64x64 texture, with 8px borders.
+        let source = Box2D::new([0., 0.], [0.5, 0.5]);
+        let slices = nine_slice(
+            Rect::new(10., 20., 100., 50.),
+            source,
+            NineSliceInsets::all(8.),
+            Size::new(64, 64),
+        );
+
+        assert_eq!(slices.len(), 9);
+        for i in [0, 2, 6, 8] {
+            assert_eq!(slices[i].0.size(), Size::new(8., 8.));
+            assert_eq!(slices[i].1.size(), Size::new(0.125, 0.125));
+        }
+
+        // Bottom-left corner of the destination maps to the bottom-left corner of the source.
+        assert_eq!(slices[0].0, Rect::new(10., 20., 8., 8.));
+        assert_eq!(slices[0].1, Box2D::new([0., 0.375], [0.125, 0.5]));
+        assert_eq!(slices[4].0, Rect::new(18., 28., 84., 34.));
+        assert_eq!(slices[8].1, Box2D::new([0.375, 0.], [0.5, 0.125]));
+    }
+
+    #[test]
+    fn test_nine_slice_clamps_borders() {
+        let slices = nine_slice(
+            Rect::new(0., 0., 8., 40.),
+            Box2D::new([0., 0.], [1., 1.]),
+            NineSliceInsets {
+                left: 8.,
+                right: 8.,
+                ..NineSliceInsets::all(4.)
+            },
+            Size::new(32, 32),
+        );
+
+        for (quad, _) in slices {
+            assert!(quad.w >= 0. && quad.h >= 0., "{quad:?}");
+        }
+        assert_eq!(slices[0].0.w, 4.);
+        assert_eq!(slices[1].0.w, 0.);
+    }
+
+    #[test]
+    fn test_sort_commands_groups_textures() {
+        let mut commands: Vec<_> = [(0, 3), (1, 1), (0, 1), (2, 5), (1, 0), (0, 2)]
+            .into_iter()
+            .map(|(texture, depth)| SpriteCommand {
+                texture,
+                depth,
+                vertices: Default::default(),
+            })
+            .collect();
+
+        let runs = sort_commands(&mut commands, SpritePass::Opaque);
+        assert_eq!(runs, [0..3, 3..5, 5..6]);
+
+        let depths: Vec<_> = commands.iter().map(|c| c.depth).collect();
+        assert_eq!(depths, [1, 2, 3, 0, 1, 5]);
+        assert!(sort_commands(&mut [], SpritePass::Opaque).is_empty());
+    }
+
+    #[test]
+    fn test_transparent_sprites_composite_back_to_front() {
+        use yapgeir_graphics_hal::{frame_buffer::ReadFormat, texture::PixelFormat};
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((2, 2)));
+        crate::test_programs::register_sprites(&graphics);
+        let mut renderer = SpriteRenderer::new(&graphics, QuadIndexBuffer::new(&graphics, 1024u16));
+        let frame_buffer = graphics.default_frame_buffer();
+        frame_buffer.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+        let white = graphics.new_texture(PixelFormat::Rgba, Size::new(1, 1), Some(&[255; 4]));
+
+        // A red sprite in front of a blue one, both half transparent, queued front first.
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        {
+            let mut batch =
+                renderer.start_transparent_batch(&frame_buffer, camera, NdcProjection::Center);
+            for (depth, tint) in [
+                (1, Rgba::new(255, 0, 0, 128)),
+                (2, Rgba::new(0, 0, 255, 128)),
+            ] {
+                batch.draw_sprite_tinted(
+                    DrawRegion::Rect(Rect::new(-1., -1., 2., 2.)),
+                    TextureRegion::Full,
+                    Sampler::nearest(&white),
+                    depth,
+                    tint,
+                );
+            }
+        }
+
+        // The red sprite in front covers half of the blue one.
+        let mut pixel = [0; 4];
+        frame_buffer.read(Rect::new(0, 0, 1, 1), ReadFormat::Rgba, &mut pixel);
+        assert_eq!(pixel, [128, 0, 64, 255]);
+    }
+
+    #[test]
+    fn test_transparent_batch_draw_calls() {
+        use yapgeir_graphics_hal::texture::PixelFormat;
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((64, 64)));
+        let mut renderer = SpriteRenderer::new(&graphics, QuadIndexBuffer::new(&graphics, 1024u16));
+        let frame_buffer = graphics.default_frame_buffer();
+        let a = graphics.new_texture_empty(PixelFormat::Rgba, Size::new(8, 8));
+        let b = graphics.new_texture_empty(PixelFormat::Rgba, Size::new(8, 8));
+
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let sampler = |texture| Sampler::new(texture, SamplerState::default());
+        let sprites = [(&a, 3), (&b, 2), (&a, 1)];
+
+        {
+            let mut batch =
+                renderer.start_sorted_batch(&frame_buffer, camera, NdcProjection::Center);
+            for (texture, depth) in sprites {
+                batch.draw_sprite(
+                    DrawRegion::Point([0., 0.]),
+                    TextureRegion::Full,
+                    sampler(texture),
+                    depth,
+                );
+            }
+        }
+        // Opaque sprites are grouped by texture.
+        assert_eq!(graphics.draw_calls(), 2);
+
+        {
+            let mut batch =
+                renderer.start_transparent_batch(&frame_buffer, camera, NdcProjection::Center);
+            for (texture, depth) in sprites {
+                batch.draw_sprite(
+                    DrawRegion::Point([0., 0.]),
+                    TextureRegion::Full,
+                    sampler(texture),
+                    depth,
+                );
+            }
+        }
+        // Transparent sprites keep their back-to-front order, which interleaves textures.
+        assert_eq!(graphics.draw_calls(), 5);
+    }
+
+    #[test]
+    fn test_batch_fills_quad_index_buffer() {
+        use yapgeir_graphics_hal::texture::PixelFormat;
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((64, 64)));
+        let mut renderer =
+            SpriteRenderer::new(&graphics, QuadIndexBuffer::with_quads(&graphics, 4));
+        let frame_buffer = graphics.default_frame_buffer();
+        let texture = graphics.new_texture_empty(PixelFormat::Rgba, Size::new(8, 8));
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+
+        let mut draw = |sprites| {
+            let sampler = Sampler::new(&texture, SamplerState::default());
+            renderer.batch(
+                &frame_buffer,
+                camera,
+                NdcProjection::Center,
+                sampler,
+                |batch| {
+                    for i in 0..sprites {
+                        batch.draw_sprite(
+                            DrawRegion::Point([i as f32, 0.]),
+                            TextureRegion::Full,
+                            0,
+                        );
+                    }
+                },
+            );
+        };
+
+        // A batch holds as many sprites as there are quads in the index buffer.
+        draw(4);
+        assert_eq!(graphics.draw_calls(), 1);
+        draw(5);
+        assert_eq!(graphics.draw_calls(), 3);
+    }
+
+    #[test]
+    fn test_batch_queries_texture_size_once() {
+        use yapgeir_graphics_hal::texture::PixelFormat;
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((64, 64)));
+        let mut renderer = SpriteRenderer::new(&graphics, QuadIndexBuffer::new(&graphics, 1024u16));
+        let frame_buffer = graphics.default_frame_buffer();
+        let texture = graphics.new_texture_empty(PixelFormat::Rgba, Size::new(16, 16));
+
+        let sprites = || {
+            (0..100).map(|i| {
+                let region = TextureRegion::Pixels(Rect::new(0, 0, 8, 8));
+                (DrawRegion::Point([i as f32, 0.]), region, i)
+            })
+        };
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let sampler = || Sampler::new(&texture, SamplerState::default());
+
+        renderer.batch(
+            &frame_buffer,
+            camera,
+            NdcProjection::TopLeft,
+            sampler(),
+            |batch| {
+                for (sprite, region, depth) in sprites() {
+                    batch.draw_sprite(sprite, region, depth);
+                }
+            },
+        );
+        let looped = texture.size_queries();
+
+        renderer.batch(
+            &frame_buffer,
+            camera,
+            NdcProjection::TopLeft,
+            sampler(),
+            |batch| batch.draw_sprites(sprites()),
+        );
+        let batched = texture.size_queries() - looped;
+
+        assert_eq!(looped, 1);
+        assert_eq!(batched, 1);
+    }
+
+    #[test]
+    fn test_batch_vertices() {
+        use yapgeir_graphics_hal::texture::PixelFormat;
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((64, 64)));
+        let mut renderer = SpriteRenderer::new(&graphics, QuadIndexBuffer::new(&graphics, 1024u16));
+        let frame_buffer = graphics.default_frame_buffer();
+        let texture_size = Size::new(32, 16);
+        let texture = graphics.new_texture_empty(PixelFormat::Rgba, texture_size);
+
+        let sprites = || {
+            [
+                (
+                    DrawRegion::Point([4., 2.]),
+                    TextureRegion::Pixels(Rect::new(8, 0, 8, 4)),
+                    1,
+                ),
+                (
+                    DrawRegion::Rect(Rect::new(0., 0., 10., 20.)),
+                    TextureRegion::Full,
+                    2,
+                ),
+                (
+                    DrawRegion::Transformed {
+                        center: [1., 1.],
+                        size: [4., 4.],
+                        rotation: 0.5,
+                        scale: [2., 1.],
+                    },
+                    TextureRegion::TexelsBox2D(Box2D::new([0., 0.5], [0.5, 1.])),
+                    3,
+                ),
+            ]
+        };
+
+        let expected: Vec<_> = sprites()
+            .into_iter()
+            .flat_map(|(sprite, region, depth)| {
+                let quad = sprite.quad(&region, texture_size);
+                quad_vertices(quad, region.to_texel_quad(texture_size), depth, [255; 4])
+            })
+            .collect();
+
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        let sampler = Sampler::new(&texture, SamplerState::default());
+        let mut batch =
+            renderer.start_batch(&frame_buffer, camera, NdcProjection::TopLeft, sampler);
+        batch.draw_sprites(sprites());
+
+        let vertices = batch.batch.unflushed();
+        assert_eq!(
+            bytemuck::cast_slice::<_, u8>(vertices),
+            bytemuck::cast_slice(&expected)
+        );
+    }
+
+    #[test]
+    fn test_tinted_sprite_readback() {
+        use yapgeir_graphics_hal::{frame_buffer::ReadFormat, texture::PixelFormat};
+        use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+        let graphics = Null::new(NullBackend::new((2, 2)));
+        crate::test_programs::register_sprites(&graphics);
+        let mut renderer = SpriteRenderer::new(&graphics, QuadIndexBuffer::new(&graphics, 1024u16));
+        let frame_buffer = graphics.default_frame_buffer();
+        frame_buffer.clear(None, Some(Rgba::new(0., 0., 1., 1.)), None, None);
+        let white = graphics.new_texture(PixelFormat::Rgba, Size::new(1, 1), Some(&[255; 4]));
+
+        let camera = [[1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        renderer.batch(
+            &frame_buffer,
+            camera,
+            NdcProjection::Center,
+            Sampler::nearest(&white),
+            |batch| {
+                let left = DrawRegion::Rect(Rect::new(-1., -1., 1., 2.));
+                batch.draw_sprite_tinted(left, TextureRegion::Full, 0, Rgba::new(255, 0, 0, 128));
+                // Fully transparent texels are discarded, keeping the cleared color.
+                let right = DrawRegion::Rect(Rect::new(0., -1., 1., 2.));
+                batch.draw_sprite_tinted(right, TextureRegion::Full, 0, Rgba::new(255, 0, 0, 0));
+            },
+        );
+
+        // A half-alpha tint halves the alpha of the opaque texture.
+        let mut pixels = [0; 2 * 4];
+        frame_buffer.read(Rect::new(0, 0, 2, 1), ReadFormat::Rgba, &mut pixels);
+        assert_eq!(pixels, [255, 0, 0, 128, 0, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,94 @@
+//! CPU implementations of the shaders of the renderers, which let the null backend
+//! rasterize their draw calls in tests.
+
+use yapgeir_graphics_hal::{shader::TextShaderSource, Rgba};
+use yapgeir_graphics_hal_null::{Null, NullProgram, ProgramInputs, VertexInputs};
+
+use crate::{
+    post_process::POST_PROCESS_VERTEX_SHADER, primitive_renderer, sprite_renderer::SHADER,
+};
+
+/// `sprite_renderer::SHADER`.
+struct SpriteProgram;
+
+impl NullProgram for SpriteProgram {
+    fn vertex(&self, inputs: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+        let view_camera: [[f32; 3]; 3] = inputs.uniform("view_camera");
+        let offset: [f32; 2] = inputs.uniform("projection_offset");
+        let scale: [f32; 2] = inputs.uniform("projection_scale");
+
+        // Matrices are column-major, and rounding matches the shader instead of `f32::round`.
+        let [x, y] = vertex.get("position");
+        let px = [0, 1].map(|r| {
+            (view_camera[0][r] * x + view_camera[1][r] * y + view_camera[2][r] + 0.5).floor()
+        });
+        let uv = [0, 1].map(|i| (px[i] + offset[i]) * scale[i]);
+
+        let [depth] = vertex.get("depth");
+        let mut varyings = vertex.get::<2>("tex_position").to_vec();
+        varyings.extend(vertex.get::<4>("color").map(|c| c / 255.));
+        ([uv[0], -uv[1], depth, 1.], varyings)
+    }
+
+    fn fragment(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+        let texel: [f32; 4] = inputs.sample("tex", [varyings[0], varyings[1]]).into();
+        let color: [f32; 4] = std::array::from_fn(|i| texel[i] * varyings[2 + i]);
+        (color[3] != 0.).then(|| color.into())
+    }
+}
+
+/// `primitive_renderer::SHADER`.
+struct PrimitiveProgram;
+
+impl NullProgram for PrimitiveProgram {
+    fn vertex(&self, inputs: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+        let view_projection: [[f32; 3]; 3] = inputs.uniform("view_projection");
+        let [x, y] = vertex.get("position");
+        let p = [0, 1, 2]
+            .map(|r| view_projection[0][r] * x + view_projection[1][r] * y + view_projection[2][r]);
+        ([p[0], -p[1], p[2], 1.], vertex.get::<4>("color").to_vec())
+    }
+
+    fn fragment(&self, _: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+        Some(Rgba::new(
+            varyings[0],
+            varyings[1],
+            varyings[2],
+            varyings[3],
+        ))
+    }
+
+    fn point_size(&self, inputs: &ProgramInputs, _: &VertexInputs) -> f32 {
+        inputs.uniform::<f32>("point_size")
+    }
+}
+
+/// `POST_PROCESS_VERTEX_SHADER` with a fragment shader sampling `tex` as is.
+struct CopyProgram;
+
+impl NullProgram for CopyProgram {
+    fn vertex(&self, _: &ProgramInputs, vertex: &VertexInputs) -> ([f32; 4], Vec<f32>) {
+        let [x, y] = vertex.get("position");
+        ([x, y, 0., 1.], vec![x * 0.5 + 0.5, y * 0.5 + 0.5])
+    }
+
+    fn fragment(&self, inputs: &ProgramInputs, varyings: &[f32]) -> Option<Rgba<f32>> {
+        Some(inputs.sample("tex", [varyings[0], varyings[1]]))
+    }
+}
+
+/// Registers `sprite_renderer::SHADER`.
+pub(crate) fn register_sprites(graphics: &Null) {
+    graphics.register_program(&SHADER, SpriteProgram);
+}
+
+/// Registers `primitive_renderer::SHADER`.
+pub(crate) fn register_primitives(graphics: &Null) {
+    graphics.register_program(&primitive_renderer::SHADER, PrimitiveProgram);
+}
+
+/// Registers a post processing shader, which copies its `tex` sampler to the target.
+pub(crate) fn register_copy(graphics: &Null, source: &TextShaderSource) {
+    assert_eq!(source.vertex, POST_PROCESS_VERTEX_SHADER);
+    graphics.register_program(source, CopyProgram);
+}
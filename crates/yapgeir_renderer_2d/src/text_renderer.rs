@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use yapgeir_geometry::{Box2D, Rect};
+use yapgeir_graphics_hal::{sampler::Sampler, Graphics, Rgba, Size};
+
+use crate::{
+    quad_index_buffer::QuadIndexBuffer,
+    sprite_renderer::{DrawRegion, SpriteBatch, SpriteRenderer, TextureRegion},
+    NdcProjection,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Glyph {
+    /// Glyph rectangle in texture space with (0; 0) representing top-left corner,
+    /// and (1; 1) representing bottom right corner.
+    pub region: Box2D<f32>,
+    /// Size of the glyph quad in pixels.
+    pub size: Size<f32>,
+    /// Offset from the pen position on the baseline to the top-left corner of the glyph.
+    /// Positive Y goes up from the baseline.
+    pub bearing: [f32; 2],
+    /// Distance the pen moves after drawing this glyph.
+    pub advance: f32,
+}
+
+/// A bitmap font descriptor, mapping characters to the regions of a font atlas texture.
+#[derive(Debug, Clone, Default)]
+pub struct BitmapFont {
+    pub glyphs: HashMap<char, Glyph>,
+    /// Distance between the baselines of two consecutive lines.
+    pub line_height: f32,
+}
+
+impl BitmapFont {
+    /// Create a monospace font from an atlas, where all glyphs have the same size
+    /// and are laid out in rows in the order of `chars`.
+    ///
+    /// Glyphs are placed on top of the baseline.
+    pub fn monospace(atlas_size: Size<u32>, glyph_size: Size<u32>, chars: &str) -> Self {
+        let columns = atlas_size.w / glyph_size.w;
+
+        let glyphs = chars
+            .chars()
+            .enumerate()
+            .map(|(i, c)| {
+                let i = i as u32;
+                let region = TextureRegion::Pixels(Rect::new(
+                    i % columns * glyph_size.w,
+                    i / columns * glyph_size.h,
+                    glyph_size.w,
+                    glyph_size.h,
+                ))
+                .to_texel_quad(atlas_size);
+
+                let glyph = Glyph {
+                    region: Box2D::new(region[0], region[2]),
+                    size: Size::new(glyph_size.w as f32, glyph_size.h as f32),
+                    bearing: [0., glyph_size.h as f32],
+                    advance: glyph_size.w as f32,
+                };
+
+                (c, glyph)
+            })
+            .collect();
+
+        Self {
+            glyphs,
+            line_height: glyph_size.h as f32,
+        }
+    }
+
+    /// Lay out a string, returning world space quads and texture regions of every glyph.
+    ///
+    /// Characters without a glyph are skipped, and `\n` moves the pen to the next line.
+    pub fn layout<'a>(
+        &'a self,
+        text: &'a str,
+        origin: [f32; 2],
+        scale: f32,
+    ) -> impl Iterator<Item = (Rect<f32>, Box2D<f32>)> + 'a {
+        let mut pen = origin;
+
+        text.chars().filter_map(move |c| {
+            if c == '\n' {
+                pen = [origin[0], pen[1] - self.line_height * scale];
+                return None;
+            }
+
+            let glyph = self.glyphs.get(&c)?;
+            let quad = Rect::new(
+                pen[0] + glyph.bearing[0] * scale,
+                pen[1] + (glyph.bearing[1] - glyph.size.h) * scale,
+                glyph.size.w * scale,
+                glyph.size.h * scale,
+            );
+
+            pen[0] += glyph.advance * scale;
+            Some((quad, glyph.region))
+        })
+    }
+}
+
+pub struct TextBatch<'a, G>
+where
+    G: Graphics,
+{
+    batch: SpriteBatch<'a, G>,
+    font: &'a BitmapFont,
+}
+
+impl<'a, G> TextBatch<'a, G>
+where
+    G: Graphics,
+{
+    /// Draw a string with the pen starting at the `origin` point on the baseline.
+    pub fn draw_text(
+        &mut self,
+        text: &str,
+        origin: [f32; 2],
+        scale: f32,
+        depth: u16,
+        tint: Rgba<u8>,
+    ) {
+        for (quad, region) in self.font.layout(text, origin, scale) {
+            self.batch.draw_sprite_tinted(
+                DrawRegion::Rect(quad),
+                TextureRegion::TexelsBox2D(region),
+                depth,
+                tint,
+            );
+        }
+    }
+}
+
+/// Draws bitmap text using a sprite batch, one quad per glyph.
+pub struct TextRenderer<G>
+where
+    G: Graphics,
+{
+    sprites: SpriteRenderer<G>,
+    pub font: BitmapFont,
+}
+
+impl<G> TextRenderer<G>
+where
+    G: Graphics,
+{
+    pub fn new(ctx: &G, quad_index_buffer: QuadIndexBuffer<G>, font: BitmapFont) -> Self {
+        Self {
+            sprites: SpriteRenderer::new(ctx, quad_index_buffer),
+            font,
+        }
+    }
+
+    /// Create a new text draw batch.
+    ///
+    /// Has the same semantics as `SpriteRenderer::start_batch`, with `sampler` being the font atlas.
+    pub fn start_batch<'a>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+        sampler: Sampler<G, &'a G::Texture>,
+    ) -> TextBatch<'a, G> {
+        TextBatch {
+            batch: self
+                .sprites
+                .start_batch(frame_buffer, view_camera, projection, sampler),
+            font: &self.font,
+        }
+    }
+
+    /// Create a new text draw batch and execute draw calls with it.
+    pub fn batch<'a>(
+        &'a mut self,
+        frame_buffer: &'a G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+        sampler: Sampler<G, &'a G::Texture>,
+
+        draw: impl FnOnce(&mut TextBatch<'a, G>),
+    ) {
+        let mut batch = self.start_batch(frame_buffer, view_camera, projection, sampler);
+        draw(&mut batch);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layout_advance() {
+        let font = BitmapFont::monospace(Size::new(64, 64), Size::new(8, 16), "AB");
+        let quads: Vec<_> = font.layout("AB", [10., 20.], 2.).collect();
+
+        assert_eq!(quads.len(), 2);
+        assert_eq!(quads[0].0, Rect::new(10., 20., 16., 32.));
+        assert_eq!(quads[1].0, Rect::new(26., 20., 16., 32.));
+        assert_eq!(quads[0].1, Box2D::new([0., 0.], [0.125, 0.25]));
+        assert_eq!(quads[1].1, Box2D::new([0.125, 0.], [0.25, 0.25]));
+    }
+}
@@ -0,0 +1,261 @@
+use std::rc::Rc;
+use yapgeir_geometry::Rect;
+use yapgeir_graphics_hal::{
+    buffer::{Buffer, BufferKind, BufferUsage},
+    draw_descriptor::AsVertexBindings,
+    draw_params::DrawParameters,
+    frame_buffer::{FrameBuffer, Indices},
+    index_buffer::PrimitiveMode,
+    sampler::Sampler,
+    samplers::SamplerAttribute,
+    texture::Texture,
+    uniforms::UniformBuffer,
+    Graphics, Rgba, Size,
+};
+
+use crate::{
+    quad_index_buffer::QuadIndexBuffer,
+    sprite_renderer::{quad_vertices, SpriteUniforms, SpriteVertex, TextureRegion, SHADER},
+    NdcProjection,
+};
+
+/// Describes where tiles are placed in the world and where they are taken from in the atlas.
+struct TileLayout {
+    /// Size of the map in tiles.
+    size: Size<u32>,
+    /// Size of a single tile in pixels, both in the world and in the atlas.
+    tile_size: Size<u32>,
+    atlas_size: Size<u32>,
+}
+
+impl TileLayout {
+    /// Offset of the first vertex of a tile in the vertex buffer.
+    fn offset(&self, x: u32, y: u32) -> usize {
+        assert!(x < self.size.w && y < self.size.h, "tile is out of bounds");
+        (y * self.size.w + x) as usize * 4
+    }
+
+    fn vertices(&self, x: u32, y: u32, index: u16) -> [SpriteVertex; 4] {
+        let columns = self.atlas_size.w / self.tile_size.w;
+        let index = index as u32;
+
+        let quad = Rect::new(
+            (x * self.tile_size.w) as f32,
+            (y * self.tile_size.h) as f32,
+            self.tile_size.w as f32,
+            self.tile_size.h as f32,
+        )
+        .points();
+
+        let texture_region = TextureRegion::Pixels(Rect::new(
+            index % columns * self.tile_size.w,
+            index / columns * self.tile_size.h,
+            self.tile_size.w,
+            self.tile_size.h,
+        ))
+        .to_texel_quad(self.atlas_size);
+
+        quad_vertices(quad, texture_region, 0, Rgba::all(u8::MAX).into())
+    }
+
+    fn all_vertices(&self, tiles: &[u16]) -> Vec<SpriteVertex> {
+        assert_eq!(tiles.len(), (self.size.w * self.size.h) as usize);
+
+        let mut vertices = Vec::with_capacity(tiles.len() * 4);
+        for y in 0..self.size.h {
+            for x in 0..self.size.w {
+                let index = tiles[(y * self.size.w + x) as usize];
+                vertices.extend_from_slice(&self.vertices(x, y, index));
+            }
+        }
+
+        vertices
+    }
+}
+
+/// Renders a static grid of tiles from a single atlas texture with a single draw call.
+///
+/// Tile vertices are uploaded to the GPU once, and only the vertices of the
+/// changed tiles are rewritten with `set_tile`.
+///
+/// Tilemap doesn't write to the depth buffer, so it should be rendered before the sprites.
+pub struct TilemapRenderer<G>
+where
+    G: Graphics,
+{
+    layout: TileLayout,
+    atlas: Sampler<G, Rc<G::Texture>>,
+
+    vertices: Buffer<G, SpriteVertex>,
+    draw_descriptor: G::DrawDescriptor,
+    uniforms: G::UniformBuffer<SpriteUniforms>,
+    draw_parameters: DrawParameters,
+}
+
+impl<G> TilemapRenderer<G>
+where
+    G: Graphics,
+{
+    /// Create a new tilemap renderer.
+    ///
+    /// # Arguments
+    ///
+    /// * `quad_index_buffer` - Quad indices, must be large enough to fit all of the tiles.
+    /// * `atlas` - A texture with tiles laid out in rows, and sampling parameters for it.
+    /// * `size` - Size of the map in tiles.
+    /// * `tile_size` - Size of a single tile in pixels.
+    /// * `tiles` - Row-major tile indices in the atlas, must contain exactly `size.w * size.h` tiles.
+    pub fn new(
+        ctx: &G,
+        quad_index_buffer: QuadIndexBuffer<G>,
+        atlas: Sampler<G, Rc<G::Texture>>,
+        size: Size<u32>,
+        tile_size: Size<u32>,
+        tiles: &[u16],
+    ) -> Self {
+        assert!(
+            (size.w * size.h) as usize <= quad_index_buffer.quads(),
+            "quad index buffer is too small for the tilemap"
+        );
+
+        let layout = TileLayout {
+            size,
+            tile_size,
+            atlas_size: atlas.texture.size(),
+        };
+
+        let vertices = ctx.new_buffer(
+            BufferKind::Vertex,
+            BufferUsage::Static,
+            &layout.all_vertices(tiles),
+        );
+
+        let draw_descriptor = ctx.new_draw_descriptor(
+            Rc::new(ctx.new_shader(&SHADER)),
+            quad_index_buffer.bindings(),
+            &[vertices.bindings()],
+        );
+
+        Self {
+            layout,
+            atlas,
+            vertices,
+            draw_descriptor,
+            uniforms: ctx.new_uniform_buffer(&SpriteUniforms::default()),
+            draw_parameters: Default::default(),
+        }
+    }
+
+    pub fn size(&self) -> Size<u32> {
+        self.layout.size
+    }
+
+    /// Replace a single tile, rewriting only its vertices on the GPU.
+    pub fn set_tile(&mut self, x: u32, y: u32, index: u16) {
+        self.vertices
+            .write(self.layout.offset(x, y), &self.layout.vertices(x, y, index));
+    }
+
+    /// Render the whole tilemap.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_buffer` - Frame buffer to draw to.
+    /// * `view_camera` - A camera matrix that will transform world space to pixel space.
+    /// * `projection` - Describes how pixels are projected to normalized display coordinates.
+    pub fn render(
+        &self,
+        frame_buffer: &G::FrameBuffer,
+        view_camera: [[f32; 3]; 3],
+        projection: NdcProjection,
+    ) {
+        let (projection_offset, projection_scale) =
+            projection.offset_and_scale(frame_buffer.size());
+
+        self.uniforms.write(&SpriteUniforms {
+            view_camera,
+            projection_offset,
+            projection_scale,
+        });
+
+        frame_buffer.draw(
+            &self.draw_descriptor,
+            &self.draw_parameters,
+            &[SamplerAttribute {
+                name: "tex",
+                location: 0,
+                sampler: Sampler::new(&*self.atlas.texture, self.atlas.state),
+            }],
+            &[&self.uniforms],
+            &Indices {
+                mode: PrimitiveMode::Triangles,
+                offset: 0,
+                len: (self.layout.size.w * self.layout.size.h) as usize * 6,
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use yapgeir_graphics_hal::{sampler::SamplerState, texture::PixelFormat};
+    use yapgeir_graphics_hal_null::{Null, NullBackend};
+
+    use super::*;
+
+    #[test]
+    fn test_set_tile_rewrites_one_quad() {
+        let layout = TileLayout {
+            size: Size::new(4, 3),
+            tile_size: Size::new(16, 16),
+            atlas_size: Size::new(64, 64),
+        };
+
+        let mut tiles = vec![0u16; 12];
+        let before = layout.all_vertices(&tiles);
+
+        tiles[6] = 5;
+        let after = layout.all_vertices(&tiles);
+
+        let changed: Vec<usize> = (0..before.len())
+            .filter(|&i| before[i].tex_position != after[i].tex_position)
+            .collect();
+
+        let offset = layout.offset(2, 1);
+        assert_eq!(changed, (offset..offset + 4).collect::<Vec<_>>());
+        let expected = layout.vertices(2, 1, 5);
+        for (vertex, expected) in after[offset..offset + 4].iter().zip(expected) {
+            assert_eq!(vertex.position, expected.position);
+            assert_eq!(vertex.tex_position, expected.tex_position);
+        }
+    }
+
+    #[test]
+    fn test_set_tile_writes_one_quad() {
+        let graphics = Null::new(NullBackend::new((64, 48)));
+        let atlas = Rc::new(graphics.new_texture_empty(PixelFormat::Rgba, Size::new(64, 64)));
+        let mut renderer = TilemapRenderer::new(
+            &graphics,
+            QuadIndexBuffer::with_quads(&graphics, 12),
+            Sampler::new(atlas, SamplerState::nearest()),
+            Size::new(4, 3),
+            Size::new(16, 16),
+            &[0; 12],
+        );
+
+        renderer.set_tile(2, 1, 5);
+
+        // Only the 4 vertices of the tile are written, after the initial upload.
+        let vertex_size = std::mem::size_of::<SpriteVertex>();
+        let offset = renderer.layout.offset(2, 1) * vertex_size;
+        let quad = offset..offset + 4 * vertex_size;
+        let buffer = &renderer.vertices.bytes;
+        assert_eq!(buffer.writes(), std::slice::from_ref(&quad));
+
+        let expected = renderer.layout.vertices(2, 1, 5);
+        assert_eq!(
+            &buffer.data()[quad],
+            bytemuck::cast_slice::<_, u8>(&expected)
+        );
+    }
+}
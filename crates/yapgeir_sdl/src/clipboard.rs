@@ -0,0 +1,61 @@
+use sdl2::clipboard::ClipboardUtil;
+use yapgeir_realm::{Realm, Res};
+
+/// A resource for reading and writing the system clipboard.
+///
+/// This is the same clipboard that egui uses, so text copied from egui widgets
+/// can be read here and vice versa.
+pub struct Clipboard {
+    clipboard: ClipboardUtil,
+}
+
+impl Clipboard {
+    pub fn new(clipboard: ClipboardUtil) -> Self {
+        Self { clipboard }
+    }
+
+    /// Returns the text in the clipboard, or None if the clipboard is empty.
+    pub fn get(&self) -> Option<String> {
+        if !self.clipboard.has_clipboard_text() {
+            return None;
+        }
+
+        match self.clipboard.clipboard_text() {
+            Ok(text) => Some(text),
+            Err(e) => {
+                eprintln!("Unable to read clipboard: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn set(&self, text: &str) {
+        if let Err(e) = self.clipboard.set_clipboard_text(text) {
+            eprintln!("Unable to write clipboard: {e}");
+        }
+    }
+}
+
+pub fn plugin(realm: &mut Realm) {
+    realm.initialize_resource_with(|video: Res<sdl2::VideoSubsystem>| {
+        Clipboard::new(video.clipboard())
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        let Ok(video) = sdl2::init().and_then(|sdl| sdl.video()) else {
+            eprintln!("SDL video is unavailable, skipping");
+            return;
+        };
+
+        let clipboard = Clipboard::new(video.clipboard());
+        clipboard.set("seed: 1234");
+        assert_eq!(clipboard.get().as_deref(), Some("seed: 1234"));
+    }
+}
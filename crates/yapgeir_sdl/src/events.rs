@@ -1,6 +1,6 @@
 use sdl2::event::Event as SdlEvent;
 use yapgeir_events::Events;
-use yapgeir_realm::{Exit, Realm, Res, ResMut};
+use yapgeir_realm::{stage, Exit, Realm, Res, ResMut};
 
 fn update(
     mut event_pump: ResMut<sdl2::EventPump>,
@@ -11,7 +11,7 @@ fn update(
         if matches!(event, SdlEvent::Quit { .. }) {
             **&mut *exit = true;
         }
-        events.push(event);
+        events.send(event);
     }
 }
 
@@ -21,5 +21,5 @@ pub fn plugin(realm: &mut Realm) {
         .initialize_resource_with(|sdl: Res<sdl2::Sdl>| {
             sdl.event_pump().expect("Unable to get event pump")
         })
-        .add_system(update);
+        .add_system_to_stage(stage::INPUT, update);
 }
@@ -6,11 +6,13 @@ use yapgeir_core::ScreenPpt;
 use yapgeir_events::Events;
 use yapgeir_input::{
     buttons::ButtonAction,
-    controller::{GamepadButton, GamepadId},
-    mouse::{MouseButton, MouseButtonEvent},
+    controller::{GamepadAxis, GamepadButton, GamepadId},
+    keyboard::TextInputEvent,
+    mouse::{Mouse, MouseButton, MouseButtonEvent},
+    touch::{FingerId, TouchEvent},
     Axial, Input,
 };
-use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_realm::{stage, Realm, Res, ResMut};
 
 pub struct SdlControllers {
     subsystem: sdl2::GameControllerSubsystem,
@@ -52,6 +54,17 @@ fn gamepad_button(button: &sdl2::controller::Button) -> GamepadButton {
     }
 }
 
+fn gamepad_axis(axis: &Axis) -> GamepadAxis {
+    match axis {
+        Axis::LeftX => GamepadAxis::LeftX,
+        Axis::LeftY => GamepadAxis::LeftY,
+        Axis::RightX => GamepadAxis::RightX,
+        Axis::RightY => GamepadAxis::RightY,
+        Axis::TriggerLeft => GamepadAxis::TriggerLeft,
+        Axis::TriggerRight => GamepadAxis::TriggerRight,
+    }
+}
+
 fn mouse_button(button: &sdl2::mouse::MouseButton) -> Option<MouseButton> {
     match button {
         sdl2::mouse::MouseButton::Unknown => None,
@@ -63,21 +76,95 @@ fn mouse_button(button: &sdl2::mouse::MouseButton) -> Option<MouseButton> {
     }
 }
 
+/// A resource controlling the mouse pointer.
+pub struct MouseControl {
+    mouse: sdl2::mouse::MouseUtil,
+    relative: bool,
+}
+
+impl MouseControl {
+    pub fn new(mouse: sdl2::mouse::MouseUtil) -> Self {
+        Self {
+            relative: mouse.relative_mouse_mode(),
+            mouse,
+        }
+    }
+
+    /// Hide the cursor and lock it within the window, e.g. for first-person camera controls.
+    ///
+    /// In relative mode `Mouse::motion` still accumulates the movement of the mouse,
+    /// while `Mouse::cursor_position` stays frozen. If the game has an egui overlay,
+    /// relative mode should be disabled while egui wants the pointer
+    /// (`egui::Context::wants_pointer_input`), otherwise the UI can't be interacted with.
+    pub fn set_relative_mouse_mode(&mut self, enabled: bool) {
+        self.mouse.set_relative_mouse_mode(enabled);
+        self.relative = enabled;
+    }
+
+    pub fn relative_mouse_mode(&self) -> bool {
+        self.relative
+    }
+}
+
+/// Start emitting `TextInputEvent`s. On mobile platforms this also shows the on-screen keyboard.
+pub fn start_text_input(video: &sdl2::VideoSubsystem) {
+    video.text_input().start();
+}
+
+/// Stop emitting `TextInputEvent`s.
+pub fn stop_text_input(video: &sdl2::VideoSubsystem) {
+    video.text_input().stop();
+}
+
+fn text_input_event(event: &SdlEvent) -> Option<TextInputEvent> {
+    match event {
+        SdlEvent::TextInput { text, .. } => Some(TextInputEvent(text.clone())),
+        _ => None,
+    }
+}
+
+/// Converts normalized finger coordinates to pixels relative to window.
+fn finger_position(x: f32, y: f32, drawable_size: (u32, u32)) -> Axial<i32> {
+    Axial::new(
+        (x * drawable_size.0 as f32) as i32,
+        (y * drawable_size.1 as f32) as i32,
+    )
+}
+
+/// Applies a motion event scaled to pixels. Multiple motion events in a frame are accumulated.
+fn mouse_motion(mouse: &mut Mouse, relative: bool, position: Axial<i32>, motion: Axial<i32>) {
+    mouse.motion.x += motion.x;
+    mouse.motion.y += motion.y;
+    if !relative {
+        mouse.cursor_position = position;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn update(
     mut input: ResMut<Input>,
+    mouse_control: Res<MouseControl>,
     mut controllers: ResMut<SdlControllers>,
     mut ppt: ResMut<ScreenPpt>,
     mut mouse_button_events: ResMut<Events<MouseButtonEvent>>,
+    mut touch_events: ResMut<Events<TouchEvent>>,
+    mut text_input_events: ResMut<Events<TextInputEvent>>,
     events: Res<Events<SdlEvent>>,
     window: Res<Rc<RefCell<sdl2::video::Window>>>,
 ) {
+    let drawable_size = window.borrow().drawable_size();
+
     for e in &**events {
+        if let Some(text_input) = text_input_event(e) {
+            text_input_events.send(text_input);
+        }
+
         match e {
             SdlEvent::MouseButtonDown {
                 mouse_btn, x, y, ..
             } => {
                 if let Some(button) = mouse_button(mouse_btn) {
-                    mouse_button_events.push(MouseButtonEvent {
+                    mouse_button_events.send(MouseButtonEvent {
                         coordinate: Axial::new(
                             (*x as f32 * **ppt as f32) as i32,
                             (*y as f32 * **ppt as f32) as i32,
@@ -95,7 +182,7 @@ fn update(
                 mouse_btn, x, y, ..
             } => {
                 if let Some(button) = mouse_button(mouse_btn) {
-                    mouse_button_events.push(MouseButtonEvent {
+                    mouse_button_events.send(MouseButtonEvent {
                         coordinate: Axial::new(
                             (*x as f32 * **ppt as f32) as i32,
                             (*y as f32 * **ppt as f32) as i32,
@@ -114,11 +201,51 @@ fn update(
             }
             SdlEvent::MouseMotion {
                 x, y, xrel, yrel, ..
+            } => mouse_motion(
+                &mut input.mouse,
+                mouse_control.relative,
+                Axial::new((*x as f32 * **ppt) as i32, (*y as f32 * **ppt) as i32),
+                Axial::new((*xrel as f32 * **ppt) as i32, (*yrel as f32 * **ppt) as i32),
+            ),
+            SdlEvent::FingerDown {
+                finger_id,
+                x,
+                y,
+                pressure,
+                ..
             } => {
-                input.mouse.motion.x = (*xrel as f32 * **ppt as f32) as i32;
-                input.mouse.motion.y = (*yrel as f32 * **ppt as f32) as i32;
-                input.mouse.cursor_position.x = (*x as f32 * **ppt as f32) as i32;
-                input.mouse.cursor_position.y = (*y as f32 * **ppt as f32) as i32;
+                let coordinate = finger_position(*x, *y, drawable_size);
+                let finger = FingerId(*finger_id);
+                touch_events.send(TouchEvent {
+                    coordinate,
+                    finger,
+                    action: ButtonAction::Down,
+                });
+                input.touches.press(finger, coordinate, *pressure);
+            }
+            SdlEvent::FingerMotion {
+                finger_id,
+                x,
+                y,
+                pressure,
+                ..
+            } => {
+                let coordinate = finger_position(*x, *y, drawable_size);
+                input
+                    .touches
+                    .move_to(FingerId(*finger_id), coordinate, *pressure);
+            }
+            SdlEvent::FingerUp {
+                finger_id, x, y, ..
+            } => {
+                let coordinate = finger_position(*x, *y, drawable_size);
+                let finger = FingerId(*finger_id);
+                touch_events.send(TouchEvent {
+                    coordinate,
+                    finger,
+                    action: ButtonAction::Up,
+                });
+                input.touches.release(finger, coordinate);
             }
             SdlEvent::KeyDown {
                 scancode: Some(scancode),
@@ -138,14 +265,7 @@ fn update(
                     .gamepads
                     .get_mut(&GamepadId::new(*which))
                     .expect("gamepad not found");
-                match axis {
-                    Axis::LeftX => gamepad.left_stick.x = *value as f32 / i32::MAX as f32,
-                    Axis::LeftY => gamepad.left_stick.y = *value as f32 / i32::MAX as f32,
-                    Axis::RightX => gamepad.right_stick.x = *value as f32 / i32::MAX as f32,
-                    Axis::RightY => gamepad.right_stick.y = *value as f32 / i32::MAX as f32,
-                    Axis::TriggerLeft => gamepad.left_trigger = *value as f32 / i32::MAX as f32,
-                    Axis::TriggerRight => gamepad.right_trigger = *value as f32 / i32::MAX as f32,
-                }
+                gamepad.set_axis(gamepad_axis(axis), *value);
             }
             SdlEvent::ControllerButtonDown { button, which, .. } => {
                 let button = gamepad_button(button) as usize;
@@ -200,5 +320,51 @@ pub fn plugin(realm: &mut Realm) {
 
             SdlControllers::new(subsystem)
         })
-        .add_system(update);
+        .initialize_resource_with(|sdl: Res<sdl2::Sdl>| MouseControl::new(sdl.mouse()))
+        .add_system_to_stage(stage::INPUT, update);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_input_event() {
+        let event = SdlEvent::TextInput {
+            timestamp: 0,
+            window_id: 0,
+            text: "á".to_string(),
+        };
+
+        assert_eq!(
+            text_input_event(&event),
+            Some(TextInputEvent("á".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_relative_motion_freezes_cursor() {
+        let mut mouse = Mouse::default();
+        mouse_motion(&mut mouse, false, Axial::new(10, 10), Axial::new(2, 0));
+        mouse_motion(&mut mouse, true, Axial::new(12, 10), Axial::new(2, 0));
+        mouse_motion(&mut mouse, true, Axial::new(12, 13), Axial::new(0, 3));
+
+        assert_eq!(mouse.motion, Axial::new(4, 3));
+        assert_eq!(mouse.cursor_position, Axial::new(10, 10));
+    }
+
+    #[test]
+    fn test_toggle_relative_mouse_mode() {
+        std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        let Ok(sdl) = sdl2::init() else {
+            eprintln!("SDL is unavailable, skipping");
+            return;
+        };
+
+        let mut control = MouseControl::new(sdl.mouse());
+        control.set_relative_mouse_mode(true);
+        assert!(control.relative_mouse_mode());
+        control.set_relative_mouse_mode(false);
+        assert!(!control.relative_mouse_mode());
+    }
 }
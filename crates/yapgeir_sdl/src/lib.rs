@@ -1,8 +1,11 @@
 use yapgeir_core::WindowSize;
 use yapgeir_realm::{Plugin, Realm};
 
+use crate::window::FullscreenMode;
+
 pub use sdl2;
 
+pub mod clipboard;
 pub mod events;
 pub mod input;
 pub mod timer;
@@ -11,6 +14,7 @@ pub mod window;
 pub struct SdlSettings {
     pub title: String,
     pub window_size: WindowSize,
+    pub fullscreen: FullscreenMode,
     pub gl_profile: sdl2::video::GLProfile,
     pub depth_size: u8,
 }
@@ -20,6 +24,7 @@ impl Default for SdlSettings {
         Self {
             title: "Hello, yapgeir!".into(),
             window_size: WindowSize::new(1920, 1080),
+            fullscreen: FullscreenMode::Windowed,
             #[cfg(not(target_os = "emscripten"))]
             gl_profile: sdl2::video::GLProfile::Compatibility,
             #[cfg(target_os = "emscripten")]
@@ -33,6 +38,7 @@ pub fn plugin(settings: SdlSettings) -> impl Plugin {
     move |realm: &mut Realm| {
         realm
             .add_plugin(window::plugin(settings))
+            .add_plugin(clipboard::plugin)
             .add_plugin(timer::plugin)
             .add_plugin(events::plugin)
             .add_plugin(input::plugin);
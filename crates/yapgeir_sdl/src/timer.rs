@@ -1,5 +1,5 @@
 use yapgeir_core::{Delta, Frame};
-use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_realm::{stage, Realm, Res, ResMut};
 
 struct Timer {
     timer: sdl2::TimerSubsystem,
@@ -36,5 +36,5 @@ pub fn plugin(realm: &mut Realm) {
             let timer = sdl.timer().expect("Unable to get sdl timer");
             Timer::new(timer)
         })
-        .add_system(update);
+        .add_system_to_stage(stage::FIRST, update);
 }
@@ -1,17 +1,140 @@
 use std::{cell::RefCell, rc::Rc};
 
-use yapgeir_core::{ScreenPpt, WindowSize};
-use yapgeir_realm::{Plugin, Realm, Res, ResMut};
+use sdl2::{
+    event::{Event as SdlEvent, WindowEvent},
+    video::{FullscreenType, Window},
+};
+use yapgeir_core::{ScreenPpt, WindowResized, WindowSize};
+use yapgeir_events::{EventReader, Events};
+use yapgeir_realm::{stage, Plugin, Realm, Res, ResMut};
 
 use crate::SdlSettings;
 
+/// Fullscreen state of the window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// A borderless window covering the whole screen, keeping the desktop resolution.
+    Desktop,
+    /// Exclusive fullscreen, changing the display resolution to the window size.
+    Exclusive,
+}
+
+impl From<FullscreenMode> for FullscreenType {
+    fn from(mode: FullscreenMode) -> Self {
+        match mode {
+            FullscreenMode::Windowed => FullscreenType::Off,
+            FullscreenMode::Desktop => FullscreenType::Desktop,
+            FullscreenMode::Exclusive => FullscreenType::True,
+        }
+    }
+}
+
+/// A resource for changing the window mode at runtime.
+///
+/// Changes of the window size are reported by SDL as regular window events,
+/// so `WindowSize` is updated and `WindowResized` is sent on the next frame.
+pub struct WindowControl {
+    window: Rc<RefCell<Window>>,
+}
+
+impl WindowControl {
+    pub fn new(window: Rc<RefCell<Window>>) -> Self {
+        Self { window }
+    }
+
+    pub fn set_fullscreen(&self, mode: FullscreenMode) -> Result<(), String> {
+        self.window.borrow_mut().set_fullscreen(mode.into())
+    }
+
+    pub fn fullscreen(&self) -> FullscreenMode {
+        match self.window.borrow().fullscreen_state() {
+            FullscreenType::Off => FullscreenMode::Windowed,
+            FullscreenType::Desktop => FullscreenMode::Desktop,
+            FullscreenType::True => FullscreenMode::Exclusive,
+        }
+    }
+
+    pub fn set_borderless(&self, borderless: bool) {
+        self.window.borrow_mut().set_bordered(!borderless);
+    }
+
+    pub fn set_title(&self, title: &str) -> Result<(), String> {
+        self.window
+            .borrow_mut()
+            .set_title(title)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Set the size of the window in points, same as `SdlSettings::window_size`.
+    pub fn set_size(&self, size: WindowSize) -> Result<(), String> {
+        self.window
+            .borrow_mut()
+            .set_size(size.w, size.h)
+            .map_err(|e| e.to_string())
+    }
+}
+
+fn screen_ppt(window: &sdl2::video::Window) -> ScreenPpt {
+    ScreenPpt(window.drawable_size().0 as f32 / window.size().0 as f32)
+}
+
+fn is_resize(event: &SdlEvent) -> bool {
+    matches!(
+        event,
+        SdlEvent::Window {
+            win_event: WindowEvent::Resized(..) | WindowEvent::SizeChanged(..),
+            ..
+        }
+    )
+}
+
+/// Sends a single `WindowResized` event if any of the unread SDL events resizes the window,
+/// since SDL usually reports both `Resized` and `SizeChanged` for a single resize.
+fn emit_window_resized(
+    reader: &mut EventReader<SdlEvent>,
+    sdl_events: &Events<SdlEvent>,
+    resized: &mut Events<WindowResized>,
+    size: WindowSize,
+    ppt: ScreenPpt,
+) -> bool {
+    // The reader marks all events as seen, even if `any` stops early.
+    let is_resized = reader.read(sdl_events).any(is_resize);
+    if is_resized {
+        resized.send(WindowResized { size, ppt });
+    }
+
+    is_resized
+}
+
+/// Reader of SDL events used to detect window resizes.
+#[derive(Default)]
+struct ResizeReader(EventReader<SdlEvent>);
+
 fn update_window_size(
     mut window_size: ResMut<WindowSize>,
+    mut ppt: ResMut<ScreenPpt>,
+    mut reader: ResMut<ResizeReader>,
+    mut resized: ResMut<Events<WindowResized>>,
+    sdl_events: Res<Events<SdlEvent>>,
     window: Res<Rc<RefCell<sdl2::video::Window>>>,
 ) {
-    let size: (u32, u32) = window.borrow().drawable_size();
+    let window = window.borrow();
+    let size: (u32, u32) = window.drawable_size();
     window_size.w = size.0;
     window_size.h = size.1;
+
+    let new_ppt = screen_ppt(&window);
+    if emit_window_resized(
+        &mut reader.0,
+        &sdl_events,
+        &mut resized,
+        *window_size,
+        new_ppt,
+    ) {
+        *ppt = new_ppt;
+    }
 }
 
 pub fn plugin(settings: SdlSettings) -> impl Plugin {
@@ -23,7 +146,7 @@ pub fn plugin(settings: SdlSettings) -> impl Plugin {
         gl_attr.set_context_profile(settings.gl_profile);
         gl_attr.set_depth_size(settings.depth_size);
 
-        let window = video
+        let mut window = video
             .window(
                 &settings.title,
                 settings.window_size.w,
@@ -34,20 +157,83 @@ pub fn plugin(settings: SdlSettings) -> impl Plugin {
             .resizable()
             .build()
             .expect("Unable to init window");
+        window
+            .set_fullscreen(settings.fullscreen.into())
+            .expect("Unable to set fullscreen mode");
 
         let gl_context = window
             .gl_create_context()
             .expect("Unable to create GLContext");
 
-        let ppt = ScreenPpt(window.drawable_size().0 as f32 / window.size().0 as f32);
+        let ppt = screen_ppt(&window);
+        let window = Rc::new(RefCell::new(window));
 
         realm
+            .add_plugin(yapgeir_events::plugin::<WindowResized>)
             .add_resource(settings.window_size)
             .add_resource(ppt)
             .add_resource(sdl)
             .add_resource(video)
-            .add_resource(Rc::new(RefCell::new(window)))
+            .add_resource(WindowControl::new(window.clone()))
+            .add_resource(window)
             .add_resource(gl_context)
-            .add_system(update_window_size);
+            .initialize_resource::<ResizeReader>()
+            .add_system_to_stage(stage::INPUT, update_window_size);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window_event(win_event: WindowEvent) -> SdlEvent {
+        SdlEvent::Window {
+            timestamp: 0,
+            window_id: 0,
+            win_event,
+        }
+    }
+
+    #[test]
+    fn test_window_resized_emitted_once() {
+        let mut reader = EventReader::default();
+        let mut sdl_events = Events::default();
+        let mut resized = Events::default();
+        let size = WindowSize::new(800, 600);
+
+        sdl_events.send(window_event(WindowEvent::Resized(800, 600)));
+        sdl_events.send(window_event(WindowEvent::SizeChanged(800, 600)));
+        sdl_events.send(window_event(WindowEvent::FocusGained));
+        emit_window_resized(&mut reader, &sdl_events, &mut resized, size, ScreenPpt(2.));
+        assert_eq!(
+            *resized,
+            [WindowResized {
+                size,
+                ppt: ScreenPpt(2.)
+            }]
+        );
+
+        // Already seen events don't resize the window again.
+        emit_window_resized(&mut reader, &sdl_events, &mut resized, size, ScreenPpt(2.));
+        assert_eq!(resized.len(), 1);
+    }
+
+    #[test]
+    fn test_window_control() {
+        std::env::set_var("SDL_VIDEODRIVER", "dummy");
+        let Ok(video) = sdl2::init().and_then(|sdl| sdl.video()) else {
+            eprintln!("SDL video is unavailable, skipping");
+            return;
+        };
+        let window = video.window("test", 320, 240).build().unwrap();
+        let control = WindowControl::new(Rc::new(RefCell::new(window)));
+
+        control.set_borderless(true);
+        control.set_title("renamed").unwrap();
+        control.set_size(WindowSize::new(640, 480)).unwrap();
+        // The dummy driver may not support fullscreen, only check that nothing panics.
+        let _ = control.set_fullscreen(FullscreenMode::Desktop);
+        let _ = control.set_fullscreen(FullscreenMode::Windowed);
+        let _ = control.fullscreen();
     }
 }
@@ -1,7 +1,9 @@
 use std::{cell::RefCell, ffi::c_void, rc::Rc};
 
+use yapgeir_core::{GraphicsContextEvent, GraphicsState};
+use yapgeir_events::Events;
 use yapgeir_graphics_hal::{Graphics, Size, WindowBackend};
-use yapgeir_realm::{Realm, Res};
+use yapgeir_realm::{stage, Realm, Res, ResMut};
 use yapgeir_sdl::sdl2::{self, video::SwapInterval};
 
 pub struct SdlWindowBackend(Rc<RefCell<sdl2::video::Window>>);
@@ -18,12 +20,44 @@ impl WindowBackend for SdlWindowBackend {
     fn default_frame_buffer_size(&self) -> Size<u32> {
         self.0.borrow().drawable_size().into()
     }
+
+    #[cfg(target_os = "emscripten")]
+    fn is_context_lost(&self) -> bool {
+        unsafe { emscripten::is_webgl_context_lost() }
+    }
+}
+
+// SDL doesn't report a loss of the GL context, but on the web it can be checked
+// with `isContextLost()` of the WebGL context SDL has made current.
+#[cfg(target_os = "emscripten")]
+mod emscripten {
+    extern "C" {
+        fn emscripten_webgl_get_current_context() -> usize;
+        fn emscripten_is_webgl_context_lost(context: usize) -> std::ffi::c_int;
+    }
+
+    pub unsafe fn is_webgl_context_lost() -> bool {
+        emscripten_is_webgl_context_lost(emscripten_webgl_get_current_context()) != 0
+    }
+}
+
+fn update_graphics_state<G: Graphics>(
+    graphics: Res<G>,
+    mut state: ResMut<GraphicsState>,
+    mut events: ResMut<Events<GraphicsContextEvent>>,
+) {
+    events.extend(state.update(graphics.is_context_lost()));
 }
 
 pub fn plugin<G>(realm: &mut Realm)
 where
     G: Graphics<Backend = SdlWindowBackend>,
 {
+    realm
+        .add_plugin(yapgeir_events::plugin::<GraphicsContextEvent>)
+        .add_resource(GraphicsState::default())
+        .add_system_to_stage(stage::FIRST, update_graphics_state::<G>);
+
     realm.initialize_resource_with(move |window: Res<Rc<RefCell<sdl2::video::Window>>>| {
         let backend = SdlWindowBackend(window.clone());
         let renderer = G::new(backend);
@@ -1,7 +1,7 @@
-use derive_more::{Constructor, Deref, DerefMut, From};
-use nalgebra::{Isometry2, Matrix3};
+use derive_more::{Deref, DerefMut, From};
+use nalgebra::{Isometry2, Matrix3, Vector2};
 use smart_default::SmartDefault;
-use yapgeir_geometry::Box2D;
+use yapgeir_geometry::{Box2D, Rect};
 
 #[cfg(feature = "reflection")]
 use yapgeir_reflection::bevy_reflect::{self, Reflect};
@@ -29,6 +29,21 @@ pub struct Drawable {
     pub sprite: Sprite,
 }
 
+impl Drawable {
+    /// Creates a drawable from a rectangle of an atlas in pixels, see `Sprite::from_pixel_rect`.
+    pub fn from_pixel_rect(
+        atlas_size: impl Into<[u32; 2]>,
+        pixel_rect: Rect<u32>,
+        logical_size: impl Into<[u32; 2]>,
+    ) -> Self {
+        let size = logical_size.into();
+        Self {
+            size,
+            sprite: Sprite::from_pixel_rect(atlas_size, pixel_rect, size),
+        }
+    }
+}
+
 /// The actual
 #[derive(Debug, Clone, Copy, Default)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
@@ -49,6 +64,30 @@ pub struct Sprite {
     pub sub_texture: Box2D<f32>,
 }
 
+impl Sprite {
+    /// Creates a sprite from a rectangle of an atlas in pixels, with Y going down.
+    ///
+    /// The rectangle is drawn over `logical_size` pixels centered at the origin,
+    /// which is usually the size of the rectangle itself.
+    pub fn from_pixel_rect(
+        atlas_size: impl Into<[u32; 2]>,
+        pixel_rect: Rect<u32>,
+        logical_size: impl Into<[u32; 2]>,
+    ) -> Self {
+        let [atlas_w, atlas_h] = atlas_size.into().map(|v| v as f32);
+        let half_size = logical_size.into().map(|v| v as f32 / 2.);
+        let texel = |x: u32, y: u32| [x as f32 / atlas_w, y as f32 / atlas_h];
+
+        Self {
+            boundaries: Box2D::new([-half_size[0], -half_size[1]], half_size),
+            sub_texture: Box2D::new(
+                texel(pixel_rect.x, pixel_rect.y),
+                texel(pixel_rect.x + pixel_rect.w, pixel_rect.y + pixel_rect.h),
+            ),
+        }
+    }
+}
+
 /// Sprite's quad in world space.
 /// Defines an area where the sprite should be drawn.
 /// `yapgeir_world_2d_sprites` plugin will automatically add this component
@@ -78,6 +117,14 @@ pub struct Static;
 #[cfg_attr(feature = "reflection", derive(Reflect))]
 pub struct Dirty;
 
+/// Marks an entity whose `DrawQuad` is outside of the area visible through the `WorldCamera`.
+/// Entities with this component can be skipped during rendering.
+///
+/// This component is managed automatically by the `yapgeir_world_2d_sprites` culling system.
+#[derive(Default, Debug, Clone)]
+#[cfg_attr(feature = "reflection", derive(Reflect))]
+pub struct Culled;
+
 /// A view+projection matrix passed to a shader.
 /// A camera defines how world space is transformed into screen space.
 #[derive(Default, Clone, From, Deref, DerefMut)]
@@ -92,15 +139,71 @@ pub enum Flip {
     Y,
 }
 
+/// A reflectable wrapper for `Isometry2<f32>`.
+///
+/// nalgebra types don't implement `Reflect`, so the isometry is reflected as an opaque value,
+/// which can be edited with a custom inspector element.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Deref, DerefMut, From)]
+#[cfg_attr(
+    feature = "reflection",
+    derive(Reflect),
+    reflect_value(Default, Debug, PartialEq)
+)]
+pub struct Isometry(pub Isometry2<f32>);
+
+/// A reflectable wrapper for `Vector2<f32>`, e.g. for velocities of reflected components.
+///
+/// Like `Isometry`, it is reflected as an opaque value with a custom inspector element.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Deref, DerefMut, From)]
+#[cfg_attr(
+    feature = "reflection",
+    derive(Reflect),
+    reflect_value(Default, Debug, PartialEq)
+)]
+pub struct Vector(pub Vector2<f32>);
+
 /// Transformation matrix of an entity. The unit of this matrix
 /// is an abstract point. Unless `TransformPpt` resource
 /// is registered, it is assumed that one point translates to one pixel.
-#[derive(Constructor, Default, Debug, Clone)]
+#[derive(SmartDefault, Debug, Clone)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
 pub struct Transform {
-    #[cfg_attr(feature = "reflection", reflect(ignore))]
-    pub isometry: Isometry2<f32>,
+    pub isometry: Isometry,
     pub flip: Option<Flip>,
+    /// Non-uniform scale along the X and Y axes, applied before the rotation.
+    #[default([1., 1.])]
+    pub scale: [f32; 2],
+}
+
+impl Transform {
+    pub fn new(isometry: Isometry2<f32>, flip: Option<Flip>) -> Self {
+        Self {
+            isometry: Isometry(isometry),
+            flip,
+            scale: [1., 1.],
+        }
+    }
+
+    pub fn with_scale(self, scale: [f32; 2]) -> Self {
+        Self { scale, ..self }
+    }
+
+    /// Returns the scale with the flip applied as a negative scale along the flipped axis.
+    pub fn flipped_scale(&self) -> [f32; 2] {
+        let [x, y] = self.scale;
+        match self.flip {
+            Some(Flip::X) => [-x, y],
+            Some(Flip::Y) => [x, -y],
+            None => [x, y],
+        }
+    }
+
+    /// Returns a homogeneous transformation matrix, which applies scale (including flip),
+    /// rotation and translation to a point in that order.
+    pub fn matrix(&self) -> Matrix3<f32> {
+        self.isometry.to_homogeneous()
+            * Matrix3::new_nonuniform_scaling(&Vector2::from(self.flipped_scale()))
+    }
 }
 
 /// Depth used for depth buffer to define render order.
@@ -114,3 +217,42 @@ pub struct Depth(pub u16);
 #[derive(SmartDefault, Debug, Clone, Copy, Deref, DerefMut)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
 pub struct TransformPpt(#[default(1.)] pub f32);
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Point2;
+
+    use super::*;
+
+    #[test]
+    fn test_transform_matrix_order() {
+        let transform = Transform::new(
+            Isometry2::new(Vector2::new(10., 20.), std::f32::consts::FRAC_PI_2),
+            None,
+        )
+        .with_scale([2., 3.]);
+
+        // Scaled to [2, 3], rotated to [-3, 2], then translated.
+        let point = transform.matrix().transform_point(&Point2::new(1., 1.));
+        assert!((point - Point2::new(7., 22.)).norm() < 1e-5, "{point}");
+    }
+
+    #[test]
+    fn test_sprite_from_pixel_rect() {
+        let drawable = Drawable::from_pixel_rect([256, 128], Rect::new(64, 32, 16, 8), [16, 8]);
+
+        assert_eq!(drawable.size, [16, 8]);
+        assert_eq!(
+            drawable.sprite.sub_texture,
+            Box2D::new([64. / 256., 32. / 128.], [80. / 256., 40. / 128.])
+        );
+        assert_eq!(drawable.sprite.boundaries, Box2D::new([-8., -4.], [8., 4.]));
+
+        // Matches the sprites of a uniform sprite sheet.
+        let sheet = SpriteSheet::new([256, 128], [16, 8]);
+        assert_eq!(
+            sheet.drawable(4, 4).sprite.sub_texture,
+            drawable.sprite.sub_texture
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use yapgeir_geometry::Box2D;
 
 use crate::{Drawable, Sprite};
@@ -29,6 +31,36 @@ impl SpriteSheet {
         }
     }
 
+    /// Returns the number of columns and rows of sprites which fit into the texture.
+    pub fn dimensions(&self) -> [u32; 2] {
+        [
+            self.texture_size[0] / self.sprite_size[0],
+            self.texture_size[1] / self.sprite_size[1],
+        ]
+    }
+
+    pub fn cell_count(&self) -> u32 {
+        let [columns, rows] = self.dimensions();
+        columns * rows
+    }
+
+    /// Returns all sprites of a row, from left to right.
+    pub fn row(&self, y: u32) -> impl Iterator<Item = Drawable> + '_ {
+        (0..self.dimensions()[0]).map(move |x| self.drawable(x, y))
+    }
+
+    /// Returns all sprites of a column, from top to bottom.
+    pub fn column(&self, x: u32) -> impl Iterator<Item = Drawable> + '_ {
+        (0..self.dimensions()[1]).map(move |y| self.drawable(x, y))
+    }
+
+    /// Returns sprites by their cell indices, counted in row-major order.
+    /// Cells may span multiple rows, e.g. for animations longer than one row.
+    pub fn range(&self, cells: Range<u32>) -> impl Iterator<Item = Drawable> + '_ {
+        let columns = self.dimensions()[0];
+        cells.map(move |i| self.drawable(i % columns, i / columns))
+    }
+
     pub fn drawable(&self, x: u32, y: u32) -> Drawable {
         Drawable {
             size: self.sprite_size,
@@ -45,3 +77,33 @@ impl SpriteSheet {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row() {
+        let sheet = SpriteSheet::new([64, 32], [16, 16]);
+        assert_eq!(sheet.dimensions(), [4, 2]);
+        assert_eq!(sheet.cell_count(), 8);
+
+        let row: Vec<_> = sheet.row(0).map(|d| d.sprite.sub_texture).collect();
+        assert_eq!(row.len(), 4);
+        for (i, a) in row.iter().enumerate() {
+            assert!(row[i + 1..].iter().all(|b| a != b));
+        }
+
+        assert_eq!(sheet.column(1).count(), 2);
+    }
+
+    #[test]
+    fn test_range_wraps_rows() {
+        let sheet = SpriteSheet::new([64, 32], [16, 16]);
+
+        let range: Vec<_> = sheet.range(3..6).map(|d| d.sprite.sub_texture).collect();
+        let expected =
+            [(3, 0), (0, 1), (1, 1)].map(|(x, y)| sheet.drawable(x, y).sprite.sub_texture);
+        assert_eq!(range, expected);
+    }
+}
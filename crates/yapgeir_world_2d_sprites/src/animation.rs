@@ -5,6 +5,7 @@ use hecs::{Entity, Without, World};
 use yapgeir_assets::animations::{Animation, AnimationKind, AnimationSequence};
 use yapgeir_collections::{PersistentSlotMap, Slot};
 use yapgeir_core::Delta;
+use yapgeir_events::Events;
 use yapgeir_realm::{system, Realm, Res, ResMut};
 use yapgeir_world_2d::Drawable;
 
@@ -14,19 +15,24 @@ use yapgeir_reflection::{
     RealmExtensions,
 };
 
+/// A key of an animation sequence in `AnimationStorage`, with the slot generation,
+/// so that a key of a removed sequence doesn't refer to a sequence inserted later.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Constructor, Hash)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
-pub struct AnimationSequenceKey(u16);
+pub struct AnimationSequenceKey(u16, u32);
 
 impl From<Slot> for AnimationSequenceKey {
     fn from(slot: Slot) -> Self {
-        Self(slot.0 as u16)
+        Self(slot.index as u16, slot.generation)
     }
 }
 
 impl Into<Slot> for AnimationSequenceKey {
     fn into(self) -> Slot {
-        Slot(self.0 as usize)
+        Slot {
+            index: self.0 as usize,
+            generation: self.1,
+        }
     }
 }
 
@@ -63,6 +69,12 @@ impl AnimationStorage {
     pub fn find_key(&self, key: &str) -> Option<AnimationSequenceKey> {
         self.0.find_slot_by_key(key).map(|slot| slot.into())
     }
+
+    /// Removes an animation sequence, e.g. when unloading a level.
+    /// Animators still playing the sequence must be removed beforehand.
+    pub fn remove(&mut self, key: AnimationSequenceKey) -> Option<AnimationSequence> {
+        self.0.remove(key.into())
+    }
 }
 
 impl Index<AnimationKey> for AnimationStorage {
@@ -88,18 +100,55 @@ pub struct Frame {
     reversed: bool,
 }
 
-fn next_frame(animation: &Animation, frame: Frame) -> Frame {
+/// Returns the frame an animation starts with, unless a specific start frame is requested.
+/// `Loop` and `Single` animations played backwards start with the last frame.
+fn start_frame(animation: &Animation, backwards: bool, start: Option<u8>) -> Frame {
+    match start {
+        Some(index) => Frame {
+            index: index.min((animation.frames.len() - 1) as u8),
+            reversed: false,
+        },
+        None => first_frame(animation, backwards),
+    }
+}
+
+fn first_frame(animation: &Animation, backwards: bool) -> Frame {
+    Frame {
+        index: match backwards && animation.kind != AnimationKind::PingPong {
+            true => (animation.frames.len() - 1) as u8,
+            false => 0,
+        },
+        reversed: false,
+    }
+}
+
+fn is_end(animation: &Animation, frame: Frame, backwards: bool) -> bool {
+    match backwards {
+        true => animation.kind == AnimationKind::Single && frame.index == 0,
+        false => animation.is_end(frame.index),
+    }
+}
+
+fn next_frame(animation: &Animation, frame: Frame, backwards: bool) -> Frame {
     let is_last_frame = animation.is_last_frame(frame.index);
 
-    match animation.kind {
-        AnimationKind::Loop => Frame {
+    match (animation.kind, backwards) {
+        (AnimationKind::Loop, false) => Frame {
             index: match is_last_frame {
                 true => 0,
                 false => frame.index + 1,
             },
             reversed: false,
         },
-        AnimationKind::PingPong => {
+        (AnimationKind::Loop, true) => Frame {
+            index: match frame.index {
+                0 => (animation.frames.len() - 1) as u8,
+                index => index - 1,
+            },
+            reversed: false,
+        },
+        // Ping-pong animations change the direction anyway.
+        (AnimationKind::PingPong, _) => {
             let reversed = is_last_frame || (frame.reversed && frame.index > 0);
             Frame {
                 index: match reversed {
@@ -109,10 +158,14 @@ fn next_frame(animation: &Animation, frame: Frame) -> Frame {
                 reversed,
             }
         }
-        AnimationKind::Single => Frame {
+        (AnimationKind::Single, false) => Frame {
             index: frame.index + 1,
             reversed: false,
         },
+        (AnimationKind::Single, true) => Frame {
+            index: frame.index - 1,
+            reversed: false,
+        },
     }
 }
 
@@ -124,6 +177,14 @@ pub enum FrameState {
     Frame(Frame),
 }
 
+/// An event sent once an animator finishes the last animation of a sequence,
+/// which only happens if that animation is `AnimationKind::Single`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub sequence: AnimationSequenceKey,
+}
+
 /// A component that will drive drawable change on an entity
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "reflection", derive(Reflect))]
@@ -132,6 +193,13 @@ pub struct Animator {
     next_sequence: Option<AnimationSequenceKey>,
     elapsed: f32,
     frame: FrameState,
+    /// Playback speed multiplier, negative values play frames in reverse.
+    speed: f32,
+    paused: bool,
+    /// Plays frames in reverse, combined with the sign of `speed`.
+    reversed: bool,
+    /// Frame of the first animation to start with instead of the first one.
+    start_frame: Option<u8>,
 }
 
 impl Animator {
@@ -141,20 +209,84 @@ impl Animator {
             next_sequence: None,
             elapsed: 0.,
             frame: FrameState::Started,
+            speed: 1.,
+            paused: false,
+            reversed: false,
+            start_frame: None,
+        }
+    }
+
+    /// Create an animator starting on a specific frame of the first animation in a sequence.
+    /// The frame index is clamped to the last frame of the animation.
+    pub fn new_at(sequence: AnimationSequenceKey, frame: u8) -> Self {
+        Self {
+            start_frame: Some(frame),
+            ..Self::new(sequence)
         }
     }
 
+    /// Play frames from the last to the first one, e.g. to close a door
+    /// using the frames of an animation opening it.
+    ///
+    /// Unlike `AnimationKind::PingPong`, a reversed `Single` animation ends on the first frame.
+    /// Negative speed reverses the direction once more.
+    pub fn set_reversed(&mut self, reversed: bool) {
+        self.reversed = reversed;
+    }
+
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+
+    fn is_backwards(&self) -> bool {
+        self.reversed != (self.speed < 0.)
+    }
+
+    pub fn speed(&self) -> f32 {
+        self.speed
+    }
+
+    /// Set the playback speed, where 1 is the normal speed and 0 stops the animation.
+    /// Negative speed plays `Loop` and `Single` animations backwards.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    /// Stop advancing frames, keeping the current speed.
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Returns true if the sequence is not playing, either because
+    /// it is a different one, or because it has already ended.
+    fn is_stopped(&self, sequence: AnimationSequenceKey) -> bool {
+        self.animation.0 != sequence || self.frame == FrameState::Ended
+    }
+
+    /// Play a sequence after the current frame ends.
+    /// An ended sequence is played from the start again.
     pub fn play_deferred(&mut self, sequence: AnimationSequenceKey) {
-        if self.animation.0 != sequence {
+        if self.is_stopped(sequence) {
             self.next_sequence = Some(sequence);
         }
     }
 
+    /// Play a sequence immediately.
+    /// An ended sequence is played from the start again.
     pub fn play_now(&mut self, sequence: AnimationSequenceKey) {
-        if self.animation.0 != sequence {
+        if self.is_stopped(sequence) {
             self.animation = AnimationKey(sequence, 0);
             self.frame = FrameState::Started;
             self.elapsed = 0.;
+            self.start_frame = None;
         }
     }
 }
@@ -179,49 +311,78 @@ impl DrawableAdder {
     }
 }
 
-fn update(mut world: ResMut<World>, store: Res<AnimationStorage>, delta: Res<Delta>) {
-    for (_, (a, drawable)) in world.query_mut::<(&mut Animator, &mut Drawable)>() {
+fn update_animators(
+    world: &mut World,
+    store: &AnimationStorage,
+    delta: f32,
+    finished: &mut Events<AnimationFinished>,
+) {
+    for (entity, (a, drawable)) in world.query_mut::<(&mut Animator, &mut Drawable)>() {
+        let backwards = a.is_backwards();
         let frame = match (a.frame, mem::take(&mut a.next_sequence)) {
             (FrameState::Ended, None) => {
                 continue;
             }
-            (FrameState::Started, None) => Frame::default(),
+            (FrameState::Started, None) => {
+                start_frame(&store[a.animation], backwards, a.start_frame.take())
+            }
             (FrameState::Started, Some(next)) | (FrameState::Ended, Some(next)) => {
                 a.play_now(next);
-                Frame::default()
+                first_frame(&store[a.animation], backwards)
             }
-            (FrameState::Frame(frame), next) => {
-                let animation = &store[a.animation];
-
-                a.elapsed += **delta;
-                if a.elapsed < animation.frame_time {
-                    // Put it back for now
-                    a.next_sequence = next;
-                    continue;
+            (FrameState::Frame(mut frame), mut next) => {
+                if !a.paused {
+                    a.elapsed += delta * a.speed.abs();
                 }
 
-                a.elapsed -= animation.frame_time;
-
-                match next {
-                    Some(next) => {
-                        a.play_now(next);
-                        Frame::default()
+                // Fast animations can advance multiple frames during a single update.
+                let mut advanced = false;
+                let ended = loop {
+                    let animation = &store[a.animation];
+                    let wait = a.elapsed < animation.frame_time
+                        // Guards against an infinite loop with zero frame time.
+                        || (advanced && animation.frame_time <= 0.);
+                    if wait {
+                        break false;
                     }
-                    None if animation.is_end(frame.index) => {
-                        match store.is_last_in_sequence(a.animation) {
-                            true => {
-                                a.frame = FrameState::Ended;
-                                continue;
-                            }
-                            false => {
-                                a.frame = FrameState::Started;
-                                a.animation.1 += 1;
-                                Frame::default()
+
+                    a.elapsed -= animation.frame_time;
+                    advanced = true;
+
+                    frame = match next.take() {
+                        Some(next) => {
+                            a.play_now(next);
+                            first_frame(&store[a.animation], backwards)
+                        }
+                        None if is_end(animation, frame, backwards) => {
+                            match store.is_last_in_sequence(a.animation) {
+                                true => break true,
+                                false => {
+                                    a.animation.1 += 1;
+                                    first_frame(&store[a.animation], backwards)
+                                }
                             }
                         }
-                    }
-                    None => next_frame(animation, frame),
+                        None => next_frame(animation, frame, backwards),
+                    };
+                };
+
+                if ended {
+                    a.frame = FrameState::Ended;
+                    finished.send(AnimationFinished {
+                        entity,
+                        sequence: a.animation.0,
+                    });
+                    continue;
+                }
+
+                if !advanced {
+                    // Put it back for now
+                    a.next_sequence = next;
+                    continue;
                 }
+
+                frame
             }
         };
 
@@ -232,6 +393,15 @@ fn update(mut world: ResMut<World>, store: Res<AnimationStorage>, delta: Res<Del
     }
 }
 
+fn update(
+    mut world: ResMut<World>,
+    store: Res<AnimationStorage>,
+    delta: Res<Delta>,
+    mut finished: ResMut<Events<AnimationFinished>>,
+) {
+    update_animators(&mut world, &store, **delta, &mut finished);
+}
+
 pub fn plugin(realm: &mut Realm) {
     #[cfg(feature = "reflection")]
     realm
@@ -239,7 +409,189 @@ pub fn plugin(realm: &mut Realm) {
         .register_non_default_type::<Animator>();
 
     realm
-        .add_resource(AnimationStorage::default())
-        .add_system(DrawableAdder::default())
-        .add_system(update);
+        .add_plugin(yapgeir_events::plugin::<AnimationFinished>)
+        .add_resource(AnimationStorage::default());
+    realm.add_system(DrawableAdder::default());
+    realm.add_system(update);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sequence(kind: AnimationKind, frames: usize) -> AnimationSequence {
+        AnimationSequence::new(vec![Animation {
+            frames: vec![Drawable::default(); frames],
+            kind,
+            frame_time: 1.,
+        }])
+    }
+
+    fn frame_index(world: &World, entity: Entity) -> Option<u8> {
+        match world.get::<&Animator>(entity).unwrap().frame {
+            FrameState::Frame(frame) => Some(frame.index),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_remove_sequence() {
+        let mut store = AnimationStorage::default();
+        let walk = store.insert("walk", sequence(AnimationKind::Loop, 4));
+        let idle = store.insert("idle", sequence(AnimationKind::Single, 1));
+
+        assert!(store.remove(walk).is_some());
+        assert_eq!(store.find_key("walk"), None);
+
+        // The slot of the removed sequence is reused with a new key.
+        let jump = store.insert("jump", sequence(AnimationKind::Single, 2));
+        assert_ne!(jump, walk);
+        assert!(store.remove(walk).is_none());
+        assert_eq!(store[jump][0].frames.len(), 2);
+
+        assert_eq!(store.find_key("idle"), Some(idle));
+        assert_eq!(store[idle][0].frames.len(), 1);
+    }
+
+    #[test]
+    fn test_speed() {
+        let mut store = AnimationStorage::default();
+        let walk = store.insert("walk", sequence(AnimationKind::Loop, 4));
+
+        let mut world = World::new();
+        let entity = world.spawn((Animator::new(walk), Drawable::default()));
+        let update = |world: &mut World, delta: f32| {
+            update_animators(world, &store, delta, &mut Events::default());
+            frame_index(world, entity)
+        };
+        let set_speed = |world: &mut World, speed: f32| {
+            world.get::<&mut Animator>(entity).unwrap().set_speed(speed);
+        };
+
+        assert_eq!(update(&mut world, 1.), Some(0));
+
+        // Half speed takes two frame times to advance a frame.
+        set_speed(&mut world, 0.5);
+        assert_eq!(update(&mut world, 1.), Some(0));
+        assert_eq!(update(&mut world, 1.), Some(1));
+
+        // Double speed advances two frames during a single frame time.
+        set_speed(&mut world, 2.);
+        assert_eq!(update(&mut world, 1.), Some(3));
+
+        // Negative speed plays frames in reverse.
+        set_speed(&mut world, -1.);
+        assert_eq!(update(&mut world, 1.), Some(2));
+
+        world.get::<&mut Animator>(entity).unwrap().pause();
+        assert_eq!(update(&mut world, 10.), Some(2));
+        world.get::<&mut Animator>(entity).unwrap().resume();
+        assert_eq!(update(&mut world, 1.), Some(1));
+    }
+
+    #[test]
+    fn test_single_backwards() {
+        let mut store = AnimationStorage::default();
+        let rewind = store.insert("rewind", sequence(AnimationKind::Single, 3));
+
+        let mut world = World::new();
+        let mut animator = Animator::new(rewind);
+        animator.set_speed(-1.);
+        let entity = world.spawn((animator, Drawable::default()));
+
+        let mut finished = Events::default();
+        let frames: Vec<_> = (0..4)
+            .map(|_| {
+                update_animators(&mut world, &store, 1., &mut finished);
+                frame_index(&world, entity)
+            })
+            .collect();
+
+        assert_eq!(frames, [Some(2), Some(1), Some(0), None]);
+        assert_eq!(finished.len(), 1);
+    }
+
+    #[test]
+    fn test_reversed_single() {
+        let mut store = AnimationStorage::default();
+        let door = store.insert("door", sequence(AnimationKind::Single, 4));
+
+        let mut world = World::new();
+        let mut closing = Animator::new_at(door, 2);
+        closing.set_reversed(true);
+        let entity = world.spawn((closing, Drawable::default()));
+
+        let mut finished = Events::default();
+        let frames: Vec<_> = (0..4)
+            .map(|_| {
+                update_animators(&mut world, &store, 1., &mut finished);
+                frame_index(&world, entity)
+            })
+            .collect();
+
+        assert_eq!(frames, [Some(2), Some(1), Some(0), None]);
+        assert_eq!(
+            *finished,
+            [AnimationFinished {
+                entity,
+                sequence: door
+            }]
+        );
+    }
+
+    #[test]
+    fn test_new_at() {
+        let mut store = AnimationStorage::default();
+        let walk = store.insert("walk", sequence(AnimationKind::Loop, 4));
+
+        let mut world = World::new();
+        let entity = world.spawn((Animator::new_at(walk, 3), Drawable::default()));
+        let update = |world: &mut World| {
+            update_animators(world, &store, 1., &mut Events::default());
+            frame_index(world, entity)
+        };
+
+        assert_eq!(update(&mut world), Some(3));
+        assert_eq!(update(&mut world), Some(0));
+
+        // Out of range start frames are clamped.
+        let mut animator = Animator::new_at(walk, 10);
+        animator.set_reversed(true);
+        let entity = world.spawn((animator, Drawable::default()));
+        update_animators(&mut world, &store, 1., &mut Events::default());
+        assert_eq!(frame_index(&world, entity), Some(3));
+    }
+
+    #[test]
+    fn test_finished_once() {
+        let mut store = AnimationStorage::default();
+        let death = store.insert("death", sequence(AnimationKind::Single, 2));
+
+        let mut world = World::new();
+        let entity = world.spawn((Animator::new(death), Drawable::default()));
+
+        let mut finished = Events::default();
+        for _ in 0..10 {
+            update_animators(&mut world, &store, 1., &mut finished);
+        }
+
+        assert_eq!(
+            *finished,
+            [AnimationFinished {
+                entity,
+                sequence: death
+            }]
+        );
+
+        // Replaying an ended sequence finishes it again.
+        world
+            .get::<&mut Animator>(entity)
+            .unwrap()
+            .play_deferred(death);
+        for _ in 0..10 {
+            update_animators(&mut world, &store, 1., &mut finished);
+        }
+
+        assert_eq!(finished.len(), 2);
+    }
 }
@@ -0,0 +1,125 @@
+use nalgebra::{Matrix3, Point2, Rotation2, Vector2};
+use smart_default::SmartDefault;
+use yapgeir_core::WindowSize;
+use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_world_2d::WorldCamera;
+
+#[cfg(feature = "reflection")]
+use yapgeir_reflection::{
+    bevy_reflect::{self, Reflect},
+    RealmExtensions,
+};
+
+/// A 2D camera looking at a point of the world.
+///
+/// The `plugin` converts it into the `WorldCamera` matrix on every frame.
+#[derive(SmartDefault, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "reflection", derive(Reflect))]
+pub struct Camera2D {
+    /// A point in world space shown in the center of the screen.
+    pub position: [f32; 2],
+    /// Number of pixels per world unit.
+    #[default(1.)]
+    pub zoom: f32,
+    /// Rotation of the camera in radians, counter-clockwise.
+    /// The world appears rotated in the opposite direction.
+    pub rotation: f32,
+}
+
+impl Camera2D {
+    /// Returns a matrix transforming world space into pixel space with (0; 0)
+    /// in the center of the screen, as expected by `WorldCamera`.
+    pub fn matrix(&self) -> Matrix3<f32> {
+        Matrix3::new_nonuniform_scaling(&Vector2::new(self.zoom, self.zoom))
+            * Rotation2::new(-self.rotation).to_homogeneous()
+            * Matrix3::new_translation(&-Vector2::from(self.position))
+    }
+
+    /// Converts a point in world space to a window position in pixels,
+    /// with (0; 0) in the top-left corner.
+    pub fn world_to_screen(&self, world: [f32; 2], window: WindowSize) -> [f32; 2] {
+        let pixel = self.matrix().transform_point(&Point2::from(world));
+        [
+            pixel.x + window.w as f32 / 2.,
+            window.h as f32 / 2. - pixel.y,
+        ]
+    }
+
+    /// Converts a window position in pixels, with (0; 0) in the top-left corner,
+    /// to a point in world space.
+    ///
+    /// Returns `None` if the zoom is zero.
+    pub fn screen_to_world(&self, screen: [f32; 2], window: WindowSize) -> Option<[f32; 2]> {
+        let pixel = Point2::new(
+            screen[0] - window.w as f32 / 2.,
+            window.h as f32 / 2. - screen[1],
+        );
+        let inverse = self.matrix().try_inverse()?;
+        Some(inverse.transform_point(&pixel).into())
+    }
+}
+
+fn update_world_camera(camera: Res<Camera2D>, mut world_camera: ResMut<WorldCamera>) {
+    **world_camera = camera.matrix();
+}
+
+/// Adds a `Camera2D` resource, which updates the `WorldCamera` on every frame.
+///
+/// Must be added before the systems which render with the `WorldCamera`.
+pub fn plugin(realm: &mut Realm) {
+    #[cfg(feature = "reflection")]
+    realm.register_type::<Camera2D>();
+
+    realm
+        .initialize_resource::<Camera2D>()
+        .initialize_resource::<WorldCamera>()
+        .add_system(update_world_camera);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(a: [f32; 2], b: [f32; 2]) {
+        assert!(
+            (a[0] - b[0]).abs() < 1e-4 && (a[1] - b[1]).abs() < 1e-4,
+            "{a:?} != {b:?}"
+        );
+    }
+
+    #[test]
+    fn test_matrix() {
+        let camera = Camera2D {
+            position: [10., 20.],
+            zoom: 2.,
+            rotation: std::f32::consts::FRAC_PI_2,
+        };
+        let pixel = |x: f32, y: f32| camera.matrix().transform_point(&Point2::new(x, y));
+
+        assert_near(pixel(10., 20.).into(), [0., 0.]);
+        // One unit to the right of the camera is two pixels below the center,
+        // since the camera is rotated a quarter turn counter-clockwise.
+        assert_near(pixel(11., 20.).into(), [0., -2.]);
+        assert_near(pixel(10., 21.).into(), [2., 0.]);
+
+        assert_eq!(Camera2D::default().matrix(), Matrix3::identity());
+    }
+
+    #[test]
+    fn test_screen_world_round_trip() {
+        let window = WindowSize::new(600, 400);
+        let camera = Camera2D {
+            position: [-5., 7.],
+            zoom: 3.,
+            rotation: 0.3,
+        };
+
+        assert_near(camera.world_to_screen([-5., 7.], window), [300., 200.]);
+
+        let world = camera.screen_to_world([123., 45.], window).unwrap();
+        assert_near(camera.world_to_screen(world, window), [123., 45.]);
+
+        let zero = Camera2D { zoom: 0., ..camera };
+        assert_eq!(zero.screen_to_world([0., 0.], window), None);
+    }
+}
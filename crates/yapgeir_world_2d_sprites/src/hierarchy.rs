@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use derive_more::Deref;
+use hecs::{Entity, Without, World};
+use nalgebra::Matrix3;
+use yapgeir_realm::ResMut;
+use yapgeir_world_2d::Transform;
+
+/// Attaches an entity to a parent entity.
+/// `Transform` of an entity with a parent is relative to the parent's world transformation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deref)]
+pub struct Parent(pub Entity);
+
+/// World space transformation matrix of an entity with a `Parent`.
+///
+/// This component is managed automatically by the hierarchy system,
+/// and is used instead of the `Transform` when calculating a `DrawQuad`.
+#[derive(Debug, Clone, Copy, PartialEq, Deref)]
+pub struct GlobalTransform(pub Matrix3<f32>);
+
+/// Reused allocations for the hierarchy system.
+#[derive(Default)]
+pub(crate) struct HierarchyCache {
+    resolved: HashMap<Entity, Matrix3<f32>>,
+    skipped: HashSet<Entity>,
+    /// Entities in cycles which were already reported, kept while they stay skipped.
+    reported: HashSet<Entity>,
+    /// Entities at which cycles were found in this update, which weren't reported before.
+    cycles: Vec<Entity>,
+    path: Vec<Entity>,
+    orphans: Vec<Entity>,
+}
+
+fn local_matrix(world: &World, entity: Entity) -> Matrix3<f32> {
+    world
+        .get::<&Transform>(entity)
+        .map(|transform| transform.matrix())
+        .unwrap_or_else(|_| Matrix3::identity())
+}
+
+fn parent(world: &World, entity: Entity) -> Option<Entity> {
+    world.get::<&Parent>(entity).ok().map(|parent| parent.0)
+}
+
+/// Calculates world matrices of all entities with a `Parent`.
+///
+/// Each entity is resolved by walking up to the closest root or an already resolved
+/// ancestor, and then composing the matrices back down the path, so parents are
+/// always resolved before their children. Entities in or below a cycle are skipped.
+fn resolve_hierarchy(world: &World, cache: &mut HierarchyCache) {
+    let HierarchyCache {
+        resolved,
+        skipped,
+        reported,
+        cycles,
+        path,
+        ..
+    } = cache;
+
+    resolved.clear();
+    skipped.clear();
+    cycles.clear();
+
+    for (entity, _) in world.query::<&Parent>().iter() {
+        let mut current = entity;
+        let base = loop {
+            if let Some(matrix) = resolved.get(&current) {
+                break Some(*matrix);
+            }
+
+            if skipped.contains(&current) {
+                break None;
+            }
+
+            if let Some(start) = path.iter().position(|e| *e == current) {
+                // Each entity of a cycle is reported once, and not on every update.
+                let new = path[start..]
+                    .iter()
+                    .fold(false, |new, e| reported.insert(*e) | new);
+                if new {
+                    cycles.push(current);
+                }
+                break None;
+            }
+
+            match parent(world, current) {
+                Some(parent) => {
+                    path.push(current);
+                    current = parent;
+                }
+                None => break Some(local_matrix(world, current)),
+            }
+        };
+
+        match base {
+            Some(mut matrix) => {
+                for e in path.drain(0..).rev() {
+                    matrix *= local_matrix(world, e);
+                    resolved.insert(e, matrix);
+                }
+            }
+            None => skipped.extend(path.drain(0..)),
+        }
+    }
+
+    // Cycles which were broken are reported again if they are formed again.
+    reported.retain(|e| skipped.contains(e));
+}
+
+pub(crate) fn update_hierarchy(mut world: ResMut<World>, mut cache: ResMut<HierarchyCache>) {
+    resolve_hierarchy(&world, &mut cache);
+
+    for entity in &cache.cycles {
+        eprintln!("Transform hierarchy cycle detected at {entity:?}, skipping");
+    }
+
+    for (entity, matrix) in cache.resolved.drain() {
+        world
+            .insert_one(entity, GlobalTransform(matrix))
+            .expect("Unable to insert GlobalTransform for entity");
+    }
+
+    // Entities detached from their parents are transformed by their own `Transform` again.
+    let HierarchyCache { orphans, .. } = &mut *cache;
+    world
+        .query::<Without<&GlobalTransform, &Parent>>()
+        .iter()
+        .for_each(|(e, _)| orphans.push(e));
+
+    for e in orphans.drain(0..) {
+        world
+            .remove_one::<GlobalTransform>(e)
+            .expect("Unable to remove GlobalTransform from entity");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::{Isometry2, Point2, Vector2};
+
+    use super::*;
+
+    fn translation(x: f32, y: f32) -> Transform {
+        Transform::new(Isometry2::translation(x, y), None)
+    }
+
+    #[test]
+    fn test_three_level_chain() {
+        let mut world = World::new();
+        let root = world.spawn((Transform::new(
+            Isometry2::new(Vector2::new(10., 0.), std::f32::consts::FRAC_PI_2),
+            None,
+        ),));
+        let child = world.spawn((translation(5., 0.),));
+        let grandchild = world.spawn((translation(1., 0.), Parent(child)));
+        // Attach the child last, so that the grandchild is visited before its parent.
+        world.insert_one(child, Parent(root)).unwrap();
+
+        let mut cache = HierarchyCache::default();
+        resolve_hierarchy(&world, &mut cache);
+
+        let position = |e: Entity| cache.resolved[&e].transform_point(&Point2::origin());
+        assert!((position(child) - Point2::new(10., 5.)).norm() < 1e-5);
+        assert!((position(grandchild) - Point2::new(10., 6.)).norm() < 1e-5);
+        assert!(!cache.resolved.contains_key(&root));
+    }
+
+    #[test]
+    fn test_cycle_is_skipped() {
+        let mut world = World::new();
+        let a = world.spawn((translation(1., 0.),));
+        let b = world.spawn((translation(1., 0.), Parent(a)));
+        world.insert_one(a, Parent(b)).unwrap();
+        let c = world.spawn((translation(1., 0.),));
+
+        let mut cache = HierarchyCache::default();
+        resolve_hierarchy(&world, &mut cache);
+
+        assert!(cache.resolved.is_empty());
+        assert!(cache.skipped.contains(&a) && cache.skipped.contains(&b));
+        assert!(!cache.skipped.contains(&c));
+    }
+
+    #[test]
+    fn test_cycle_is_reported_once() {
+        let mut world = World::new();
+        let a = world.spawn((translation(1., 0.),));
+        let b = world.spawn((translation(1., 0.), Parent(a)));
+        world.insert_one(a, Parent(b)).unwrap();
+
+        let mut cache = HierarchyCache::default();
+        resolve_hierarchy(&world, &mut cache);
+        assert_eq!(cache.cycles.len(), 1);
+
+        resolve_hierarchy(&world, &mut cache);
+        assert!(cache.cycles.is_empty());
+
+        // Once broken, the same cycle is reported again when it's formed again.
+        world.remove_one::<Parent>(a).unwrap();
+        resolve_hierarchy(&world, &mut cache);
+        assert!(cache.cycles.is_empty());
+
+        world.insert_one(a, Parent(b)).unwrap();
+        resolve_hierarchy(&world, &mut cache);
+        assert_eq!(cache.cycles.len(), 1);
+    }
+}
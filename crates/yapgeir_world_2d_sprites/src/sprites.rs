@@ -2,36 +2,40 @@ use std::ops::Div;
 
 use derive_more::{Deref, DerefMut};
 use hecs::{Entity, With, Without, World};
-use nalgebra::Point;
+use nalgebra::{Matrix3, Point, Point2};
+use yapgeir_core::WindowSize;
+use yapgeir_geometry::Box2D;
 use yapgeir_realm::{Realm, Res, ResMut};
 use yapgeir_world_2d::{
-    Dirty, DrawQuad, Drawable, Flip, Static, Transform, TransformPpt, WorldCamera,
+    Culled, Dirty, DrawQuad, Drawable, Static, Transform, TransformPpt, WorldCamera,
 };
 
+use crate::hierarchy::{update_hierarchy, GlobalTransform, HierarchyCache};
+
 #[cfg(feature = "reflection")]
 use yapgeir_reflection::RealmExtensions;
 
+type ModelQuery<'a> = (
+    &'a Transform,
+    &'a Drawable,
+    &'a mut DrawQuad,
+    Option<&'a GlobalTransform>,
+);
+
 /// Applies transformation matrix to a Drawable, updating a DrawQuad.
+///
+/// Entities with a `GlobalTransform` are transformed with it instead of their local `Transform`.
 fn update_model(
     ppt: &TransformPpt,
-    (_, (transform, drawable, model)): (Entity, (&Transform, &Drawable, &mut DrawQuad)),
+    (_, (transform, drawable, model, global)): (Entity, ModelQuery),
 ) {
+    let matrix = global.map_or_else(|| transform.matrix(), |global| **global);
+
     *model = drawable
         .sprite
         .boundaries
         .points()
-        .map(|mut p| {
-            match transform.flip {
-                Some(Flip::X) => p[0] = -p[0],
-                Some(Flip::Y) => p[1] = -p[1],
-                None => (),
-            };
-
-            transform
-                .isometry
-                .transform_point(&Point::from(p).div(**ppt))
-                .into()
-        })
+        .map(|p| matrix.transform_point(&Point::from(p).div(**ppt)).into())
         .into();
 }
 
@@ -81,13 +85,13 @@ fn update_quads(
 
     // Update non-static entities
     world
-        .query::<Without<(&Transform, &Drawable, &mut DrawQuad), &Static>>()
+        .query::<Without<ModelQuery, &Static>>()
         .iter()
         .for_each(|entity| update_model(&ppt, entity));
 
     // Update dirty static entities
     world
-        .query::<With<With<(&Transform, &Drawable, &mut DrawQuad), &Static>, &Dirty>>()
+        .query::<With<With<ModelQuery, &Static>, &Dirty>>()
         .iter()
         .for_each(|entity| {
             cache.push(entity.0);
@@ -102,6 +106,76 @@ fn update_quads(
     }
 }
 
+/// Calculates an axis aligned bounding box of a quad.
+pub fn quad_aabb(quad: &[[f32; 2]; 4]) -> Box2D<f32> {
+    quad.iter()
+        .skip(1)
+        .fold(Box2D::new(quad[0], quad[0]), |aabb, p| {
+            Box2D::new(
+                [aabb.a[0].min(p[0]), aabb.a[1].min(p[1])],
+                [aabb.b[0].max(p[0]), aabb.b[1].max(p[1])],
+            )
+        })
+}
+
+/// Calculates a world space rectangle visible through the camera,
+/// assuming that the camera transforms world space into pixel space
+/// with (0; 0) in the center of a frame buffer of a given size.
+pub fn visible_rect(camera: &Matrix3<f32>, size: WindowSize) -> Box2D<f32> {
+    let Some(inverse) = camera.try_inverse() else {
+        // A degenerate camera can't see anything.
+        return Box2D::default();
+    };
+
+    let (hw, hh) = (size.w as f32 / 2., size.h as f32 / 2.);
+    let corners = [[-hw, -hh], [-hw, hh], [hw, hh], [hw, -hh]]
+        .map(|p| inverse.transform_point(&Point2::from(p)).into());
+
+    quad_aabb(&corners)
+}
+
+fn intersects(a: &Box2D<f32>, b: &Box2D<f32>) -> bool {
+    a.a[0] <= b.b[0] && b.a[0] <= a.b[0] && a.a[1] <= b.b[1] && b.a[1] <= a.b[1]
+}
+
+/// Marks entities with a `DrawQuad` outside of the `visible` rectangle as `Culled`,
+/// and removes the marker from the ones that became visible.
+fn cull(world: &mut World, cache: &mut Vec<Entity>, visible: Box2D<f32>) {
+    world
+        .query::<Without<&DrawQuad, &Culled>>()
+        .iter()
+        .filter(|(_, quad)| !intersects(&quad_aabb(quad), &visible))
+        .for_each(|(e, _)| cache.push(e));
+
+    for e in cache.drain(0..) {
+        world
+            .insert_one(e, Culled)
+            .expect("Unable to insert Culled for entity");
+    }
+
+    world
+        .query::<With<&DrawQuad, &Culled>>()
+        .iter()
+        .filter(|(_, quad)| intersects(&quad_aabb(quad), &visible))
+        .for_each(|(e, _)| cache.push(e));
+
+    for e in cache.drain(0..) {
+        world
+            .remove_one::<Culled>(e)
+            .expect("Unable to remove Culled from entity");
+    }
+}
+
+fn cull_quads(
+    mut world: ResMut<World>,
+    mut cache: ResMut<SpritesEntityCache>,
+    camera: Res<WorldCamera>,
+    window_size: Res<WindowSize>,
+) {
+    let visible = visible_rect(&camera, *window_size);
+    cull(&mut world, &mut cache, visible);
+}
+
 pub fn plugin(realm: &mut Realm) {
     #[cfg(feature = "reflection")]
     realm
@@ -116,6 +190,51 @@ pub fn plugin(realm: &mut Realm) {
     realm
         .initialize_resource::<WorldCamera>()
         .initialize_resource::<SpritesEntityCache>()
-        .add_system(add_draw_quads)
-        .add_system(update_quads);
+        .initialize_resource::<HierarchyCache>();
+    realm.add_system(add_draw_quads);
+    realm.add_system(update_hierarchy);
+    realm.add_system(update_quads);
+}
+
+/// Marks entities outside of the area visible through the `WorldCamera` as `Culled`.
+///
+/// Must be added after the sprites `plugin`, so that the culling is done
+/// with up to date `DrawQuad` components. Rendering systems can then skip
+/// the culled entities by querying for `Without<&DrawQuad, &Culled>`.
+pub fn culling_plugin(realm: &mut Realm) {
+    #[cfg(feature = "reflection")]
+    realm.register_type::<yapgeir_world_2d::Culled>();
+
+    realm
+        .initialize_resource::<WorldCamera>()
+        .initialize_resource::<SpritesEntityCache>()
+        .add_system(cull_quads);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_far_entity_is_culled() {
+        let mut world = World::new();
+        let quad = |x: f32, y: f32| {
+            DrawQuad::from([[x, y], [x, y + 10.], [x + 10., y + 10.], [x + 10., y]])
+        };
+
+        let near = world.spawn((quad(0., 0.),));
+        let far = world.spawn((quad(10_000., 10_000.),));
+
+        let visible = visible_rect(&Matrix3::identity(), WindowSize::new(600, 400));
+        cull(&mut world, &mut Vec::new(), visible);
+
+        let drawn: Vec<Entity> = world
+            .query::<Without<&DrawQuad, &Culled>>()
+            .iter()
+            .map(|(e, _)| e)
+            .collect();
+
+        assert_eq!(drawn, vec![near]);
+        assert!(world.get::<&Culled>(far).is_ok());
+    }
 }
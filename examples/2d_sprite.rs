@@ -7,17 +7,15 @@ use yapgeir_assets::png::decode_png;
 use yapgeir_core::{Delta, ScreenPpt, WindowSize};
 use yapgeir_egui_sdl::{Egui, EguiRenderer};
 use yapgeir_events::Events;
-use yapgeir_graphics_hal::{
-    frame_buffer::FrameBuffer, sampler::Sampler, texture::PixelFormat, Graphics,
-};
+use yapgeir_graphics_hal::{frame_buffer::FrameBuffer, sampler::Sampler, Graphics};
 use yapgeir_graphics_hal_gles2::Gles;
 use yapgeir_input::{
     buttons::ButtonAction,
     mouse::{MouseButton, MouseButtonEvent},
     Axial,
 };
-use yapgeir_inspector_egui::draw_entity;
-use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_inspector_egui::entity_browser;
+use yapgeir_realm::{stage, Realm, Res, ResMut};
 use yapgeir_reflection::{
     bevy_reflect::{self, Reflect},
     RealmExtensions, Reflection,
@@ -68,9 +66,11 @@ fn main() {
             }
         })
         // Game logic system
-        .add_system(move_tile)
-        .add_system(spawn_tile_on_left_click)
-        .add_system(despawn_tile_on_right_click)
+        .add_plugin(|realm: &mut Realm| {
+            realm.add_system(move_tile);
+            realm.add_system(spawn_tile_on_left_click);
+            realm.add_system(despawn_tile_on_right_click);
+        })
         // Sets up resources for rendering pipeline, and a system that will do actual rendering
         .add_plugin(initialize_rendering::<GraphicsAdapter>);
 
@@ -168,23 +168,24 @@ fn initialize_rendering<G: Graphics>(realm: &mut Realm) {
     realm
         .add_plugin(yapgeir_renderer_2d::plugin::<G>)
         .initialize_resource_with(|graphics: Res<G>| -> G::Texture {
-            let (tile_image, tile_size) = decode_png(include_bytes!("assets/tile.png")).unwrap();
+            let (tile_image, tile_size, format) =
+                decode_png(include_bytes!("assets/tile.png")).unwrap();
 
-            graphics.new_texture(PixelFormat::Rgba, tile_size, Some(&tile_image))
+            graphics.new_texture(format, tile_size, Some(&tile_image))
         })
         .initialize_resource_with(
             |graphics: Res<G>, quad_index_buffer: Res<QuadIndexBuffer<G>>| -> SpriteRenderer<G> {
                 SpriteRenderer::new(graphics.deref(), quad_index_buffer.clone())
             },
         )
-        .add_system(render::<G>);
+        .add_system_to_stage(stage::RENDER, render::<G>);
 }
 
 fn egui_update(
     mut gui: ResMut<Egui>,
     mut mouse: ResMut<Events<MouseButtonEvent>>,
     reflection: Res<Reflection>,
-    world: Res<World>,
+    mut world: ResMut<World>,
 ) {
     let ctx = gui.context();
 
@@ -192,16 +193,21 @@ fn egui_update(
         mouse.clear();
     }
 
-    egui::Window::new("Entities")
+    let commands = egui::Window::new("Entities")
         .min_width(200.)
         .default_width(200.)
         .scroll2([false, true])
         .show(&ctx, |ui| {
-            for entity in world.iter() {
-                draw_entity(&reflection, ui, entity);
-            }
+            let commands = entity_browser(&reflection, ui, &world);
             ui.allocate_space(ui.available_size());
-        });
+            commands
+        })
+        .and_then(|response| response.inner)
+        .unwrap_or_default();
+
+    for (entity, command) in commands {
+        command.apply(&reflection, &mut world, entity);
+    }
 }
 
 fn render<G: Graphics>(
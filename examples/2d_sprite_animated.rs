@@ -1,16 +1,14 @@
 use std::ops::Deref;
 
-use hecs::World;
-use nalgebra::{Isometry2, Matrix3, Vector2};
+use hecs::{Without, World};
+use nalgebra::{Isometry2, Vector2};
 use yapgeir_assets::{
     animations::{Animation, AnimationKind, AnimationSequence},
     png::decode_png,
 };
 use yapgeir_core::WindowSize;
 use yapgeir_events::Events;
-use yapgeir_graphics_hal::{
-    frame_buffer::FrameBuffer, sampler::Sampler, texture::PixelFormat, Graphics,
-};
+use yapgeir_graphics_hal::{frame_buffer::FrameBuffer, sampler::Sampler, Graphics};
 use yapgeir_graphics_hal_gles2::Gles;
 use yapgeir_input::{
     buttons::ButtonAction,
@@ -18,7 +16,7 @@ use yapgeir_input::{
     Axial,
 };
 use yapgeir_physics_2d::simple::KinematicBody;
-use yapgeir_realm::{Realm, Res, ResMut};
+use yapgeir_realm::{stage, Realm, Res, ResMut};
 use yapgeir_renderer_2d::{
     quad_index_buffer::QuadIndexBuffer,
     sprite_renderer::{DrawRegion, SpriteRenderer, TextureRegion},
@@ -26,7 +24,7 @@ use yapgeir_renderer_2d::{
 };
 use yapgeir_sdl::SdlSettings;
 use yapgeir_sdl_graphics::SdlWindowBackend;
-use yapgeir_world_2d::{DrawQuad, Drawable, SpriteSheet, Transform};
+use yapgeir_world_2d::{Culled, DrawQuad, Drawable, SpriteSheet, Transform, WorldCamera};
 use yapgeir_world_2d_sprites::animation::{AnimationSequenceKey, AnimationStorage, Animator};
 
 pub type GraphicsAdapter = Gles<SdlWindowBackend>;
@@ -49,13 +47,19 @@ fn main() {
         // Adds ECS as a resource
         .initialize_resource::<World>()
         // Game logic system
-        .add_system(flip_direction)
-        .add_system(spawn_entities_on_left_click)
-        .add_system(despawn_entities_on_right_click)
+        .add_plugin(|realm: &mut Realm| {
+            realm.add_system(flip_direction);
+            realm.add_system(spawn_entities_on_left_click);
+            realm.add_system(despawn_entities_on_right_click);
+        })
         // Manages animation frame changes
         .add_plugin(yapgeir_world_2d_sprites::animation::plugin)
+        // Computes the WorldCamera from a Camera2D
+        .add_plugin(yapgeir_world_2d_sprites::camera::plugin)
         // Update drawable data for rendering
         .add_plugin(yapgeir_world_2d_sprites::sprites::plugin)
+        // Skip drawing entities which are off screen
+        .add_plugin(yapgeir_world_2d_sprites::sprites::culling_plugin)
         // Manage translation changes according to velocity
         .add_plugin(yapgeir_physics_2d::simple::plugin)
         // Sets up resources for rendering pipeline, and a system that will do actual rendering
@@ -90,6 +94,7 @@ fn spawn_entity(world: &mut World, animations: &Animations, transform: Transform
                 rand::random::<f32>() * 600. - 300.,
             ),
             Vector2::default(),
+            false,
         ),
         Animator::new(animations.player),
     ));
@@ -178,16 +183,16 @@ fn initialize_rendering<G: Graphics>(realm: &mut Realm) {
     realm
         .add_plugin(yapgeir_renderer_2d::plugin::<G>)
         .initialize_resource_with(|graphics: Res<G>| -> G::Texture {
-            let (image, size) = decode_png(include_bytes!("assets/sheet.png")).unwrap();
+            let (image, size, format) = decode_png(include_bytes!("assets/sheet.png")).unwrap();
 
-            graphics.new_texture(PixelFormat::Rgba, size, Some(&image))
+            graphics.new_texture(format, size, Some(&image))
         })
         .initialize_resource_with(
             |graphics: Res<G>, quad_index_buffer: Res<QuadIndexBuffer<G>>| -> SpriteRenderer<G> {
                 SpriteRenderer::new(graphics.deref(), quad_index_buffer.clone())
             },
         )
-        .add_system(render::<G>);
+        .add_system_to_stage(stage::RENDER, render::<G>);
 }
 
 /// A resource that keeps ids of loaded animations
@@ -217,6 +222,7 @@ fn render<G: Graphics>(
     graphics: Res<G>,
     texture: Res<G::Texture>,
     world: Res<World>,
+    camera: Res<WorldCamera>,
 ) {
     let fb = graphics.default_frame_buffer();
     fb.clear(
@@ -228,11 +234,14 @@ fn render<G: Graphics>(
 
     sprite_renderer.batch(
         &fb,
-        Matrix3::identity().into(),
+        camera.0.into(),
         NdcProjection::Center,
         Sampler::nearest(&texture),
         |batch| {
-            for (_, (draw_quad, drawable)) in world.query::<(&DrawQuad, &Drawable)>().iter() {
+            for (_, (draw_quad, drawable)) in world
+                .query::<Without<(&DrawQuad, &Drawable), &Culled>>()
+                .iter()
+            {
                 batch.draw_sprite(
                     DrawRegion::Quad(**draw_quad),
                     TextureRegion::TexelsBox2D(drawable.sprite.sub_texture),
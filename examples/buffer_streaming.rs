@@ -0,0 +1,178 @@
+//! Compares the two ways `BatchRenderer` streams vertices to the GPU:
+//! orphaning a single buffer before every write, and rotating through a ring of buffers.
+//!
+//! Every frame draws many small batches with one of the approaches, switching between them
+//! every few seconds, and prints the average CPU time it took to submit a frame.
+//! Stalls on buffers still read by the GPU show up as a longer submit time.
+
+use std::{rc::Rc, time::Instant};
+
+use bytemuck::{Pod, Zeroable};
+use yapgeir_core::WindowSize;
+use yapgeir_graphics_hal::{
+    draw_params::DrawParameters, frame_buffer::FrameBuffer, shader::TextShaderSource,
+    vertex_buffer::Vertex, Graphics, Rgba,
+};
+use yapgeir_graphics_hal_gles2::Gles;
+use yapgeir_realm::{stage, Realm, Res, ResMut};
+use yapgeir_renderer_2d::{
+    batch_renderer::{BatchIndices, BatchRenderer},
+    quad_index_buffer::QuadIndexBuffer,
+};
+use yapgeir_sdl::SdlSettings;
+use yapgeir_sdl_graphics::SdlWindowBackend;
+
+pub type GraphicsAdapter = Gles<SdlWindowBackend>;
+
+/// Number of batches drawn every frame, each of them flushed with a separate draw call.
+const BATCHES: usize = 500;
+const QUADS_PER_BATCH: usize = 256;
+/// Number of buffers in the ring, compared to a single orphaned buffer.
+const RING_BUFFERS: usize = 3;
+const FRAMES_PER_MODE: u32 = 300;
+
+const SHADER: TextShaderSource = TextShaderSource {
+    vertex: r#"
+        #version 120
+
+        attribute vec2 position;
+        attribute vec4 color;
+
+        varying vec4 o_color;
+
+        void main() {
+            o_color = color;
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    "#,
+    fragment: r#"
+        #version 120
+
+        #ifdef WEB
+        precision highp float;
+        #endif
+
+        varying vec4 o_color;
+
+        void main() {
+            gl_FragColor = o_color;
+        }
+    "#,
+    defines: &[],
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Default, Zeroable, Pod, Vertex)]
+struct QuadVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+
+struct Mode<G: Graphics> {
+    name: &'static str,
+    renderer: BatchRenderer<G, QuadVertex>,
+}
+
+struct Streaming<G: Graphics> {
+    modes: [Mode<G>; 2],
+    current: usize,
+    frames: u32,
+    submit_seconds: f64,
+}
+
+fn main() {
+    let mut realm = Realm::default();
+
+    realm
+        .add_plugin(yapgeir_sdl::plugin(SdlSettings {
+            window_size: WindowSize::new(600, 400),
+            ..SdlSettings::default()
+        }))
+        .add_plugin(yapgeir_sdl_graphics::plugin::<GraphicsAdapter>)
+        .add_plugin(yapgeir_renderer_2d::plugin::<GraphicsAdapter>)
+        .add_plugin(initialize_streaming::<GraphicsAdapter>);
+
+    realm.run();
+}
+
+fn initialize_streaming<G: Graphics>(realm: &mut Realm) {
+    realm
+        .initialize_resource_with(
+            |graphics: Res<G>, quad_index_buffer: Res<QuadIndexBuffer<G>>| -> Streaming<G> {
+                let shader = Rc::new(graphics.new_shader(&SHADER));
+                let uniforms = Rc::new(graphics.new_uniform_buffer(&()));
+                let renderer = |buffers| {
+                    BatchRenderer::new(
+                        &*graphics,
+                        shader.clone(),
+                        BatchIndices::Quad(quad_index_buffer.clone()),
+                        uniforms.clone(),
+                        (QUADS_PER_BATCH * 4, buffers),
+                    )
+                };
+
+                Streaming {
+                    modes: [
+                        Mode {
+                            name: "orphaning a single buffer",
+                            renderer: renderer(1),
+                        },
+                        Mode {
+                            name: "a ring of buffers",
+                            renderer: renderer(RING_BUFFERS),
+                        },
+                    ],
+                    current: 0,
+                    frames: 0,
+                    submit_seconds: 0.,
+                }
+            },
+        )
+        .add_system_to_stage(stage::RENDER, render::<G>);
+}
+
+/// Vertices of a small quad in a grid filling the screen.
+fn quad(i: usize) -> [QuadVertex; 4] {
+    let side = 2. / 64.;
+    let (x, y) = (
+        (i % 64) as f32 * side - 1.,
+        (i / 64 % 64) as f32 * side - 1.,
+    );
+    let color = [(i % 64) as f32 / 64., (i / 64 % 64) as f32 / 64., 0.5, 1.];
+
+    [[x, y], [x, y + side], [x + side, y + side], [x + side, y]]
+        .map(|position| QuadVertex { position, color })
+}
+
+fn render<G: Graphics>(mut streaming: ResMut<Streaming<G>>, graphics: Res<G>) {
+    let fb = graphics.default_frame_buffer();
+    fb.clear(None, Some(Rgba::new(0., 0., 0., 1.)), None, None);
+
+    let draw_parameters = DrawParameters::default();
+    let current = streaming.current;
+    let renderer = &mut streaming.modes[current].renderer;
+
+    let start = Instant::now();
+    for b in 0..BATCHES {
+        let mut batch = renderer.start_batch::<G::Texture, _>(&fb, &draw_parameters, &(), []);
+        for i in 0..QUADS_PER_BATCH {
+            batch.draw(&quad(b * QUADS_PER_BATCH + i));
+        }
+    }
+    streaming.submit_seconds += start.elapsed().as_secs_f64();
+
+    graphics.swap_buffers();
+
+    streaming.frames += 1;
+    if streaming.frames == FRAMES_PER_MODE {
+        let average = streaming.submit_seconds / streaming.frames as f64 * 1000.;
+        println!(
+            "{}: {average:.3} ms per frame to submit {BATCHES} batches",
+            streaming.modes[current].name
+        );
+
+        streaming.current = (current + 1) % streaming.modes.len();
+        streaming.frames = 0;
+        streaming.submit_seconds = 0.;
+    }
+}